@@ -0,0 +1,125 @@
+//! Overhead of the event hot path
+//!
+//! Run with `cargo bench --features bench`. The events are dispatched through the `mock`
+//! backend, so this measures the crate's own dispatch and conversion overhead, not the time
+//! spent in ArkUI.
+
+use core::ffi::c_void;
+use ohos_sys::xcomponent::{OH_NativeXComponent, OH_NativeXComponent_Callback};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use xcomponent::mock::{MockXComponent, TouchEventBuilder};
+use xcomponent::touch::{TouchEvent, TouchPoint, TouchPoints};
+use xcomponent::XComponent;
+
+/// Runs `f` repeatedly for about a second and prints the mean time per iteration
+fn bench(name: &str, mut f: impl FnMut()) {
+    // Warm up and estimate the number of iterations per second.
+    let start = Instant::now();
+    let mut iterations = 0u64;
+    while start.elapsed() < Duration::from_millis(100) {
+        f();
+        iterations += 1;
+    }
+    let iterations = iterations * 10;
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    let per_iteration = start.elapsed() / iterations as u32;
+    println!("{name:<32} {per_iteration:>10?}/iter ({iterations} iterations)");
+}
+
+extern "C" fn dispatch_touch_event(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+    let xc = XComponent::new(xcomponent, window).unwrap();
+    let touch_event = xc.touch_event().unwrap();
+    black_box(
+        touch_event
+            .points()
+            .iter()
+            .map(|point| point.x)
+            .sum::<f32>(),
+    );
+}
+
+static CALLBACKS: OH_NativeXComponent_Callback = OH_NativeXComponent_Callback {
+    OnSurfaceCreated: None,
+    OnSurfaceChanged: None,
+    OnSurfaceDestroyed: None,
+    DispatchTouchEvent: Some(dispatch_touch_event),
+};
+
+fn main() {
+    // A five finger swipe, to exercise the touch point conversion.
+    let mut gesture = TouchEventBuilder::down(0, (100.0, 100.0));
+    for id in 1..5 {
+        gesture = gesture.down_pointer(id, (100.0 + 50.0 * id as f32, 100.0));
+    }
+    for step in 1..=10 {
+        for id in 0..5 {
+            gesture = gesture.move_pointer(id, (100.0 + 50.0 * id as f32, 100.0 + step as f32));
+        }
+    }
+    let events = gesture.build();
+    let raw = events[events.len() - 1];
+
+    bench("TouchEvent::from", || {
+        black_box(TouchEvent::from(black_box(raw)));
+    });
+    bench("TouchEvent::points", || {
+        let touch_event = TouchEvent::from(black_box(raw));
+        black_box(
+            touch_event
+                .points()
+                .iter()
+                .map(|point| point.x)
+                .sum::<f32>(),
+        );
+    });
+    // Alternatives to the inline storage of `TouchEvent`, for comparison.
+    bench("raw points (lazy conversion)", || {
+        let raw = black_box(raw);
+        let points = raw.touchPoints[..raw.numPoints as usize].iter();
+        black_box(points.map(|point| TouchPoint::from(point).x).sum::<f32>());
+    });
+    bench("raw points (Vec)", || {
+        let raw = black_box(raw);
+        let points: Vec<_> = raw.touchPoints[..raw.numPoints as usize]
+            .iter()
+            .map(TouchPoint::from)
+            .collect();
+        black_box(points.iter().map(|point| point.x).sum::<f32>());
+    });
+    bench("raw points (TouchPoints)", || {
+        let raw = black_box(raw);
+        let points: TouchPoints = raw.touchPoints[..raw.numPoints as usize]
+            .iter()
+            .map(TouchPoint::from)
+            .collect();
+        black_box(points.iter().map(|point| point.x).sum::<f32>());
+    });
+
+    let mock = MockXComponent::new("bench");
+    bench("dispatch_touch_event", || {
+        mock.dispatch_touch_event(&CALLBACKS, black_box(raw));
+    });
+    bench("dispatch_touch_events (gesture)", || {
+        mock.dispatch_touch_events(&CALLBACKS, events.iter().copied());
+    });
+
+    #[cfg(feature = "egui")]
+    {
+        let events: Vec<_> = events
+            .iter()
+            .copied()
+            .map(xcomponent::touch::TouchEvent::from)
+            .collect();
+        let mut input = xcomponent::egui::EguiInput::new(2.0);
+        bench("EguiInput::on_touch_event", || {
+            for event in &events {
+                input.on_touch_event(event);
+            }
+            black_box(input.take_raw_input());
+        });
+    }
+}
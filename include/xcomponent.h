@@ -0,0 +1,95 @@
+/*
+ * C ABI of the xcomponent crate, exported with its `capi` feature.
+ *
+ * See the documentation of the `xcomponent::capi` module.
+ */
+
+#ifndef XCOMPONENT_H
+#define XCOMPONENT_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#include <ace/xcomponent/native_interface_xcomponent.h>
+#include <native_window/external_window.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum {
+    /* data.surface, with a window */
+    XCOMPONENT_EVENT_SURFACE_CREATED,
+    /* data.surface, without a window */
+    XCOMPONENT_EVENT_SURFACE_CHANGED,
+    XCOMPONENT_EVENT_SURFACE_DESTROYED,
+    /* data.touch */
+    XCOMPONENT_EVENT_TOUCH,
+    /* data.mouse */
+    XCOMPONENT_EVENT_MOUSE,
+    /* data.hover */
+    XCOMPONENT_EVENT_HOVER,
+    XCOMPONENT_EVENT_FOCUS,
+    XCOMPONENT_EVENT_BLUR,
+    /* data.key */
+    XCOMPONENT_EVENT_KEY,
+} XComponentEventKind;
+
+typedef struct {
+    /*
+     * The native window of a created surface, NULL otherwise. The caller owns a reference to
+     * the window, which it releases via OH_NativeWindow_NativeObjectUnreference.
+     */
+    OHNativeWindow *window;
+    uint64_t width;
+    uint64_t height;
+} XComponentSurface;
+
+typedef struct {
+    /* An OH_NativeXComponent_KeyAction */
+    int32_t action;
+    /* An OH_NativeXComponent_KeyCode */
+    int32_t code;
+    int64_t device_id;
+    int64_t timestamp;
+} XComponentKeyEvent;
+
+typedef union {
+    XComponentSurface surface;
+    OH_NativeXComponent_TouchEvent touch;
+    OH_NativeXComponent_MouseEvent mouse;
+    /* Whether the mouse entered or left the XComponent */
+    bool hover;
+    XComponentKeyEvent key;
+} XComponentEventData;
+
+typedef struct {
+    XComponentEventKind kind;
+    /* The XComponent the event is for, which must only be used on the UI thread */
+    OH_NativeXComponent *xcomponent;
+    XComponentEventData data;
+} XComponentEvent;
+
+/*
+ * Registers the event queue as the handler of xcomponent, on the UI thread. Returns 0 on
+ * success, the ArkUI error code if registering a callback failed, or -1.
+ */
+int32_t xcomponent_register_handler(OH_NativeXComponent *xcomponent);
+
+/*
+ * Takes the next queued event into event, waiting up to timeout_ms for one. Returns false
+ * without an event if the timeout elapsed or xcomponent_wake_up was called.
+ */
+bool xcomponent_poll_event(XComponentEvent *event, uint32_t timeout_ms);
+
+/* Makes an xcomponent_poll_event waiting on another thread return */
+void xcomponent_wake_up(void);
+
+/* Sets how long OnSurfaceDestroyed waits for the render thread to take the event, 0 disables it */
+void xcomponent_set_destroy_timeout(uint32_t timeout_ms);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* XCOMPONENT_H */
@@ -0,0 +1,930 @@
+//! Accessibility of content rendered into the XComponent
+//!
+//! Screen readers can't see what is rendered into the surface of an XComponent. Instead, they
+//! query the application for the accessibility elements of the XComponent via the
+//! [`AccessibilityProvider`] of the XComponent, which forwards the requests to an
+//! [`AccessibilityHandler`].
+//!
+//! The native callbacks don't identify the provider they belong to, so there can only be one
+//! handler per process. Registering a handler replaces the previous one.
+//!
+//! Instead of implementing [`AccessibilityHandler`], toolkits can describe their widgets as an
+//! [`AccessibilityTree`] and register a [`TreeHandler`], which answers the requests from it.
+//!
+//! ## Explore by touch
+//!
+//! While a screen reader is active, the system consumes the touch events and the XComponent
+//! doesn't receive hover events for them. Instead, the screen reader hit-tests the on-screen
+//! bounds of the elements and requests [`Action::GainAccessibilityFocus`] for the element under
+//! the finger, so the bounds of all elements need to be kept up to date.
+//!
+//! ## Screen reader state
+//!
+//! The NDK can't query whether a screen reader is running. Forward the state from ArkTS to
+//! [`set_screen_reader_enabled`], e.g. from `accessibility.isScreenReaderOpenSync()` and the
+//! `screenReaderStateChange` event of `@ohos.accessibility`. Native code can then query it with
+//! [`is_screen_reader_enabled`] or listen for changes with [`on_screen_reader_changed`].
+//!
+//! ## Example
+//! ```no_run
+//! use xcomponent::accessibility::{AccessibilityHandler, ElementInfoList, SearchMode};
+//!
+//! struct Handler;
+//!
+//! impl AccessibilityHandler for Handler {
+//!     fn find_by_id(
+//!         &mut self,
+//!         element_id: i64,
+//!         _mode: SearchMode,
+//!         elements: &mut ElementInfoList<'_>,
+//!     ) -> Result<(), i32> {
+//!         let mut element = elements.add()?;
+//!         element.set_element_id(element_id as i32)?;
+//!         element.set_accessibility_text("Hello")?;
+//!         element.set_visible(true)
+//!     }
+//! }
+//!
+//! # fn on_surface_created(xcomponent: xcomponent::XComponent) {
+//! // Call this from `OnSurfaceCreated`.
+//! let provider = xcomponent.accessibility_provider().unwrap();
+//! provider.register(Handler).unwrap();
+//! # }
+//! ```
+
+use crate::log::error;
+use crate::trace::ffi_span;
+use core::marker::PhantomData;
+use core::ptr::NonNull;
+use ohos_sys::arkui::native_interface_accessibility::{
+    ArkUI_AccessibilityActionArguments, ArkUI_AccessibilityElementInfo,
+    ArkUI_AccessibilityElementInfoList, ArkUI_AccessibilityEventType,
+    ArkUI_AccessibilityFocusMoveDirection, ArkUI_AccessibilityFocusType,
+    ArkUI_AccessibilityProviderCallbacks, ArkUI_AccessibilitySearchMode,
+    ArkUI_Accessibility_ActionType, ArkUI_AccessibleAction, ArkUI_AccessibleRect,
+    ArkUI_AcessbilityErrorCode, OH_ArkUI_AccessibilityElementInfoSetAccessibilityFocused,
+    OH_ArkUI_AccessibilityElementInfoSetAccessibilityText,
+    OH_ArkUI_AccessibilityElementInfoSetCheckable, OH_ArkUI_AccessibilityElementInfoSetChecked,
+    OH_ArkUI_AccessibilityElementInfoSetChildNodeIds,
+    OH_ArkUI_AccessibilityElementInfoSetClickable,
+    OH_ArkUI_AccessibilityElementInfoSetComponentType,
+    OH_ArkUI_AccessibilityElementInfoSetContents, OH_ArkUI_AccessibilityElementInfoSetEditable,
+    OH_ArkUI_AccessibilityElementInfoSetElementId, OH_ArkUI_AccessibilityElementInfoSetEnabled,
+    OH_ArkUI_AccessibilityElementInfoSetFocusable, OH_ArkUI_AccessibilityElementInfoSetFocused,
+    OH_ArkUI_AccessibilityElementInfoSetHintText,
+    OH_ArkUI_AccessibilityElementInfoSetLongClickable,
+    OH_ArkUI_AccessibilityElementInfoSetOperationActions,
+    OH_ArkUI_AccessibilityElementInfoSetParentId, OH_ArkUI_AccessibilityElementInfoSetScreenRect,
+    OH_ArkUI_AccessibilityElementInfoSetScrollable, OH_ArkUI_AccessibilityElementInfoSetSelected,
+    OH_ArkUI_AccessibilityElementInfoSetVisible, OH_ArkUI_AccessibilityEventSetElementInfo,
+    OH_ArkUI_AccessibilityEventSetEventType, OH_ArkUI_AccessibilityProviderRegisterCallback,
+    OH_ArkUI_AddAndGetAccessibilityElementInfo, OH_ArkUI_CreateAccessibilityElementInfo,
+    OH_ArkUI_CreateAccessibilityEventInfo, OH_ArkUI_DestoryAccessibilityElementInfo,
+    OH_ArkUI_DestoryAccessibilityEventInfo, OH_ArkUI_FindAccessibilityActionArgumentByKey,
+    OH_ArkUI_SendAccessibilityAsyncEvent,
+};
+use ohos_sys_opaque_types::ArkUI_AccessibilityProvider;
+use std::ffi::{c_char, CStr, CString};
+use std::sync::Mutex;
+
+mod tree;
+pub use tree::{AccessibilityNode, AccessibilityTree, Role, TreeHandler};
+
+/// ARKUI_ACCESSIBILITY_NATIVE_RESULT_FAILED
+pub const RESULT_FAILED: i32 =
+    ArkUI_AcessbilityErrorCode::ARKUI_ACCESSIBILITY_NATIVE_RESULT_FAILED.0;
+/// ARKUI_ACCESSIBILITY_NATIVE_RESULT_BAD_PARAMETER
+pub const RESULT_BAD_PARAMETER: i32 =
+    ArkUI_AcessbilityErrorCode::ARKUI_ACCESSIBILITY_NATIVE_RESULT_BAD_PARAMETER.0;
+/// ARKUI_ACCESSIBILITY_NATIVE_RESULT_OUT_OF_MEMORY
+pub const RESULT_OUT_OF_MEMORY: i32 =
+    ArkUI_AcessbilityErrorCode::ARKUI_ACCESSIBILITY_NATIVE_RESULT_OUT_OF_MEMORY.0;
+
+/// Parent id of the root element
+pub const ROOT_PARENT_ID: i32 = -2100000;
+
+/// Which elements besides the requested one should be returned
+///
+/// Returning only the requested element is always valid, the others are only prefetched.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SearchMode(pub u32);
+
+impl SearchMode {
+    pub const CURRENT: Self = Self(0);
+    pub const PREDECESSORS: Self = Self(1);
+    pub const SIBLINGS: Self = Self(2);
+    pub const CHILDREN: Self = Self(4);
+    pub const RECURSIVE_CHILDREN: Self = Self(8);
+
+    /// Whether the elements of `mode` are requested
+    pub fn contains(self, mode: SearchMode) -> bool {
+        self.0 & mode.0 == mode.0
+    }
+}
+
+/// Kind of focus an element can have
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FocusType {
+    /// The focus which receives key events
+    Input,
+    /// The focus of the screen reader
+    Accessibility,
+}
+
+impl FocusType {
+    fn from_raw(focus_type: ArkUI_AccessibilityFocusType) -> Option<Self> {
+        match focus_type {
+            ArkUI_AccessibilityFocusType::ARKUI_ACCESSIBILITY_NATIVE_FOCUS_TYPE_INPUT => {
+                Some(Self::Input)
+            }
+            ArkUI_AccessibilityFocusType::ARKUI_ACCESSIBILITY_NATIVE_FOCUS_TYPE_ACCESSIBILITY => {
+                Some(Self::Accessibility)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Direction in which the focus should move
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FocusMoveDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+    /// The next element in reading order
+    Forward,
+    /// The previous element in reading order
+    Backward,
+}
+
+impl FocusMoveDirection {
+    fn from_raw(direction: ArkUI_AccessibilityFocusMoveDirection) -> Option<Self> {
+        type Raw = ArkUI_AccessibilityFocusMoveDirection;
+        match direction {
+            Raw::ARKUI_ACCESSIBILITY_NATIVE_DIRECTION_UP => Some(Self::Up),
+            Raw::ARKUI_ACCESSIBILITY_NATIVE_DIRECTION_DOWN => Some(Self::Down),
+            Raw::ARKUI_ACCESSIBILITY_NATIVE_DIRECTION_LEFT => Some(Self::Left),
+            Raw::ARKUI_ACCESSIBILITY_NATIVE_DIRECTION_RIGHT => Some(Self::Right),
+            Raw::ARKUI_ACCESSIBILITY_NATIVE_DIRECTION_FORWARD => Some(Self::Forward),
+            Raw::ARKUI_ACCESSIBILITY_NATIVE_DIRECTION_BACKWARD => Some(Self::Backward),
+            _ => None,
+        }
+    }
+}
+
+/// Bounds of an element in physical pixels, relative to the display
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScreenRect {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+/// An action an accessibility service can perform on an element
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    Click,
+    LongClick,
+    /// Moves the screen reader focus to the element
+    GainAccessibilityFocus,
+    /// Removes the screen reader focus from the element
+    ClearAccessibilityFocus,
+    ScrollForward,
+    ScrollBackward,
+    Copy,
+    Paste,
+    Cut,
+    SelectText,
+    SetText,
+    SetCursorPosition,
+}
+
+impl Action {
+    pub fn from_raw(action: ArkUI_Accessibility_ActionType) -> Option<Self> {
+        type Raw = ArkUI_Accessibility_ActionType;
+        match action {
+            Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_CLICK => Some(Self::Click),
+            Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_LONG_CLICK => Some(Self::LongClick),
+            Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_GAIN_ACCESSIBILITY_FOCUS => {
+                Some(Self::GainAccessibilityFocus)
+            }
+            Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_CLEAR_ACCESSIBILITY_FOCUS => {
+                Some(Self::ClearAccessibilityFocus)
+            }
+            Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_SCROLL_FORWARD => Some(Self::ScrollForward),
+            Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_SCROLL_BACKWARD => {
+                Some(Self::ScrollBackward)
+            }
+            Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_COPY => Some(Self::Copy),
+            Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_PASTE => Some(Self::Paste),
+            Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_CUT => Some(Self::Cut),
+            Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_SELECT_TEXT => Some(Self::SelectText),
+            Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_SET_TEXT => Some(Self::SetText),
+            Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_SET_CURSOR_POSITION => {
+                Some(Self::SetCursorPosition)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_raw(self) -> ArkUI_Accessibility_ActionType {
+        type Raw = ArkUI_Accessibility_ActionType;
+        match self {
+            Self::Click => Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_CLICK,
+            Self::LongClick => Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_LONG_CLICK,
+            Self::GainAccessibilityFocus => {
+                Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_GAIN_ACCESSIBILITY_FOCUS
+            }
+            Self::ClearAccessibilityFocus => {
+                Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_CLEAR_ACCESSIBILITY_FOCUS
+            }
+            Self::ScrollForward => Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_SCROLL_FORWARD,
+            Self::ScrollBackward => Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_SCROLL_BACKWARD,
+            Self::Copy => Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_COPY,
+            Self::Paste => Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_PASTE,
+            Self::Cut => Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_CUT,
+            Self::SelectText => Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_SELECT_TEXT,
+            Self::SetText => Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_SET_TEXT,
+            Self::SetCursorPosition => {
+                Raw::ARKUI_ACCESSIBILITY_NATIVE_ACTION_TYPE_SET_CURSOR_POSITION
+            }
+        }
+    }
+}
+
+/// An action requested by an accessibility service, together with its arguments
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ActionRequest {
+    Click,
+    LongClick,
+    GainAccessibilityFocus,
+    ClearAccessibilityFocus,
+    ScrollForward,
+    ScrollBackward,
+    Copy,
+    Paste,
+    Cut,
+    /// Selects the text between the character offsets `start` and `end`
+    SelectText {
+        start: i32,
+        end: i32,
+        /// Whether the selection extends forward from `start`
+        forward: bool,
+    },
+    /// Replaces the text of the element
+    SetText(String),
+    /// Moves the cursor to the given character offset
+    SetCursorPosition(i32),
+}
+
+impl ActionRequest {
+    /// The kind of action requested
+    pub fn action(&self) -> Action {
+        match self {
+            Self::Click => Action::Click,
+            Self::LongClick => Action::LongClick,
+            Self::GainAccessibilityFocus => Action::GainAccessibilityFocus,
+            Self::ClearAccessibilityFocus => Action::ClearAccessibilityFocus,
+            Self::ScrollForward => Action::ScrollForward,
+            Self::ScrollBackward => Action::ScrollBackward,
+            Self::Copy => Action::Copy,
+            Self::Paste => Action::Paste,
+            Self::Cut => Action::Cut,
+            Self::SelectText { .. } => Action::SelectText,
+            Self::SetText(_) => Action::SetText,
+            Self::SetCursorPosition(_) => Action::SetCursorPosition,
+        }
+    }
+
+    fn from_raw(
+        action: ArkUI_Accessibility_ActionType,
+        arguments: &ActionArguments<'_>,
+    ) -> Option<Self> {
+        let int = |key| arguments.get(key)?.parse().ok();
+        Some(match Action::from_raw(action)? {
+            Action::Click => Self::Click,
+            Action::LongClick => Self::LongClick,
+            Action::GainAccessibilityFocus => Self::GainAccessibilityFocus,
+            Action::ClearAccessibilityFocus => Self::ClearAccessibilityFocus,
+            Action::ScrollForward => Self::ScrollForward,
+            Action::ScrollBackward => Self::ScrollBackward,
+            Action::Copy => Self::Copy,
+            Action::Paste => Self::Paste,
+            Action::Cut => Self::Cut,
+            Action::SelectText => Self::SelectText {
+                start: int("selectTextBegin")?,
+                end: int("selectTextEnd")?,
+                forward: arguments.get("selectTextInForWard").as_deref() == Some("true"),
+            },
+            Action::SetText => Self::SetText(arguments.get("setText").unwrap_or_default()),
+            Action::SetCursorPosition => Self::SetCursorPosition(int("offset")?),
+        })
+    }
+}
+
+/// Change of an element reported to accessibility services
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EventType {
+    Clicked,
+    LongClicked,
+    Selected,
+    TextUpdate,
+    /// The page was resized or moved
+    PageStateUpdate,
+    /// Elements were added, removed or changed
+    PageContentUpdate,
+    Scrolled,
+    AccessibilityFocused,
+    AccessibilityFocusCleared,
+    PageOpen,
+    PageClose,
+    /// The bounds or content of the focused element changed
+    FocusNodeUpdate,
+}
+
+impl EventType {
+    fn as_raw(self) -> ArkUI_AccessibilityEventType {
+        type Raw = ArkUI_AccessibilityEventType;
+        match self {
+            Self::Clicked => Raw::ARKUI_ACCESSIBILITY_NATIVE_EVENT_TYPE_CLICKED,
+            Self::LongClicked => Raw::ARKUI_ACCESSIBILITY_NATIVE_EVENT_TYPE_LONG_CLICKED,
+            Self::Selected => Raw::ARKUI_ACCESSIBILITY_NATIVE_EVENT_TYPE_SELECTED,
+            Self::TextUpdate => Raw::ARKUI_ACCESSIBILITY_NATIVE_EVENT_TYPE_TEXT_UPDATE,
+            Self::PageStateUpdate => Raw::ARKUI_ACCESSIBILITY_NATIVE_EVENT_TYPE_PAGE_STATE_UPDATE,
+            Self::PageContentUpdate => {
+                Raw::ARKUI_ACCESSIBILITY_NATIVE_EVENT_TYPE_PAGE_CONTENT_UPDATE
+            }
+            Self::Scrolled => Raw::ARKUI_ACCESSIBILITY_NATIVE_EVENT_TYPE_SCROLLED,
+            Self::AccessibilityFocused => {
+                Raw::ARKUI_ACCESSIBILITY_NATIVE_EVENT_TYPE_ACCESSIBILITY_FOCUSED
+            }
+            Self::AccessibilityFocusCleared => {
+                Raw::ARKUI_ACCESSIBILITY_NATIVE_EVENT_TYPE_ACCESSIBILITY_FOCUS_CLEARED
+            }
+            Self::PageOpen => Raw::ARKUI_ACCESSIBILITY_NATIVE_EVENT_TYPE_PAGE_OPEN,
+            Self::PageClose => Raw::ARKUI_ACCESSIBILITY_NATIVE_EVENT_TYPE_PAGE_CLOSE,
+            Self::FocusNodeUpdate => Raw::ARKUI_ACCESSIBILITY_NATIVE_EVENT_TYPE_FOCUS_NODE_UPDATE,
+        }
+    }
+}
+
+/// Responds to the requests of accessibility services
+///
+/// Element ids are chosen by the handler, except for `-1`, which requests the root element.
+/// The callbacks return `Ok(())` or one of the `RESULT_*` error codes.
+pub trait AccessibilityHandler: Send {
+    /// Adds the element `element_id` to `elements`, followed by the elements requested by `mode`
+    fn find_by_id(
+        &mut self,
+        element_id: i64,
+        mode: SearchMode,
+        elements: &mut ElementInfoList<'_>,
+    ) -> Result<(), i32>;
+
+    /// Adds the elements below `element_id` containing `text` to `elements`
+    fn find_by_text(
+        &mut self,
+        element_id: i64,
+        text: &str,
+        elements: &mut ElementInfoList<'_>,
+    ) -> Result<(), i32> {
+        let _ = (element_id, text, elements);
+        Ok(())
+    }
+
+    /// Fills `element` with the element below `element_id` which has the focus of `focus_type`
+    fn find_focused(
+        &mut self,
+        element_id: i64,
+        focus_type: FocusType,
+        element: &mut ElementInfo<'_>,
+    ) -> Result<(), i32> {
+        let _ = (element_id, focus_type, element);
+        Err(RESULT_FAILED)
+    }
+
+    /// Fills `element` with the element which should get the focus after `element_id`
+    fn find_next_focus(
+        &mut self,
+        element_id: i64,
+        direction: FocusMoveDirection,
+        element: &mut ElementInfo<'_>,
+    ) -> Result<(), i32> {
+        let _ = (element_id, direction, element);
+        Err(RESULT_FAILED)
+    }
+
+    /// Performs `request` on the element `element_id`
+    fn execute_action(&mut self, element_id: i64, request: ActionRequest) -> Result<(), i32> {
+        let _ = (element_id, request);
+        Err(RESULT_FAILED)
+    }
+
+    /// Removes the accessibility focus from the focused element
+    fn clear_focus(&mut self) -> Result<(), i32> {
+        Ok(())
+    }
+
+    /// Returns the cursor position within the text of the element `element_id`
+    fn cursor_position(&mut self, element_id: i64) -> Result<i32, i32> {
+        let _ = element_id;
+        Err(RESULT_FAILED)
+    }
+}
+
+/// List of elements returned to an accessibility service
+pub struct ElementInfoList<'a> {
+    raw: NonNull<ArkUI_AccessibilityElementInfoList>,
+    phantom: PhantomData<&'a mut ArkUI_AccessibilityElementInfoList>,
+}
+
+impl ElementInfoList<'_> {
+    /// Appends a new element to the list
+    pub fn add(&mut self) -> Result<ElementInfo<'_>, i32> {
+        let _span = ffi_span!("OH_ArkUI_AddAndGetAccessibilityElementInfo");
+        let raw = unsafe { OH_ArkUI_AddAndGetAccessibilityElementInfo(self.raw.as_ptr()) };
+        let Some(raw) = NonNull::new(raw) else {
+            error!("OH_ArkUI_AddAndGetAccessibilityElementInfo failed");
+            return Err(RESULT_OUT_OF_MEMORY);
+        };
+        Ok(ElementInfo {
+            raw,
+            phantom: PhantomData,
+        })
+    }
+}
+
+/// Properties of an element returned to an accessibility service
+pub struct ElementInfo<'a> {
+    raw: NonNull<ArkUI_AccessibilityElementInfo>,
+    phantom: PhantomData<&'a mut ArkUI_AccessibilityElementInfo>,
+}
+
+fn check(function: &str, res: i32) -> Result<(), i32> {
+    if res != 0 {
+        error!("{function} failed with {res}");
+        return Err(res);
+    }
+    Ok(())
+}
+
+fn to_c_string(s: &str) -> Result<CString, i32> {
+    CString::new(s).map_err(|_| RESULT_BAD_PARAMETER)
+}
+
+macro_rules! set_str {
+    ($(#[$attr:meta])* $name:ident, $function:ident) => {
+        $(#[$attr])*
+        pub fn $name(&mut self, value: &str) -> Result<(), i32> {
+            let value = to_c_string(value)?;
+            let _span = ffi_span!(stringify!($function));
+            check(stringify!($function), unsafe {
+                $function(self.raw.as_ptr(), value.as_ptr())
+            })
+        }
+    };
+}
+
+macro_rules! set_bool {
+    ($(#[$attr:meta])* $name:ident, $function:ident) => {
+        $(#[$attr])*
+        pub fn $name(&mut self, value: bool) -> Result<(), i32> {
+            let _span = ffi_span!(stringify!($function));
+            check(stringify!($function), unsafe { $function(self.raw.as_ptr(), value) })
+        }
+    };
+}
+
+impl ElementInfo<'_> {
+    /// Sets the id of the element
+    pub fn set_element_id(&mut self, element_id: i32) -> Result<(), i32> {
+        let _span = ffi_span!("OH_ArkUI_AccessibilityElementInfoSetElementId");
+        check("OH_ArkUI_AccessibilityElementInfoSetElementId", unsafe {
+            OH_ArkUI_AccessibilityElementInfoSetElementId(self.raw.as_ptr(), element_id)
+        })
+    }
+
+    /// Sets the id of the parent element, [`ROOT_PARENT_ID`] for the root element
+    pub fn set_parent_id(&mut self, parent_id: i32) -> Result<(), i32> {
+        let _span = ffi_span!("OH_ArkUI_AccessibilityElementInfoSetParentId");
+        check("OH_ArkUI_AccessibilityElementInfoSetParentId", unsafe {
+            OH_ArkUI_AccessibilityElementInfoSetParentId(self.raw.as_ptr(), parent_id)
+        })
+    }
+
+    /// Sets the ids of the child elements
+    pub fn set_child_ids(&mut self, child_ids: &[i64]) -> Result<(), i32> {
+        let mut child_ids = child_ids.to_vec();
+        let count = i32::try_from(child_ids.len()).map_err(|_| RESULT_BAD_PARAMETER)?;
+        let _span = ffi_span!("OH_ArkUI_AccessibilityElementInfoSetChildNodeIds");
+        check("OH_ArkUI_AccessibilityElementInfoSetChildNodeIds", unsafe {
+            OH_ArkUI_AccessibilityElementInfoSetChildNodeIds(
+                self.raw.as_ptr(),
+                count,
+                child_ids.as_mut_ptr(),
+            )
+        })
+    }
+
+    /// Sets the actions the element supports, together with a description for each action
+    pub fn set_actions(
+        &mut self,
+        actions: &[(ArkUI_Accessibility_ActionType, &str)],
+    ) -> Result<(), i32> {
+        let descriptions = actions
+            .iter()
+            .map(|(_, description)| to_c_string(description))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut actions: Vec<ArkUI_AccessibleAction> = actions
+            .iter()
+            .zip(&descriptions)
+            .map(|((action_type, _), description)| ArkUI_AccessibleAction {
+                actionType: *action_type,
+                description: description.as_ptr(),
+            })
+            .collect();
+        let count = i32::try_from(actions.len()).map_err(|_| RESULT_BAD_PARAMETER)?;
+        let _span = ffi_span!("OH_ArkUI_AccessibilityElementInfoSetOperationActions");
+        check(
+            "OH_ArkUI_AccessibilityElementInfoSetOperationActions",
+            unsafe {
+                OH_ArkUI_AccessibilityElementInfoSetOperationActions(
+                    self.raw.as_ptr(),
+                    count,
+                    actions.as_mut_ptr(),
+                )
+            },
+        )
+    }
+
+    /// Sets the bounds of the element
+    pub fn set_screen_rect(&mut self, rect: ScreenRect) -> Result<(), i32> {
+        let mut rect = ArkUI_AccessibleRect {
+            leftTopX: rect.left,
+            leftTopY: rect.top,
+            rightBottomX: rect.right,
+            rightBottomY: rect.bottom,
+        };
+        let _span = ffi_span!("OH_ArkUI_AccessibilityElementInfoSetScreenRect");
+        check("OH_ArkUI_AccessibilityElementInfoSetScreenRect", unsafe {
+            OH_ArkUI_AccessibilityElementInfoSetScreenRect(self.raw.as_ptr(), &mut rect)
+        })
+    }
+
+    set_str!(
+        /// Sets the kind of component, e.g. `"Button"` or `"Text"`
+        set_component_type,
+        OH_ArkUI_AccessibilityElementInfoSetComponentType
+    );
+    set_str!(
+        /// Sets the text content of the element
+        set_contents,
+        OH_ArkUI_AccessibilityElementInfoSetContents
+    );
+    set_str!(
+        /// Sets the hint shown in an empty text field
+        set_hint_text,
+        OH_ArkUI_AccessibilityElementInfoSetHintText
+    );
+    set_str!(
+        /// Sets the text read by the screen reader
+        set_accessibility_text,
+        OH_ArkUI_AccessibilityElementInfoSetAccessibilityText
+    );
+    set_bool!(set_checkable, OH_ArkUI_AccessibilityElementInfoSetCheckable);
+    set_bool!(set_checked, OH_ArkUI_AccessibilityElementInfoSetChecked);
+    set_bool!(set_focusable, OH_ArkUI_AccessibilityElementInfoSetFocusable);
+    set_bool!(set_focused, OH_ArkUI_AccessibilityElementInfoSetFocused);
+    set_bool!(set_visible, OH_ArkUI_AccessibilityElementInfoSetVisible);
+    set_bool!(
+        set_accessibility_focused,
+        OH_ArkUI_AccessibilityElementInfoSetAccessibilityFocused
+    );
+    set_bool!(set_selected, OH_ArkUI_AccessibilityElementInfoSetSelected);
+    set_bool!(set_clickable, OH_ArkUI_AccessibilityElementInfoSetClickable);
+    set_bool!(
+        set_long_clickable,
+        OH_ArkUI_AccessibilityElementInfoSetLongClickable
+    );
+    set_bool!(set_enabled, OH_ArkUI_AccessibilityElementInfoSetEnabled);
+    set_bool!(
+        set_scrollable,
+        OH_ArkUI_AccessibilityElementInfoSetScrollable
+    );
+    set_bool!(set_editable, OH_ArkUI_AccessibilityElementInfoSetEditable);
+}
+
+/// Arguments of an accessibility action
+struct ActionArguments<'a> {
+    raw: *mut ArkUI_AccessibilityActionArguments,
+    phantom: PhantomData<&'a ArkUI_AccessibilityActionArguments>,
+}
+
+impl ActionArguments<'_> {
+    /// Returns the value of the argument `key`, if the action has one
+    fn get(&self, key: &str) -> Option<String> {
+        if self.raw.is_null() {
+            return None;
+        }
+        let key = to_c_string(key).ok()?;
+        let mut value: *mut c_char = core::ptr::null_mut();
+        let _span = ffi_span!("OH_ArkUI_FindAccessibilityActionArgumentByKey");
+        let res = unsafe {
+            OH_ArkUI_FindAccessibilityActionArgumentByKey(self.raw, key.as_ptr(), &mut value)
+        };
+        if res != 0 || value.is_null() {
+            return None;
+        }
+        // SAFETY: The value is a NUL-terminated string owned by the arguments.
+        Some(
+            unsafe { CStr::from_ptr(value) }
+                .to_string_lossy()
+                .into_owned(),
+        )
+    }
+}
+
+/// Accessibility provider of an XComponent
+///
+/// Obtained via [`XComponent::accessibility_provider`](crate::XComponent::accessibility_provider).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccessibilityProvider(NonNull<ArkUI_AccessibilityProvider>);
+
+impl AccessibilityProvider {
+    pub(crate) fn new(raw: NonNull<ArkUI_AccessibilityProvider>) -> Self {
+        Self(raw)
+    }
+
+    pub fn as_raw(&self) -> *mut ArkUI_AccessibilityProvider {
+        self.0.as_ptr()
+    }
+
+    /// Forwards the requests of accessibility services to `handler`
+    ///
+    /// Replaces the handler registered previously, also for other providers.
+    pub fn register(&self, handler: impl AccessibilityHandler + 'static) -> Result<(), i32> {
+        let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+        state.handler = Some(Box::new(handler));
+        // The callbacks live in a static, so the pointer stays valid after unlocking.
+        let callbacks: *mut ArkUI_AccessibilityProviderCallbacks = &mut state.callbacks;
+        drop(state);
+        let _span = ffi_span!("OH_ArkUI_AccessibilityProviderRegisterCallback");
+        let res =
+            unsafe { OH_ArkUI_AccessibilityProviderRegisterCallback(self.as_raw(), callbacks) };
+        check("OH_ArkUI_AccessibilityProviderRegisterCallback", res)
+    }
+
+    /// Reports a change of an element to accessibility services
+    ///
+    /// `fill` sets the properties of the element which changed. E.g. to move the focus
+    /// highlight of the screen reader, send [`EventType::FocusNodeUpdate`] with the new bounds
+    /// of the focused element whenever it moves or changes its size.
+    pub fn send_event(
+        &self,
+        event_type: EventType,
+        fill: impl FnOnce(&mut ElementInfo<'_>) -> Result<(), i32>,
+    ) -> Result<(), i32> {
+        let _span = ffi_span!("OH_ArkUI_SendAccessibilityAsyncEvent");
+        unsafe {
+            let element = NonNull::new(OH_ArkUI_CreateAccessibilityElementInfo())
+                .ok_or(RESULT_OUT_OF_MEMORY)?;
+            let Some(event) = NonNull::new(OH_ArkUI_CreateAccessibilityEventInfo()) else {
+                OH_ArkUI_DestoryAccessibilityElementInfo(element.as_ptr());
+                return Err(RESULT_OUT_OF_MEMORY);
+            };
+            let res = fill(&mut ElementInfo {
+                raw: element,
+                phantom: PhantomData,
+            })
+            .and_then(|()| {
+                check(
+                    "OH_ArkUI_AccessibilityEventSetEventType",
+                    OH_ArkUI_AccessibilityEventSetEventType(event.as_ptr(), event_type.as_raw()),
+                )
+            })
+            .and_then(|()| {
+                check(
+                    "OH_ArkUI_AccessibilityEventSetElementInfo",
+                    OH_ArkUI_AccessibilityEventSetElementInfo(event.as_ptr(), element.as_ptr()),
+                )
+            });
+            if res.is_ok() {
+                // The event is copied before the call returns.
+                OH_ArkUI_SendAccessibilityAsyncEvent(
+                    self.as_raw(),
+                    event.as_ptr(),
+                    Some(send_event_done),
+                );
+            }
+            OH_ArkUI_DestoryAccessibilityEventInfo(event.as_ptr());
+            OH_ArkUI_DestoryAccessibilityElementInfo(element.as_ptr());
+            res
+        }
+    }
+}
+
+unsafe extern "C" fn send_event_done(res: i32) {
+    if res != 0 {
+        error!("OH_ArkUI_SendAccessibilityAsyncEvent failed with {res}");
+    }
+}
+
+struct State {
+    handler: Option<Box<dyn AccessibilityHandler>>,
+    callbacks: ArkUI_AccessibilityProviderCallbacks,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    handler: None,
+    callbacks: ArkUI_AccessibilityProviderCallbacks {
+        findAccessibilityNodeInfosById: Some(find_by_id),
+        findAccessibilityNodeInfosByText: Some(find_by_text),
+        findFocusedAccessibilityNode: Some(find_focused),
+        findNextFocusAccessibilityNode: Some(find_next_focus),
+        executeAccessibilityAction: Some(execute_action),
+        clearFocusedFocusAccessibilityNode: Some(clear_focus),
+        getAccessibilityNodeCursorPosition: Some(cursor_position),
+    },
+});
+
+/// Calls `f` with the registered handler and converts the result into an error code
+fn with_handler(f: impl FnOnce(&mut dyn AccessibilityHandler) -> Result<(), i32>) -> i32 {
+    let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(handler) = state.handler.as_deref_mut() else {
+        return RESULT_FAILED;
+    };
+    match crate::panic_guard::catch("AccessibilityHandler", || f(handler)) {
+        Some(Ok(())) => 0,
+        Some(Err(res)) => res,
+        None => RESULT_FAILED,
+    }
+}
+
+fn element_info_list<'a>(
+    raw: *mut ArkUI_AccessibilityElementInfoList,
+) -> Result<ElementInfoList<'a>, i32> {
+    let raw = NonNull::new(raw).ok_or(RESULT_BAD_PARAMETER)?;
+    Ok(ElementInfoList {
+        raw,
+        phantom: PhantomData,
+    })
+}
+
+fn element_info<'a>(raw: *mut ArkUI_AccessibilityElementInfo) -> Result<ElementInfo<'a>, i32> {
+    let raw = NonNull::new(raw).ok_or(RESULT_BAD_PARAMETER)?;
+    Ok(ElementInfo {
+        raw,
+        phantom: PhantomData,
+    })
+}
+
+unsafe extern "C" fn find_by_id(
+    element_id: i64,
+    mode: ArkUI_AccessibilitySearchMode,
+    _request_id: i32,
+    elements: *mut ArkUI_AccessibilityElementInfoList,
+) -> i32 {
+    with_handler(|handler| {
+        let mut elements = element_info_list(elements)?;
+        handler.find_by_id(element_id, SearchMode(mode.0), &mut elements)
+    })
+}
+
+unsafe extern "C" fn find_by_text(
+    element_id: i64,
+    text: *const c_char,
+    _request_id: i32,
+    elements: *mut ArkUI_AccessibilityElementInfoList,
+) -> i32 {
+    with_handler(|handler| {
+        if text.is_null() {
+            return Err(RESULT_BAD_PARAMETER);
+        }
+        let text = unsafe { CStr::from_ptr(text) }.to_string_lossy();
+        let mut elements = element_info_list(elements)?;
+        handler.find_by_text(element_id, &text, &mut elements)
+    })
+}
+
+unsafe extern "C" fn find_focused(
+    element_id: i64,
+    focus_type: ArkUI_AccessibilityFocusType,
+    _request_id: i32,
+    element: *mut ArkUI_AccessibilityElementInfo,
+) -> i32 {
+    with_handler(|handler| {
+        let focus_type = FocusType::from_raw(focus_type).ok_or(RESULT_BAD_PARAMETER)?;
+        let mut element = element_info(element)?;
+        handler.find_focused(element_id, focus_type, &mut element)
+    })
+}
+
+unsafe extern "C" fn find_next_focus(
+    element_id: i64,
+    direction: ArkUI_AccessibilityFocusMoveDirection,
+    _request_id: i32,
+    element: *mut ArkUI_AccessibilityElementInfo,
+) -> i32 {
+    with_handler(|handler| {
+        let direction = FocusMoveDirection::from_raw(direction).ok_or(RESULT_BAD_PARAMETER)?;
+        let mut element = element_info(element)?;
+        handler.find_next_focus(element_id, direction, &mut element)
+    })
+}
+
+unsafe extern "C" fn execute_action(
+    element_id: i64,
+    action: ArkUI_Accessibility_ActionType,
+    arguments: *mut ArkUI_AccessibilityActionArguments,
+    _request_id: i32,
+) -> i32 {
+    with_handler(|handler| {
+        let arguments = ActionArguments {
+            raw: arguments,
+            phantom: PhantomData,
+        };
+        let request = ActionRequest::from_raw(action, &arguments).ok_or(RESULT_BAD_PARAMETER)?;
+        handler.execute_action(element_id, request)
+    })
+}
+
+unsafe extern "C" fn clear_focus() -> i32 {
+    with_handler(|handler| handler.clear_focus())
+}
+
+unsafe extern "C" fn cursor_position(element_id: i64, _request_id: i32, index: *mut i32) -> i32 {
+    with_handler(|handler| {
+        if index.is_null() {
+            return Err(RESULT_BAD_PARAMETER);
+        }
+        let position = handler.cursor_position(element_id)?;
+        unsafe { index.write(position) };
+        Ok(())
+    })
+}
+
+type ScreenReaderCallback = Box<dyn FnMut(bool) + Send>;
+
+struct ScreenReader {
+    enabled: Option<bool>,
+    next_id: u64,
+    callbacks: Vec<(u64, ScreenReaderCallback)>,
+}
+
+static SCREEN_READER: Mutex<ScreenReader> = Mutex::new(ScreenReader {
+    enabled: None,
+    next_id: 0,
+    callbacks: Vec::new(),
+});
+
+/// Whether a screen reader is running, `None` until the state was set from ArkTS
+pub fn is_screen_reader_enabled() -> Option<bool> {
+    SCREEN_READER
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .enabled
+}
+
+/// Updates the screen reader state and notifies the listeners if it changed
+pub fn set_screen_reader_enabled(enabled: bool) {
+    let mut screen_reader = SCREEN_READER.lock().unwrap_or_else(|e| e.into_inner());
+    if screen_reader.enabled == Some(enabled) {
+        return;
+    }
+    screen_reader.enabled = Some(enabled);
+    for (_, callback) in screen_reader.callbacks.iter_mut() {
+        callback(enabled);
+    }
+}
+
+/// Calls `callback` whenever a screen reader is started or stopped
+///
+/// The callback must not call other functions of this module related to the screen reader
+/// state.
+pub fn on_screen_reader_changed(
+    callback: impl FnMut(bool) + Send + 'static,
+) -> ScreenReaderListener {
+    let mut screen_reader = SCREEN_READER.lock().unwrap_or_else(|e| e.into_inner());
+    let id = screen_reader.next_id;
+    screen_reader.next_id += 1;
+    screen_reader.callbacks.push((id, Box::new(callback)));
+    ScreenReaderListener { id }
+}
+
+/// Handle of a callback registered with [`on_screen_reader_changed`]
+///
+/// The callback is removed when this handle is dropped.
+#[derive(Debug)]
+#[must_use = "the callback is removed when the listener is dropped"]
+pub struct ScreenReaderListener {
+    id: u64,
+}
+
+impl Drop for ScreenReaderListener {
+    fn drop(&mut self) {
+        let mut screen_reader = SCREEN_READER.lock().unwrap_or_else(|e| e.into_inner());
+        screen_reader.callbacks.retain(|(id, _)| *id != self.id);
+    }
+}
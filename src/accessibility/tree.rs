@@ -0,0 +1,428 @@
+//! Virtual tree of accessibility nodes
+
+use super::{
+    AccessibilityHandler, AccessibilityProvider, Action, ActionRequest, ElementInfo,
+    ElementInfoList, EventType, FocusMoveDirection, FocusType, ScreenRect, SearchMode,
+    RESULT_BAD_PARAMETER, RESULT_FAILED, ROOT_PARENT_ID,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Kind of widget an accessibility node represents
+///
+/// Screen readers announce the role together with the label of the node.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Role {
+    /// A container or decoration without a specific role
+    #[default]
+    Generic,
+    Button,
+    Text,
+    Image,
+    CheckBox,
+    Switch,
+    Slider,
+    TextField,
+    List,
+    ListItem,
+    ScrollView,
+    /// Any other ArkUI component type, e.g. `"Radio"`
+    Custom(&'static str),
+}
+
+impl Role {
+    fn component_type(self) -> Option<&'static str> {
+        match self {
+            Self::Generic => None,
+            Self::Button => Some("Button"),
+            Self::Text => Some("Text"),
+            Self::Image => Some("Image"),
+            Self::CheckBox => Some("Checkbox"),
+            Self::Switch => Some("Toggle"),
+            Self::Slider => Some("Slider"),
+            Self::TextField => Some("TextInput"),
+            Self::List => Some("List"),
+            Self::ListItem => Some("ListItem"),
+            Self::ScrollView => Some("Scroll"),
+            Self::Custom(component_type) => Some(component_type),
+        }
+    }
+}
+
+/// A widget as seen by accessibility services
+///
+/// Nodes with a label or actions can receive the screen reader focus.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AccessibilityNode {
+    pub role: Role,
+    /// Text read by the screen reader
+    pub label: String,
+    /// Current content, e.g. the text of a text field
+    pub value: String,
+    /// Hint shown in an empty text field
+    pub hint: String,
+    pub bounds: ScreenRect,
+    /// Actions the node supports, besides moving the screen reader focus
+    pub actions: Vec<Action>,
+    /// Ids of the child nodes, in reading order
+    pub children: Vec<i64>,
+    /// Whether the node is checked, `None` if it can't be checked
+    pub checked: Option<bool>,
+    pub selected: bool,
+    pub disabled: bool,
+    /// Whether the node is currently not visible, e.g. scrolled out of view
+    pub hidden: bool,
+}
+
+impl AccessibilityNode {
+    pub fn new(role: Role, label: impl Into<String>) -> Self {
+        Self {
+            role,
+            label: label.into(),
+            ..Default::default()
+        }
+    }
+
+    fn is_focusable(&self) -> bool {
+        !self.label.is_empty() || !self.actions.is_empty()
+    }
+}
+
+/// Virtual tree describing the content of an XComponent
+///
+/// Node ids are chosen by the application and need to fit into an `i32`. `-1` is reserved by
+/// the system to request the root node.
+#[derive(Debug, Default)]
+pub struct AccessibilityTree {
+    root: Option<i64>,
+    nodes: HashMap<i64, AccessibilityNode>,
+    focus: Option<i64>,
+}
+
+impl AccessibilityTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The id of the root node
+    pub fn root(&self) -> Option<i64> {
+        self.root
+    }
+
+    pub fn set_root(&mut self, id: i64) {
+        self.root = Some(id);
+    }
+
+    /// Adds or replaces the node `id`, returning the previous node
+    pub fn insert(&mut self, id: i64, node: AccessibilityNode) -> Option<AccessibilityNode> {
+        self.nodes.insert(id, node)
+    }
+
+    /// Removes the node `id`
+    ///
+    /// The id is not removed from the children of its parent.
+    pub fn remove(&mut self, id: i64) -> Option<AccessibilityNode> {
+        if self.focus == Some(id) {
+            self.focus = None;
+        }
+        if self.root == Some(id) {
+            self.root = None;
+        }
+        self.nodes.remove(&id)
+    }
+
+    /// Removes all nodes
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn get(&self, id: i64) -> Option<&AccessibilityNode> {
+        self.nodes.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: i64) -> Option<&mut AccessibilityNode> {
+        self.nodes.get_mut(&id)
+    }
+
+    /// The id of the node with the screen reader focus
+    pub fn accessibility_focus(&self) -> Option<i64> {
+        self.focus
+    }
+
+    /// Reports the bounds of the node with the screen reader focus to `provider`
+    ///
+    /// Call this whenever the focused node moves or changes its size, so the screen reader
+    /// draws its focus highlight at the right position.
+    pub fn report_focus(&self, provider: &AccessibilityProvider) -> Result<(), i32> {
+        let Some(id) = self.focus else {
+            return Ok(());
+        };
+        provider.send_event(EventType::FocusNodeUpdate, |element| self.fill(id, element))
+    }
+
+    fn resolve(&self, element_id: i64) -> Option<i64> {
+        let id = if element_id == -1 {
+            self.root?
+        } else {
+            element_id
+        };
+        self.nodes.contains_key(&id).then_some(id)
+    }
+
+    fn parent(&self, id: i64) -> Option<i64> {
+        self.nodes
+            .iter()
+            .find(|(_, node)| node.children.contains(&id))
+            .map(|(&parent, _)| parent)
+    }
+
+    /// Appends the descendants of `id` in pre-order, skipping ids without a node
+    fn descendants(&self, id: i64, out: &mut Vec<i64>) {
+        let Some(node) = self.nodes.get(&id) else {
+            return;
+        };
+        for &child in &node.children {
+            if self.nodes.contains_key(&child) && !out.contains(&child) {
+                out.push(child);
+                self.descendants(child, out);
+            }
+        }
+    }
+
+    /// The focusable nodes in reading order
+    fn focus_order(&self) -> Vec<i64> {
+        let Some(root) = self.root else {
+            return Vec::new();
+        };
+        let mut ids = vec![root];
+        self.descendants(root, &mut ids);
+        ids.retain(|id| self.nodes[id].is_focusable());
+        ids
+    }
+
+    fn fill(&self, id: i64, element: &mut ElementInfo<'_>) -> Result<(), i32> {
+        let node = self.nodes.get(&id).ok_or(RESULT_FAILED)?;
+        let focused = self.focus == Some(id);
+        element.set_element_id(to_i32(id)?)?;
+        element.set_parent_id(match self.parent(id) {
+            Some(parent) => to_i32(parent)?,
+            None => ROOT_PARENT_ID,
+        })?;
+        if let Some(component_type) = node.role.component_type() {
+            element.set_component_type(component_type)?;
+        }
+        element.set_accessibility_text(&node.label)?;
+        element.set_contents(&node.value)?;
+        element.set_hint_text(&node.hint)?;
+        element.set_child_ids(&node.children)?;
+        element.set_screen_rect(node.bounds)?;
+
+        let focus_action = if focused {
+            Action::ClearAccessibilityFocus
+        } else {
+            Action::GainAccessibilityFocus
+        };
+        let actions: Vec<_> = node
+            .actions
+            .iter()
+            .chain(node.is_focusable().then_some(&focus_action))
+            .map(|action| (action.as_raw(), ""))
+            .collect();
+        element.set_actions(&actions)?;
+
+        let supports = |action| node.actions.contains(&action);
+        element.set_checkable(node.checked.is_some())?;
+        element.set_checked(node.checked.unwrap_or(false))?;
+        element.set_selected(node.selected)?;
+        element.set_enabled(!node.disabled)?;
+        element.set_visible(!node.hidden)?;
+        element.set_focusable(node.is_focusable())?;
+        element.set_accessibility_focused(focused)?;
+        element.set_clickable(supports(Action::Click))?;
+        element.set_long_clickable(supports(Action::LongClick))?;
+        element
+            .set_scrollable(supports(Action::ScrollForward) || supports(Action::ScrollBackward))?;
+        element.set_editable(supports(Action::SetText))
+    }
+
+    fn add(&self, id: i64, elements: &mut ElementInfoList<'_>) -> Result<(), i32> {
+        self.fill(id, &mut elements.add()?)
+    }
+}
+
+fn to_i32(id: i64) -> Result<i32, i32> {
+    i32::try_from(id).map_err(|_| RESULT_BAD_PARAMETER)
+}
+
+/// [`AccessibilityHandler`] answering requests from an [`AccessibilityTree`]
+///
+/// The tree is shared with the application, which updates it whenever its content changes.
+/// Moving the screen reader focus is handled by the tree, all other actions are passed to the
+/// action callback.
+pub struct TreeHandler {
+    tree: Arc<Mutex<AccessibilityTree>>,
+    on_action: Box<dyn FnMut(i64, ActionRequest) -> bool + Send>,
+}
+
+impl TreeHandler {
+    /// Creates a handler for `tree`
+    ///
+    /// `on_action` is called with the node id for every action requested on a node supporting
+    /// it, and returns whether the action was performed. It is also informed when the screen
+    /// reader focus moves, but its result is ignored in that case.
+    pub fn new(
+        tree: Arc<Mutex<AccessibilityTree>>,
+        on_action: impl FnMut(i64, ActionRequest) -> bool + Send + 'static,
+    ) -> Self {
+        Self {
+            tree,
+            on_action: Box::new(on_action),
+        }
+    }
+
+    /// The tree this handler answers requests from
+    pub fn tree(&self) -> &Arc<Mutex<AccessibilityTree>> {
+        &self.tree
+    }
+
+    fn with_tree<R>(&self, f: impl FnOnce(&mut AccessibilityTree) -> R) -> R {
+        f(&mut self.tree.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl AccessibilityHandler for TreeHandler {
+    fn find_by_id(
+        &mut self,
+        element_id: i64,
+        mode: SearchMode,
+        elements: &mut ElementInfoList<'_>,
+    ) -> Result<(), i32> {
+        self.with_tree(|tree| {
+            let id = tree.resolve(element_id).ok_or(RESULT_FAILED)?;
+            tree.add(id, elements)?;
+            if mode.contains(SearchMode::PREDECESSORS) {
+                let mut ancestor = tree.parent(id);
+                // Bounded, in case the application created a cycle.
+                for _ in 0..tree.nodes.len() {
+                    let Some(parent) = ancestor else { break };
+                    tree.add(parent, elements)?;
+                    ancestor = tree.parent(parent);
+                }
+            }
+            if mode.contains(SearchMode::SIBLINGS) {
+                if let Some(parent) = tree.parent(id) {
+                    for &sibling in &tree.nodes[&parent].children {
+                        if sibling != id && tree.nodes.contains_key(&sibling) {
+                            tree.add(sibling, elements)?;
+                        }
+                    }
+                }
+            }
+            let mut children = Vec::new();
+            if mode.contains(SearchMode::RECURSIVE_CHILDREN) {
+                tree.descendants(id, &mut children);
+            } else if mode.contains(SearchMode::CHILDREN) {
+                children.extend(
+                    tree.nodes[&id]
+                        .children
+                        .iter()
+                        .filter(|child| tree.nodes.contains_key(child)),
+                );
+            }
+            for child in children {
+                tree.add(child, elements)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn find_by_text(
+        &mut self,
+        element_id: i64,
+        text: &str,
+        elements: &mut ElementInfoList<'_>,
+    ) -> Result<(), i32> {
+        self.with_tree(|tree| {
+            let id = tree.resolve(element_id).ok_or(RESULT_FAILED)?;
+            let mut ids = vec![id];
+            tree.descendants(id, &mut ids);
+            for id in ids {
+                let node = &tree.nodes[&id];
+                if node.label.contains(text) || node.value.contains(text) {
+                    tree.add(id, elements)?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn find_focused(
+        &mut self,
+        _element_id: i64,
+        focus_type: FocusType,
+        element: &mut ElementInfo<'_>,
+    ) -> Result<(), i32> {
+        self.with_tree(|tree| match (focus_type, tree.focus) {
+            (FocusType::Accessibility, Some(id)) => tree.fill(id, element),
+            _ => Err(RESULT_FAILED),
+        })
+    }
+
+    /// Moves through the focusable nodes in reading order
+    ///
+    /// `Up` and `Left` move like `Backward`, `Down` and `Right` like `Forward`.
+    fn find_next_focus(
+        &mut self,
+        element_id: i64,
+        direction: FocusMoveDirection,
+        element: &mut ElementInfo<'_>,
+    ) -> Result<(), i32> {
+        self.with_tree(|tree| {
+            let order = tree.focus_order();
+            let current = tree
+                .resolve(element_id)
+                .and_then(|id| order.iter().position(|&other| other == id));
+            let forward = matches!(
+                direction,
+                FocusMoveDirection::Forward | FocusMoveDirection::Down | FocusMoveDirection::Right
+            );
+            let next = match (current, forward) {
+                (Some(index), true) => order.get(index + 1),
+                (Some(index), false) => index.checked_sub(1).and_then(|index| order.get(index)),
+                (None, true) => order.first(),
+                (None, false) => order.last(),
+            };
+            tree.fill(*next.ok_or(RESULT_FAILED)?, element)
+        })
+    }
+
+    fn execute_action(&mut self, element_id: i64, request: ActionRequest) -> Result<(), i32> {
+        let action = request.action();
+        let id = self.with_tree(|tree| {
+            let id = tree.resolve(element_id).ok_or(RESULT_FAILED)?;
+            match action {
+                Action::GainAccessibilityFocus => tree.focus = Some(id),
+                Action::ClearAccessibilityFocus if tree.focus == Some(id) => tree.focus = None,
+                Action::ClearAccessibilityFocus => {}
+                _ if !tree.nodes[&id].actions.contains(&action) => return Err(RESULT_FAILED),
+                _ => {}
+            }
+            Ok(id)
+        })?;
+        // The tree is unlocked, so the callback may update it.
+        let performed = (self.on_action)(id, request);
+        match action {
+            Action::GainAccessibilityFocus | Action::ClearAccessibilityFocus => Ok(()),
+            _ if performed => Ok(()),
+            _ => Err(RESULT_FAILED),
+        }
+    }
+
+    fn clear_focus(&mut self) -> Result<(), i32> {
+        let focus = self.with_tree(|tree| tree.focus.take());
+        if let Some(id) = focus {
+            (self.on_action)(id, ActionRequest::ClearAccessibilityFocus);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,346 @@
+//! Screen reader support for toolkits describing their widgets with [AccessKit](::accesskit)
+//!
+//! [`Adapter`] mirrors the [`TreeUpdate`]s of an AccessKit tree into an [`AccessibilityTree`]
+//! and registers a [`TreeHandler`] for it, so screen readers read the AccessKit nodes and their
+//! actions are passed to an AccessKit [`ActionHandler`].
+//!
+//! AccessKit node ids are mapped to ArkUI element ids, and node bounds are converted to the
+//! screen rects ArkUI expects. AccessKit coordinates are physical pixels relative to the
+//! XComponent, [`Adapter::set_origin`] sets the position of the XComponent on the display.
+//!
+//! Only actions with an ArkUI equivalent are supported: `Click`, `ShowContextMenu` (long click),
+//! `ScrollDown` and `ScrollUp` (scroll forward and backward) and `SetValue` (set text). The
+//! screen reader focus is tracked by the [`AccessibilityTree`], independent of the keyboard
+//! focus of the updates.
+//!
+//! ## Example
+//! ```no_run
+//! use accesskit::{
+//!     ActionHandler, ActionRequest, Node, NodeId, Rect, Role, Tree, TreeId, TreeUpdate,
+//! };
+//! use xcomponent::accesskit::Adapter;
+//!
+//! struct Actions;
+//!
+//! impl ActionHandler for Actions {
+//!     fn do_action(&mut self, request: ActionRequest) {
+//!         // Queue the action for the toolkit ...
+//!     }
+//! }
+//!
+//! # fn on_surface_created(xcomponent: xcomponent::XComponent) {
+//! // Call this from `OnSurfaceCreated`.
+//! let provider = xcomponent.accessibility_provider().unwrap();
+//! let mut adapter = Adapter::new(provider, Actions).unwrap();
+//!
+//! // Whenever the UI changed:
+//! let mut button = Node::new(Role::Button);
+//! button.set_label("OK");
+//! button.set_bounds(Rect::new(100.0, 100.0, 300.0, 160.0));
+//! button.add_action(accesskit::Action::Click);
+//! let mut window = Node::new(Role::Window);
+//! window.set_children([NodeId(1)]);
+//! adapter.update(TreeUpdate {
+//!     nodes: vec![(NodeId(0), window), (NodeId(1), button)],
+//!     tree: Some(Tree::new(NodeId(0))),
+//!     tree_id: TreeId::ROOT,
+//!     focus: NodeId(0),
+//! });
+//! # }
+//! ```
+
+use crate::accessibility::{
+    AccessibilityNode, AccessibilityProvider, AccessibilityTree, Action, ActionRequest, Role,
+    ScreenRect, TreeHandler,
+};
+use crate::log::error;
+use crate::Offset;
+use ::accesskit::{
+    Action as AccessKitAction, ActionData, ActionHandler, ActionRequest as AccessKitRequest,
+    Affine, Node, NodeId, Role as AccessKitRole, Toggled, TreeId, TreeUpdate,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// ArkUI actions and the AccessKit actions they are performed as
+const ACTIONS: [(Action, AccessKitAction); 5] = [
+    (Action::Click, AccessKitAction::Click),
+    (Action::LongClick, AccessKitAction::ShowContextMenu),
+    (Action::ScrollForward, AccessKitAction::ScrollDown),
+    (Action::ScrollBackward, AccessKitAction::ScrollUp),
+    (Action::SetText, AccessKitAction::SetValue),
+];
+
+/// Mapping between AccessKit node ids and ArkUI element ids
+#[derive(Default)]
+struct Ids {
+    element_ids: HashMap<NodeId, i64>,
+    node_ids: HashMap<i64, NodeId>,
+    next: i64,
+}
+
+impl Ids {
+    fn element_id(&mut self, node_id: NodeId) -> i64 {
+        if let Some(&id) = self.element_ids.get(&node_id) {
+            return id;
+        }
+        let id = self.next;
+        self.next += 1;
+        self.element_ids.insert(node_id, id);
+        self.node_ids.insert(id, node_id);
+        id
+    }
+
+    fn remove(&mut self, node_id: NodeId) -> Option<i64> {
+        let id = self.element_ids.remove(&node_id)?;
+        self.node_ids.remove(&id);
+        Some(id)
+    }
+}
+
+/// Exposes an AccessKit tree via the accessibility provider of an XComponent
+///
+/// Like the provider, the adapter must only be used on the UI thread.
+pub struct Adapter {
+    provider: AccessibilityProvider,
+    tree: Arc<Mutex<AccessibilityTree>>,
+    ids: Arc<Mutex<Ids>>,
+    /// The current AccessKit nodes, which updates only contain if they changed
+    nodes: HashMap<NodeId, Node>,
+    root: Option<NodeId>,
+    origin: (f64, f64),
+}
+
+impl Adapter {
+    /// Registers a handler for the adapter's tree at `provider`
+    ///
+    /// Replaces the accessibility handler registered previously. `action_handler` is called on
+    /// the UI thread for the actions screen readers request.
+    pub fn new(
+        provider: AccessibilityProvider,
+        mut action_handler: impl ActionHandler + Send + 'static,
+    ) -> Result<Self, i32> {
+        let tree = Arc::new(Mutex::new(AccessibilityTree::new()));
+        let ids = Arc::new(Mutex::new(Ids::default()));
+        let node_ids = ids.clone();
+        let handler = TreeHandler::new(tree.clone(), move |id, request| {
+            let target = node_ids
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .node_ids
+                .get(&id)
+                .copied();
+            let Some(request) = target.and_then(|target| to_accesskit(target, request)) else {
+                return false;
+            };
+            // The ids are unlocked, so the handler may update the adapter.
+            action_handler.do_action(request);
+            true
+        });
+        provider.register(handler)?;
+        Ok(Self {
+            provider,
+            tree,
+            ids,
+            nodes: HashMap::new(),
+            root: None,
+            origin: (0.0, 0.0),
+        })
+    }
+
+    /// The tree the AccessKit nodes are mirrored into
+    pub fn tree(&self) -> &Arc<Mutex<AccessibilityTree>> {
+        &self.tree
+    }
+
+    /// Sets the position of the XComponent on the display in physical pixels
+    ///
+    /// This is the [offset](crate::XComponent::offset) of the XComponent, plus the position of
+    /// its window if the window doesn't start at the top left corner of the display.
+    pub fn set_origin(&mut self, origin: Offset) {
+        self.origin = (origin.x, origin.y);
+        self.sync();
+    }
+
+    /// Applies `update` to the tree
+    ///
+    /// Updates of subtrees, i.e. with a `tree_id` other than [`TreeId::ROOT`], are not
+    /// supported and ignored.
+    pub fn update(&mut self, update: TreeUpdate) {
+        if update.tree_id != TreeId::ROOT {
+            error!(
+                "Ignoring the update of AccessKit subtree {:?}",
+                update.tree_id
+            );
+            return;
+        }
+        if let Some(tree) = update.tree {
+            self.root = Some(tree.root);
+        }
+        self.nodes.extend(update.nodes);
+        self.sync();
+    }
+
+    /// Mirrors the AccessKit nodes reachable from the root into the tree
+    fn sync(&mut self) {
+        let mut reachable = HashMap::new();
+        if let Some(root) = self.root {
+            self.collect(root, Affine::IDENTITY, false, &mut reachable);
+        }
+        // Nodes removed from the children of their parent are removed with their descendants.
+        self.nodes.retain(|id, _| reachable.contains_key(id));
+
+        let mut ids = self.ids.lock().unwrap_or_else(|e| e.into_inner());
+        let mut tree = self.tree.lock().unwrap_or_else(|e| e.into_inner());
+        let stale: Vec<_> = ids
+            .element_ids
+            .keys()
+            .filter(|id| !reachable.contains_key(id))
+            .copied()
+            .collect();
+        for node_id in stale {
+            if let Some(id) = ids.remove(node_id) {
+                tree.remove(id);
+            }
+        }
+        for (&node_id, &(transform, hidden)) in &reachable {
+            let node = &self.nodes[&node_id];
+            let id = ids.element_id(node_id);
+            let mut converted = convert(node, transform, self.origin, hidden);
+            converted.children = node
+                .children()
+                .iter()
+                .filter(|child| reachable.contains_key(child))
+                .map(|&child| ids.element_id(child))
+                .collect();
+            tree.insert(id, converted);
+        }
+        match self.root.filter(|root| reachable.contains_key(root)) {
+            Some(root) => tree.set_root(ids.element_id(root)),
+            None => tree.clear(),
+        }
+        drop(ids);
+        // The focused node may have moved.
+        if let Err(res) = tree.report_focus(&self.provider) {
+            error!("Reporting the accessibility focus failed: {res}");
+        }
+    }
+
+    /// Collects the transform to the XComponent and whether it is hidden for `id` and its
+    /// descendants
+    fn collect(
+        &self,
+        id: NodeId,
+        parent_transform: Affine,
+        parent_hidden: bool,
+        reachable: &mut HashMap<NodeId, (Affine, bool)>,
+    ) {
+        let Some(node) = self.nodes.get(&id) else {
+            return;
+        };
+        let transform = match node.transform() {
+            Some(&transform) => parent_transform * transform,
+            None => parent_transform,
+        };
+        let hidden = parent_hidden || node.is_hidden();
+        // Also stops at cycles.
+        if reachable.insert(id, (transform, hidden)).is_some() {
+            return;
+        }
+        for &child in node.children() {
+            self.collect(child, transform, hidden, reachable);
+        }
+    }
+}
+
+/// Converts `node`, without its children
+fn convert(node: &Node, transform: Affine, origin: (f64, f64), hidden: bool) -> AccessibilityNode {
+    let role = role(node.role());
+    // The text of labels is their value.
+    let text_as_label = node.label().is_none() && role == Role::Text;
+    let value = node
+        .value()
+        .map(str::to_owned)
+        .or_else(|| node.numeric_value().map(|value| value.to_string()))
+        .unwrap_or_default();
+    let (label, value) = match node.label() {
+        Some(label) => (label.to_owned(), value),
+        None if text_as_label => (value, String::new()),
+        None => (String::new(), value),
+    };
+    let bounds = node
+        .bounds()
+        .map(|bounds| {
+            let rect = transform.transform_rect_bbox(bounds);
+            let (x, y) = origin;
+            ScreenRect {
+                left: (rect.x0 + x).round() as i32,
+                top: (rect.y0 + y).round() as i32,
+                right: (rect.x1 + x).round() as i32,
+                bottom: (rect.y1 + y).round() as i32,
+            }
+        })
+        .unwrap_or_default();
+    AccessibilityNode {
+        role,
+        label,
+        value,
+        hint: node.placeholder().unwrap_or_default().to_owned(),
+        bounds,
+        actions: ACTIONS
+            .iter()
+            .filter(|&&(_, action)| node.supports_action(action))
+            .map(|&(action, _)| action)
+            .collect(),
+        children: Vec::new(),
+        checked: node.toggled().map(|toggled| toggled == Toggled::True),
+        selected: node.is_selected().unwrap_or(false),
+        disabled: node.is_disabled(),
+        hidden,
+    }
+}
+
+fn role(role: AccessKitRole) -> Role {
+    match role {
+        AccessKitRole::Button | AccessKitRole::DefaultButton => Role::Button,
+        AccessKitRole::Label
+        | AccessKitRole::TextRun
+        | AccessKitRole::Paragraph
+        | AccessKitRole::Heading => Role::Text,
+        AccessKitRole::Image => Role::Image,
+        AccessKitRole::CheckBox => Role::CheckBox,
+        AccessKitRole::Switch => Role::Switch,
+        AccessKitRole::Slider => Role::Slider,
+        AccessKitRole::TextInput
+        | AccessKitRole::MultilineTextInput
+        | AccessKitRole::SearchInput
+        | AccessKitRole::PasswordInput => Role::TextField,
+        AccessKitRole::List | AccessKitRole::ListBox => Role::List,
+        AccessKitRole::ListItem | AccessKitRole::ListBoxOption => Role::ListItem,
+        AccessKitRole::ScrollView => Role::ScrollView,
+        AccessKitRole::RadioButton => Role::Custom("Radio"),
+        AccessKitRole::ProgressIndicator => Role::Custom("Progress"),
+        _ => Role::Generic,
+    }
+}
+
+/// Converts `request` for `target`, `None` if AccessKit has no equivalent
+fn to_accesskit(target: NodeId, request: ActionRequest) -> Option<AccessKitRequest> {
+    let (action, data) = match request {
+        ActionRequest::SetText(text) => (
+            AccessKitAction::SetValue,
+            Some(ActionData::Value(text.into())),
+        ),
+        request => {
+            let action = request.action();
+            let &(_, action) = ACTIONS.iter().find(|&&(other, _)| other == action)?;
+            (action, None)
+        }
+    };
+    Some(AccessKitRequest {
+        action,
+        target_tree: TreeId::ROOT,
+        target_node: target,
+        data,
+    })
+}
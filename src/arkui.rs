@@ -0,0 +1,325 @@
+//! XComponents created via the ArkUI C node API
+//!
+//! XComponents which are created as `ARKUI_NODE_XCOMPONENT` nodes of the native node API,
+//! instead of being declared in ArkTS, have no `OH_NativeXComponent`. Their surface is managed by
+//! an `OH_ArkUI_SurfaceHolder`, whose lifecycle callbacks [`SurfaceHolder`] forwards to a
+//! [`SurfaceCallbacks`] implementation. Unlike the callbacks of `OH_NativeXComponent`, these
+//! carry user data, so every holder has its own state.
+//!
+//! ## Example
+//! ```no_run
+//! use xcomponent::arkui::{Surface, SurfaceCallbacks, SurfaceHolder};
+//! # let node: ohos_sys_opaque_types::ArkUI_NodeHandle = core::ptr::null_mut();
+//!
+//! struct Renderer;
+//!
+//! impl SurfaceCallbacks for Renderer {
+//!     fn on_surface_created(&mut self, surface: &Surface<'_>) {
+//!         let window = surface.native_window().unwrap();
+//!         // Create an EGL surface for `window.as_ptr()` ...
+//!     }
+//! }
+//!
+//! // `node` was created with `ARKUI_NODE_XCOMPONENT` via the native node API.
+//! let holder = unsafe { SurfaceHolder::new(node, Renderer) }.unwrap();
+//! // Dropping the holder stops the callbacks.
+//! drop(holder);
+//! ```
+
+use crate::log::error;
+use crate::native_window::NativeWindow;
+use crate::trace::ffi_span;
+use core::cell::RefCell;
+use core::marker::PhantomData;
+use core::ptr::NonNull;
+use ohos_sys::xcomponent::{
+    OH_ArkUI_SurfaceCallback, OH_ArkUI_SurfaceCallback_Create, OH_ArkUI_SurfaceCallback_Dispose,
+    OH_ArkUI_SurfaceCallback_SetSurfaceChangedEvent,
+    OH_ArkUI_SurfaceCallback_SetSurfaceCreatedEvent,
+    OH_ArkUI_SurfaceCallback_SetSurfaceDestroyedEvent, OH_ArkUI_SurfaceHolder,
+    OH_ArkUI_SurfaceHolder_AddSurfaceCallback, OH_ArkUI_SurfaceHolder_Create,
+    OH_ArkUI_SurfaceHolder_Dispose, OH_ArkUI_SurfaceHolder_GetUserData,
+    OH_ArkUI_SurfaceHolder_RemoveSurfaceCallback, OH_ArkUI_SurfaceHolder_SetUserData,
+    OH_ArkUI_XComponent_GetNativeWindow,
+};
+use ohos_sys_opaque_types::ArkUI_NodeHandle;
+
+/// Handles the surface callbacks of a [`SurfaceHolder`]
+///
+/// All methods default to doing nothing. They are called on the UI thread.
+pub trait SurfaceCallbacks: 'static {
+    /// The surface was created, rendering can start
+    fn on_surface_created(&mut self, surface: &Surface<'_>) {
+        let _ = surface;
+    }
+
+    /// The size of the surface changed, in physical pixels
+    fn on_surface_changed(&mut self, surface: &Surface<'_>, width: u64, height: u64) {
+        let _ = (surface, width, height);
+    }
+
+    /// The surface was destroyed, its native window must not be used anymore
+    fn on_surface_destroyed(&mut self, surface: &Surface<'_>) {
+        let _ = surface;
+    }
+}
+
+/// The surface of a [`SurfaceHolder`], during one of its callbacks
+pub struct Surface<'a> {
+    holder: NonNull<OH_ArkUI_SurfaceHolder>,
+    _marker: PhantomData<&'a OH_ArkUI_SurfaceHolder>,
+}
+
+impl Surface<'_> {
+    /// Returns a reference to the native window of the surface
+    pub fn native_window(&self) -> Result<NativeWindow, i32> {
+        // SAFETY: The holder is valid during the callback.
+        let window = unsafe { OH_ArkUI_XComponent_GetNativeWindow(self.holder.as_ptr()) };
+        let Some(window) = NonNull::new(window) else {
+            error!("OH_ArkUI_XComponent_GetNativeWindow returned no window");
+            // OH_NATIVEXCOMPONENT_RESULT_FAILED
+            return Err(-1);
+        };
+        // SAFETY: The window of the surface is valid during the callback.
+        unsafe { NativeWindow::from_raw(window) }
+    }
+
+    pub fn as_raw(&self) -> *mut OH_ArkUI_SurfaceHolder {
+        self.holder.as_ptr()
+    }
+}
+
+type State = RefCell<Box<dyn SurfaceCallbacks>>;
+
+/// The surface holder of an XComponent node, calling [`SurfaceCallbacks`] until it is dropped
+pub struct SurfaceHolder {
+    node: ArkUI_NodeHandle,
+    holder: NonNull<OH_ArkUI_SurfaceHolder>,
+    callback: NonNull<OH_ArkUI_SurfaceCallback>,
+    state: NonNull<State>,
+    #[cfg(feature = "api-20")]
+    has_frame_callback: core::cell::Cell<bool>,
+}
+
+impl SurfaceHolder {
+    /// Creates a surface holder for the XComponent `node` and registers `callbacks` with it
+    ///
+    /// # Safety
+    ///
+    /// `node` must be a valid XComponent node created via the native node API, which outlives
+    /// the holder.
+    pub unsafe fn new(
+        node: ArkUI_NodeHandle,
+        callbacks: impl SurfaceCallbacks,
+    ) -> Result<Self, i32> {
+        crate::thread::check_ui_thread("SurfaceHolder::new");
+        // SAFETY: The caller ensures that `node` is a valid XComponent node.
+        let Some(holder) = NonNull::new(unsafe { OH_ArkUI_SurfaceHolder_Create(node) }) else {
+            error!("OH_ArkUI_SurfaceHolder_Create failed");
+            // OH_NATIVEXCOMPONENT_RESULT_FAILED
+            return Err(-1);
+        };
+        let Some(callback) = NonNull::new(unsafe { OH_ArkUI_SurfaceCallback_Create() }) else {
+            error!("OH_ArkUI_SurfaceCallback_Create failed");
+            unsafe { OH_ArkUI_SurfaceHolder_Dispose(holder.as_ptr()) };
+            // OH_NATIVEXCOMPONENT_RESULT_FAILED
+            return Err(-1);
+        };
+        let state: Box<State> = Box::new(RefCell::new(Box::new(callbacks)));
+        let state = NonNull::from(Box::leak(state));
+        // SAFETY: `holder` and `callback` were just created and are disposed on errors, together
+        // with `state`. The user data and callbacks are only used while the callback is added.
+        unsafe {
+            let res = {
+                let mut span = ffi_span!("OH_ArkUI_SurfaceHolder_SetUserData", holder);
+                let res =
+                    OH_ArkUI_SurfaceHolder_SetUserData(holder.as_ptr(), state.as_ptr().cast());
+                span.set_result(res);
+                res
+            };
+            if res != 0 {
+                error!("OH_ArkUI_SurfaceHolder_SetUserData failed with {res}");
+                dispose(holder, callback, state);
+                return Err(res);
+            }
+            OH_ArkUI_SurfaceCallback_SetSurfaceCreatedEvent(
+                callback.as_ptr(),
+                Some(on_surface_created),
+            );
+            OH_ArkUI_SurfaceCallback_SetSurfaceChangedEvent(
+                callback.as_ptr(),
+                Some(on_surface_changed),
+            );
+            OH_ArkUI_SurfaceCallback_SetSurfaceDestroyedEvent(
+                callback.as_ptr(),
+                Some(on_surface_destroyed),
+            );
+            let mut span = ffi_span!("OH_ArkUI_SurfaceHolder_AddSurfaceCallback", holder);
+            let res = OH_ArkUI_SurfaceHolder_AddSurfaceCallback(holder.as_ptr(), callback.as_ptr());
+            span.set_result(res);
+            if res != 0 {
+                error!("OH_ArkUI_SurfaceHolder_AddSurfaceCallback failed with {res}");
+                dispose(holder, callback, state);
+                return Err(res);
+            }
+        }
+        Ok(Self {
+            node,
+            holder,
+            callback,
+            state,
+            #[cfg(feature = "api-20")]
+            has_frame_callback: core::cell::Cell::new(false),
+        })
+    }
+
+    /// The XComponent node of the holder
+    pub fn node(&self) -> ArkUI_NodeHandle {
+        self.node
+    }
+
+    pub fn as_raw(&self) -> *mut OH_ArkUI_SurfaceHolder {
+        self.holder.as_ptr()
+    }
+
+    /// Calls `callback` on the UI thread for every frame rendered by the compositor
+    ///
+    /// See [`XComponent::set_frame_callback`](crate::XComponent::set_frame_callback). The
+    /// callback is removed when the holder is dropped.
+    #[cfg(feature = "api-20")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "api-20")))]
+    pub fn set_frame_callback(
+        &self,
+        callback: impl FnMut(core::time::Duration, core::time::Duration) + 'static,
+    ) -> Result<(), i32> {
+        use ohos_sys::xcomponent::OH_ArkUI_XComponent_RegisterOnFrameCallback;
+
+        crate::frame::set(self.node as usize, std::rc::Rc::new(RefCell::new(callback)));
+        let mut span = ffi_span!("OH_ArkUI_XComponent_RegisterOnFrameCallback", self.node);
+        // SAFETY: The node outlives `self`, which unregisters the callback when dropped.
+        let res = unsafe {
+            OH_ArkUI_XComponent_RegisterOnFrameCallback(
+                self.node,
+                Some(crate::frame::on_node_frame),
+            )
+        };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_ArkUI_XComponent_RegisterOnFrameCallback failed with {res}");
+            crate::frame::remove(self.node as usize);
+            return Err(res);
+        }
+        self.has_frame_callback.set(true);
+        Ok(())
+    }
+
+    /// Stops calling the callback set via [`SurfaceHolder::set_frame_callback`]
+    #[cfg(feature = "api-20")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "api-20")))]
+    pub fn remove_frame_callback(&self) -> Result<(), i32> {
+        use ohos_sys::xcomponent::OH_ArkUI_XComponent_UnregisterOnFrameCallback;
+
+        crate::frame::remove(self.node as usize);
+        self.has_frame_callback.set(false);
+        let mut span = ffi_span!("OH_ArkUI_XComponent_UnregisterOnFrameCallback", self.node);
+        // SAFETY: The node outlives `self`.
+        let res = unsafe { OH_ArkUI_XComponent_UnregisterOnFrameCallback(self.node) };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_ArkUI_XComponent_UnregisterOnFrameCallback failed with {res}");
+            return Err(res);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SurfaceHolder {
+    fn drop(&mut self) {
+        #[cfg(feature = "api-20")]
+        if self.has_frame_callback.get() {
+            let _ = self.remove_frame_callback();
+        }
+        let mut span = ffi_span!("OH_ArkUI_SurfaceHolder_RemoveSurfaceCallback", self.holder);
+        // SAFETY: The callback was added in `new`.
+        let res = unsafe {
+            OH_ArkUI_SurfaceHolder_RemoveSurfaceCallback(
+                self.holder.as_ptr(),
+                self.callback.as_ptr(),
+            )
+        };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_ArkUI_SurfaceHolder_RemoveSurfaceCallback failed with {res}");
+        }
+        // SAFETY: The callback is removed, so ArkUI doesn't use the state anymore.
+        unsafe { dispose(self.holder, self.callback, self.state) };
+    }
+}
+
+/// Disposes of the native objects of a holder and drops its state
+///
+/// # Safety
+///
+/// The callback must not be added to the holder.
+unsafe fn dispose(
+    holder: NonNull<OH_ArkUI_SurfaceHolder>,
+    callback: NonNull<OH_ArkUI_SurfaceCallback>,
+    state: NonNull<State>,
+) {
+    unsafe {
+        OH_ArkUI_SurfaceCallback_Dispose(callback.as_ptr());
+        OH_ArkUI_SurfaceHolder_Dispose(holder.as_ptr());
+        drop(Box::from_raw(state.as_ptr()));
+    }
+}
+
+/// Calls `f` with the callbacks of `holder`
+fn with_callbacks(
+    callback: &str,
+    holder: *mut OH_ArkUI_SurfaceHolder,
+    f: impl FnOnce(&mut dyn SurfaceCallbacks, &Surface<'_>),
+) {
+    let Some(holder) = NonNull::new(holder) else {
+        error!("{callback} was called with a null surface holder");
+        return;
+    };
+    // SAFETY: The user data of holders with our callbacks is the state of their
+    // `SurfaceHolder`, which outlives the registration of the callbacks.
+    let state = unsafe { OH_ArkUI_SurfaceHolder_GetUserData(holder.as_ptr()) };
+    let Some(state) = (unsafe { state.cast::<State>().as_ref() }) else {
+        error!("{callback} was called for a surface holder without state");
+        return;
+    };
+    let Ok(mut callbacks) = state.try_borrow_mut() else {
+        error!("{callback} was called during another callback of the surface holder");
+        return;
+    };
+    let surface = Surface {
+        holder,
+        _marker: PhantomData,
+    };
+    let _span = crate::trace::callback_span!(callback, holder);
+    crate::panic_guard::catch(callback, || f(&mut **callbacks, &surface));
+}
+
+unsafe extern "C" fn on_surface_created(holder: *mut OH_ArkUI_SurfaceHolder) {
+    with_callbacks("OnSurfaceCreated", holder, |callbacks, surface| {
+        callbacks.on_surface_created(surface)
+    });
+}
+
+unsafe extern "C" fn on_surface_changed(
+    holder: *mut OH_ArkUI_SurfaceHolder,
+    width: u64,
+    height: u64,
+) {
+    with_callbacks("OnSurfaceChanged", holder, |callbacks, surface| {
+        callbacks.on_surface_changed(surface, width, height)
+    });
+}
+
+unsafe extern "C" fn on_surface_destroyed(holder: *mut OH_ArkUI_SurfaceHolder) {
+    with_callbacks("OnSurfaceDestroyed", holder, |callbacks, surface| {
+        callbacks.on_surface_destroyed(surface)
+    });
+}
@@ -0,0 +1,181 @@
+//! Typed axis events, e.g. of mouse wheels and touchpads
+//!
+//! ArkUI delivers axis events as `ArkUI_UIInputEvent`s to the callback registered via
+//! [`register_axis_event_callback`], or to [`XComponentHandler::on_axis`] with
+//! [`register_handler`]. [`AxisEvent`] copies the axis values out of the event, which is only
+//! valid during the callback. Coordinates are in physical pixels, `x` and `y` relative to the
+//! XComponent and `screen_x` and `screen_y` relative to the display.
+//!
+//! [`XComponentHandler::on_axis`]: crate::handler::XComponentHandler::on_axis
+//! [`register_handler`]: crate::handler::register_handler
+//!
+//! ## Example
+//! ```no_run
+//! use ohos_sys::arkui::ui_input_event::{ArkUI_UIInputEvent, ArkUI_UIInputEvent_Type};
+//! use ohos_sys::xcomponent::OH_NativeXComponent;
+//! use xcomponent::axis::AxisEvent;
+//!
+//! # #[cfg(all(feature = "register", not(feature = "napi-ohos-v1")))]
+//! fn init(exports: napi_ohos::JsObject, env: napi_ohos::Env) -> napi_ohos::Result<()> {
+//!     xcomponent::axis::register_axis_event_callback(&exports, &env, on_ui_input_event)
+//!         .expect("Registering the axis event callback failed");
+//!     Ok(())
+//! }
+//!
+//! unsafe extern "C" fn on_ui_input_event(
+//!     _xcomponent: *mut OH_NativeXComponent,
+//!     event: *mut ArkUI_UIInputEvent,
+//!     _type: ArkUI_UIInputEvent_Type,
+//! ) {
+//!     if let Some(axis_event) = unsafe { AxisEvent::from_raw(event) } {
+//!         // Scroll the content by `axis_event.vertical` ...
+//!     }
+//! }
+//! ```
+
+use ohos_sys::arkui::ui_input_event::{
+    ArkUI_UIInputEvent, ArkUI_UIInputEvent_Type, OH_ArkUI_AxisEvent_GetHorizontalAxisValue,
+    OH_ArkUI_AxisEvent_GetPinchAxisScaleValue, OH_ArkUI_AxisEvent_GetVerticalAxisValue,
+    OH_ArkUI_PointerEvent_GetDisplayX, OH_ArkUI_PointerEvent_GetDisplayY,
+    OH_ArkUI_PointerEvent_GetX, OH_ArkUI_PointerEvent_GetY, OH_ArkUI_UIInputEvent_GetEventTime,
+    OH_ArkUI_UIInputEvent_GetType,
+};
+use ohos_sys::xcomponent::OH_NativeXComponent;
+
+/// Callback receiving the UI input events of an XComponent
+pub type UIInputEventCallback = unsafe extern "C" fn(
+    xcomponent: *mut OH_NativeXComponent,
+    event: *mut ArkUI_UIInputEvent,
+    type_: ArkUI_UIInputEvent_Type,
+);
+
+/// Phase of an axis gesture, e.g. a touchpad scroll
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AxisAction {
+    Begin,
+    Update,
+    End,
+    /// The gesture was interrupted
+    Cancel,
+}
+
+impl AxisAction {
+    /// Converts a raw `UI_AXIS_EVENT_ACTION_*` value
+    ///
+    /// Returns `None` for `UI_AXIS_EVENT_ACTION_NONE` and unknown values.
+    pub fn from_raw(action: i32) -> Option<Self> {
+        match action {
+            1 => Some(Self::Begin),
+            2 => Some(Self::Update),
+            3 => Some(Self::End),
+            4 => Some(Self::Cancel),
+            _ => None,
+        }
+    }
+}
+
+/// An axis event of an XComponent
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AxisEvent {
+    /// Always `None` without the `api-15` feature, since ArkUI only reports it since API 15
+    pub action: Option<AxisAction>,
+    /// Value of the vertical scroll axis, e.g. of a mouse wheel
+    pub vertical: f64,
+    /// Value of the horizontal scroll axis
+    pub horizontal: f64,
+    /// Scale of a touchpad pinch
+    pub pinch_scale: f64,
+    pub x: f32,
+    pub y: f32,
+    pub screen_x: f32,
+    pub screen_y: f32,
+    /// Time of the event in nanoseconds
+    pub timestamp: i64,
+}
+
+impl AxisEvent {
+    /// Reads the axis event `event`
+    ///
+    /// Returns `None` if `event` is null or not an axis event.
+    ///
+    /// # Safety
+    ///
+    /// `event` must be null or a valid UI input event, e.g. the event passed to the
+    /// [`UIInputEventCallback`] during the callback.
+    pub unsafe fn from_raw(event: *const ArkUI_UIInputEvent) -> Option<Self> {
+        if event.is_null()
+            || unsafe { OH_ArkUI_UIInputEvent_GetType(event) }
+                != ArkUI_UIInputEvent_Type::ARKUI_UIINPUTEVENT_TYPE_AXIS.0 as i32
+        {
+            return None;
+        }
+        #[cfg(feature = "api-15")]
+        let action = AxisAction::from_raw(unsafe {
+            ohos_sys::arkui::ui_input_event::OH_ArkUI_AxisEvent_GetAxisAction(event)
+        });
+        #[cfg(not(feature = "api-15"))]
+        let action = None;
+        // SAFETY: `event` is a valid axis event.
+        unsafe {
+            Some(Self {
+                action,
+                vertical: OH_ArkUI_AxisEvent_GetVerticalAxisValue(event),
+                horizontal: OH_ArkUI_AxisEvent_GetHorizontalAxisValue(event),
+                pinch_scale: OH_ArkUI_AxisEvent_GetPinchAxisScaleValue(event),
+                x: OH_ArkUI_PointerEvent_GetX(event),
+                y: OH_ArkUI_PointerEvent_GetY(event),
+                screen_x: OH_ArkUI_PointerEvent_GetDisplayX(event),
+                screen_y: OH_ArkUI_PointerEvent_GetDisplayY(event),
+                timestamp: OH_ArkUI_UIInputEvent_GetEventTime(event),
+            })
+        }
+    }
+}
+
+/// Register `callback` for the axis events of the XComponent
+///
+/// Like [`register_xcomponent_callbacks`](crate::register_xcomponent_callbacks), this is
+/// intended to be called from the module init function.
+#[cfg(feature = "register")]
+#[cfg_attr(docsrs, doc(cfg(feature = "register")))]
+pub fn register_axis_event_callback<E: crate::NapiExports>(
+    exports: &E,
+    env: &E::Env,
+    callback: UIInputEventCallback,
+) -> Result<(), crate::RegisterCallbackError> {
+    crate::thread::check_ui_thread("register_axis_event_callback");
+    register_axis_event_callback_raw(exports.native_xcomponent(env)?, callback)
+}
+
+#[cfg(feature = "register")]
+pub(crate) fn register_axis_event_callback_raw(
+    native_xcomponent: *mut OH_NativeXComponent,
+    callback: UIInputEventCallback,
+) -> Result<(), crate::RegisterCallbackError> {
+    use crate::trace::ffi_span;
+    use ohos_sys::xcomponent::OH_NativeXComponent_RegisterUIInputEventCallback;
+
+    #[cfg(feature = "mock")]
+    if crate::mock::with_mock(native_xcomponent, |_| ()).is_some() {
+        return Ok(());
+    }
+    let mut span = ffi_span!(
+        "OH_NativeXComponent_RegisterUIInputEventCallback",
+        native_xcomponent
+    );
+    let res = unsafe {
+        OH_NativeXComponent_RegisterUIInputEventCallback(
+            native_xcomponent,
+            Some(callback),
+            ArkUI_UIInputEvent_Type::ARKUI_UIINPUTEVENT_TYPE_AXIS,
+        )
+    };
+    span.set_result(res);
+    if res != 0 {
+        crate::log::error!("OH_NativeXComponent_RegisterUIInputEventCallback failed with {res}");
+        return Err(crate::RegisterCallbackError::RegisterCallbackFailed(res));
+    }
+    Ok(())
+}
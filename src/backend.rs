@@ -0,0 +1,59 @@
+//! The parts of this crate a windowing backend needs, e.g. an OpenHarmony backend of winit
+//!
+//! A backend registers the [`EventQueue`] handler from the module init function and runs its
+//! event loop on a thread of its own, which maps the queued events to its own events:
+//!
+//! | Backend concept | This crate |
+//! |---|---|
+//! | Window created / resized / destroyed | [`Event::SurfaceCreated`], [`Event::SurfaceChanged`], [`Event::SurfaceDestroyed`] |
+//! | Focus | [`Event::Focus`], [`Event::Blur`] |
+//! | Input | [`Event::Touch`], [`Event::Mouse`], [`Event::Hover`], [`Event::Key`] |
+//! | Cursor moved / entered / left | [`HoverTracker`], fed with the mouse and hover events |
+//! | Window handle | [`NativeWindow`], which implements `HasWindowHandle` |
+//! | Scale factor | [`scale_factor`], [`on_scale_factor_changed`] |
+//! | Redraw requests | [`XComponent::set_frame_callback`] on the UI thread, or a `VsyncLoop` with the `vsync` feature |
+//! | Waking up the event loop | [`wake_up`], making [`poll_events`] return |
+//!
+//! ## Example
+//! ```no_run
+//! use std::time::Duration;
+//! use xcomponent::backend::{self, Event};
+//!
+//! # #[cfg(all(feature = "register", not(feature = "napi-ohos-v1")))]
+//! fn init(exports: napi_ohos::JsObject, env: napi_ohos::Env) -> napi_ohos::Result<()> {
+//!     xcomponent::handler::register_handler::<backend::EventQueue, _>(&exports, &env)
+//!         .expect("Registering the event queue failed");
+//!     std::thread::spawn(event_loop);
+//!     Ok(())
+//! }
+//!
+//! fn event_loop() {
+//!     let scale_factor = backend::scale_factor().unwrap_or(1.0);
+//!     loop {
+//!         for queued in backend::poll_events(Duration::from_millis(16)) {
+//!             match queued.event {
+//!                 Event::SurfaceCreated { window, size } => { /* Emit `Resumed` ... */ }
+//!                 Event::Touch(touch_event) => { /* Emit `Touch` events ... */ }
+//!                 _ => {}
+//!             }
+//!         }
+//!     }
+//! }
+//!
+//! // From any thread, e.g. an `EventLoopProxy`:
+//! backend::wake_up();
+//! ```
+
+pub use crate::display::{on_scale_factor_changed, scale_factor, ScaleFactorChanged};
+pub use crate::event_queue::{
+    drain_events, poll_events, set_destroy_timeout, wake_up, Event, EventQueue, QueuedEvent,
+};
+pub use crate::handle::XComponentRef;
+pub use crate::hover::{HoverEvent, HoverTracker};
+pub use crate::key::{KeyAction, KeyEvent, ModifierTracker, Modifiers};
+pub use crate::mouse::{MouseAction, MouseButton, MouseEvent};
+pub use crate::native_window::NativeWindow;
+pub use crate::touch::{HistoricalPoint, ToolType, TouchAction, TouchEvent, TouchPoint};
+#[cfg(feature = "vsync")]
+pub use crate::vsync::VsyncLoop;
+pub use crate::{Offset, Size, XComponent};
@@ -0,0 +1,208 @@
+//! Copy RGBA8 pixel data into CPU-mapped buffers
+//!
+//! Native window buffers have a row stride which is usually larger than the width of the
+//! buffer and may use a different pixel format than the source image. [`blit_rgba8`] handles
+//! both, so that simple applications can display decoded images without writing pixel loops.
+
+use crate::format::PixelFormat;
+
+/// Errors when copying pixel data with [`blit_rgba8`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlitError {
+    /// Converting RGBA8 data into this format is not supported.
+    UnsupportedFormat(PixelFormat),
+    /// The source slice is smaller than `width * height * 4` bytes.
+    SourceTooSmall,
+    /// The destination stride can't hold a single row of the destination format.
+    InvalidStride,
+}
+
+impl core::fmt::Display for BlitError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnsupportedFormat(format) => write!(f, "can't convert RGBA8 into {format:?}"),
+            Self::SourceTooSmall => f.write_str("the source is smaller than width * height * 4"),
+            Self::InvalidStride => f.write_str("the destination stride is too small for one row"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BlitError {}
+
+/// Number of bytes per pixel of the formats supported as destination
+fn bytes_per_pixel(format: PixelFormat) -> Option<usize> {
+    match format {
+        PixelFormat::Rgba8888
+        | PixelFormat::Rgbx8888
+        | PixelFormat::Bgra8888
+        | PixelFormat::Bgrx8888 => Some(4),
+        PixelFormat::Rgb888 => Some(3),
+        PixelFormat::Rgb565 | PixelFormat::Bgr565 => Some(2),
+        _ => None,
+    }
+}
+
+/// Copies tightly packed RGBA8 pixels into a buffer with the given stride and format
+///
+/// The source is placed at the top left corner of the destination. Rows or columns which
+/// don't fit into the destination are clipped. `dst_stride` is the distance between the start
+/// of two rows in `dst` in bytes.
+pub fn blit_rgba8(
+    src: &[u8],
+    src_width: u32,
+    src_height: u32,
+    dst: &mut [u8],
+    dst_stride: usize,
+    dst_format: PixelFormat,
+) -> Result<(), BlitError> {
+    let bpp = bytes_per_pixel(dst_format).ok_or(BlitError::UnsupportedFormat(dst_format))?;
+    let src_row_len = src_width as usize * 4;
+    if src.len() < src_row_len * src_height as usize {
+        return Err(BlitError::SourceTooSmall);
+    }
+    if dst_stride < bpp {
+        return Err(BlitError::InvalidStride);
+    }
+    let columns = (src_width as usize).min(dst_stride / bpp);
+    if columns == 0 {
+        return Ok(());
+    }
+
+    let src_rows = src.chunks_exact(src_row_len).take(src_height as usize);
+    for (src_row, dst_row) in src_rows.zip(dst.chunks_mut(dst_stride)) {
+        if dst_row.len() < columns * bpp {
+            break;
+        }
+        let src_row = &src_row[..columns * 4];
+        let dst_row = &mut dst_row[..columns * bpp];
+        if dst_format == PixelFormat::Rgba8888 {
+            dst_row.copy_from_slice(src_row);
+            continue;
+        }
+        for (s, d) in src_row.chunks_exact(4).zip(dst_row.chunks_exact_mut(bpp)) {
+            let [r, g, b, a] = [s[0], s[1], s[2], s[3]];
+            match dst_format {
+                PixelFormat::Rgbx8888 => d.copy_from_slice(&[r, g, b, 0xff]),
+                PixelFormat::Bgra8888 => d.copy_from_slice(&[b, g, r, a]),
+                PixelFormat::Bgrx8888 => d.copy_from_slice(&[b, g, r, 0xff]),
+                PixelFormat::Rgb888 => d.copy_from_slice(&[r, g, b]),
+                PixelFormat::Rgb565 => d.copy_from_slice(&pack_565(r, g, b).to_le_bytes()),
+                PixelFormat::Bgr565 => d.copy_from_slice(&pack_565(b, g, r).to_le_bytes()),
+                _ => unreachable!("format without bytes_per_pixel"),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn pack_565(high: u8, mid: u8, low: u8) -> u16 {
+    (u16::from(high) >> 3) << 11 | (u16::from(mid) >> 2) << 5 | u16::from(low) >> 3
+}
+
+/// Copies an [`image::RgbaImage`] into a buffer with the given stride and format
+///
+/// See [`blit_rgba8`] for details.
+#[cfg(feature = "image")]
+#[cfg_attr(docsrs, doc(cfg(feature = "image")))]
+pub fn blit_image(
+    image: &image::RgbaImage,
+    dst: &mut [u8],
+    dst_stride: usize,
+    dst_format: PixelFormat,
+) -> Result<(), BlitError> {
+    blit_rgba8(
+        image.as_raw(),
+        image.width(),
+        image.height(),
+        dst,
+        dst_stride,
+        dst_format,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2x2 RGBA8 pixels with distinct values
+    const SRC: [u8; 16] = [
+        1, 2, 3, 4, 5, 6, 7, 8, //
+        9, 10, 11, 12, 13, 14, 15, 16,
+    ];
+
+    #[test]
+    fn stride_larger_than_width() {
+        let mut dst = [0xaa; 2 * 12];
+        blit_rgba8(&SRC, 2, 2, &mut dst, 12, PixelFormat::Rgba8888).unwrap();
+        assert_eq!(dst[..8], SRC[..8]);
+        assert_eq!(dst[12..20], SRC[8..]);
+        // The padding at the end of the rows is untouched.
+        assert!(dst[8..12].iter().chain(&dst[20..]).all(|&b| b == 0xaa));
+    }
+
+    #[test]
+    fn converts_formats() {
+        let pixel = [0xff, 0x80, 0x00, 0x40];
+        let blit = |format, bpp| {
+            let mut dst = vec![0; bpp];
+            blit_rgba8(&pixel, 1, 1, &mut dst, bpp, format).unwrap();
+            dst
+        };
+        assert_eq!(blit(PixelFormat::Rgbx8888, 4), [0xff, 0x80, 0x00, 0xff]);
+        assert_eq!(blit(PixelFormat::Bgra8888, 4), [0x00, 0x80, 0xff, 0x40]);
+        assert_eq!(blit(PixelFormat::Bgrx8888, 4), [0x00, 0x80, 0xff, 0xff]);
+        assert_eq!(blit(PixelFormat::Rgb888, 3), [0xff, 0x80, 0x00]);
+        assert_eq!(blit(PixelFormat::Rgb565, 2), 0xfc00_u16.to_le_bytes());
+        assert_eq!(blit(PixelFormat::Bgr565, 2), 0x041f_u16.to_le_bytes());
+    }
+
+    #[test]
+    fn partial_overlap_is_clipped() {
+        // One column and one row of the destination fit.
+        let mut dst = [0xaa; 6];
+        blit_rgba8(&SRC, 2, 2, &mut dst, 6, PixelFormat::Rgba8888).unwrap();
+        assert_eq!(dst, [1, 2, 3, 4, 0xaa, 0xaa]);
+
+        // The source is smaller than the destination.
+        let mut dst = [0xaa; 3 * 12];
+        blit_rgba8(&SRC, 2, 2, &mut dst, 12, PixelFormat::Rgba8888).unwrap();
+        assert!(dst[24..].iter().all(|&b| b == 0xaa));
+    }
+
+    #[test]
+    fn zero_width() {
+        let mut dst = [0xaa; 8];
+        blit_rgba8(&[], 0, 2, &mut dst, 4, PixelFormat::Rgba8888).unwrap();
+        blit_rgba8(&[], 2, 0, &mut dst, 4, PixelFormat::Rgba8888).unwrap();
+        assert_eq!(dst, [0xaa; 8]);
+    }
+
+    #[test]
+    fn short_destination() {
+        // The second row has space for one of the two pixels, it is skipped entirely.
+        let mut dst = [0xaa; 8 + 4];
+        blit_rgba8(&SRC, 2, 2, &mut dst, 8, PixelFormat::Rgba8888).unwrap();
+        assert_eq!(dst[..8], SRC[..8]);
+        assert_eq!(dst[8..], [0xaa; 4]);
+        // And without space for any row
+        blit_rgba8(&SRC, 2, 2, &mut [], 8, PixelFormat::Rgba8888).unwrap();
+    }
+
+    #[test]
+    fn errors() {
+        let mut dst = [0; 16];
+        assert_eq!(
+            blit_rgba8(&SRC[..15], 2, 2, &mut dst, 8, PixelFormat::Rgba8888),
+            Err(BlitError::SourceTooSmall)
+        );
+        assert_eq!(
+            blit_rgba8(&SRC, 2, 2, &mut dst, 1, PixelFormat::Rgb565),
+            Err(BlitError::InvalidStride)
+        );
+        assert_eq!(
+            blit_rgba8(&SRC, 2, 2, &mut dst, 8, PixelFormat::Rgba4444),
+            Err(BlitError::UnsupportedFormat(PixelFormat::Rgba4444))
+        );
+    }
+}
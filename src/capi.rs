@@ -0,0 +1,265 @@
+//! C ABI over the handler and event queue of this crate
+//!
+//! C and C++ engines can use the lifecycle and input handling of this crate instead of
+//! registering the XComponent callbacks themselves: [`xcomponent_register_handler`] registers
+//! the [`EventQueue`] handler for an XComponent on the UI thread, and the render thread takes
+//! the queued callbacks with [`xcomponent_poll_event`]. The declarations are in
+//! `include/xcomponent.h`.
+//!
+//! The functions are exported from the shared library of the app which links this crate with the
+//! `capi` feature, like the other `#[no_mangle]` functions of a Rust `cdylib`.
+//!
+//! ## Example
+//! ```c
+//! #include "xcomponent.h"
+//!
+//! // On the UI thread, e.g. in the module init function:
+//! xcomponent_register_handler(native_xcomponent);
+//!
+//! // On the render thread:
+//! OHNativeWindow *window = NULL;
+//! XComponentEvent event;
+//! while (xcomponent_poll_event(&event, 16)) {
+//!     switch (event.kind) {
+//!     case XCOMPONENT_EVENT_SURFACE_CREATED:
+//!         window = event.data.surface.window;
+//!         // Create the swapchain for `window` ...
+//!         break;
+//!     case XCOMPONENT_EVENT_SURFACE_DESTROYED:
+//!         // Stop using the window and release it ...
+//!         OH_NativeWindow_NativeObjectUnreference(window);
+//!         window = NULL;
+//!         break;
+//!     default:
+//!         break;
+//!     }
+//! }
+//! ```
+
+use crate::event_queue::{self, Event, EventQueue};
+use crate::handler;
+use crate::RegisterCallbackError;
+use core::mem::ManuallyDrop;
+use core::ptr;
+use ohos_sys::xcomponent::{
+    OH_NativeXComponent, OH_NativeXComponent_KeyAction, OH_NativeXComponent_MouseEvent,
+    OH_NativeXComponent_MouseEventAction, OH_NativeXComponent_MouseEventButton,
+    OH_NativeXComponent_TouchEvent,
+};
+use ohos_sys_opaque_types::OHNativeWindow;
+use std::time::Duration;
+
+/// The kind of an [`XComponentEvent`], which selects the field of its data
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XComponentEventKind {
+    /// `data.surface`, with a window
+    SurfaceCreated,
+    /// `data.surface`, without a window
+    SurfaceChanged,
+    SurfaceDestroyed,
+    /// `data.touch`
+    Touch,
+    /// `data.mouse`
+    Mouse,
+    /// `data.hover`
+    Hover,
+    Focus,
+    Blur,
+    /// `data.key`
+    Key,
+}
+
+/// The surface of [`XComponentEventKind::SurfaceCreated`] and
+/// [`XComponentEventKind::SurfaceChanged`]
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct XComponentSurface {
+    /// The native window of a created surface, null otherwise
+    ///
+    /// The caller owns a reference to the window, which it releases via
+    /// `OH_NativeWindow_NativeObjectUnreference` once it stopped using the window.
+    pub window: *mut OHNativeWindow,
+    pub width: u64,
+    pub height: u64,
+}
+
+/// The key event of [`XComponentEventKind::Key`]
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct XComponentKeyEvent {
+    /// An `OH_NativeXComponent_KeyAction`, `OH_NATIVEXCOMPONENT_KEY_ACTION_UNKNOWN` if ArkUI
+    /// reported an unknown action
+    pub action: i32,
+    /// An `OH_NativeXComponent_KeyCode`
+    pub code: i32,
+    pub device_id: i64,
+    pub timestamp: i64,
+}
+
+/// The data of an [`XComponentEvent`], see [`XComponentEventKind`]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union XComponentEventData {
+    pub surface: XComponentSurface,
+    pub touch: OH_NativeXComponent_TouchEvent,
+    pub mouse: OH_NativeXComponent_MouseEvent,
+    /// Whether the mouse entered or left the XComponent
+    pub hover: bool,
+    pub key: XComponentKeyEvent,
+}
+
+/// A queued XComponent callback
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct XComponentEvent {
+    pub kind: XComponentEventKind,
+    /// The XComponent the event is for, which must only be used on the UI thread
+    pub xcomponent: *mut OH_NativeXComponent,
+    pub data: XComponentEventData,
+}
+
+impl XComponentEvent {
+    fn new(xcomponent: usize, event: Event) -> Self {
+        let (kind, data) = match event {
+            Event::SurfaceCreated { window, size } => (
+                XComponentEventKind::SurfaceCreated,
+                XComponentEventData {
+                    surface: XComponentSurface {
+                        // The reference is passed on to the caller.
+                        window: ManuallyDrop::new(window).as_ptr(),
+                        width: size.width,
+                        height: size.height,
+                    },
+                },
+            ),
+            Event::SurfaceChanged { size, .. } => (
+                XComponentEventKind::SurfaceChanged,
+                XComponentEventData {
+                    surface: XComponentSurface {
+                        window: ptr::null_mut(),
+                        width: size.width,
+                        height: size.height,
+                    },
+                },
+            ),
+            Event::SurfaceDestroyed => (
+                XComponentEventKind::SurfaceDestroyed,
+                XComponentEventData { hover: false },
+            ),
+            Event::Touch(touch_event) => (
+                XComponentEventKind::Touch,
+                XComponentEventData {
+                    touch: *touch_event.as_raw(),
+                },
+            ),
+            Event::Mouse(mouse_event) => (
+                XComponentEventKind::Mouse,
+                XComponentEventData {
+                    mouse: OH_NativeXComponent_MouseEvent {
+                        x: mouse_event.x,
+                        y: mouse_event.y,
+                        screenX: mouse_event.screen_x,
+                        screenY: mouse_event.screen_y,
+                        timestamp: mouse_event.timestamp,
+                        action: mouse_event.action.map_or(
+                            OH_NativeXComponent_MouseEventAction::OH_NATIVEXCOMPONENT_MOUSE_NONE,
+                            |action| action.as_raw(),
+                        ),
+                        button: mouse_event.button.map_or(
+                            OH_NativeXComponent_MouseEventButton::OH_NATIVEXCOMPONENT_NONE_BUTTON,
+                            |button| button.as_raw(),
+                        ),
+                    },
+                },
+            ),
+            Event::Hover(is_hover) => (
+                XComponentEventKind::Hover,
+                XComponentEventData { hover: is_hover },
+            ),
+            Event::Focus => (
+                XComponentEventKind::Focus,
+                XComponentEventData { hover: false },
+            ),
+            Event::Blur => (
+                XComponentEventKind::Blur,
+                XComponentEventData { hover: false },
+            ),
+            Event::Key(key_event) => (
+                XComponentEventKind::Key,
+                XComponentEventData {
+                    key: XComponentKeyEvent {
+                        action: key_event.action.map_or(
+                            OH_NativeXComponent_KeyAction::OH_NATIVEXCOMPONENT_KEY_ACTION_UNKNOWN,
+                            |action| action.as_raw(),
+                        )
+                        .0,
+                        code: key_event.code.0,
+                        device_id: key_event.device_id,
+                        timestamp: key_event.timestamp,
+                    },
+                },
+            ),
+        };
+        Self {
+            kind,
+            xcomponent: xcomponent as *mut OH_NativeXComponent,
+            data,
+        }
+    }
+}
+
+/// Registers the event queue as the handler of `xcomponent`
+///
+/// Must be called on the UI thread. Returns `0` on success, the ArkUI error code if registering
+/// a callback failed, or `-1`.
+///
+/// # Safety
+///
+/// `xcomponent` must be a valid native XComponent.
+#[no_mangle]
+pub unsafe extern "C" fn xcomponent_register_handler(xcomponent: *mut OH_NativeXComponent) -> i32 {
+    let result = crate::panic_guard::catch("xcomponent_register_handler", || {
+        handler::replace_handler::<EventQueue>(xcomponent)
+    });
+    match result {
+        Some(Ok(_)) => 0,
+        Some(Err(RegisterCallbackError::RegisterCallbackFailed(res))) => res,
+        // OH_NATIVEXCOMPONENT_RESULT_FAILED
+        Some(Err(_)) | None => -1,
+    }
+}
+
+/// Takes the next queued event into `event`, waiting up to `timeout_ms` for one
+///
+/// Returns `false` without an event if the timeout elapsed or [`xcomponent_wake_up`] was called.
+///
+/// # Safety
+///
+/// `event` must be valid for writing an [`XComponentEvent`].
+#[no_mangle]
+pub unsafe extern "C" fn xcomponent_poll_event(
+    event: *mut XComponentEvent,
+    timeout_ms: u32,
+) -> bool {
+    let Some(queued) = event_queue::poll_event(Duration::from_millis(timeout_ms.into())) else {
+        return false;
+    };
+    // SAFETY: The caller ensures that `event` is valid for writes.
+    unsafe { event.write(XComponentEvent::new(queued.component, queued.event)) };
+    true
+}
+
+/// Makes an [`xcomponent_poll_event`] waiting on another thread return
+#[no_mangle]
+pub extern "C" fn xcomponent_wake_up() {
+    event_queue::wake_up();
+}
+
+/// Sets how long `OnSurfaceDestroyed` waits for the render thread to take the event
+///
+/// `0` disables waiting.
+#[no_mangle]
+pub extern "C" fn xcomponent_set_destroy_timeout(timeout_ms: u32) {
+    event_queue::set_destroy_timeout(Duration::from_millis(timeout_ms.into()));
+}
@@ -0,0 +1,373 @@
+//! Copy and paste via the system pasteboard
+//!
+//! Text fields rendered into an XComponent don't get the copy and paste support of ArkUI text
+//! fields. [`Clipboard`] reads and writes the pasteboard, whose data is stored as UDMF records.
+//! Plain text, HTML and file URIs are supported, records of other types are skipped when reading.
+//! Images are not supported yet, UDMF stores them as pixel maps of the image kit.
+//!
+//! The pasteboard is shared by all apps and not tied to an XComponent, so [`Clipboard`] can be
+//! created anywhere. Reading requires the `ohos.permission.READ_PASTEBOARD` permission, or the
+//! user tapping a paste button of ArkUI before, writing requires no permission.
+//!
+//! ## Example
+//! ```no_run
+//! use xcomponent::clipboard::{Clipboard, ClipboardItem};
+//!
+//! let clipboard = Clipboard::new().unwrap();
+//! // On Ctrl+C:
+//! clipboard.set_text("Hello").unwrap();
+//! // On Ctrl+V:
+//! if let Some(text) = clipboard.text().unwrap() {
+//!     // Insert `text` ...
+//! }
+//! // Text fields accepting rich text:
+//! for item in clipboard.items().unwrap() {
+//!     if let ClipboardItem::Html(html) = item {
+//!         // Insert `html` ...
+//!     }
+//! }
+//! ```
+
+use crate::log::error;
+use crate::trace::ffi_span;
+use core::ffi::{c_char, c_int, CStr};
+use core::ptr::NonNull;
+use ohos_sys::pasteboard::{
+    OH_Pasteboard, OH_Pasteboard_ClearData, OH_Pasteboard_Create, OH_Pasteboard_Destroy,
+    OH_Pasteboard_GetData, OH_Pasteboard_HasData, OH_Pasteboard_HasType,
+    OH_Pasteboard_IsRemoteData, OH_Pasteboard_SetData,
+};
+use ohos_sys::udmf::data_management_framework::{
+    OH_UdmfData_AddRecord, OH_UdmfData_Create, OH_UdmfData_Destroy, OH_UdmfData_GetRecords,
+    OH_UdmfRecord_AddFileUri, OH_UdmfRecord_AddHtml, OH_UdmfRecord_AddPlainText,
+    OH_UdmfRecord_Create, OH_UdmfRecord_Destroy, OH_UdmfRecord_GetFileUri, OH_UdmfRecord_GetHtml,
+    OH_UdmfRecord_GetPlainText, OH_UdmfRecord_GetTypes,
+};
+use ohos_sys::udmf::data_struct::{
+    OH_UdsFileUri_Create, OH_UdsFileUri_Destroy, OH_UdsFileUri_GetFileUri,
+    OH_UdsFileUri_SetFileUri, OH_UdsHtml_Create, OH_UdsHtml_Destroy, OH_UdsHtml_GetContent,
+    OH_UdsHtml_SetContent, OH_UdsPlainText_Create, OH_UdsPlainText_Destroy,
+    OH_UdsPlainText_GetContent, OH_UdsPlainText_SetContent,
+};
+use ohos_sys::udmf::meta::{UDMF_META_GENERAL_FILE_URI, UDMF_META_HTML, UDMF_META_PLAIN_TEXT};
+use ohos_sys_opaque_types::OH_UdmfRecord;
+use std::ffi::CString;
+
+// OH_NATIVEXCOMPONENT_RESULT_FAILED
+const RESULT_FAILED: i32 = -1;
+
+/// An entry of the pasteboard
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ClipboardItem {
+    Text(String),
+    Html(String),
+    /// URI of a file, e.g. a `file://` URI of an image copied in the gallery
+    Uri(String),
+}
+
+/// A native object, destroyed when dropped
+struct Owned<T> {
+    ptr: NonNull<T>,
+    destroy: unsafe extern "C" fn(*mut T),
+}
+
+impl<T> Owned<T> {
+    /// Takes ownership of `ptr`, which `create` returned
+    fn new(create: &str, ptr: *mut T, destroy: unsafe extern "C" fn(*mut T)) -> Result<Self, i32> {
+        match NonNull::new(ptr) {
+            Some(ptr) => Ok(Self { ptr, destroy }),
+            None => {
+                error!("{create} failed");
+                Err(RESULT_FAILED)
+            }
+        }
+    }
+
+    fn as_ptr(&self) -> *mut T {
+        self.ptr.as_ptr()
+    }
+}
+
+impl<T> Drop for Owned<T> {
+    fn drop(&mut self) {
+        // SAFETY: The object is owned and not used afterwards.
+        unsafe { (self.destroy)(self.ptr.as_ptr()) }
+    }
+}
+
+fn check(function: &str, res: c_int) -> Result<(), i32> {
+    if res != 0 {
+        error!("{function} failed with {res}");
+        return Err(res);
+    }
+    Ok(())
+}
+
+/// Converts `text` for a UDMF setter
+fn c_string(text: &str) -> Result<CString, i32> {
+    CString::new(text).map_err(|_| {
+        error!("Can't copy text containing a NUL character");
+        RESULT_FAILED
+    })
+}
+
+/// Copies the string returned by a UDMF getter, `None` if it returned null
+///
+/// # Safety
+///
+/// `ptr` must be null or a valid C string.
+unsafe fn to_string(ptr: *const c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+    // SAFETY: Guaranteed by the caller.
+    Some(
+        unsafe { CStr::from_ptr(ptr) }
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
+/// Connection to the system pasteboard
+#[derive(Debug)]
+pub struct Clipboard {
+    pasteboard: NonNull<OH_Pasteboard>,
+}
+
+// SAFETY: The pasteboard is a client of the pasteboard service, which may be used from any thread.
+// Reading data from remote devices should even happen off the UI thread.
+unsafe impl Send for Clipboard {}
+
+impl Clipboard {
+    pub fn new() -> Result<Self, i32> {
+        let _span = ffi_span!("OH_Pasteboard_Create");
+        // SAFETY: Creating a pasteboard has no preconditions.
+        let pasteboard = NonNull::new(unsafe { OH_Pasteboard_Create() }).ok_or_else(|| {
+            error!("OH_Pasteboard_Create failed");
+            RESULT_FAILED
+        })?;
+        Ok(Self { pasteboard })
+    }
+
+    /// Whether the pasteboard contains any data
+    pub fn has_data(&self) -> bool {
+        // SAFETY: `self.pasteboard` is valid.
+        unsafe { OH_Pasteboard_HasData(self.pasteboard.as_ptr()) }
+    }
+
+    /// Whether the pasteboard contains plain text, e.g. to enable a paste menu entry
+    ///
+    /// Unlike reading the text, this requires no permission.
+    pub fn has_text(&self) -> bool {
+        // SAFETY: `self.pasteboard` is valid and the type is a C string.
+        unsafe { OH_Pasteboard_HasType(self.pasteboard.as_ptr(), UDMF_META_PLAIN_TEXT.as_ptr()) }
+    }
+
+    /// Whether the data was copied on another device, reading it then takes a while
+    pub fn is_remote(&self) -> bool {
+        // SAFETY: `self.pasteboard` is valid.
+        unsafe { OH_Pasteboard_IsRemoteData(self.pasteboard.as_ptr()) }
+    }
+
+    /// The first plain text on the pasteboard, `None` if there is none
+    pub fn text(&self) -> Result<Option<String>, i32> {
+        Ok(self.items()?.into_iter().find_map(|item| match item {
+            ClipboardItem::Text(text) => Some(text),
+            _ => None,
+        }))
+    }
+
+    /// The supported entries of the pasteboard, in order
+    ///
+    /// A record with several representations, e.g. HTML and its plain text, yields an item for
+    /// each of them.
+    pub fn items(&self) -> Result<Vec<ClipboardItem>, i32> {
+        let mut status = 0;
+        let data = {
+            let _span = ffi_span!("OH_Pasteboard_GetData");
+            // SAFETY: `self.pasteboard` is valid.
+            unsafe { OH_Pasteboard_GetData(self.pasteboard.as_ptr(), &mut status) }
+        };
+        let data = NonNull::new(data).map(|ptr| Owned {
+            ptr,
+            destroy: OH_UdmfData_Destroy,
+        });
+        check("OH_Pasteboard_GetData", status)?;
+        let Some(data) = data else {
+            error!("OH_Pasteboard_GetData failed");
+            return Err(RESULT_FAILED);
+        };
+        let mut count = 0;
+        // SAFETY: `data` is valid, the records are owned by it.
+        let records = unsafe { OH_UdmfData_GetRecords(data.as_ptr(), &mut count) };
+        if records.is_null() {
+            return Ok(Vec::new());
+        }
+        // SAFETY: `records` points to `count` records.
+        let records = unsafe { core::slice::from_raw_parts(records, count as usize) };
+        let mut items = Vec::new();
+        for &record in records {
+            // SAFETY: `record` is valid while `data` is alive.
+            unsafe { record_items(record, &mut items) }?;
+        }
+        Ok(items)
+    }
+
+    /// Replaces the data of the pasteboard with `text`
+    pub fn set_text(&self, text: &str) -> Result<(), i32> {
+        self.set_items(&[ClipboardItem::Text(text.to_owned())])
+    }
+
+    /// Replaces the data of the pasteboard with one record per item
+    pub fn set_items(&self, items: &[ClipboardItem]) -> Result<(), i32> {
+        // SAFETY: Creating UDMF objects has no preconditions.
+        let data = Owned::new(
+            "OH_UdmfData_Create",
+            unsafe { OH_UdmfData_Create() },
+            OH_UdmfData_Destroy,
+        )?;
+        for item in items {
+            let record = Owned::new(
+                "OH_UdmfRecord_Create",
+                unsafe { OH_UdmfRecord_Create() },
+                OH_UdmfRecord_Destroy,
+            )?;
+            // SAFETY: `record` is valid.
+            unsafe { add_item(record.as_ptr(), item) }?;
+            // SAFETY: `data` and `record` are valid, `data` keeps its own reference to the record.
+            let res = unsafe { OH_UdmfData_AddRecord(data.as_ptr(), record.as_ptr()) };
+            check("OH_UdmfData_AddRecord", res)?;
+        }
+        let _span = ffi_span!("OH_Pasteboard_SetData");
+        // SAFETY: `self.pasteboard` and `data` are valid, the pasteboard copies the data.
+        let res = unsafe { OH_Pasteboard_SetData(self.pasteboard.as_ptr(), data.as_ptr()) };
+        check("OH_Pasteboard_SetData", res)
+    }
+
+    /// Removes all data from the pasteboard
+    pub fn clear(&self) -> Result<(), i32> {
+        let _span = ffi_span!("OH_Pasteboard_ClearData");
+        // SAFETY: `self.pasteboard` is valid.
+        let res = unsafe { OH_Pasteboard_ClearData(self.pasteboard.as_ptr()) };
+        check("OH_Pasteboard_ClearData", res)
+    }
+}
+
+impl Drop for Clipboard {
+    fn drop(&mut self) {
+        // SAFETY: `self.pasteboard` isn't used afterwards.
+        unsafe { OH_Pasteboard_Destroy(self.pasteboard.as_ptr()) }
+    }
+}
+
+/// Appends the supported representations of `record` to `items`
+///
+/// # Safety
+///
+/// `record` must be valid.
+unsafe fn record_items(
+    record: *mut OH_UdmfRecord,
+    items: &mut Vec<ClipboardItem>,
+) -> Result<(), i32> {
+    let mut count = 0;
+    // SAFETY: `record` is valid, the types are owned by it.
+    let types = unsafe { OH_UdmfRecord_GetTypes(record, &mut count) };
+    if types.is_null() {
+        return Ok(());
+    }
+    // SAFETY: `types` points to `count` C strings.
+    let types = unsafe { core::slice::from_raw_parts(types, count as usize) };
+    for &type_ in types {
+        // SAFETY: The types are C strings.
+        let type_ = unsafe { CStr::from_ptr(type_) };
+        // SAFETY: `record` is valid and the UDS objects are created for the matching type.
+        let item = unsafe {
+            if type_ == UDMF_META_PLAIN_TEXT {
+                let text = Owned::new(
+                    "OH_UdsPlainText_Create",
+                    OH_UdsPlainText_Create(),
+                    OH_UdsPlainText_Destroy,
+                )?;
+                let res = OH_UdmfRecord_GetPlainText(record, text.as_ptr());
+                check("OH_UdmfRecord_GetPlainText", res)?;
+                to_string(OH_UdsPlainText_GetContent(text.as_ptr())).map(ClipboardItem::Text)
+            } else if type_ == UDMF_META_HTML {
+                let html =
+                    Owned::new("OH_UdsHtml_Create", OH_UdsHtml_Create(), OH_UdsHtml_Destroy)?;
+                let res = OH_UdmfRecord_GetHtml(record, html.as_ptr());
+                check("OH_UdmfRecord_GetHtml", res)?;
+                to_string(OH_UdsHtml_GetContent(html.as_ptr())).map(ClipboardItem::Html)
+            } else if type_ == UDMF_META_GENERAL_FILE_URI {
+                let uri = Owned::new(
+                    "OH_UdsFileUri_Create",
+                    OH_UdsFileUri_Create(),
+                    OH_UdsFileUri_Destroy,
+                )?;
+                let res = OH_UdmfRecord_GetFileUri(record, uri.as_ptr());
+                check("OH_UdmfRecord_GetFileUri", res)?;
+                to_string(OH_UdsFileUri_GetFileUri(uri.as_ptr())).map(ClipboardItem::Uri)
+            } else {
+                None
+            }
+        };
+        items.extend(item);
+    }
+    Ok(())
+}
+
+/// Adds `item` to `record`
+///
+/// # Safety
+///
+/// `record` must be valid.
+unsafe fn add_item(record: *mut OH_UdmfRecord, item: &ClipboardItem) -> Result<(), i32> {
+    // SAFETY: `record` is valid, the record copies the content of the UDS objects.
+    unsafe {
+        match item {
+            ClipboardItem::Text(text) => {
+                let content = c_string(text)?;
+                let uds = Owned::new(
+                    "OH_UdsPlainText_Create",
+                    OH_UdsPlainText_Create(),
+                    OH_UdsPlainText_Destroy,
+                )?;
+                check(
+                    "OH_UdsPlainText_SetContent",
+                    OH_UdsPlainText_SetContent(uds.as_ptr(), content.as_ptr()),
+                )?;
+                check(
+                    "OH_UdmfRecord_AddPlainText",
+                    OH_UdmfRecord_AddPlainText(record, uds.as_ptr()),
+                )
+            }
+            ClipboardItem::Html(html) => {
+                let content = c_string(html)?;
+                let uds = Owned::new("OH_UdsHtml_Create", OH_UdsHtml_Create(), OH_UdsHtml_Destroy)?;
+                check(
+                    "OH_UdsHtml_SetContent",
+                    OH_UdsHtml_SetContent(uds.as_ptr(), content.as_ptr()),
+                )?;
+                check(
+                    "OH_UdmfRecord_AddHtml",
+                    OH_UdmfRecord_AddHtml(record, uds.as_ptr()),
+                )
+            }
+            ClipboardItem::Uri(uri) => {
+                let content = c_string(uri)?;
+                let uds = Owned::new(
+                    "OH_UdsFileUri_Create",
+                    OH_UdsFileUri_Create(),
+                    OH_UdsFileUri_Destroy,
+                )?;
+                check(
+                    "OH_UdsFileUri_SetFileUri",
+                    OH_UdsFileUri_SetFileUri(uds.as_ptr(), content.as_ptr()),
+                )?;
+                check(
+                    "OH_UdmfRecord_AddFileUri",
+                    OH_UdmfRecord_AddFileUri(record, uds.as_ptr()),
+                )
+            }
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! System configuration changes reported by ArkUI
+//!
+//! ArkUI reports configuration changes to nodes of the UI tree. The listeners of this module
+//! are registered on the `ArkUI_NodeHandle` of the XComponent, which can be obtained from its
+//! `FrameNode` via `OH_ArkUI_GetNodeHandleFromNapiValue`.
+//!
+//! ArkUI only reports changes, so the current configuration is unknown until the first change.
+//! Pass the initial configuration from ArkTS if it is needed at startup.
+
+use crate::log::error;
+use crate::trace::ffi_span;
+use ohos_sys::arkui::native_node::{
+    OH_ArkUI_RegisterSystemColorModeChangeEvent, OH_ArkUI_RegisterSystemFontStyleChangeEvent,
+    OH_ArkUI_SystemFontStyleEvent_GetFontSizeScale,
+    OH_ArkUI_SystemFontStyleEvent_GetFontWeightScale,
+    OH_ArkUI_UnregisterSystemColorModeChangeEvent, OH_ArkUI_UnregisterSystemFontStyleChangeEvent,
+};
+use ohos_sys::arkui::native_type::{ArkUI_SystemColorMode, ArkUI_SystemFontStyleEvent};
+use ohos_sys_opaque_types::{ArkUI_Node, ArkUI_NodeHandle};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::ptr::NonNull;
+
+/// Light or dark appearance of the system
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ColorMode {
+    Light,
+    Dark,
+}
+
+impl ColorMode {
+    fn from_raw(color_mode: ArkUI_SystemColorMode) -> Option<Self> {
+        match color_mode {
+            ArkUI_SystemColorMode::ARKUI_SYSTEM_COLOR_MODE_LIGHT => Some(Self::Light),
+            ArkUI_SystemColorMode::ARKUI_SYSTEM_COLOR_MODE_DARK => Some(Self::Dark),
+            _ => None,
+        }
+    }
+}
+
+/// Font settings of the system
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FontStyle {
+    /// Factor applied to all font sizes, `1.0` by default
+    pub size_scale: f32,
+    /// Factor applied to all font weights, `1.0` by default
+    pub weight_scale: f32,
+}
+
+impl FontStyle {
+    /// Whether the user enabled bold text
+    pub fn is_bold(&self) -> bool {
+        self.weight_scale > 1.0
+    }
+
+    /// Scales a font size in `fp` according to the system setting
+    pub fn scale_size(&self, size: f32) -> f32 {
+        size * self.size_scale
+    }
+}
+
+thread_local! {
+    /// The generation of the current listener of each node and setting, by the address of the
+    /// node and the name of the unregister function
+    static CURRENT: RefCell<HashMap<(usize, &'static str), u64>> = RefCell::new(HashMap::new());
+    static NEXT_GENERATION: Cell<u64> = const { Cell::new(0) };
+}
+
+struct State<T> {
+    value: Cell<Option<T>>,
+    callback: Box<dyn Fn(T)>,
+}
+
+/// Listener for changes of a system setting
+///
+/// The listener is unregistered when this handle is dropped. ArkUI supports only one listener
+/// per node and setting, registering a second one replaces the first. Dropping a replaced
+/// listener doesn't unregister the one which replaced it.
+pub struct ConfigListener<T: Copy + 'static> {
+    node: NonNull<ArkUI_Node>,
+    state: NonNull<State<T>>,
+    unregister: (&'static str, unsafe extern "C" fn(ArkUI_NodeHandle)),
+    /// Tells this listener apart from later listeners of the same node and setting
+    generation: u64,
+}
+
+impl<T: Copy + 'static> ConfigListener<T> {
+    /// The value reported by the last change, if any
+    pub fn value(&self) -> Option<T> {
+        // SAFETY: The state is only freed on drop.
+        unsafe { self.state.as_ref() }.value.get()
+    }
+}
+
+impl<T: Copy + 'static> Drop for ConfigListener<T> {
+    fn drop(&mut self) {
+        let (function, unregister) = self.unregister;
+        let key = (self.node.as_ptr() as usize, function);
+        let is_current = CURRENT.with_borrow_mut(|current| {
+            let is_current = current.get(&key) == Some(&self.generation);
+            if is_current {
+                current.remove(&key);
+            }
+            is_current
+        });
+        // A replaced listener isn't called by ArkUI anymore, so only its state needs to be freed.
+        if is_current {
+            let _span = ffi_span!(function);
+            unsafe { unregister(self.node.as_ptr()) };
+        }
+        unsafe { drop(Box::from_raw(self.state.as_ptr())) };
+    }
+}
+
+/// Allocates the listener state and registers it via `register_fn`
+///
+/// # Safety
+///
+/// `register_fn` must pass its second argument as user data to a callback which accesses it as
+/// `State<T>`.
+unsafe fn register<T: Copy + 'static>(
+    function: &str,
+    node: ArkUI_NodeHandle,
+    callback: Box<dyn Fn(T)>,
+    register_fn: impl FnOnce(ArkUI_NodeHandle, *mut c_void) -> i32,
+    unregister: (&'static str, unsafe extern "C" fn(ArkUI_NodeHandle)),
+) -> Result<ConfigListener<T>, i32> {
+    crate::thread::check_ui_thread(function);
+    let Some(node) = NonNull::new(node) else {
+        // ARKUI_ERROR_CODE_PARAM_INVALID
+        return Err(401);
+    };
+    let state = Box::into_raw(Box::new(State {
+        value: Cell::new(None),
+        callback,
+    }));
+    let res = register_fn(node.as_ptr(), state.cast());
+    if res != 0 {
+        error!("{function} failed with {res}");
+        drop(Box::from_raw(state));
+        return Err(res);
+    }
+    let generation = NEXT_GENERATION.replace(NEXT_GENERATION.get() + 1);
+    CURRENT.with_borrow_mut(|current| {
+        current.insert((node.as_ptr() as usize, unregister.0), generation);
+    });
+    Ok(ConfigListener {
+        node,
+        state: NonNull::new_unchecked(state),
+        unregister,
+        generation,
+    })
+}
+
+/// Updates the state behind `user_data` and calls its callback
+///
+/// # Safety
+///
+/// `user_data` must be the state of a registered [`ConfigListener<T>`].
+unsafe fn notify<T: Copy + 'static>(user_data: *mut c_void, value: T) {
+    // SAFETY: The state stays alive until the listener is unregistered.
+    let state = unsafe { &*user_data.cast::<State<T>>() };
+    state.value.set(Some(value));
+    crate::panic_guard::catch("configuration listener", || (state.callback)(value));
+}
+
+/// Calls `callback` on the UI thread whenever the system color mode changes
+///
+/// # Safety
+///
+/// `node` must be a valid node handle, which outlives the returned listener.
+pub unsafe fn on_color_mode_changed(
+    node: ArkUI_NodeHandle,
+    callback: impl Fn(ColorMode) + 'static,
+) -> Result<ConfigListener<ColorMode>, i32> {
+    let _span = ffi_span!("OH_ArkUI_RegisterSystemColorModeChangeEvent");
+    register(
+        "OH_ArkUI_RegisterSystemColorModeChangeEvent",
+        node,
+        Box::new(callback),
+        |node, user_data| unsafe {
+            OH_ArkUI_RegisterSystemColorModeChangeEvent(node, user_data, Some(color_mode_changed))
+        },
+        (
+            "OH_ArkUI_UnregisterSystemColorModeChangeEvent",
+            OH_ArkUI_UnregisterSystemColorModeChangeEvent,
+        ),
+    )
+}
+
+/// Calls `callback` on the UI thread whenever the system font size or weight changes
+///
+/// # Safety
+///
+/// `node` must be a valid node handle, which outlives the returned listener.
+pub unsafe fn on_font_style_changed(
+    node: ArkUI_NodeHandle,
+    callback: impl Fn(FontStyle) + 'static,
+) -> Result<ConfigListener<FontStyle>, i32> {
+    let _span = ffi_span!("OH_ArkUI_RegisterSystemFontStyleChangeEvent");
+    register(
+        "OH_ArkUI_RegisterSystemFontStyleChangeEvent",
+        node,
+        Box::new(callback),
+        |node, user_data| unsafe {
+            OH_ArkUI_RegisterSystemFontStyleChangeEvent(node, user_data, Some(font_style_changed))
+        },
+        (
+            "OH_ArkUI_UnregisterSystemFontStyleChangeEvent",
+            OH_ArkUI_UnregisterSystemFontStyleChangeEvent,
+        ),
+    )
+}
+
+unsafe extern "C" fn color_mode_changed(color_mode: ArkUI_SystemColorMode, user_data: *mut c_void) {
+    if let Some(color_mode) = ColorMode::from_raw(color_mode) {
+        notify(user_data, color_mode);
+    }
+}
+
+unsafe extern "C" fn font_style_changed(
+    event: *mut ArkUI_SystemFontStyleEvent,
+    user_data: *mut c_void,
+) {
+    let font_style = FontStyle {
+        size_scale: OH_ArkUI_SystemFontStyleEvent_GetFontSizeScale(event),
+        weight_scale: OH_ArkUI_SystemFontStyleEvent_GetFontWeightScale(event),
+    };
+    notify(user_data, font_style);
+}
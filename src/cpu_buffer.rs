@@ -0,0 +1,302 @@
+//! Rendering into native window buffers from the CPU
+//!
+//! [`NativeWindow::request_cpu_buffer`] dequeues a buffer from the window and maps it into memory,
+//! so that CPU renderers can draw into it without EGL. [`CpuBuffer::flush`] queues the buffer for
+//! display, dropping a [`CpuBuffer`] without flushing it returns it to the window unchanged.
+//!
+//! The usage of the window must allow the CPU to write to the buffers, see
+//! [`NativeWindow::set_usage`].
+//!
+//! ## Example
+//! ```no_run
+//! use ohos_sys::native_buffer::native_buffer::OH_NativeBuffer_Usage;
+//! use xcomponent::cpu_buffer::DamageRect;
+//! use xcomponent::format::PixelFormat;
+//! # let window: xcomponent::native_window::NativeWindow = todo!();
+//!
+//! window.set_format(PixelFormat::Rgba8888).unwrap();
+//! window
+//!     .set_usage(
+//!         OH_NativeBuffer_Usage::NATIVEBUFFER_USAGE_CPU_READ
+//!             | OH_NativeBuffer_Usage::NATIVEBUFFER_USAGE_CPU_WRITE
+//!             | OH_NativeBuffer_Usage::NATIVEBUFFER_USAGE_MEM_DMA,
+//!     )
+//!     .unwrap();
+//!
+//! let mut buffer = window.request_cpu_buffer().unwrap();
+//! let (stride, height) = (buffer.stride(), buffer.height() as usize);
+//! for row in buffer.data_mut().chunks_mut(stride).take(height) {
+//!     row.fill(0xff);
+//! }
+//! let damage = DamageRect::new(0, 0, buffer.width(), buffer.height());
+//! buffer.flush(&[damage]).unwrap();
+//! ```
+
+use crate::format::PixelFormat;
+use crate::log::error;
+use crate::native_window::NativeWindow;
+use crate::trace::ffi_span;
+use core::ffi::c_int;
+use core::ptr::{self, NonNull};
+use ohos_sys::native_buffer::buffer_common::OH_NativeBuffer_Format;
+use ohos_sys::native_window::{
+    OH_NativeWindow_GetBufferHandleFromNative, OH_NativeWindow_NativeWindowAbortBuffer,
+    OH_NativeWindow_NativeWindowFlushBuffer, OH_NativeWindow_NativeWindowRequestBuffer, Region,
+    Region_Rect,
+};
+use ohos_sys_opaque_types::OHNativeWindowBuffer;
+
+/// How long to wait for the consumer to release a requested buffer, in milliseconds
+const FENCE_TIMEOUT_MS: c_int = 3000;
+
+/// A region of a buffer which changed, in pixels relative to the top left corner of the buffer
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DamageRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl DamageRect {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+/// A buffer of a [`NativeWindow`], mapped into memory
+#[derive(Debug)]
+pub struct CpuBuffer<'a> {
+    window: &'a NativeWindow,
+    buffer: NonNull<OHNativeWindowBuffer>,
+    data: NonNull<u8>,
+    len: usize,
+    width: u32,
+    height: u32,
+    stride: usize,
+    format: Option<PixelFormat>,
+}
+
+impl NativeWindow {
+    /// Requests a buffer from the window and maps it for reading and writing
+    ///
+    /// Waits until the consumer of the window released the buffer, and fails if it didn't within
+    /// a few seconds. Fails for windows of mock XComponents.
+    #[cfg_attr(docsrs, doc(cfg(feature = "cpu-buffer")))]
+    pub fn request_cpu_buffer(&self) -> Result<CpuBuffer<'_>, i32> {
+        if self.is_mocked() {
+            // OH_NATIVEXCOMPONENT_RESULT_FAILED
+            return Err(-1);
+        }
+        let mut buffer = ptr::null_mut();
+        let mut fence_fd = -1;
+        let mut span = ffi_span!("OH_NativeWindow_NativeWindowRequestBuffer");
+        // SAFETY: `self` keeps the window alive, and the out pointers are valid.
+        let res = unsafe {
+            OH_NativeWindow_NativeWindowRequestBuffer(self.as_ptr(), &mut buffer, &mut fence_fd)
+        };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_NativeWindow_NativeWindowRequestBuffer failed with {res}");
+            return Err(res);
+        }
+        let buffer = NonNull::new(buffer).ok_or(-1)?;
+        if let Err(res) = wait_for_fence(fence_fd) {
+            // The consumer may still read the buffer, so it must not be written to.
+            abort(self, buffer);
+            return Err(res);
+        }
+        // SAFETY: The buffer was just requested from the window.
+        unsafe { CpuBuffer::map(self, buffer) }.inspect_err(|_| abort(self, buffer))
+    }
+}
+
+impl<'a> CpuBuffer<'a> {
+    /// Maps `buffer`, which must be a requested buffer of `window`
+    unsafe fn map(
+        window: &'a NativeWindow,
+        buffer: NonNull<OHNativeWindowBuffer>,
+    ) -> Result<Self, i32> {
+        let handle = OH_NativeWindow_GetBufferHandleFromNative(buffer.as_ptr());
+        let Some(handle) = handle.as_ref() else {
+            error!("OH_NativeWindow_GetBufferHandleFromNative returned null");
+            return Err(-1);
+        };
+        let (Ok(len), Ok(width), Ok(height), Ok(stride)) = (
+            usize::try_from(handle.size),
+            u32::try_from(handle.width),
+            u32::try_from(handle.height),
+            usize::try_from(handle.stride),
+        ) else {
+            error!("Buffer handle with invalid geometry: {handle:?}");
+            return Err(-1);
+        };
+        let data = libc::mmap(
+            handle.virAddr,
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            handle.fd,
+            0,
+        );
+        if data == libc::MAP_FAILED {
+            error!(
+                "Mapping the buffer failed: {}",
+                std::io::Error::last_os_error()
+            );
+            return Err(-1);
+        }
+        Ok(Self {
+            window,
+            buffer,
+            data: NonNull::new(data.cast()).ok_or(-1)?,
+            len,
+            width,
+            height,
+            stride,
+            format: PixelFormat::from_raw(OH_NativeBuffer_Format(handle.format as _)),
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Distance between the start of two rows in bytes
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// The pixel format, or `None` if it isn't one of the known [`PixelFormat`]s
+    pub fn format(&self) -> Option<PixelFormat> {
+        self.format
+    }
+
+    /// The pixels, `stride() * height()` bytes, which may be followed by padding
+    pub fn data(&self) -> &[u8] {
+        // SAFETY: `data` is mapped for `len` bytes until `self` is dropped or flushed.
+        unsafe { core::slice::from_raw_parts(self.data.as_ptr(), self.len) }
+    }
+
+    pub fn data_mut(&mut self) -> &mut [u8] {
+        // SAFETY: `data` is mapped for `len` bytes until `self` is dropped or flushed, and `self`
+        // is borrowed mutably.
+        unsafe { core::slice::from_raw_parts_mut(self.data.as_ptr(), self.len) }
+    }
+
+    /// Queues the buffer for display
+    ///
+    /// `damage` is the region which changed since the buffer was last displayed, an empty slice
+    /// marks the whole buffer as damaged.
+    pub fn flush(self, damage: &[DamageRect]) -> Result<(), i32> {
+        let mut rects: Vec<Region_Rect> = damage
+            .iter()
+            .map(|rect| Region_Rect {
+                x: rect.x,
+                y: rect.y,
+                w: rect.width,
+                h: rect.height,
+            })
+            .collect();
+        let region = Region {
+            rects: if rects.is_empty() {
+                ptr::null_mut()
+            } else {
+                rects.as_mut_ptr()
+            },
+            rectNumber: i32::try_from(rects.len()).map_err(|_| -1)?,
+        };
+        self.unmap();
+        let this = core::mem::ManuallyDrop::new(self);
+        let mut span = ffi_span!("OH_NativeWindow_NativeWindowFlushBuffer");
+        // SAFETY: The buffer was requested from the window and is unmapped, `rects` outlives
+        // the call. No fence is needed, since the CPU finished writing.
+        let res = unsafe {
+            OH_NativeWindow_NativeWindowFlushBuffer(
+                this.window.as_ptr(),
+                this.buffer.as_ptr(),
+                -1,
+                region,
+            )
+        };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_NativeWindow_NativeWindowFlushBuffer failed with {res}");
+            abort(this.window, this.buffer);
+            return Err(res);
+        }
+        Ok(())
+    }
+
+    fn unmap(&self) {
+        // SAFETY: `data` was mapped for `len` bytes by `map`, the slices borrowing it are gone.
+        if unsafe { libc::munmap(self.data.as_ptr().cast(), self.len) } != 0 {
+            error!(
+                "Unmapping the buffer failed: {}",
+                std::io::Error::last_os_error()
+            );
+        }
+    }
+}
+
+impl Drop for CpuBuffer<'_> {
+    fn drop(&mut self) {
+        self.unmap();
+        abort(self.window, self.buffer);
+    }
+}
+
+/// Returns `buffer` to `window` without displaying it
+fn abort(window: &NativeWindow, buffer: NonNull<OHNativeWindowBuffer>) {
+    let mut span = ffi_span!("OH_NativeWindow_NativeWindowAbortBuffer");
+    // SAFETY: The callers ensure that `buffer` was requested from `window`.
+    let res = unsafe { OH_NativeWindow_NativeWindowAbortBuffer(window.as_ptr(), buffer.as_ptr()) };
+    span.set_result(res);
+    if res != 0 {
+        error!("OH_NativeWindow_NativeWindowAbortBuffer failed with {res}");
+    }
+}
+
+/// Waits until the fence of a requested buffer signals and closes it, `-1` means no fence
+///
+/// Fails if the fence didn't signal within [`FENCE_TIMEOUT_MS`] or waiting failed.
+fn wait_for_fence(fence_fd: c_int) -> Result<(), i32> {
+    if fence_fd < 0 {
+        return Ok(());
+    }
+    let mut poll_fd = libc::pollfd {
+        fd: fence_fd,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let result = loop {
+        // SAFETY: `poll_fd` is valid for the call.
+        let res = unsafe { libc::poll(&mut poll_fd, 1, FENCE_TIMEOUT_MS) };
+        if res > 0 {
+            break Ok(());
+        }
+        if res == 0 {
+            error!("The buffer fence didn't signal within {FENCE_TIMEOUT_MS} ms");
+            // OH_NATIVEXCOMPONENT_RESULT_FAILED
+            break Err(-1);
+        }
+        let err = std::io::Error::last_os_error();
+        if err.kind() != std::io::ErrorKind::Interrupted {
+            error!("Waiting for the buffer fence failed: {err}");
+            // OH_NATIVEXCOMPONENT_RESULT_FAILED
+            break Err(-1);
+        }
+    };
+    // SAFETY: The fence was passed to us by `OH_NativeWindow_NativeWindowRequestBuffer`.
+    unsafe { libc::close(fence_fd) };
+    result
+}
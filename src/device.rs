@@ -0,0 +1,55 @@
+//! Information about the device the application runs on
+
+use ohos_sys::deviceinfo::OH_GetDeviceType;
+use std::ffi::CStr;
+
+/// Form factor of the device
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DeviceType {
+    Phone,
+    Tablet,
+    /// Laptop-like devices with a keyboard and a pointing device
+    TwoInOne,
+    Tv,
+    Wearable,
+    LiteWearable,
+    Car,
+    SmartVision,
+    /// A device type which is not known to this crate
+    Other(String),
+}
+
+impl DeviceType {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "phone" | "default" => Self::Phone,
+            "tablet" => Self::Tablet,
+            "2in1" => Self::TwoInOne,
+            "tv" => Self::Tv,
+            "wearable" => Self::Wearable,
+            "liteWearable" => Self::LiteWearable,
+            "car" => Self::Car,
+            "smartVision" => Self::SmartVision,
+            other => Self::Other(other.to_string()),
+        }
+    }
+
+    /// Whether a mouse or touchpad, and thus hover events, can usually be expected
+    pub fn has_pointer(&self) -> bool {
+        matches!(self, Self::TwoInOne)
+    }
+}
+
+/// Returns the type of the device
+pub fn device_type() -> DeviceType {
+    // SAFETY: `OH_GetDeviceType` returns a NUL-terminated string with static lifetime.
+    let name = unsafe {
+        let name = OH_GetDeviceType();
+        if name.is_null() {
+            return DeviceType::Other(String::new());
+        }
+        CStr::from_ptr(name)
+    };
+    DeviceType::from_name(&name.to_string_lossy())
+}
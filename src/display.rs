@@ -0,0 +1,499 @@
+//! Properties of the display hosting the XComponent
+//!
+//! The XComponent reports sizes and coordinates in physical pixels, while ArkUI layouts are
+//! specified in virtual pixels (`vp`). The [`scale_factor`] is the number of physical pixels per
+//! virtual pixel.
+//!
+//! The native display manager only exposes the properties of the default display, which is the
+//! display hosting the XComponent on all current OpenHarmony devices.
+//!
+//! ## Example
+//! ```no_run
+//! let scale_factor = xcomponent::display::scale_factor().unwrap_or(1.0);
+//! let listener = xcomponent::display::on_scale_factor_changed(|event| {
+//!     // Re-layout with `event.scale_factor` ...
+//! })
+//! .expect("Failed to register the display change listener");
+//! // Dropping the listener stops the notifications.
+//! drop(listener);
+//! ```
+
+use crate::geometry::LogicalSize;
+use crate::log::error;
+use crate::trace::ffi_span;
+use crate::XComponent;
+use ohos_sys::window_manager::display_info::{
+    NativeDisplayManagerResult, NativeDisplayManager_FoldDisplayMode,
+    NativeDisplayManager_Orientation, NativeDisplayManager_Rotation,
+};
+use ohos_sys::window_manager::display_manager::{
+    OH_NativeDisplayManager_GetDefaultDisplayDensityDpi,
+    OH_NativeDisplayManager_GetDefaultDisplayHeight,
+    OH_NativeDisplayManager_GetDefaultDisplayOrientation,
+    OH_NativeDisplayManager_GetDefaultDisplayRefreshRate,
+    OH_NativeDisplayManager_GetDefaultDisplayRotation,
+    OH_NativeDisplayManager_GetDefaultDisplayVirtualPixelRatio,
+    OH_NativeDisplayManager_GetDefaultDisplayWidth, OH_NativeDisplayManager_GetFoldDisplayMode,
+    OH_NativeDisplayManager_IsFoldable, OH_NativeDisplayManager_RegisterDisplayChangeListener,
+    OH_NativeDisplayManager_RegisterFoldDisplayModeChangeListener,
+    OH_NativeDisplayManager_UnregisterDisplayChangeListener,
+    OH_NativeDisplayManager_UnregisterFoldDisplayModeChangeListener,
+};
+use std::sync::{Arc, Mutex};
+
+/// Notification that the scale factor of a display changed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScaleFactorChanged {
+    pub display_id: u64,
+    /// The new number of physical pixels per virtual pixel
+    pub scale_factor: f32,
+}
+
+/// Clockwise rotation of a display relative to its natural orientation
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Rotation {
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+impl Rotation {
+    fn from_raw(rotation: NativeDisplayManager_Rotation) -> Option<Self> {
+        let rotation = match rotation {
+            NativeDisplayManager_Rotation::DISPLAY_MANAGER_ROTATION_0 => Self::Deg0,
+            NativeDisplayManager_Rotation::DISPLAY_MANAGER_ROTATION_90 => Self::Deg90,
+            NativeDisplayManager_Rotation::DISPLAY_MANAGER_ROTATION_180 => Self::Deg180,
+            NativeDisplayManager_Rotation::DISPLAY_MANAGER_ROTATION_270 => Self::Deg270,
+            _ => return None,
+        };
+        Some(rotation)
+    }
+
+    /// The clockwise rotation in degrees
+    pub fn degrees(self) -> u32 {
+        match self {
+            Self::Deg0 => 0,
+            Self::Deg90 => 90,
+            Self::Deg180 => 180,
+            Self::Deg270 => 270,
+        }
+    }
+
+    /// Whether width and height of the display are swapped relative to the natural orientation
+    pub fn swaps_axes(self) -> bool {
+        matches!(self, Self::Deg90 | Self::Deg270)
+    }
+
+    /// The 2x2 column-major matrix which applies this rotation to normalized device coordinates
+    ///
+    /// Renderers doing pre-rotation multiply their output positions with this matrix, so the
+    /// content appears upright after the compositor rotated the buffer.
+    pub fn transform(self) -> [f32; 4] {
+        match self {
+            Self::Deg0 => [1.0, 0.0, 0.0, 1.0],
+            Self::Deg90 => [0.0, -1.0, 1.0, 0.0],
+            Self::Deg180 => [-1.0, 0.0, 0.0, -1.0],
+            Self::Deg270 => [0.0, 1.0, -1.0, 0.0],
+        }
+    }
+}
+
+/// Orientation of a display
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Orientation {
+    Portrait,
+    Landscape,
+    PortraitInverted,
+    LandscapeInverted,
+    Unknown,
+}
+
+impl Orientation {
+    fn from_raw(orientation: NativeDisplayManager_Orientation) -> Self {
+        match orientation {
+            NativeDisplayManager_Orientation::DISPLAY_MANAGER_PORTRAIT => Self::Portrait,
+            NativeDisplayManager_Orientation::DISPLAY_MANAGER_LANDSCAPE => Self::Landscape,
+            NativeDisplayManager_Orientation::DISPLAY_MANAGER_PORTRAIT_INVERTED => {
+                Self::PortraitInverted
+            }
+            NativeDisplayManager_Orientation::DISPLAY_MANAGER_LANDSCAPE_INVERTED => {
+                Self::LandscapeInverted
+            }
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Notification that the rotation of a display changed
+///
+/// A rotation is usually followed by a surface size change of the XComponent. Renderers can use
+/// this event to tell such a resize apart from other resizes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RotationChanged {
+    pub display_id: u64,
+    pub rotation: Rotation,
+    pub orientation: Orientation,
+}
+
+/// Which screens of a foldable device are in use
+///
+/// The native display manager does not report the fold angle, so a half-folded device is
+/// reported like an unfolded one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FoldDisplayMode {
+    /// The device is unfolded and uses its full screen
+    Full,
+    /// The device is folded and uses its main screen
+    Main,
+    /// The device is folded and uses its secondary screen
+    Sub,
+    /// Both screens are used together
+    Coordination,
+    Unknown,
+}
+
+impl FoldDisplayMode {
+    fn from_raw(mode: NativeDisplayManager_FoldDisplayMode) -> Self {
+        match mode {
+            NativeDisplayManager_FoldDisplayMode::DISPLAY_MANAGER_FOLD_DISPLAY_MODE_FULL => {
+                Self::Full
+            }
+            NativeDisplayManager_FoldDisplayMode::DISPLAY_MANAGER_FOLD_DISPLAY_MODE_MAIN => {
+                Self::Main
+            }
+            NativeDisplayManager_FoldDisplayMode::DISPLAY_MANAGER_FOLD_DISPLAY_MODE_SUB => {
+                Self::Sub
+            }
+            NativeDisplayManager_FoldDisplayMode::DISPLAY_MANAGER_FOLD_DISPLAY_MODE_COORDINATION => {
+                Self::Coordination
+            }
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Notification that a foldable device was folded or unfolded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FoldDisplayModeChanged {
+    pub mode: FoldDisplayMode,
+    /// The mode of the default display after the change, if it could be queried
+    pub display_mode: Option<DisplayMode>,
+}
+
+/// A listener, shared so that notifications can call it without holding the lock of
+/// [`LISTENERS`]
+#[derive(Clone)]
+enum Callback {
+    ScaleFactor(Arc<Mutex<dyn FnMut(ScaleFactorChanged) + Send>>),
+    Rotation(Arc<Mutex<dyn FnMut(RotationChanged) + Send>>),
+    Fold(Arc<Mutex<dyn FnMut(FoldDisplayModeChanged) + Send>>),
+}
+
+impl Callback {
+    fn is_fold(&self) -> bool {
+        matches!(self, Callback::Fold(_))
+    }
+}
+
+/// Calls a listener with `event`, catching panics
+fn notify<E>(callback: &Mutex<dyn FnMut(E) + Send>, event: E) {
+    let mut callback = callback.lock().unwrap_or_else(|e| e.into_inner());
+    crate::panic_guard::catch("display listener", || callback(event));
+}
+
+struct Listeners {
+    /// Index of the native display change listener, if registered.
+    native_index: Option<u32>,
+    /// Index of the native fold display mode listener, if registered.
+    fold_native_index: Option<u32>,
+    last_scale_factor: Option<f32>,
+    last_rotation: Option<Rotation>,
+    next_id: u64,
+    callbacks: Vec<(u64, Callback)>,
+}
+
+static LISTENERS: Mutex<Listeners> = Mutex::new(Listeners {
+    native_index: None,
+    fold_native_index: None,
+    last_scale_factor: None,
+    last_rotation: None,
+    next_id: 0,
+    callbacks: Vec::new(),
+});
+
+fn check(function: &str, res: NativeDisplayManagerResult) -> Result<(), i32> {
+    res.map_err(|err| {
+        let code = err.0.get() as i32;
+        error!("{function} failed with {code}");
+        code
+    })
+}
+
+/// Queries a property of the default display via `OH_NativeDisplayManager_GetDefaultDisplay*`
+macro_rules! get_default_display {
+    ($function:ident, $init:expr) => {{
+        let mut value = $init;
+        let _span = ffi_span!(stringify!($function));
+        let res = unsafe { $function(&mut value) };
+        check(stringify!($function), res).map(|()| value)
+    }};
+}
+
+/// Returns the number of physical pixels per virtual pixel of the default display
+pub fn scale_factor() -> Result<f32, i32> {
+    get_default_display!(
+        OH_NativeDisplayManager_GetDefaultDisplayVirtualPixelRatio,
+        0.0
+    )
+}
+
+impl XComponent<'_> {
+    /// Returns the number of physical pixels per virtual pixel of the display hosting the
+    /// XComponent, see [`scale_factor`]
+    #[cfg_attr(docsrs, doc(cfg(feature = "display")))]
+    pub fn scale_factor(&self) -> Result<f32, i32> {
+        scale_factor()
+    }
+
+    /// Returns the size of the XComponent in virtual pixels
+    #[cfg_attr(docsrs, doc(cfg(feature = "display")))]
+    pub fn logical_size(&self) -> Result<LogicalSize, i32> {
+        let size = crate::geometry::PhysicalSize::from(self.try_size()?);
+        Ok(size.to_logical(self.scale_factor()?))
+    }
+}
+
+/// Returns the physical pixel density of the default display in pixels per inch
+pub fn density_dpi() -> Result<i32, i32> {
+    get_default_display!(OH_NativeDisplayManager_GetDefaultDisplayDensityDpi, 0)
+}
+
+/// Returns the refresh rate of the default display in Hz
+pub fn refresh_rate() -> Result<u32, i32> {
+    get_default_display!(OH_NativeDisplayManager_GetDefaultDisplayRefreshRate, 0)
+}
+
+/// Returns the current rotation of the default display
+pub fn rotation() -> Result<Rotation, i32> {
+    let rotation = get_default_display!(
+        OH_NativeDisplayManager_GetDefaultDisplayRotation,
+        NativeDisplayManager_Rotation::DISPLAY_MANAGER_ROTATION_0
+    )?;
+    // Unknown values are not expected, treat them like the natural orientation.
+    Ok(Rotation::from_raw(rotation).unwrap_or(Rotation::Deg0))
+}
+
+/// Returns the current orientation of the default display
+pub fn orientation() -> Result<Orientation, i32> {
+    let orientation = get_default_display!(
+        OH_NativeDisplayManager_GetDefaultDisplayOrientation,
+        NativeDisplayManager_Orientation::DISPLAY_MANAGER_UNKNOWN
+    )?;
+    Ok(Orientation::from_raw(orientation))
+}
+
+/// Whether the device has a foldable display
+pub fn is_foldable() -> bool {
+    let _span = ffi_span!("OH_NativeDisplayManager_IsFoldable");
+    unsafe { OH_NativeDisplayManager_IsFoldable() }
+}
+
+/// Returns the current display mode of a foldable device
+pub fn fold_display_mode() -> Result<FoldDisplayMode, i32> {
+    let mode = get_default_display!(
+        OH_NativeDisplayManager_GetFoldDisplayMode,
+        NativeDisplayManager_FoldDisplayMode::DISPLAY_MANAGER_FOLD_DISPLAY_MODE_UNKNOWN
+    )?;
+    Ok(FoldDisplayMode::from_raw(mode))
+}
+
+/// Resolution and refresh rate of a display
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DisplayMode {
+    /// Width in physical pixels
+    pub width: u32,
+    /// Height in physical pixels
+    pub height: u32,
+    /// Refresh rate in Hz
+    pub refresh_rate: u32,
+}
+
+/// Returns the current mode of the default display
+///
+/// The native display manager does not expose the other modes supported by the panel, so this is
+/// the only mode which is known to be supported.
+pub fn current_mode() -> Result<DisplayMode, i32> {
+    let width: i32 = get_default_display!(OH_NativeDisplayManager_GetDefaultDisplayWidth, 0)?;
+    let height: i32 = get_default_display!(OH_NativeDisplayManager_GetDefaultDisplayHeight, 0)?;
+    Ok(DisplayMode {
+        width: width.max(0) as u32,
+        height: height.max(0) as u32,
+        refresh_rate: refresh_rate()?,
+    })
+}
+
+/// Calls `callback` whenever the scale factor of the default display changes
+///
+/// The callback is invoked on the thread of the display manager. Notifications stop when the
+/// returned listener is dropped, except for a notification which is already in progress.
+pub fn on_scale_factor_changed(
+    callback: impl FnMut(ScaleFactorChanged) + Send + 'static,
+) -> Result<DisplayListener, i32> {
+    add_listener(Callback::ScaleFactor(Arc::new(Mutex::new(callback))))
+}
+
+/// Calls `callback` whenever the rotation of the default display changes
+///
+/// The callback is invoked and removed like the one of [`on_scale_factor_changed`].
+pub fn on_rotation_changed(
+    callback: impl FnMut(RotationChanged) + Send + 'static,
+) -> Result<DisplayListener, i32> {
+    add_listener(Callback::Rotation(Arc::new(Mutex::new(callback))))
+}
+
+/// Calls `callback` whenever a foldable device is folded or unfolded
+///
+/// The callback is invoked and removed like the one of [`on_scale_factor_changed`].
+pub fn on_fold_display_mode_changed(
+    callback: impl FnMut(FoldDisplayModeChanged) + Send + 'static,
+) -> Result<DisplayListener, i32> {
+    add_listener(Callback::Fold(Arc::new(Mutex::new(callback))))
+}
+
+fn add_listener(callback: Callback) -> Result<DisplayListener, i32> {
+    let mut listeners = LISTENERS.lock().unwrap_or_else(|e| e.into_inner());
+    if callback.is_fold() {
+        if listeners.fold_native_index.is_none() {
+            let mut index: u32 = 0;
+            let _span = ffi_span!("OH_NativeDisplayManager_RegisterFoldDisplayModeChangeListener");
+            let res = unsafe {
+                OH_NativeDisplayManager_RegisterFoldDisplayModeChangeListener(
+                    Some(on_fold_display_mode_changed_cb),
+                    &mut index,
+                )
+            };
+            check(
+                "OH_NativeDisplayManager_RegisterFoldDisplayModeChangeListener",
+                res,
+            )?;
+            listeners.fold_native_index = Some(index);
+        }
+    } else if listeners.native_index.is_none() {
+        let mut index: u32 = 0;
+        let _span = ffi_span!("OH_NativeDisplayManager_RegisterDisplayChangeListener");
+        let res = unsafe {
+            OH_NativeDisplayManager_RegisterDisplayChangeListener(
+                Some(on_display_changed),
+                &mut index,
+            )
+        };
+        check("OH_NativeDisplayManager_RegisterDisplayChangeListener", res)?;
+        listeners.native_index = Some(index);
+        listeners.last_scale_factor = scale_factor().ok();
+        listeners.last_rotation = rotation().ok();
+    }
+    let id = listeners.next_id;
+    listeners.next_id += 1;
+    listeners.callbacks.push((id, callback));
+    Ok(DisplayListener { id })
+}
+
+/// Handle of a callback registered with one of the `on_*_changed` functions of this module
+///
+/// The callback is removed when this handle is dropped.
+#[derive(Debug)]
+#[must_use = "the callback is removed when the listener is dropped"]
+pub struct DisplayListener {
+    id: u64,
+}
+
+impl Drop for DisplayListener {
+    fn drop(&mut self) {
+        let mut listeners = LISTENERS.lock().unwrap_or_else(|e| e.into_inner());
+        listeners.callbacks.retain(|(id, _)| *id != self.id);
+        if !listeners.callbacks.iter().any(|(_, c)| c.is_fold()) {
+            if let Some(index) = listeners.fold_native_index.take() {
+                let _span =
+                    ffi_span!("OH_NativeDisplayManager_UnregisterFoldDisplayModeChangeListener");
+                let res = unsafe {
+                    OH_NativeDisplayManager_UnregisterFoldDisplayModeChangeListener(index)
+                };
+                let _ = check(
+                    "OH_NativeDisplayManager_UnregisterFoldDisplayModeChangeListener",
+                    res,
+                );
+            }
+        }
+        if listeners.callbacks.iter().any(|(_, c)| !c.is_fold()) {
+            return;
+        }
+        if let Some(index) = listeners.native_index.take() {
+            let _span = ffi_span!("OH_NativeDisplayManager_UnregisterDisplayChangeListener");
+            let res = unsafe { OH_NativeDisplayManager_UnregisterDisplayChangeListener(index) };
+            let _ = check(
+                "OH_NativeDisplayManager_UnregisterDisplayChangeListener",
+                res,
+            );
+        }
+    }
+}
+
+unsafe extern "C" fn on_display_changed(display_id: u64) {
+    // The display manager reports any change of the display, so only forward the properties
+    // which actually changed.
+    let scale_factor = scale_factor().ok();
+    let rotation = rotation().ok();
+    let orientation = orientation().unwrap_or(Orientation::Unknown);
+    let mut listeners = LISTENERS.lock().unwrap_or_else(|e| e.into_inner());
+    let scale_factor = scale_factor.filter(|s| listeners.last_scale_factor != Some(*s));
+    let rotation = rotation.filter(|r| listeners.last_rotation != Some(*r));
+    if let Some(scale_factor) = scale_factor {
+        listeners.last_scale_factor = Some(scale_factor);
+    }
+    if let Some(rotation) = rotation {
+        listeners.last_rotation = Some(rotation);
+    }
+    // Call the listeners without the lock, so they may register or drop listeners.
+    let callbacks: Vec<Callback> = listeners.callbacks.iter().map(|(_, c)| c.clone()).collect();
+    drop(listeners);
+    for callback in callbacks {
+        match (callback, scale_factor, rotation) {
+            (Callback::ScaleFactor(callback), Some(scale_factor), _) => notify(
+                &callback,
+                ScaleFactorChanged {
+                    display_id,
+                    scale_factor,
+                },
+            ),
+            (Callback::Rotation(callback), _, Some(rotation)) => notify(
+                &callback,
+                RotationChanged {
+                    display_id,
+                    rotation,
+                    orientation,
+                },
+            ),
+            _ => {}
+        }
+    }
+}
+
+unsafe extern "C" fn on_fold_display_mode_changed_cb(mode: NativeDisplayManager_FoldDisplayMode) {
+    let event = FoldDisplayModeChanged {
+        mode: FoldDisplayMode::from_raw(mode),
+        display_mode: current_mode().ok(),
+    };
+    let callbacks: Vec<Callback> = LISTENERS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .callbacks
+        .iter()
+        .map(|(_, c)| c.clone())
+        .collect();
+    for callback in callbacks {
+        if let Callback::Fold(callback) = callback {
+            notify(&callback, event);
+        }
+    }
+}
@@ -0,0 +1,214 @@
+//! Frame callbacks with their own frame rate, independent of the UI
+//!
+//! A [`DisplaySoloist`] calls a closure for every frame at the rate set via
+//! [`DisplaySoloist::set_expected_frame_rate_range`], optionally on its own thread. Like a
+//! [`VsyncLoop`](crate::vsync::VsyncLoop) with the `vsync` feature it can drive a render thread,
+//! but the system can lower its rate, e.g. to save power when the content is static, without
+//! affecting the UI or other render threads.
+//!
+//! ## Example
+//! ```no_run
+//! use xcomponent::display_soloist::DisplaySoloist;
+//!
+//! let soloist = DisplaySoloist::new(true).expect("Failed to create the DisplaySoloist");
+//! soloist.set_expected_frame_rate_range(30, 60, 60).unwrap();
+//! soloist
+//!     .start(|timestamp, target_timestamp| {
+//!         // Render the frame for `target_timestamp` ...
+//!     })
+//!     .unwrap();
+//! // Dropping the soloist stops the callbacks.
+//! drop(soloist);
+//! ```
+
+use crate::log::error;
+use crate::trace::ffi_span;
+use crate::FrameRateError;
+use core::ffi::{c_longlong, c_void};
+use core::ptr::NonNull;
+use core::time::Duration;
+use std::sync::Mutex;
+
+/// Bindings of the native DisplaySoloist API, which `ohos-sys` doesn't provide
+mod ffi {
+    #![allow(non_camel_case_types)]
+
+    use core::ffi::{c_longlong, c_void};
+
+    #[repr(C)]
+    pub(super) struct OH_DisplaySoloist {
+        _unused: [u8; 0],
+    }
+
+    pub(super) type OH_DisplaySoloist_FrameCallback = Option<
+        unsafe extern "C" fn(
+            timestamp: c_longlong,
+            target_timestamp: c_longlong,
+            data: *mut c_void,
+        ),
+    >;
+
+    #[repr(C)]
+    pub(super) struct DisplaySoloist_ExpectedRateRange {
+        pub(super) min: i32,
+        pub(super) max: i32,
+        pub(super) expected: i32,
+    }
+
+    #[link(name = "native_display_soloist")]
+    extern "C" {
+        pub(super) fn OH_DisplaySoloist_Create(
+            use_exclusive_thread: bool,
+        ) -> *mut OH_DisplaySoloist;
+        pub(super) fn OH_DisplaySoloist_Destroy(display_soloist: *mut OH_DisplaySoloist) -> i32;
+        pub(super) fn OH_DisplaySoloist_Start(
+            display_soloist: *mut OH_DisplaySoloist,
+            callback: OH_DisplaySoloist_FrameCallback,
+            data: *mut c_void,
+        ) -> i32;
+        pub(super) fn OH_DisplaySoloist_Stop(display_soloist: *mut OH_DisplaySoloist) -> i32;
+        pub(super) fn OH_DisplaySoloist_SetExpectedFrameRateRange(
+            display_soloist: *mut OH_DisplaySoloist,
+            range: *mut DisplaySoloist_ExpectedRateRange,
+        ) -> i32;
+    }
+}
+
+type FrameCallback = Box<dyn FnMut(Duration, Duration) + Send>;
+
+/// A frame-rate-managed source of frame callbacks, destroyed when dropped
+pub struct DisplaySoloist {
+    soloist: NonNull<ffi::OH_DisplaySoloist>,
+    /// The closure called by [`on_frame`], which stays at the same address until `self` is dropped
+    callback: Box<Mutex<Option<FrameCallback>>>,
+}
+
+// SAFETY: The DisplaySoloist can be used from any thread, and the closure is `Send`.
+unsafe impl Send for DisplaySoloist {}
+
+impl DisplaySoloist {
+    /// Creates a DisplaySoloist, calling its callbacks on its own thread if `exclusive_thread`
+    ///
+    /// Otherwise, the callbacks are called on the thread creating the DisplaySoloist, which
+    /// needs an event loop, like the UI thread.
+    pub fn new(exclusive_thread: bool) -> Result<Self, i32> {
+        let soloist = unsafe { ffi::OH_DisplaySoloist_Create(exclusive_thread) };
+        let Some(soloist) = NonNull::new(soloist) else {
+            error!("OH_DisplaySoloist_Create failed");
+            // OH_NATIVEXCOMPONENT_RESULT_FAILED
+            return Err(-1);
+        };
+        Ok(Self {
+            soloist,
+            callback: Box::new(Mutex::new(None)),
+        })
+    }
+
+    /// Calls `callback` with the timestamp and the target timestamp of every frame
+    ///
+    /// Replaces the closure of a previous call. Must not be called from the closure.
+    pub fn start(
+        &self,
+        callback: impl FnMut(Duration, Duration) + Send + 'static,
+    ) -> Result<(), i32> {
+        *self.lock() = Some(Box::new(callback));
+        let data: *const Mutex<Option<FrameCallback>> = &*self.callback;
+        let mut span = ffi_span!("OH_DisplaySoloist_Start", self.soloist);
+        // SAFETY: `data` lives until the DisplaySoloist is destroyed.
+        let res = unsafe {
+            ffi::OH_DisplaySoloist_Start(
+                self.soloist.as_ptr(),
+                Some(on_frame),
+                data.cast_mut().cast(),
+            )
+        };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_DisplaySoloist_Start failed with {res}");
+            *self.lock() = None;
+            return Err(res);
+        }
+        Ok(())
+    }
+
+    /// Stops calling the closure set via [`DisplaySoloist::start`], and drops it
+    ///
+    /// Must not be called from the closure.
+    pub fn stop(&self) -> Result<(), i32> {
+        let mut span = ffi_span!("OH_DisplaySoloist_Stop", self.soloist);
+        let res = unsafe { ffi::OH_DisplaySoloist_Stop(self.soloist.as_ptr()) };
+        span.set_result(res);
+        // Waits for a running callback, the closure isn't called anymore afterwards.
+        drop(self.lock().take());
+        if res != 0 {
+            error!("OH_DisplaySoloist_Stop failed with {res}");
+            return Err(res);
+        }
+        Ok(())
+    }
+
+    /// Asks the system to call the closure at `expected` frames per second
+    ///
+    /// See [`XComponent::set_expected_frame_rate_range`](crate::XComponent::set_expected_frame_rate_range).
+    pub fn set_expected_frame_rate_range(
+        &self,
+        min: i32,
+        max: i32,
+        expected: i32,
+    ) -> Result<(), FrameRateError> {
+        if min < 0 || min > expected || expected > max || max <= 0 {
+            return Err(FrameRateError::InvalidRange { min, max, expected });
+        }
+        let mut range = ffi::DisplaySoloist_ExpectedRateRange { min, max, expected };
+        let mut span = ffi_span!("OH_DisplaySoloist_SetExpectedFrameRateRange", self.soloist);
+        let res = unsafe {
+            ffi::OH_DisplaySoloist_SetExpectedFrameRateRange(self.soloist.as_ptr(), &mut range)
+        };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_DisplaySoloist_SetExpectedFrameRateRange failed with {res}");
+            return Err(FrameRateError::Native(res));
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<FrameCallback>> {
+        self.callback.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for DisplaySoloist {
+    fn drop(&mut self) {
+        drop(self.lock().take());
+        let mut span = ffi_span!("OH_DisplaySoloist_Destroy", self.soloist);
+        // SAFETY: The closure is dropped, so callbacks until the soloist is destroyed do nothing.
+        let res = unsafe { ffi::OH_DisplaySoloist_Destroy(self.soloist.as_ptr()) };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_DisplaySoloist_Destroy failed with {res}");
+        }
+    }
+}
+
+unsafe extern "C" fn on_frame(
+    timestamp: c_longlong,
+    target_timestamp: c_longlong,
+    data: *mut c_void,
+) {
+    // SAFETY: `data` is the closure of a DisplaySoloist, which outlives its callbacks.
+    let Some(callback) = (unsafe {
+        data.cast_const()
+            .cast::<Mutex<Option<FrameCallback>>>()
+            .as_ref()
+    }) else {
+        return;
+    };
+    let mut callback = callback.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(callback) = callback.as_mut() {
+        let nanos =
+            |timestamp: c_longlong| Duration::from_nanos(u64::try_from(timestamp).unwrap_or(0));
+        crate::panic_guard::catch("OH_DisplaySoloist_FrameCallback", || {
+            callback(nanos(timestamp), nanos(target_timestamp))
+        });
+    }
+}
@@ -0,0 +1,76 @@
+//! Create EGL window surfaces for XComponents
+//!
+//! On OpenHarmony, `EGLNativeWindowType` is a pointer to the `OHNativeWindow`, which EGL
+//! references for as long as the surface exists. The surface can thus outlive the
+//! [`XComponent`] or [`NativeWindow`] it was created from, but ArkUI stops displaying it once
+//! `OnSurfaceDestroyed` returned, so it should be destroyed there.
+//!
+//! ## Example
+//! ```no_run
+//! # use ohos_sys::xcomponent::OH_NativeXComponent;
+//! # use core::ffi::c_void;
+//! use khronos_egl as egl;
+//!
+//! extern "C" fn on_surface_created(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+//!     let xc = xcomponent::XComponent::new(xcomponent, window).unwrap();
+//!     let egl = egl::Instance::new(egl::Static);
+//!     let display = unsafe { egl.get_display(egl::DEFAULT_DISPLAY) }.unwrap();
+//!     egl.initialize(display).unwrap();
+//!     let attributes = [egl::RENDERABLE_TYPE, egl::OPENGL_ES3_BIT, egl::NONE];
+//!     let config = egl.choose_first_config(display, &attributes).unwrap().unwrap();
+//!     let surface = xc.create_egl_window_surface(&egl, display, config, None).unwrap();
+//!     // Create a context and make it current with `surface` ...
+//! }
+//! ```
+
+use crate::log::error;
+use crate::native_window::NativeWindow;
+use crate::XComponent;
+use khronos_egl as egl;
+
+// khronos-egl is used with the `static` backend, so we need to link EGL ourselves.
+#[link(name = "EGL")]
+extern "C" {}
+
+impl XComponent<'_> {
+    /// Creates an EGL window surface for the native window of the XComponent
+    ///
+    /// `config` must be a config of `display`. Fails with `BadNativeWindow` for mock XComponents.
+    #[cfg_attr(docsrs, doc(cfg(feature = "egl")))]
+    pub fn create_egl_window_surface<T: egl::api::EGL1_0>(
+        &self,
+        egl: &egl::Instance<T>,
+        display: egl::Display,
+        config: egl::Config,
+        attributes: Option<&[egl::Int]>,
+    ) -> Result<egl::Surface, egl::Error> {
+        let window = self
+            .native_window()
+            .map_err(|_| egl::Error::BadNativeWindow)?;
+        window.create_egl_window_surface(egl, display, config, attributes)
+    }
+}
+
+impl NativeWindow {
+    /// Creates an EGL window surface for the window
+    ///
+    /// `config` must be a config of `display`. Fails with `BadNativeWindow` for the windows of
+    /// mock XComponents.
+    #[cfg_attr(docsrs, doc(cfg(feature = "egl")))]
+    pub fn create_egl_window_surface<T: egl::api::EGL1_0>(
+        &self,
+        egl: &egl::Instance<T>,
+        display: egl::Display,
+        config: egl::Config,
+        attributes: Option<&[egl::Int]>,
+    ) -> Result<egl::Surface, egl::Error> {
+        if self.is_mocked() {
+            return Err(egl::Error::BadNativeWindow);
+        }
+        // SAFETY: `self` keeps the window alive during the call, and EGL takes a reference of its
+        // own. On OpenHarmony, the native window type of EGL is the `OHNativeWindow`.
+        let surface =
+            unsafe { egl.create_window_surface(display, config, self.as_ptr().cast(), attributes) };
+        surface.inspect_err(|err| error!("eglCreateWindowSurface failed with {err}"))
+    }
+}
@@ -0,0 +1,458 @@
+//! Translate XComponent input into [`egui`] input
+//!
+//! [`EguiInput`] accumulates the events of one frame into an [`egui::RawInput`], which can
+//! then be passed to `egui::Context::run`. It translates touch, mouse, hover, key and focus
+//! events. Key events only carry the physical key, so typed text needs to come from the input
+//! method, see the `text-input` feature, as [`egui::Event::Text`].
+//!
+//! [`RawInput::time`] is set from the timestamps of the events and of frame callbacks, see
+//! [`EguiInput::on_frame`], converted by the [`time`](crate::time) module. egui's animations and
+//! double click detection thereby follow the time the input happened, not when it was handled.
+//!
+//! ## Example
+//! ```
+//! # use ohos_sys::xcomponent::OH_NativeXComponent;
+//! # use core::ffi::c_void;
+//! # use std::sync::Mutex;
+//! static EGUI_INPUT: Mutex<Option<xcomponent::egui::EguiInput>> = Mutex::new(None);
+//!
+//! pub extern "C" fn on_surface_changed_cb(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+//!     let xc = xcomponent::XComponent::new(xcomponent, window).expect("Invalid XC");
+//!     let mut input = EGUI_INPUT.lock().unwrap();
+//!     let input = input.get_or_insert_with(|| xcomponent::egui::EguiInput::new(2.0));
+//!     input.on_size_changed(&xc.try_size().unwrap());
+//! }
+//!
+//! pub extern "C" fn on_dispatch_touch_event_cb(
+//!     component: *mut OH_NativeXComponent,
+//!     window: *mut c_void,
+//! ) {
+//!     let xc = xcomponent::XComponent::new(component, window).unwrap();
+//!     let touch_event = xc.touch_event().unwrap();
+//!     if let Some(input) = EGUI_INPUT.lock().unwrap().as_mut() {
+//!         input.on_touch_event(&touch_event);
+//!     }
+//! }
+//! ```
+
+use crate::key::{KeyAction, KeyEvent, ModifierTracker};
+use crate::mouse::{MouseAction, MouseButton, MouseEvent};
+use crate::touch::{TouchAction, TouchEvent};
+use crate::Size;
+use ::egui::{
+    Event, Key, Modifiers, PointerButton, Pos2, RawInput, Rect, TouchDeviceId, TouchId, TouchPhase,
+    Vec2, ViewportId,
+};
+use core::time::Duration;
+use ohos_sys::xcomponent::OH_NativeXComponent_KeyCode;
+
+/// Collects XComponent input events as [`egui::RawInput`]
+///
+/// The XComponent reports sizes and coordinates in physical pixels, while egui works in points.
+/// The conversion uses the `pixels_per_point` passed to [`EguiInput::new`] or
+/// [`EguiInput::set_pixels_per_point`].
+pub struct EguiInput {
+    raw_input: RawInput,
+    pixels_per_point: f32,
+    /// Physical size of the XComponent, if known.
+    size: Option<(u64, u64)>,
+    /// The touch point which is currently emulating the egui pointer.
+    pointer_touch_id: Option<i32>,
+    modifiers: ModifierTracker,
+}
+
+impl EguiInput {
+    pub fn new(pixels_per_point: f32) -> Self {
+        let mut input = Self {
+            raw_input: RawInput::default(),
+            pixels_per_point,
+            size: None,
+            pointer_touch_id: None,
+            modifiers: ModifierTracker::new(),
+        };
+        input.update_viewport();
+        input
+    }
+
+    /// The current number of physical pixels per egui point
+    pub fn pixels_per_point(&self) -> f32 {
+        self.pixels_per_point
+    }
+
+    /// Update the number of physical pixels per egui point, e.g. after the display density changed
+    pub fn set_pixels_per_point(&mut self, pixels_per_point: f32) {
+        self.pixels_per_point = pixels_per_point;
+        self.update_viewport();
+    }
+
+    /// Update the area egui may use to the new size of the XComponent
+    ///
+    /// Should be called from `OnSurfaceCreated` and `OnSurfaceChanged`.
+    pub fn on_size_changed(&mut self, size: &Size) {
+        self.size = Some((size.width, size.height));
+        self.update_viewport();
+    }
+
+    /// Translate a touch event into egui events
+    ///
+    /// The first touch point that goes down additionally emulates the egui pointer, so that
+    /// regular widgets can be clicked and dragged.
+    pub fn on_touch_event(&mut self, touch_event: &TouchEvent) {
+        self.set_time(touch_event.timestamp);
+        let Some(phase) = touch_event.action.map(touch_phase) else {
+            return;
+        };
+        let pos = self.to_points(touch_event.x, touch_event.y);
+        let id = touch_event.id;
+        self.raw_input.events.push(Event::Touch {
+            device_id: TouchDeviceId(touch_event.device_id as u64),
+            id: TouchId::from(id),
+            phase,
+            pos,
+            force: Some(touch_event.force),
+        });
+
+        match phase {
+            TouchPhase::Start if self.pointer_touch_id.is_none() => {
+                self.pointer_touch_id = Some(id);
+                self.raw_input.events.push(Event::PointerMoved(pos));
+                self.push_pointer_button(pos, true);
+            }
+            TouchPhase::Move if self.pointer_touch_id == Some(id) => {
+                self.raw_input.events.push(Event::PointerMoved(pos));
+            }
+            TouchPhase::End | TouchPhase::Cancel if self.pointer_touch_id == Some(id) => {
+                self.pointer_touch_id = None;
+                self.push_pointer_button(pos, false);
+                self.raw_input.events.push(Event::PointerGone);
+            }
+            _ => {}
+        }
+    }
+
+    /// Translate a mouse event into egui pointer events
+    pub fn on_mouse_event(&mut self, mouse_event: &MouseEvent) {
+        self.set_time(mouse_event.timestamp);
+        let pos = self.to_points(mouse_event.x, mouse_event.y);
+        match mouse_event.action {
+            Some(MouseAction::Move) => self.raw_input.events.push(Event::PointerMoved(pos)),
+            Some(action @ (MouseAction::Press | MouseAction::Release)) => {
+                let Some(button) = mouse_event.button.map(pointer_button) else {
+                    return;
+                };
+                self.raw_input.events.push(Event::PointerButton {
+                    pos,
+                    button,
+                    pressed: action == MouseAction::Press,
+                    modifiers: egui_modifiers(self.modifiers.modifiers()),
+                });
+            }
+            Some(MouseAction::Cancel) => self.raw_input.events.push(Event::PointerGone),
+            None => {}
+        }
+    }
+
+    /// Translate a hover event, the pointer leaving the XComponent removes the egui pointer
+    pub fn on_hover(&mut self, is_hover: bool) {
+        if !is_hover {
+            self.raw_input.events.push(Event::PointerGone);
+        }
+    }
+
+    /// Translate a key event into egui key and modifier events
+    ///
+    /// Keys without an [`egui::Key`] only update the modifiers.
+    pub fn on_key_event(&mut self, key_event: &KeyEvent) {
+        self.set_time(key_event.timestamp);
+        let previous = self.modifiers.modifiers();
+        let modifiers = self.modifiers.on_key_event(key_event);
+        let modifiers = egui_modifiers(modifiers);
+        if modifiers != egui_modifiers(previous) {
+            self.raw_input
+                .events
+                .push(Event::ModifiersChanged(modifiers));
+        }
+        let (Some(action), Some(key)) = (key_event.action, egui_key(key_event.code)) else {
+            return;
+        };
+        self.raw_input.events.push(Event::Key {
+            key,
+            physical_key: Some(key),
+            pressed: action == KeyAction::Down,
+            repeat: false,
+            modifiers,
+        });
+    }
+
+    /// Translate the XComponent gaining (`true`) or losing the focus
+    ///
+    /// Losing the focus releases all modifiers, since their key up events go elsewhere.
+    pub fn on_focus_changed(&mut self, focused: bool) {
+        self.raw_input.events.push(Event::WindowFocused(focused));
+        if !focused && !self.modifiers.modifiers().is_empty() {
+            self.modifiers.reset();
+            self.raw_input
+                .events
+                .push(Event::ModifiersChanged(Modifiers::NONE));
+        }
+    }
+
+    /// Advances the time to the `timestamp` of a frame callback
+    ///
+    /// Should be called from the callback set via
+    /// [`XComponent::set_frame_callback`](crate::XComponent::set_frame_callback) before taking the
+    /// input, so animations run in frames without input.
+    pub fn on_frame(&mut self, timestamp: Duration) {
+        self.set_time(timestamp.as_nanos().try_into().unwrap_or(i64::MAX));
+    }
+
+    /// Returns the input collected since the last call and starts a new frame
+    pub fn take_raw_input(&mut self) -> RawInput {
+        let raw_input = self.raw_input.take();
+        self.update_viewport();
+        raw_input
+    }
+
+    /// Sets the time to `timestamp`, unless the time is already later
+    ///
+    /// Events can be older than the last frame, but egui requires the time to increase.
+    fn set_time(&mut self, timestamp: i64) {
+        let time = crate::time::since_epoch(timestamp).as_secs_f64();
+        if self.raw_input.time.is_none_or(|current| current < time) {
+            self.raw_input.time = Some(time);
+        }
+    }
+
+    fn to_points(&self, x: f32, y: f32) -> Pos2 {
+        Pos2::new(x / self.pixels_per_point, y / self.pixels_per_point)
+    }
+
+    fn push_pointer_button(&mut self, pos: Pos2, pressed: bool) {
+        self.raw_input.events.push(Event::PointerButton {
+            pos,
+            button: PointerButton::Primary,
+            pressed,
+            modifiers: egui_modifiers(self.modifiers.modifiers()),
+        });
+    }
+
+    fn update_viewport(&mut self) {
+        self.raw_input
+            .viewports
+            .entry(ViewportId::ROOT)
+            .or_default()
+            .native_pixels_per_point = Some(self.pixels_per_point);
+        if let Some((width, height)) = self.size {
+            let size = Vec2::new(width as f32, height as f32) / self.pixels_per_point;
+            self.raw_input.screen_rect = Some(Rect::from_min_size(Pos2::ZERO, size));
+        }
+    }
+}
+
+fn touch_phase(action: TouchAction) -> TouchPhase {
+    match action {
+        TouchAction::Down => TouchPhase::Start,
+        TouchAction::Move => TouchPhase::Move,
+        TouchAction::Up => TouchPhase::End,
+        TouchAction::Cancel => TouchPhase::Cancel,
+    }
+}
+
+fn pointer_button(button: MouseButton) -> PointerButton {
+    match button {
+        MouseButton::Left => PointerButton::Primary,
+        MouseButton::Right => PointerButton::Secondary,
+        MouseButton::Middle => PointerButton::Middle,
+        MouseButton::Back => PointerButton::Extra1,
+        MouseButton::Forward => PointerButton::Extra2,
+    }
+}
+
+fn egui_modifiers(modifiers: crate::key::Modifiers) -> Modifiers {
+    use crate::key::Modifiers as M;
+    let ctrl = modifiers.contains(M::CTRL);
+    Modifiers {
+        alt: modifiers.contains(M::ALT),
+        ctrl,
+        shift: modifiers.contains(M::SHIFT),
+        mac_cmd: false,
+        command: ctrl,
+    }
+}
+
+/// The egui key of a physical key, assuming a US layout
+fn egui_key(code: OH_NativeXComponent_KeyCode) -> Option<Key> {
+    use OH_NativeXComponent_KeyCode as K;
+    Some(match code {
+        K::KEY_DPAD_DOWN => Key::ArrowDown,
+        K::KEY_DPAD_LEFT => Key::ArrowLeft,
+        K::KEY_DPAD_RIGHT => Key::ArrowRight,
+        K::KEY_DPAD_UP => Key::ArrowUp,
+        K::KEY_ESCAPE => Key::Escape,
+        K::KEY_TAB => Key::Tab,
+        K::KEY_DEL => Key::Backspace,
+        K::KEY_ENTER | K::KEY_NUMPAD_ENTER => Key::Enter,
+        K::KEY_SPACE => Key::Space,
+        K::KEY_INSERT => Key::Insert,
+        K::KEY_FORWARD_DEL => Key::Delete,
+        K::KEY_MOVE_HOME => Key::Home,
+        K::KEY_MOVE_END => Key::End,
+        K::KEY_PAGE_UP => Key::PageUp,
+        K::KEY_PAGE_DOWN => Key::PageDown,
+        K::KEY_COPY => Key::Copy,
+        K::KEY_CUT => Key::Cut,
+        K::KEY_PASTE => Key::Paste,
+        K::KEY_COMMA | K::KEY_NUMPAD_COMMA => Key::Comma,
+        K::KEY_BACKSLASH => Key::Backslash,
+        K::KEY_SLASH | K::KEY_NUMPAD_DIVIDE => Key::Slash,
+        K::KEY_LEFT_BRACKET => Key::OpenBracket,
+        K::KEY_RIGHT_BRACKET => Key::CloseBracket,
+        K::KEY_GRAVE => Key::Backtick,
+        K::KEY_MINUS | K::KEY_NUMPAD_SUBTRACT => Key::Minus,
+        K::KEY_PERIOD | K::KEY_NUMPAD_DOT => Key::Period,
+        K::KEY_PLUS | K::KEY_NUMPAD_ADD => Key::Plus,
+        K::KEY_EQUALS | K::KEY_NUMPAD_EQUALS => Key::Equals,
+        K::KEY_SEMICOLON => Key::Semicolon,
+        K::KEY_APOSTROPHE => Key::Quote,
+        K::KEY_0 | K::KEY_NUMPAD_0 => Key::Num0,
+        K::KEY_1 | K::KEY_NUMPAD_1 => Key::Num1,
+        K::KEY_2 | K::KEY_NUMPAD_2 => Key::Num2,
+        K::KEY_3 | K::KEY_NUMPAD_3 => Key::Num3,
+        K::KEY_4 | K::KEY_NUMPAD_4 => Key::Num4,
+        K::KEY_5 | K::KEY_NUMPAD_5 => Key::Num5,
+        K::KEY_6 | K::KEY_NUMPAD_6 => Key::Num6,
+        K::KEY_7 | K::KEY_NUMPAD_7 => Key::Num7,
+        K::KEY_8 | K::KEY_NUMPAD_8 => Key::Num8,
+        K::KEY_9 | K::KEY_NUMPAD_9 => Key::Num9,
+        K::KEY_A => Key::A,
+        K::KEY_B => Key::B,
+        K::KEY_C => Key::C,
+        K::KEY_D => Key::D,
+        K::KEY_E => Key::E,
+        K::KEY_F => Key::F,
+        K::KEY_G => Key::G,
+        K::KEY_H => Key::H,
+        K::KEY_I => Key::I,
+        K::KEY_J => Key::J,
+        K::KEY_K => Key::K,
+        K::KEY_L => Key::L,
+        K::KEY_M => Key::M,
+        K::KEY_N => Key::N,
+        K::KEY_O => Key::O,
+        K::KEY_P => Key::P,
+        K::KEY_Q => Key::Q,
+        K::KEY_R => Key::R,
+        K::KEY_S => Key::S,
+        K::KEY_T => Key::T,
+        K::KEY_U => Key::U,
+        K::KEY_V => Key::V,
+        K::KEY_W => Key::W,
+        K::KEY_X => Key::X,
+        K::KEY_Y => Key::Y,
+        K::KEY_Z => Key::Z,
+        K::KEY_F1 => Key::F1,
+        K::KEY_F2 => Key::F2,
+        K::KEY_F3 => Key::F3,
+        K::KEY_F4 => Key::F4,
+        K::KEY_F5 => Key::F5,
+        K::KEY_F6 => Key::F6,
+        K::KEY_F7 => Key::F7,
+        K::KEY_F8 => Key::F8,
+        K::KEY_F9 => Key::F9,
+        K::KEY_F10 => Key::F10,
+        K::KEY_F11 => Key::F11,
+        K::KEY_F12 => Key::F12,
+        K::KEY_F13 => Key::F13,
+        K::KEY_F14 => Key::F14,
+        K::KEY_F15 => Key::F15,
+        K::KEY_F16 => Key::F16,
+        K::KEY_F17 => Key::F17,
+        K::KEY_F18 => Key::F18,
+        K::KEY_F19 => Key::F19,
+        K::KEY_F20 => Key::F20,
+        K::KEY_F21 => Key::F21,
+        K::KEY_F22 => Key::F22,
+        K::KEY_F23 => Key::F23,
+        K::KEY_F24 => Key::F24,
+        _ => return None,
+    })
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::mock::TouchEventBuilder;
+
+    fn touch_events(builder: TouchEventBuilder, start: i64) -> Vec<TouchEvent> {
+        builder
+            .build()
+            .into_iter()
+            .map(|raw| {
+                let mut event = TouchEvent::from(raw);
+                event.timestamp += start;
+                event
+            })
+            .collect()
+    }
+
+    #[test]
+    fn first_touch_point_emulates_the_pointer() {
+        let mut input = EguiInput::new(2.0);
+        let builder = TouchEventBuilder::down(1, (100.0, 200.0))
+            .down_pointer(2, (300.0, 400.0))
+            .move_pointer(2, (310.0, 410.0))
+            .move_pointer(1, (110.0, 210.0))
+            .up_pointer(1)
+            .up_pointer(2);
+        for event in touch_events(builder, 0) {
+            input.on_touch_event(&event);
+        }
+        let pointer_events: Vec<_> = input
+            .take_raw_input()
+            .events
+            .into_iter()
+            .filter(|event| !matches!(event, Event::Touch { .. }))
+            .collect();
+        assert_eq!(
+            pointer_events,
+            [
+                Event::PointerMoved(Pos2::new(50.0, 100.0)),
+                Event::PointerButton {
+                    pos: Pos2::new(50.0, 100.0),
+                    button: PointerButton::Primary,
+                    pressed: true,
+                    modifiers: Modifiers::NONE,
+                },
+                Event::PointerMoved(Pos2::new(55.0, 105.0)),
+                Event::PointerButton {
+                    pos: Pos2::new(55.0, 105.0),
+                    button: PointerButton::Primary,
+                    pressed: false,
+                    modifiers: Modifiers::NONE,
+                },
+                Event::PointerGone,
+            ]
+        );
+    }
+
+    #[test]
+    fn time_follows_the_event_timestamps() {
+        let start = crate::time::now();
+        let mut input = EguiInput::new(1.0);
+        assert_eq!(input.take_raw_input().time, None);
+
+        let events = touch_events(TouchEventBuilder::down(0, (10.0, 10.0)).up(), start);
+        input.on_touch_event(&events[0]);
+        let down = input.take_raw_input().time.unwrap();
+        input.on_touch_event(&events[1]);
+        let up = input.take_raw_input().time.unwrap();
+        // One frame at 60 Hz apart
+        assert!((up - down - 0.016_666_667).abs() < 1e-6, "{down} {up}");
+
+        // Frames advance the time without input, older events don't turn it back.
+        input.on_frame(Duration::from_nanos(start as u64 + 300_000_000));
+        input.on_touch_event(&events[0]);
+        let frame = input.take_raw_input().time.unwrap();
+        assert!((frame - down - 0.3).abs() < 1e-6, "{down} {frame}");
+    }
+}
@@ -0,0 +1,499 @@
+//! Queue bridging XComponent callbacks to a render thread
+//!
+//! ArkUI calls the XComponent callbacks on the UI thread, while apps usually render on a thread
+//! of their own. [`EventQueue`] is an [`XComponentHandler`] which pushes every callback as an
+//! [`Event`] into a queue owned by this crate, which the render thread empties with
+//! [`drain_events`] or [`poll_events`].
+//!
+//! [`Event::SurfaceCreated`] carries a reference to the native window, so the window object stays
+//! alive as long as the render thread uses it. ArkUI stops displaying the window once
+//! `OnSurfaceDestroyed` returns, so [`EventQueue`] blocks in `OnSurfaceDestroyed` until the render
+//! thread took the [`Event::SurfaceDestroyed`], or until the timeout set via
+//! [`set_destroy_timeout`] elapsed.
+//!
+//! The queue is unbounded: if the render thread stalls, e.g. while compiling shaders, events keep
+//! accumulating until it empties the queue again. Only `OnSurfaceDestroyed` waits for it.
+//!
+//! With the `async` feature, the events can also be awaited one by one via `next_event` or an
+//! `EventStream`, instead of polling.
+//!
+//! ## Example
+//! ```no_run
+//! use std::time::Duration;
+//! use xcomponent::event_queue::{self, Event, EventQueue};
+//!
+//! # #[cfg(all(feature = "register", not(feature = "napi-ohos-v1")))]
+//! fn init(exports: napi_ohos::JsObject, env: napi_ohos::Env) -> napi_ohos::Result<()> {
+//!     xcomponent::handler::register_handler::<EventQueue, _>(&exports, &env)
+//!         .expect("Registering the event queue failed");
+//!     std::thread::spawn(render_loop);
+//!     Ok(())
+//! }
+//!
+//! fn render_loop() {
+//!     loop {
+//!         for queued in event_queue::poll_events(Duration::from_millis(16)) {
+//!             match queued.event {
+//!                 Event::SurfaceCreated { window, size } => { /* Create the swapchain ... */ }
+//!                 Event::SurfaceDestroyed => { /* Stop using the window ... */ }
+//!                 _ => {}
+//!             }
+//!         }
+//!         // Render a frame ...
+//!     }
+//! }
+//! ```
+
+use crate::handler::{SurfaceChanged, XComponentHandler};
+use crate::key::KeyEvent;
+use crate::log::error;
+use crate::mouse::MouseEvent;
+use crate::native_window::NativeWindow;
+use crate::touch::TouchEvent;
+use crate::{Size, XComponent};
+use ohos_sys::xcomponent::OH_NativeXComponent;
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Timeout of `OnSurfaceDestroyed` waiting for the render thread by default
+pub const DEFAULT_DESTROY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// An XComponent callback
+// Touch events are by far the most frequent events, boxing them would allocate for every event.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug)]
+pub enum Event {
+    /// With the `rwh_06` feature, `window` implements `HasWindowHandle`.
+    SurfaceCreated {
+        window: NativeWindow,
+        size: Size,
+    },
+    /// The size of the surface changed, see [`XComponentHandler::on_surface_resized`]
+    SurfaceChanged {
+        size: Size,
+        /// `None` if the previous size is unknown
+        old_size: Option<Size>,
+    },
+    SurfaceDestroyed,
+    Touch(TouchEvent),
+    Mouse(MouseEvent),
+    /// The mouse entered (`true`) or left the XComponent
+    Hover(bool),
+    Focus,
+    Blur,
+    Key(KeyEvent),
+}
+
+/// An [`Event`] with the XComponent it is for
+#[derive(Clone, Debug)]
+pub struct QueuedEvent {
+    /// Address of the `OH_NativeXComponent`, to tell multiple XComponents apart
+    pub component: usize,
+    /// When the callback was called
+    pub time: Instant,
+    pub event: Event,
+}
+
+struct Queue {
+    events: VecDeque<QueuedEvent>,
+    /// Number of events pushed and taken so far, to wait for an event to be taken
+    pushed: u64,
+    taken: u64,
+    destroy_timeout: Duration,
+    /// Whether [`wake_up`] was called since the last [`poll_events`]
+    woken: bool,
+    /// Tasks waiting for an event
+    #[cfg(feature = "async")]
+    wakers: Vec<core::task::Waker>,
+}
+
+static QUEUE: Mutex<Queue> = Mutex::new(Queue {
+    events: VecDeque::new(),
+    pushed: 0,
+    taken: 0,
+    destroy_timeout: DEFAULT_DESTROY_TIMEOUT,
+    woken: false,
+    #[cfg(feature = "async")]
+    wakers: Vec::new(),
+});
+/// Notified when events are pushed
+static PUSHED: Condvar = Condvar::new();
+/// Notified when events are taken
+static TAKEN: Condvar = Condvar::new();
+
+fn queue() -> MutexGuard<'static, Queue> {
+    QUEUE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Sets how long `OnSurfaceDestroyed` waits for the render thread to take the event
+///
+/// `Duration::ZERO` disables waiting, e.g. for tests without a render thread.
+pub fn set_destroy_timeout(timeout: Duration) {
+    queue().destroy_timeout = timeout;
+}
+
+/// Takes all queued events without blocking
+pub fn drain_events() -> Vec<QueuedEvent> {
+    take(queue())
+}
+
+/// Takes all queued events, waiting up to `timeout` for an event if there is none
+///
+/// Returns early without events if [`wake_up`] is called meanwhile, or was called since the last
+/// call.
+pub fn poll_events(timeout: Duration) -> Vec<QueuedEvent> {
+    let mut queue = PUSHED
+        .wait_timeout_while(queue(), timeout, |queue| {
+            queue.events.is_empty() && !queue.woken
+        })
+        .unwrap_or_else(|e| e.into_inner())
+        .0;
+    queue.woken = false;
+    take(queue)
+}
+
+/// Takes the next event, waiting up to `timeout` for one like [`poll_events`]
+#[cfg(feature = "capi")]
+pub(crate) fn poll_event(timeout: Duration) -> Option<QueuedEvent> {
+    let mut queue = PUSHED
+        .wait_timeout_while(queue(), timeout, |queue| {
+            queue.events.is_empty() && !queue.woken
+        })
+        .unwrap_or_else(|e| e.into_inner())
+        .0;
+    queue.woken = false;
+    let event = queue.events.pop_front()?;
+    queue.taken += 1;
+    TAKEN.notify_all();
+    Some(event)
+}
+
+/// Makes a [`poll_events`] waiting on another thread return, e.g. to handle a user event
+pub fn wake_up() {
+    queue().woken = true;
+    PUSHED.notify_all();
+}
+
+fn take(mut queue: MutexGuard<'_, Queue>) -> Vec<QueuedEvent> {
+    let events: Vec<_> = queue.events.drain(..).collect();
+    if !events.is_empty() {
+        queue.taken += events.len() as u64;
+        TAKEN.notify_all();
+    }
+    events
+}
+
+/// Queues `event` and returns its sequence number
+pub(crate) fn push(xcomponent: *mut OH_NativeXComponent, event: Event) -> u64 {
+    let mut queue = queue();
+    queue.events.push_back(QueuedEvent {
+        component: xcomponent as usize,
+        time: Instant::now(),
+        event,
+    });
+    queue.pushed += 1;
+    PUSHED.notify_all();
+    #[cfg(feature = "async")]
+    queue.wakers.drain(..).for_each(core::task::Waker::wake);
+    queue.pushed
+}
+
+/// Takes the next event, or registers `cx` to be woken once there is one
+#[cfg(feature = "async")]
+fn poll_next(cx: &mut core::task::Context<'_>) -> core::task::Poll<QueuedEvent> {
+    let mut queue = queue();
+    let Some(event) = queue.events.pop_front() else {
+        if !queue.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            queue.wakers.push(cx.waker().clone());
+        }
+        return core::task::Poll::Pending;
+    };
+    queue.taken += 1;
+    TAKEN.notify_all();
+    core::task::Poll::Ready(event)
+}
+
+/// Waits for the next event and takes it
+///
+/// Works with any executor, the task is woken by the UI thread pushing the event.
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub async fn next_event() -> QueuedEvent {
+    core::future::poll_fn(poll_next).await
+}
+
+/// The queued events as a `futures_core::Stream`, which never ends
+///
+/// Like [`next_event`], every event is only taken by one stream or call.
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+#[derive(Debug, Default)]
+pub struct EventStream {
+    _private: (),
+}
+
+#[cfg(feature = "async")]
+impl EventStream {
+    pub const fn new() -> Self {
+        Self { _private: () }
+    }
+}
+
+#[cfg(feature = "async")]
+impl futures_core::Stream for EventStream {
+    type Item = QueuedEvent;
+
+    fn poll_next(
+        self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+    ) -> core::task::Poll<Option<QueuedEvent>> {
+        poll_next(cx).map(Some)
+    }
+}
+
+/// [`XComponentHandler`] queueing all callbacks, see the [module documentation](self)
+pub struct EventQueue;
+
+impl EventQueue {
+    fn push(xcomponent: &XComponent<'_>, event: Event) {
+        push(xcomponent.xcomponent.as_ptr(), event);
+    }
+}
+
+impl XComponentHandler for EventQueue {
+    fn on_surface_created(xcomponent: &XComponent<'_>) {
+        // Errors are logged by `native_window`. Without a reference, the render thread can't
+        // use the window safely, so the surface isn't reported.
+        let Ok(window) = xcomponent.native_window() else {
+            return;
+        };
+        // Still report the window if only querying its size failed.
+        let size = xcomponent.try_size().unwrap_or(Size {
+            width: 0,
+            height: 0,
+            _opaque: [],
+        });
+        Self::push(xcomponent, Event::SurfaceCreated { window, size });
+    }
+
+    fn on_surface_resized(xcomponent: &XComponent<'_>, change: SurfaceChanged) {
+        let event = Event::SurfaceChanged {
+            size: change.new_size,
+            old_size: change.old_size,
+        };
+        Self::push(xcomponent, event);
+    }
+
+    fn on_surface_destroyed(xcomponent: &XComponent<'_>) {
+        let sequence = push(xcomponent.xcomponent.as_ptr(), Event::SurfaceDestroyed);
+        let queue = queue();
+        let timeout = queue.destroy_timeout;
+        if timeout.is_zero() {
+            return;
+        }
+        let (_queue, result) = TAKEN
+            .wait_timeout_while(queue, timeout, |queue| queue.taken < sequence)
+            .unwrap_or_else(|e| e.into_inner());
+        if result.timed_out() {
+            error!("The render thread didn't take SurfaceDestroyed within {timeout:?}");
+        }
+    }
+
+    fn on_touch(xcomponent: &XComponent<'_>, touch_event: TouchEvent) {
+        Self::push(xcomponent, Event::Touch(touch_event));
+    }
+
+    fn on_mouse(xcomponent: &XComponent<'_>, mouse_event: MouseEvent) {
+        Self::push(xcomponent, Event::Mouse(mouse_event));
+    }
+
+    fn on_hover(xcomponent: *mut OH_NativeXComponent, is_hover: bool) {
+        push(xcomponent, Event::Hover(is_hover));
+    }
+
+    fn on_focus(xcomponent: &XComponent<'_>) {
+        Self::push(xcomponent, Event::Focus);
+    }
+
+    fn on_blur(xcomponent: &XComponent<'_>) {
+        Self::push(xcomponent, Event::Blur);
+    }
+
+    fn on_key(xcomponent: &XComponent<'_>, key_event: KeyEvent) {
+        Self::push(xcomponent, Event::Key(key_event));
+    }
+}
+
+/// Serializes the unit tests using the queue, which is shared by all threads
+#[cfg(test)]
+pub(crate) fn lock_for_test() -> MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    let lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut queue = queue();
+    queue.events.clear();
+    queue.woken = false;
+    queue.destroy_timeout = DEFAULT_DESTROY_TIMEOUT;
+    drop(queue);
+    lock
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn events(queued: &[QueuedEvent]) -> Vec<String> {
+        queued
+            .iter()
+            .map(|queued| format!("{} {:?}", queued.component, queued.event))
+            .collect()
+    }
+
+    #[test]
+    fn events_are_taken_in_order() {
+        let _lock = lock_for_test();
+        push(0x10 as *mut _, Event::Focus);
+        push(0x20 as *mut _, Event::Hover(true));
+        push(0x10 as *mut _, Event::Blur);
+        let queued = drain_events();
+        assert_eq!(events(&queued), ["16 Focus", "32 Hover(true)", "16 Blur"]);
+        assert!(queued.windows(2).all(|pair| pair[0].time <= pair[1].time));
+        assert!(drain_events().is_empty());
+    }
+
+    #[test]
+    fn poll_events_waits_for_an_event() {
+        let _lock = lock_for_test();
+        let pusher = thread::spawn(|| {
+            thread::sleep(Duration::from_millis(20));
+            push(0x10 as *mut _, Event::Focus);
+        });
+        assert_eq!(events(&poll_events(Duration::from_secs(10))), ["16 Focus"]);
+        pusher.join().unwrap();
+        let start = Instant::now();
+        assert!(poll_events(Duration::from_millis(20)).is_empty());
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn wake_up_returns_without_events() {
+        let _lock = lock_for_test();
+        let start = Instant::now();
+        let waker = thread::spawn(|| {
+            thread::sleep(Duration::from_millis(20));
+            wake_up();
+        });
+        assert!(poll_events(Duration::from_secs(10)).is_empty());
+        assert!(start.elapsed() < Duration::from_secs(10));
+        waker.join().unwrap();
+
+        // A wake up before polling is remembered once.
+        wake_up();
+        let start = Instant::now();
+        assert!(poll_events(Duration::from_secs(10)).is_empty());
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert!(poll_events(Duration::from_millis(20)).is_empty());
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[cfg(feature = "mock")]
+    mod destroy {
+        use super::*;
+        use crate::handler::xcomponent_callbacks;
+        use crate::mock::MockXComponent;
+
+        fn destroyed(mock: &MockXComponent) -> Duration {
+            let callbacks = xcomponent_callbacks::<EventQueue>();
+            mock.surface_created(callbacks);
+            let start = Instant::now();
+            mock.surface_destroyed(callbacks);
+            start.elapsed()
+        }
+
+        #[test]
+        fn waits_for_the_render_thread() {
+            let _lock = lock_for_test();
+            set_destroy_timeout(Duration::from_secs(10));
+            let mock = MockXComponent::new("destroy-wait");
+            let render_thread = thread::spawn(|| {
+                let mut taken = Vec::new();
+                while !taken
+                    .iter()
+                    .any(|queued: &QueuedEvent| matches!(queued.event, Event::SurfaceDestroyed))
+                {
+                    thread::sleep(Duration::from_millis(20));
+                    taken.extend(drain_events());
+                }
+                taken
+            });
+            let elapsed = destroyed(&mock);
+            assert!(elapsed < Duration::from_secs(10));
+            let taken = render_thread.join().unwrap();
+            assert!(matches!(
+                taken.iter().map(|queued| &queued.event).collect::<Vec<_>>()[..],
+                [Event::SurfaceCreated { .. }, Event::SurfaceDestroyed]
+            ));
+        }
+
+        #[test]
+        fn zero_timeout_does_not_wait() {
+            let _lock = lock_for_test();
+            set_destroy_timeout(Duration::ZERO);
+            let mock = MockXComponent::new("destroy-no-wait");
+            assert!(destroyed(&mock) < Duration::from_secs(1));
+            assert_eq!(drain_events().len(), 2);
+        }
+
+        #[test]
+        fn timeout_expires() {
+            let _lock = lock_for_test();
+            set_destroy_timeout(Duration::from_millis(50));
+            let mock = MockXComponent::new("destroy-timeout");
+            assert!(destroyed(&mock) >= Duration::from_millis(50));
+            // The event stays queued for a render thread which is just slow.
+            let queued = drain_events();
+            assert!(matches!(
+                queued.last().map(|queued| &queued.event),
+                Some(Event::SurfaceDestroyed)
+            ));
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn event_stream() {
+        use core::pin::Pin;
+        use core::sync::atomic::{AtomicBool, Ordering};
+        use core::task::{Context, Poll};
+        use futures_core::Stream;
+        use std::sync::Arc;
+        use std::task::Wake;
+
+        struct Flag(AtomicBool);
+
+        impl Wake for Flag {
+            fn wake(self: Arc<Self>) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        let _lock = lock_for_test();
+        let flag = Arc::new(Flag(AtomicBool::new(false)));
+        let waker = Arc::clone(&flag).into();
+        let mut cx = Context::from_waker(&waker);
+        let mut stream = EventStream::new();
+        assert!(Pin::new(&mut stream).poll_next(&mut cx).is_pending());
+        assert!(!flag.0.load(Ordering::SeqCst));
+
+        push(0x10 as *mut _, Event::Focus);
+        assert!(flag.0.load(Ordering::SeqCst));
+        push(0x10 as *mut _, Event::Blur);
+        let Poll::Ready(Some(first)) = Pin::new(&mut stream).poll_next(&mut cx) else {
+            panic!("the stream has no event");
+        };
+        assert!(matches!(first.event, Event::Focus));
+        // Events are taken one by one.
+        assert_eq!(events(&drain_events()), ["16 Blur"]);
+    }
+}
@@ -0,0 +1,134 @@
+//! Ring buffer of recent calls into the native APIs
+//!
+//! Recording is disabled by default and can be toggled at runtime, e.g. from a debug menu or
+//! when a failure is detected. Every recorded call has the name of the native function, the
+//! address of the `OH_NativeXComponent` it was called on, if any, the return code of functions
+//! which return one, and how long the call took. Once the buffer is full, the oldest calls are
+//! discarded.
+//!
+//! ## Example
+//! ```no_run
+//! xcomponent::ffi_log::set_enabled(true);
+//! // ... Reproduce the issue ...
+//! for call in xcomponent::ffi_log::calls() {
+//!     println!("{call}");
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Number of calls kept by default
+pub const DEFAULT_CAPACITY: usize = 256;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+struct Log {
+    calls: VecDeque<FfiCall>,
+    capacity: usize,
+}
+
+static LOG: Mutex<Log> = Mutex::new(Log {
+    calls: VecDeque::new(),
+    capacity: DEFAULT_CAPACITY,
+});
+
+fn log() -> std::sync::MutexGuard<'static, Log> {
+    LOG.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// A recorded call of a native function
+#[derive(Clone, Debug)]
+pub struct FfiCall {
+    pub function: &'static str,
+    /// Address of the `OH_NativeXComponent` the function was called on
+    pub component: Option<usize>,
+    /// The return code, if the function returns one
+    pub result: Option<i32>,
+    /// When the call started
+    pub start: Instant,
+    pub duration: Duration,
+}
+
+impl fmt::Display for FfiCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.function)?;
+        if let Some(component) = self.component {
+            write!(f, "({component:#x})")?;
+        }
+        if let Some(result) = self.result {
+            write!(f, " -> {result}")?;
+        }
+        write!(f, " in {:?}", self.duration)
+    }
+}
+
+/// Starts or stops recording calls
+///
+/// Already recorded calls are kept when recording stops.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Sets the number of calls to keep, discarding the oldest calls if there are more
+pub fn set_capacity(capacity: usize) {
+    let mut log = log();
+    log.capacity = capacity;
+    let excess = log.calls.len().saturating_sub(capacity);
+    log.calls.drain(..excess);
+}
+
+/// The recorded calls, oldest first
+pub fn calls() -> Vec<FfiCall> {
+    log().calls.iter().cloned().collect()
+}
+
+/// Discards all recorded calls
+pub fn clear() {
+    log().calls.clear();
+}
+
+/// A call which is in progress
+pub(crate) struct PendingCall {
+    function: &'static str,
+    component: Option<usize>,
+    pub(crate) result: Option<i32>,
+    start: Instant,
+}
+
+impl PendingCall {
+    /// Starts recording a call, if recording is enabled
+    pub(crate) fn begin(function: &'static str, component: Option<usize>) -> Option<Self> {
+        is_enabled().then(|| Self {
+            function,
+            component,
+            result: None,
+            start: Instant::now(),
+        })
+    }
+
+    pub(crate) fn finish(self) {
+        let call = FfiCall {
+            function: self.function,
+            component: self.component,
+            result: self.result,
+            start: self.start,
+            duration: self.start.elapsed(),
+        };
+        let mut log = log();
+        if log.capacity == 0 {
+            return;
+        }
+        if log.calls.len() >= log.capacity {
+            log.calls.pop_front();
+        }
+        log.calls.push_back(call);
+    }
+}
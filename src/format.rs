@@ -0,0 +1,124 @@
+//! Pixel formats of native buffers
+
+use ohos_sys::native_buffer::buffer_common::OH_NativeBuffer_Format;
+
+/// Pixel format of a native buffer
+///
+/// The names follow the OpenHarmony naming, e.g. [`PixelFormat::Rgba8888`] is
+/// `NATIVEBUFFER_PIXEL_FMT_RGBA_8888`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PixelFormat {
+    Rgb565,
+    Rgba5658,
+    Rgbx4444,
+    Rgba4444,
+    Rgb444,
+    Rgbx5551,
+    Rgba5551,
+    Rgb555,
+    Rgbx8888,
+    Rgba8888,
+    Rgb888,
+    Bgr565,
+    Bgrx4444,
+    Bgra4444,
+    Bgrx5551,
+    Bgra5551,
+    Bgrx8888,
+    Bgra8888,
+}
+
+impl PixelFormat {
+    /// Converts a raw `OH_NativeBuffer_Format` value
+    ///
+    /// Returns `None` for values which are not a known pixel format.
+    pub fn from_raw(format: OH_NativeBuffer_Format) -> Option<Self> {
+        let format = match format {
+            OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_RGB_565 => Self::Rgb565,
+            OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_RGBA_5658 => Self::Rgba5658,
+            OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_RGBX_4444 => Self::Rgbx4444,
+            OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_RGBA_4444 => Self::Rgba4444,
+            OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_RGB_444 => Self::Rgb444,
+            OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_RGBX_5551 => Self::Rgbx5551,
+            OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_RGBA_5551 => Self::Rgba5551,
+            OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_RGB_555 => Self::Rgb555,
+            OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_RGBX_8888 => Self::Rgbx8888,
+            OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_RGBA_8888 => Self::Rgba8888,
+            OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_RGB_888 => Self::Rgb888,
+            OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_BGR_565 => Self::Bgr565,
+            OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_BGRX_4444 => Self::Bgrx4444,
+            OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_BGRA_4444 => Self::Bgra4444,
+            OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_BGRX_5551 => Self::Bgrx5551,
+            OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_BGRA_5551 => Self::Bgra5551,
+            OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_BGRX_8888 => Self::Bgrx8888,
+            OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_BGRA_8888 => Self::Bgra8888,
+            _ => return None,
+        };
+        Some(format)
+    }
+
+    /// The raw `OH_NativeBuffer_Format` value of this format
+    pub fn as_raw(self) -> OH_NativeBuffer_Format {
+        match self {
+            Self::Rgb565 => OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_RGB_565,
+            Self::Rgba5658 => OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_RGBA_5658,
+            Self::Rgbx4444 => OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_RGBX_4444,
+            Self::Rgba4444 => OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_RGBA_4444,
+            Self::Rgb444 => OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_RGB_444,
+            Self::Rgbx5551 => OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_RGBX_5551,
+            Self::Rgba5551 => OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_RGBA_5551,
+            Self::Rgb555 => OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_RGB_555,
+            Self::Rgbx8888 => OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_RGBX_8888,
+            Self::Rgba8888 => OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_RGBA_8888,
+            Self::Rgb888 => OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_RGB_888,
+            Self::Bgr565 => OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_BGR_565,
+            Self::Bgrx4444 => OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_BGRX_4444,
+            Self::Bgra4444 => OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_BGRA_4444,
+            Self::Bgrx5551 => OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_BGRX_5551,
+            Self::Bgra5551 => OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_BGRA_5551,
+            Self::Bgrx8888 => OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_BGRX_8888,
+            Self::Bgra8888 => OH_NativeBuffer_Format::NATIVEBUFFER_PIXEL_FMT_BGRA_8888,
+        }
+    }
+}
+
+// OpenHarmony names 32-bit and 24-bit formats by their byte order in memory, while DRM names
+// formats by their bit layout in a little-endian word. E.g. `RGBA_8888` stores the bytes
+// R, G, B, A, which is `DRM_FORMAT_ABGR8888`. 16-bit formats are named by their bit layout in
+// both. Formats without an unambiguous DRM equivalent are not mapped.
+#[cfg(feature = "drm-fourcc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "drm-fourcc")))]
+impl PixelFormat {
+    /// The equivalent DRM fourcc code, if there is one
+    pub fn to_drm_fourcc(self) -> Option<drm_fourcc::DrmFourcc> {
+        use drm_fourcc::DrmFourcc;
+        let fourcc = match self {
+            Self::Rgb565 => DrmFourcc::Rgb565,
+            Self::Bgr565 => DrmFourcc::Bgr565,
+            Self::Rgb888 => DrmFourcc::Bgr888,
+            Self::Rgbx8888 => DrmFourcc::Xbgr8888,
+            Self::Rgba8888 => DrmFourcc::Abgr8888,
+            Self::Bgrx8888 => DrmFourcc::Xrgb8888,
+            Self::Bgra8888 => DrmFourcc::Argb8888,
+            _ => return None,
+        };
+        Some(fourcc)
+    }
+
+    /// The pixel format equivalent to a DRM fourcc code, if there is one
+    pub fn from_drm_fourcc(fourcc: drm_fourcc::DrmFourcc) -> Option<Self> {
+        use drm_fourcc::DrmFourcc;
+        let format = match fourcc {
+            DrmFourcc::Rgb565 => Self::Rgb565,
+            DrmFourcc::Bgr565 => Self::Bgr565,
+            DrmFourcc::Bgr888 => Self::Rgb888,
+            DrmFourcc::Xbgr8888 => Self::Rgbx8888,
+            DrmFourcc::Abgr8888 => Self::Rgba8888,
+            DrmFourcc::Xrgb8888 => Self::Bgrx8888,
+            DrmFourcc::Argb8888 => Self::Bgra8888,
+            _ => return None,
+        };
+        Some(format)
+    }
+}
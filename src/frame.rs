@@ -0,0 +1,73 @@
+//! Per-component frame callbacks
+//!
+//! `OH_NativeXComponent_RegisterOnFrameCallback` takes a plain function without user data, so
+//! the closures are kept in a map keyed by the address of the component and dispatched from one
+//! trampoline. ArkUI calls the trampoline on the UI thread, which is also where callbacks are
+//! set, so the map is thread-local. XComponent nodes of the [`arkui`](crate::arkui) module share
+//! the map, keyed by the address of the node.
+
+use ohos_sys::xcomponent::OH_NativeXComponent;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::Duration;
+
+type FrameCallback = Rc<RefCell<dyn FnMut(Duration, Duration)>>;
+
+thread_local! {
+    static CALLBACKS: RefCell<HashMap<usize, FrameCallback>> = RefCell::new(HashMap::new());
+}
+
+/// Sets the frame callback of the component or node at address `key`
+pub(crate) fn set(key: usize, callback: FrameCallback) {
+    CALLBACKS.with_borrow_mut(|callbacks| callbacks.insert(key, callback));
+}
+
+pub(crate) fn remove(key: usize) {
+    CALLBACKS.with_borrow_mut(|callbacks| callbacks.remove(&key));
+}
+
+/// Whether `key` has a frame callback
+#[cfg(any(feature = "mock", feature = "register"))]
+pub(crate) fn contains(key: usize) -> bool {
+    CALLBACKS.with_borrow(|callbacks| callbacks.contains_key(&key))
+}
+
+/// Trampoline for `OH_NativeXComponent_RegisterOnFrameCallback`
+pub(crate) unsafe extern "C" fn on_frame(
+    xcomponent: *mut OH_NativeXComponent,
+    timestamp: u64,
+    target_timestamp: u64,
+) {
+    dispatch(xcomponent as usize, timestamp, target_timestamp);
+}
+
+/// Trampoline for `OH_ArkUI_XComponent_RegisterOnFrameCallback`
+#[cfg(all(feature = "arkui", feature = "api-20"))]
+pub(crate) unsafe extern "C" fn on_node_frame(
+    node: ohos_sys_opaque_types::ArkUI_NodeHandle,
+    timestamp: u64,
+    target_timestamp: u64,
+) {
+    dispatch(node as usize, timestamp, target_timestamp);
+}
+
+/// Calls the frame callback of `key`, if any
+///
+/// The map is not borrowed during the call, so the callback may replace or remove itself.
+fn dispatch(key: usize, timestamp: u64, target_timestamp: u64) {
+    let Some(callback) = CALLBACKS.with_borrow(|callbacks| callbacks.get(&key).cloned()) else {
+        return;
+    };
+    let Ok(mut callback) = callback.try_borrow_mut() else {
+        // ArkUI doesn't nest frame callbacks, but a mock might.
+        return;
+    };
+    let _span = crate::trace::callback_span!("OnFrameCallback", key as *const ());
+    crate::panic_guard::catch("OnFrameCallback", || {
+        callback(
+            Duration::from_nanos(timestamp),
+            Duration::from_nanos(target_timestamp),
+        )
+    });
+}
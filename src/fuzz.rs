@@ -0,0 +1,140 @@
+//! Entry points for fuzzing the decoding of native input
+//!
+//! These functions are not part of the stable API. They are called by the `cargo-fuzz` targets
+//! in the `fuzz` directory of the repository, and only exist so the targets can reach the
+//! private decoding paths. Like the [`mock`](crate::mock) module, the fuzz targets run on the
+//! host and need stub libraries for the OpenHarmony libraries linked by `ohos-sys`.
+//!
+//! All entry points are deterministic and only allocate proportionally to their input.
+
+use ohos_sys::xcomponent::{
+    OH_NativeXComponent_TouchEvent, OH_NativeXComponent_TouchEventType,
+    OH_NativeXComponent_TouchPoint,
+};
+
+/// Little-endian reader, which reads zeros once the data is exhausted
+struct Bytes<'a>(&'a [u8]);
+
+impl Bytes<'_> {
+    fn take<const N: usize>(&mut self) -> [u8; N] {
+        let mut bytes = [0; N];
+        let len = N.min(self.0.len());
+        bytes[..len].copy_from_slice(&self.0[..len]);
+        self.0 = &self.0[len..];
+        bytes
+    }
+
+    fn i32(&mut self) -> i32 {
+        i32::from_le_bytes(self.take())
+    }
+
+    fn u32(&mut self) -> u32 {
+        u32::from_le_bytes(self.take())
+    }
+
+    fn i64(&mut self) -> i64 {
+        i64::from_le_bytes(self.take())
+    }
+
+    fn f32(&mut self) -> f32 {
+        f32::from_le_bytes(self.take())
+    }
+
+    fn f64(&mut self) -> f64 {
+        f64::from_le_bytes(self.take())
+    }
+
+    fn bool(&mut self) -> bool {
+        self.take::<1>()[0] & 1 != 0
+    }
+
+    fn touch_point(&mut self) -> OH_NativeXComponent_TouchPoint {
+        OH_NativeXComponent_TouchPoint {
+            id: self.i32(),
+            screenX: self.f32(),
+            screenY: self.f32(),
+            x: self.f32(),
+            y: self.f32(),
+            type_: OH_NativeXComponent_TouchEventType(self.u32()),
+            size: self.f64(),
+            force: self.f32(),
+            timeStamp: self.i64(),
+            isPressed: self.bool(),
+        }
+    }
+}
+
+/// Decodes a raw touch event from arbitrary bytes
+///
+/// Every input yields an event, missing bytes are read as zero. Unlike the events of ArkUI, the
+/// event may have any `type_`, a `numPoints` larger than its `touchPoints` and non-finite
+/// coordinates.
+pub fn raw_touch_event(data: &[u8]) -> OH_NativeXComponent_TouchEvent {
+    let mut bytes = Bytes(data);
+    OH_NativeXComponent_TouchEvent {
+        id: bytes.i32(),
+        screenX: bytes.f32(),
+        screenY: bytes.f32(),
+        x: bytes.f32(),
+        y: bytes.f32(),
+        type_: OH_NativeXComponent_TouchEventType(bytes.u32()),
+        size: bytes.f64(),
+        force: bytes.f32(),
+        deviceId: bytes.i64(),
+        timeStamp: bytes.i64(),
+        numPoints: bytes.u32(),
+        touchPoints: core::array::from_fn(|_| bytes.touch_point()),
+    }
+}
+
+/// Converts the touch event decoded from `data` into a [`TouchEvent`](crate::touch::TouchEvent)
+pub fn touch_event(data: &[u8]) {
+    let touch_event = crate::touch::TouchEvent::from(raw_touch_event(data));
+    assert!(touch_event.points().len() <= touch_event.as_raw().touchPoints.len());
+    for point in touch_event.points() {
+        let _ = touch_event.point(point.id);
+    }
+}
+
+/// Parses `data` as a line of a [`record`](crate::record) trace
+#[cfg(all(feature = "mock", feature = "record"))]
+pub fn trace_line(data: &[u8]) {
+    if let Ok(line) = core::str::from_utf8(data) {
+        let _ = crate::record::parse_line(line);
+    }
+}
+
+/// Writes the touch event decoded from `data` as a trace line and parses it back
+///
+/// # Panics
+///
+/// Panics if the parsed event differs from the written one.
+#[cfg(all(feature = "mock", feature = "record"))]
+pub fn trace_round_trip(data: &[u8]) {
+    use crate::mock::MockEvent;
+
+    let mut line = String::from("0 touch");
+    crate::record::format_touch_event(&mut line, &raw_touch_event(data));
+    let Some((_, MockEvent::Touch(parsed))) = crate::record::parse_line(&line) else {
+        panic!("failed to parse `{line}`");
+    };
+    let mut reformatted = String::from("0 touch");
+    crate::record::format_touch_event(&mut reformatted, &parsed);
+    assert_eq!(line, reformatted);
+}
+
+/// Feeds the touch events decoded from consecutive chunks of `data` into an
+/// [`EguiInput`](crate::egui::EguiInput)
+#[cfg(feature = "egui")]
+pub fn egui_touch_events(data: &[u8]) {
+    let mut input = crate::egui::EguiInput::new(2.0);
+    input.on_size_changed(&crate::Size {
+        width: 1080,
+        height: 2340,
+        _opaque: [],
+    });
+    for chunk in data.chunks(core::mem::size_of::<OH_NativeXComponent_TouchEvent>()) {
+        input.on_touch_event(&crate::touch::TouchEvent::from(raw_touch_event(chunk)));
+    }
+    let _ = input.take_raw_input();
+}
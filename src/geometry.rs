@@ -0,0 +1,139 @@
+//! Geometry of the XComponent and its window
+//!
+//! The XComponent does not necessarily cover its whole window, and in split-screen or floating
+//! window mode the window does not cover the whole display. All coordinates are in physical
+//! pixels, and each rectangle is relative to its parent:
+//!
+//! * [`ComponentRect`]: the XComponent, relative to the top-left corner of its window
+//! * [`WindowRect`]: the window, relative to the top-left corner of the display
+//!
+//! Touch event coordinates (`x`, `y`) are relative to the XComponent, while `screenX` and
+//! `screenY` are relative to the display.
+//!
+//! [`PhysicalSize`] and [`LogicalSize`] convert between physical pixels and the virtual pixels
+//! (`vp`) of ArkUI layouts, given the scale factor of the display, e.g. from
+//! [`display::scale_factor`](crate::display::scale_factor).
+
+use crate::Size;
+
+/// Position and size of an XComponent within its window
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ComponentRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: u64,
+    pub height: u64,
+}
+
+impl ComponentRect {
+    /// Converts a point in window coordinates into component coordinates
+    pub fn window_to_component(&self, x: f64, y: f64) -> (f64, f64) {
+        (x - self.x, y - self.y)
+    }
+
+    /// Converts a point in component coordinates into window coordinates
+    pub fn component_to_window(&self, x: f64, y: f64) -> (f64, f64) {
+        (x + self.x, y + self.y)
+    }
+
+    /// Whether the point in window coordinates lies within the XComponent
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x
+            && y >= self.y
+            && x < self.x + self.width as f64
+            && y < self.y + self.height as f64
+    }
+
+    /// The position and size of the XComponent relative to the display
+    pub fn in_display(&self, window: &WindowRect) -> ComponentRect {
+        let (x, y) = window.window_to_display(self.x, self.y);
+        ComponentRect { x, y, ..*self }
+    }
+}
+
+/// Position and size of a window on its display
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WindowRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl WindowRect {
+    /// Converts a point in display coordinates into window coordinates
+    pub fn display_to_window(&self, x: f64, y: f64) -> (f64, f64) {
+        (x - self.x as f64, y - self.y as f64)
+    }
+
+    /// Converts a point in window coordinates into display coordinates
+    pub fn window_to_display(&self, x: f64, y: f64) -> (f64, f64) {
+        (x + self.x as f64, y + self.y as f64)
+    }
+
+    /// Whether the point in display coordinates lies within the window
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        let (x, y) = self.display_to_window(x, y);
+        x >= 0.0 && y >= 0.0 && x < self.width as f64 && y < self.height as f64
+    }
+}
+
+/// A size in physical pixels
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PhysicalSize {
+    pub width: u64,
+    pub height: u64,
+}
+
+impl PhysicalSize {
+    pub const fn new(width: u64, height: u64) -> Self {
+        Self { width, height }
+    }
+
+    /// Converts into virtual pixels, with `scale_factor` physical pixels per virtual pixel
+    pub fn to_logical(self, scale_factor: f32) -> LogicalSize {
+        let scale_factor = f64::from(scale_factor);
+        LogicalSize {
+            width: self.width as f64 / scale_factor,
+            height: self.height as f64 / scale_factor,
+        }
+    }
+}
+
+impl From<Size> for PhysicalSize {
+    fn from(size: Size) -> Self {
+        Self::new(size.width, size.height)
+    }
+}
+
+/// A size in virtual pixels (`vp`)
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LogicalSize {
+    pub width: f64,
+    pub height: f64,
+}
+
+impl LogicalSize {
+    pub const fn new(width: f64, height: f64) -> Self {
+        Self { width, height }
+    }
+
+    /// Converts into physical pixels, with `scale_factor` physical pixels per virtual pixel
+    ///
+    /// The result is rounded to the nearest pixel, negative sizes become zero.
+    pub fn to_physical(self, scale_factor: f32) -> PhysicalSize {
+        let scale_factor = f64::from(scale_factor);
+        PhysicalSize {
+            width: round(self.width * scale_factor),
+            height: round(self.height * scale_factor),
+        }
+    }
+}
+
+/// Rounds to the nearest non-negative integer, without `f64::round` which needs `std`
+fn round(value: f64) -> u64 {
+    // Saturating casts turn NaN and negative values into 0.
+    (value + 0.5) as u64
+}
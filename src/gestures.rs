@@ -0,0 +1,431 @@
+//! Recognition of taps, long presses, pans and pinches
+//!
+//! [`GestureRecognizer`] consumes the touch events of an XComponent and emits [`Gesture`]s. Long
+//! presses are reported once a pointer rested for [`GestureConfig::long_press`], which the
+//! recognizer can only notice when it is called, so call [`GestureRecognizer::update`] from the
+//! frame callback while a pointer is down.
+//!
+//! Every tap is reported as [`Gesture::Tap`], a second tap shortly after the first one and close
+//! to it is additionally reported as [`Gesture::DoubleTap`]. All positions are in component
+//! coordinates, all timestamps in nanoseconds as in [`TouchEvent::timestamp`].
+//!
+//! ## Example
+//! ```no_run
+//! use xcomponent::gestures::{Gesture, GestureRecognizer};
+//!
+//! let mut recognizer = GestureRecognizer::new();
+//! // In `DispatchTouchEvent`:
+//! # let touch_event: xcomponent::touch::TouchEvent = todo!();
+//! for gesture in recognizer.on_touch(&touch_event) {
+//!     match gesture {
+//!         Gesture::Pan { dx, dy, .. } => { /* Scroll the map by (dx, dy) ... */ }
+//!         Gesture::Pinch { scale, .. } => { /* Zoom the map ... */ }
+//!         _ => {}
+//!     }
+//! }
+//! // In the frame callback:
+//! # let timestamp = 0;
+//! if let Some(Gesture::LongPress { x, y }) = recognizer.update(timestamp) {
+//!     // Open the context menu at (x, y) ...
+//! }
+//! ```
+
+use crate::touch::{TouchAction, TouchEvent, TouchPoint};
+use core::time::Duration;
+use smallvec::SmallVec;
+
+/// A recognized gesture
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Gesture {
+    Tap {
+        x: f32,
+        y: f32,
+    },
+    DoubleTap {
+        x: f32,
+        y: f32,
+    },
+    LongPress {
+        x: f32,
+        y: f32,
+    },
+    /// A single pointer started moving, at the position where it touched down
+    PanStart {
+        x: f32,
+        y: f32,
+    },
+    /// The panning pointer moved by `(dx, dy)` to `(x, y)`
+    Pan {
+        x: f32,
+        y: f32,
+        dx: f32,
+        dy: f32,
+        /// In pixels per second
+        velocity_x: f32,
+        velocity_y: f32,
+    },
+    /// The panning pointer was lifted, e.g. to start a fling with the velocity
+    PanEnd {
+        velocity_x: f32,
+        velocity_y: f32,
+    },
+    /// A second pointer touched down
+    PinchStart {
+        focus_x: f32,
+        focus_y: f32,
+    },
+    /// The two pointers moved
+    Pinch {
+        /// Center between the pointers
+        focus_x: f32,
+        focus_y: f32,
+        /// Distance between the pointers relative to the distance at [`Gesture::PinchStart`]
+        scale: f32,
+        /// Angle of the line between the pointers relative to the angle at
+        /// [`Gesture::PinchStart`], in radians, clockwise
+        rotation: f32,
+    },
+    /// One of the two pointers was lifted
+    PinchEnd,
+}
+
+/// The gestures recognized from one touch event
+pub type Gestures = SmallVec<[Gesture; 2]>;
+
+/// Thresholds of a [`GestureRecognizer`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GestureConfig {
+    /// Distance in pixels a pointer may move and still count as a tap or long press
+    pub tap_slop: f32,
+    /// Time a pointer needs to rest for a long press
+    pub long_press: Duration,
+    /// Maximum time between the end of a tap and the start of the second tap of a double tap
+    pub double_tap_timeout: Duration,
+    /// Maximum distance in pixels between the two taps of a double tap
+    pub double_tap_slop: f32,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            tap_slop: 16.0,
+            long_press: Duration::from_millis(500),
+            double_tap_timeout: Duration::from_millis(300),
+            double_tap_slop: 100.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum State {
+    Idle,
+    /// A single pointer is down and didn't move beyond the tap slop
+    Pressed {
+        id: i32,
+        x: f32,
+        y: f32,
+        time: i64,
+        long_pressed: bool,
+    },
+    Panning {
+        id: i32,
+        x: f32,
+        y: f32,
+        time: i64,
+        velocity: (f32, f32),
+    },
+    Pinching {
+        distance: f32,
+        angle: f32,
+    },
+    /// The pointers belong to a gesture which ended, wait until all are lifted
+    Done,
+}
+
+/// Recognizes [`Gesture`]s from touch events
+#[derive(Clone, Debug)]
+pub struct GestureRecognizer {
+    config: GestureConfig,
+    state: State,
+    /// Position and time of the last tap, for detecting double taps
+    last_tap: Option<(f32, f32, i64)>,
+}
+
+impl Default for GestureRecognizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GestureRecognizer {
+    pub fn new() -> Self {
+        Self::with_config(GestureConfig::default())
+    }
+
+    pub fn with_config(config: GestureConfig) -> Self {
+        Self {
+            config,
+            state: State::Idle,
+            last_tap: None,
+        }
+    }
+
+    pub fn config(&self) -> &GestureConfig {
+        &self.config
+    }
+
+    /// Updates the recognizer with `event` and returns the recognized gestures
+    pub fn on_touch(&mut self, event: &TouchEvent) -> Gestures {
+        let mut gestures = Gestures::new();
+        let Some(action) = event.action else {
+            return gestures;
+        };
+        let ended = matches!(action, TouchAction::Up | TouchAction::Cancel);
+        // The lifted pointer is still listed in the points of its up event.
+        let pointers: SmallVec<[&TouchPoint; 2]> = event
+            .points()
+            .iter()
+            .filter(|point| !(ended && point.id == event.id))
+            .take(2)
+            .collect();
+        if action == TouchAction::Cancel {
+            match self.state {
+                State::Panning { .. } => gestures.push(Gesture::PanEnd {
+                    velocity_x: 0.0,
+                    velocity_y: 0.0,
+                }),
+                State::Pinching { .. } => gestures.push(Gesture::PinchEnd),
+                _ => {}
+            }
+            self.state = State::Idle;
+            return gestures;
+        }
+
+        self.state = match (self.state, action) {
+            (State::Idle, TouchAction::Down) => State::Pressed {
+                id: event.id,
+                x: event.x,
+                y: event.y,
+                time: event.timestamp,
+                long_pressed: false,
+            },
+            (State::Pressed { .. } | State::Panning { .. }, TouchAction::Down)
+                if pointers.len() == 2 =>
+            {
+                if let State::Panning { .. } = self.state {
+                    gestures.push(Gesture::PanEnd {
+                        velocity_x: 0.0,
+                        velocity_y: 0.0,
+                    });
+                }
+                let (focus_x, focus_y) = focus(pointers[0], pointers[1]);
+                gestures.push(Gesture::PinchStart { focus_x, focus_y });
+                State::Pinching {
+                    distance: distance(pointers[0], pointers[1]),
+                    angle: angle(pointers[0], pointers[1]),
+                }
+            }
+            (
+                pressed @ State::Pressed {
+                    id,
+                    x,
+                    y,
+                    time,
+                    long_pressed,
+                },
+                TouchAction::Move,
+            ) => {
+                let Some(point) = event.point(id) else {
+                    return gestures;
+                };
+                if (point.x - x).hypot(point.y - y) <= self.config.tap_slop {
+                    pressed
+                } else if long_pressed {
+                    State::Done
+                } else {
+                    gestures.push(Gesture::PanStart { x, y });
+                    let velocity = velocity(point.x - x, point.y - y, event.timestamp - time);
+                    gestures.push(Gesture::Pan {
+                        x: point.x,
+                        y: point.y,
+                        dx: point.x - x,
+                        dy: point.y - y,
+                        velocity_x: velocity.0,
+                        velocity_y: velocity.1,
+                    });
+                    State::Panning {
+                        id,
+                        x: point.x,
+                        y: point.y,
+                        time: event.timestamp,
+                        velocity,
+                    }
+                }
+            }
+            (
+                State::Panning {
+                    id,
+                    x,
+                    y,
+                    time,
+                    velocity: previous,
+                },
+                TouchAction::Move,
+            ) => {
+                let Some(point) = event.point(id) else {
+                    return gestures;
+                };
+                let (dx, dy) = (point.x - x, point.y - y);
+                let current = velocity(dx, dy, event.timestamp - time);
+                // Smooth the velocity, touch sampling is noisy.
+                let velocity = (
+                    0.6 * current.0 + 0.4 * previous.0,
+                    0.6 * current.1 + 0.4 * previous.1,
+                );
+                gestures.push(Gesture::Pan {
+                    x: point.x,
+                    y: point.y,
+                    dx,
+                    dy,
+                    velocity_x: velocity.0,
+                    velocity_y: velocity.1,
+                });
+                State::Panning {
+                    id,
+                    x: point.x,
+                    y: point.y,
+                    time: event.timestamp,
+                    velocity,
+                }
+            }
+            (
+                pinching @ State::Pinching {
+                    distance: d,
+                    angle: a,
+                },
+                TouchAction::Move,
+            ) => {
+                if let [first, second] = pointers[..] {
+                    let (focus_x, focus_y) = focus(first, second);
+                    gestures.push(Gesture::Pinch {
+                        focus_x,
+                        focus_y,
+                        scale: if d > 0.0 {
+                            distance(first, second) / d
+                        } else {
+                            1.0
+                        },
+                        rotation: normalize_angle(angle(first, second) - a),
+                    });
+                }
+                pinching
+            }
+            (
+                State::Pressed {
+                    id,
+                    x,
+                    y,
+                    long_pressed,
+                    ..
+                },
+                TouchAction::Up,
+            ) if id == event.id => {
+                if !long_pressed {
+                    self.tap(x, y, event.timestamp, &mut gestures);
+                }
+                State::Idle
+            }
+            (State::Panning { id, velocity, .. }, TouchAction::Up) if id == event.id => {
+                gestures.push(Gesture::PanEnd {
+                    velocity_x: velocity.0,
+                    velocity_y: velocity.1,
+                });
+                State::Done
+            }
+            (State::Pinching { .. }, TouchAction::Up) => {
+                gestures.push(Gesture::PinchEnd);
+                State::Done
+            }
+            (state, _) => state,
+        };
+        if pointers.is_empty() {
+            self.state = State::Idle;
+        }
+        gestures
+    }
+
+    /// Reports a long press once the pressed pointer rested long enough at `timestamp`
+    pub fn update(&mut self, timestamp: i64) -> Option<Gesture> {
+        let State::Pressed {
+            x,
+            y,
+            time,
+            long_pressed: ref mut long_pressed @ false,
+            ..
+        } = self.state
+        else {
+            return None;
+        };
+        let elapsed = u64::try_from(timestamp.saturating_sub(time)).unwrap_or(0);
+        if Duration::from_nanos(elapsed) < self.config.long_press {
+            return None;
+        }
+        *long_pressed = true;
+        self.last_tap = None;
+        Some(Gesture::LongPress { x, y })
+    }
+
+    /// Forgets the current gesture, e.g. when the XComponent lost focus
+    pub fn reset(&mut self) {
+        self.state = State::Idle;
+        self.last_tap = None;
+    }
+
+    fn tap(&mut self, x: f32, y: f32, timestamp: i64, gestures: &mut Gestures) {
+        gestures.push(Gesture::Tap { x, y });
+        let is_double_tap = self.last_tap.is_some_and(|(last_x, last_y, last_time)| {
+            let elapsed = u64::try_from(timestamp.saturating_sub(last_time)).unwrap_or(0);
+            Duration::from_nanos(elapsed) <= self.config.double_tap_timeout
+                && (x - last_x).hypot(y - last_y) <= self.config.double_tap_slop
+        });
+        if is_double_tap {
+            gestures.push(Gesture::DoubleTap { x, y });
+            self.last_tap = None;
+        } else {
+            self.last_tap = Some((x, y, timestamp));
+        }
+    }
+}
+
+/// Velocity in pixels per second of moving by `(dx, dy)` in `nanoseconds`
+fn velocity(dx: f32, dy: f32, nanoseconds: i64) -> (f32, f32) {
+    if nanoseconds <= 0 {
+        return (0.0, 0.0);
+    }
+    let seconds = nanoseconds as f32 / 1e9;
+    (dx / seconds, dy / seconds)
+}
+
+fn focus(first: &TouchPoint, second: &TouchPoint) -> (f32, f32) {
+    ((first.x + second.x) / 2.0, (first.y + second.y) / 2.0)
+}
+
+fn distance(first: &TouchPoint, second: &TouchPoint) -> f32 {
+    (second.x - first.x).hypot(second.y - first.y)
+}
+
+fn angle(first: &TouchPoint, second: &TouchPoint) -> f32 {
+    (second.y - first.y).atan2(second.x - first.x)
+}
+
+/// Maps `angle` into `-PI..=PI`, so that rotating across the negative x-axis doesn't jump
+fn normalize_angle(angle: f32) -> f32 {
+    use core::f32::consts::{PI, TAU};
+    if angle > PI {
+        angle - TAU
+    } else if angle < -PI {
+        angle + TAU
+    } else {
+        angle
+    }
+}
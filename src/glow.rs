@@ -0,0 +1,21 @@
+//! Load OpenGL ES functions into a [`glow::Context`]
+//!
+//! The functions are resolved via `eglGetProcAddress`, so an EGL context must have been
+//! created and made current before calling [`glow_context`].
+
+use core::ffi::c_void;
+use khronos_egl as egl;
+
+/// Creates a [`glow::Context`] for the EGL context which is current on the calling thread
+///
+/// # Safety
+///
+/// An EGL context must be current on the calling thread, and the returned context must only
+/// be used while the same EGL context is current.
+pub unsafe fn glow_context() -> ::glow::Context {
+    let egl = egl::Instance::new(egl::Static);
+    ::glow::Context::from_loader_function(|name| {
+        egl.get_proc_address(name)
+            .map_or(core::ptr::null(), |f| f as *const c_void)
+    })
+}
@@ -0,0 +1,176 @@
+//! Owned handles of XComponents for render threads
+//!
+//! [`XComponent`] borrows the pointers ArkUI passes to a callback, so it can't leave the
+//! callback or the UI thread. An [`XComponentRef`] created via [`XComponent::to_ref`] owns a
+//! reference to the native window and can be sent to a render thread. It is invalidated once the
+//! surface is destroyed, after which it returns errors instead of handing out the window.
+//!
+//! Clones of a handle share its validity. Each surface of an XComponent gets a new
+//! [generation](XComponentRef::generation), so handles of a destroyed surface stay invalid when
+//! the XComponent creates a new surface, e.g. after the app returned from the background.
+//!
+//! Handlers registered via the [`handler`](crate::handler) module invalidate the handles of an
+//! XComponent automatically after [`XComponentHandler::on_surface_destroyed`] returned. With raw
+//! callbacks, `OnSurfaceDestroyed` needs to call [`invalidate`].
+//!
+//! [`XComponentHandler::on_surface_destroyed`]: crate::handler::XComponentHandler::on_surface_destroyed
+//!
+//! ## Example
+//! ```no_run
+//! # use ohos_sys::xcomponent::OH_NativeXComponent;
+//! # use core::ffi::c_void;
+//! extern "C" fn on_surface_created(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+//!     let xc = xcomponent::XComponent::new(xcomponent, window).unwrap();
+//!     let handle = xc.to_ref().unwrap();
+//!     std::thread::spawn(move || {
+//!         while let Ok(window) = handle.native_window() {
+//!             // Render a frame into `window.as_ptr()` ...
+//!         }
+//!     });
+//! }
+//!
+//! extern "C" fn on_surface_destroyed(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+//!     // Stops the render thread above.
+//!     xcomponent::handle::invalidate(xcomponent);
+//! }
+//! ```
+
+use crate::native_window::NativeWindow;
+use crate::XComponent;
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use ohos_sys::xcomponent::OH_NativeXComponent;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread::ThreadId;
+
+/// The current surface of each XComponent with handles, by the address of the
+/// `OH_NativeXComponent`
+static SURFACES: Mutex<Option<HashMap<usize, Arc<SurfaceState>>>> = Mutex::new(None);
+
+/// The generation of the next surface with handles
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(1);
+
+/// Returns a generation larger than all previous ones
+///
+/// Also distinguishes the registrations tracked by the [`strict`](crate::strict) module.
+pub(crate) fn next_generation() -> u64 {
+    NEXT_GENERATION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// The validity of a surface, shared by all handles created for it
+#[derive(Debug)]
+struct SurfaceState {
+    valid: AtomicBool,
+    generation: u64,
+}
+
+/// An owned, `Send` handle of an XComponent, see the [module documentation](self)
+#[derive(Clone, Debug)]
+pub struct XComponentRef {
+    xcomponent: NonNull<OH_NativeXComponent>,
+    window: NativeWindow,
+    surface: Arc<SurfaceState>,
+    /// The thread the handle was created on, i.e. the UI thread of the XComponent
+    ui_thread: ThreadId,
+}
+
+// SAFETY: The pointer to the XComponent is only dereferenced on the thread which created the
+// handle, see `XComponentRef::with_xcomponent`, and the native window is `Send` and `Sync`.
+unsafe impl Send for XComponentRef {}
+unsafe impl Sync for XComponentRef {}
+
+impl XComponentRef {
+    /// Whether the surface of the XComponent was not destroyed yet
+    pub fn is_valid(&self) -> bool {
+        self.surface.valid.load(Ordering::Acquire)
+    }
+
+    /// Identifies the surface the handle was created for
+    ///
+    /// Handles of the same surface have the same generation, handles of a later surface of the
+    /// same XComponent a larger one.
+    pub fn generation(&self) -> u64 {
+        self.surface.generation
+    }
+
+    /// Returns an error once the surface was destroyed
+    fn check(&self) -> Result<(), i32> {
+        if !self.is_valid() {
+            // OH_NATIVEXCOMPONENT_RESULT_FAILED
+            return Err(-1);
+        }
+        Ok(())
+    }
+
+    /// The native window of the XComponent, until its surface was destroyed
+    ///
+    /// The window object itself stays alive as long as the handle, but ArkUI doesn't display
+    /// its buffers anymore once the surface was destroyed.
+    pub fn native_window(&self) -> Result<&NativeWindow, i32> {
+        self.check()?;
+        Ok(&self.window)
+    }
+
+    /// Calls `f` with the XComponent, until its surface was destroyed
+    ///
+    /// Returns an error when called on a thread other than the UI thread which created the
+    /// handle, since the XComponent may only be used on the UI thread.
+    pub fn with_xcomponent<R>(&self, f: impl FnOnce(&XComponent<'_>) -> R) -> Result<R, i32> {
+        self.check()?;
+        if std::thread::current().id() != self.ui_thread {
+            // OH_NATIVEXCOMPONENT_RESULT_FAILED
+            return Err(-1);
+        }
+        // OH_NATIVEXCOMPONENT_RESULT_FAILED
+        let xc =
+            XComponent::new(self.xcomponent.as_ptr(), self.window.as_ptr().cast()).ok_or(-1)?;
+        Ok(f(&xc))
+    }
+
+    /// The raw XComponent, which must only be used on the UI thread while the handle is valid
+    pub fn raw_xcomponent(&self) -> *mut OH_NativeXComponent {
+        self.xcomponent.as_ptr()
+    }
+}
+
+impl XComponent<'_> {
+    /// Creates an owned handle of the XComponent, e.g. for a render thread
+    pub fn to_ref(&self) -> Result<XComponentRef, i32> {
+        let window = self.native_window()?;
+        let surface = {
+            let mut surfaces = SURFACES.lock().unwrap_or_else(|e| e.into_inner());
+            let surfaces = surfaces.get_or_insert_with(HashMap::new);
+            Arc::clone(
+                surfaces
+                    .entry(self.xcomponent.as_ptr() as usize)
+                    .or_insert_with(|| {
+                        Arc::new(SurfaceState {
+                            valid: AtomicBool::new(true),
+                            generation: next_generation(),
+                        })
+                    }),
+            )
+        };
+        Ok(XComponentRef {
+            xcomponent: self.xcomponent,
+            window,
+            surface,
+            ui_thread: std::thread::current().id(),
+        })
+    }
+}
+
+/// Invalidates all handles of `xcomponent`, to be called when its surface was destroyed
+///
+/// Handles created afterwards are for a new surface, with a new generation.
+pub fn invalidate(xcomponent: *mut OH_NativeXComponent) {
+    let surface = SURFACES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_mut()
+        .and_then(|surfaces| surfaces.remove(&(xcomponent as usize)));
+    if let Some(surface) = surface {
+        surface.valid.store(false, Ordering::Release);
+    }
+}
@@ -0,0 +1,530 @@
+//! Trait-based XComponent callbacks
+//!
+//! Implementing [`XComponentHandler`] replaces writing an `extern "C"` function per callback and
+//! a static callback struct. The shims calling the handler are generated per handler type, and
+//! are available via [`xcomponent_callbacks`], [`mouse_event_callbacks`] and
+//! [`event_callbacks`], e.g. for use with a mock or a recorder. With the `register` feature,
+//! [`register_handler`] registers all of them, and [`replace_handler`] and [`unregister_handler`]
+//! replace or remove the handler of an XComponent later, e.g. when recreating a render engine.
+//!
+//! ## Example
+//! ```no_run
+//! use xcomponent::handler::XComponentHandler;
+//! use xcomponent::touch::TouchEvent;
+//! use xcomponent::XComponent;
+//!
+//! struct App;
+//!
+//! impl XComponentHandler for App {
+//!     fn on_surface_created(xcomponent: &XComponent<'_>) {
+//!         let size = xcomponent.try_size();
+//!         // Create the renderer ...
+//!     }
+//!
+//!     fn on_touch(xcomponent: &XComponent<'_>, touch_event: TouchEvent) {
+//!         // Handle the touch event ...
+//!     }
+//! }
+//!
+//! # #[cfg(all(feature = "register", not(feature = "napi-ohos-v1")))]
+//! fn init(exports: napi_ohos::JsObject, env: napi_ohos::Env) -> napi_ohos::Result<()> {
+//!     xcomponent::handler::register_handler::<App, _>(&exports, &env)
+//!         .expect("Registering the handler failed");
+//!     Ok(())
+//! }
+//! ```
+
+use crate::key::KeyEvent;
+use crate::log::error;
+use crate::metrics::{self, EventCategory};
+use crate::mouse::MouseEvent;
+use crate::panic_guard;
+use crate::touch::TouchEvent;
+use crate::trace::callback_span;
+use crate::{EventCallbacks, Size, XComponent};
+use core::ffi::c_void;
+use core::marker::PhantomData;
+use ohos_sys::xcomponent::{
+    OH_NativeXComponent, OH_NativeXComponent_Callback, OH_NativeXComponent_MouseEvent_Callback,
+};
+
+/// Handles the callbacks of an XComponent
+///
+/// All methods default to doing nothing. They are called on the UI thread, with the XComponent
+/// the callback is for. Since ArkUI callbacks carry no user data, handlers have no `self`, state
+/// needs to be kept e.g. in a `thread_local`, keyed by [`XComponent::id`] if there are multiple
+/// XComponents.
+pub trait XComponentHandler: 'static {
+    /// The surface was created, rendering can start
+    fn on_surface_created(xcomponent: &XComponent<'_>) {
+        let _ = xcomponent;
+    }
+
+    /// The size or position of the surface changed
+    fn on_surface_changed(xcomponent: &XComponent<'_>) {
+        let _ = xcomponent;
+    }
+
+    /// The size of the surface changed, called after [`XComponentHandler::on_surface_changed`]
+    ///
+    /// Not called if only the position changed, or if querying the size failed.
+    fn on_surface_resized(xcomponent: &XComponent<'_>, change: SurfaceChanged) {
+        let _ = (xcomponent, change);
+    }
+
+    /// The surface was destroyed, the native window must not be used anymore
+    ///
+    /// Afterwards, the [`XComponentRef`](crate::handle::XComponentRef)s of the XComponent are
+    /// invalidated.
+    fn on_surface_destroyed(xcomponent: &XComponent<'_>) {
+        let _ = xcomponent;
+    }
+
+    fn on_touch(xcomponent: &XComponent<'_>, touch_event: TouchEvent) {
+        let _ = (xcomponent, touch_event);
+    }
+
+    fn on_mouse(xcomponent: &XComponent<'_>, mouse_event: MouseEvent) {
+        let _ = (xcomponent, mouse_event);
+    }
+
+    /// The mouse entered (`is_hover == true`) or left the XComponent
+    ///
+    /// ArkUI passes no native window with this callback, so only the raw XComponent is available.
+    fn on_hover(xcomponent: *mut OH_NativeXComponent, is_hover: bool) {
+        let _ = (xcomponent, is_hover);
+    }
+
+    fn on_focus(xcomponent: &XComponent<'_>) {
+        let _ = xcomponent;
+    }
+
+    fn on_blur(xcomponent: &XComponent<'_>) {
+        let _ = xcomponent;
+    }
+
+    fn on_key(xcomponent: &XComponent<'_>, key_event: KeyEvent) {
+        let _ = (xcomponent, key_event);
+    }
+
+    /// The surface was shown again after being hidden
+    #[cfg(feature = "api-12")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "api-12")))]
+    fn on_surface_show(xcomponent: &XComponent<'_>) {
+        let _ = xcomponent;
+    }
+
+    /// The surface was hidden, e.g. since the app moved to the background
+    ///
+    /// Rendering should stop until [`XComponentHandler::on_surface_show`].
+    #[cfg(feature = "api-12")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "api-12")))]
+    fn on_surface_hide(xcomponent: &XComponent<'_>) {
+        let _ = xcomponent;
+    }
+
+    /// An axis event, e.g. of a mouse wheel
+    ///
+    /// Like for hover events, ArkUI passes no native window with this callback.
+    #[cfg(feature = "axis")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "axis")))]
+    fn on_axis(xcomponent: *mut OH_NativeXComponent, axis_event: crate::axis::AxisEvent) {
+        let _ = (xcomponent, axis_event);
+    }
+}
+
+/// The new and the previous size of a surface, see [`XComponentHandler::on_surface_resized`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SurfaceChanged {
+    pub new_size: Size,
+    /// `None` if the size wasn't known before, e.g. without the `std` feature
+    pub old_size: Option<Size>,
+}
+
+impl SurfaceChanged {
+    /// Whether the size differs from the previous one, or the previous one is unknown
+    pub fn is_resized(&self) -> bool {
+        self.old_size != Some(self.new_size)
+    }
+}
+
+#[cfg(feature = "std")]
+thread_local! {
+    /// The last known sizes of the surfaces, by the address of their `OH_NativeXComponent`
+    static SIZES: core::cell::RefCell<std::collections::HashMap<usize, Size>> =
+        core::cell::RefCell::new(std::collections::HashMap::new());
+}
+
+/// Queries the size of the surface and remembers it, returning the change from the last size
+fn update_size(xcomponent: &XComponent<'_>) -> Option<SurfaceChanged> {
+    let new_size = xcomponent.try_size().ok()?;
+    #[cfg(feature = "std")]
+    let old_size = SIZES
+        .with_borrow_mut(|sizes| sizes.insert(xcomponent.xcomponent.as_ptr() as usize, new_size));
+    #[cfg(not(feature = "std"))]
+    let old_size = None;
+    Some(SurfaceChanged { new_size, old_size })
+}
+
+struct Shims<T>(PhantomData<T>);
+
+impl<T: XComponentHandler> Shims<T> {
+    const CALLBACKS: OH_NativeXComponent_Callback = OH_NativeXComponent_Callback {
+        OnSurfaceCreated: Some(Self::on_surface_created),
+        OnSurfaceChanged: Some(Self::on_surface_changed),
+        OnSurfaceDestroyed: Some(Self::on_surface_destroyed),
+        DispatchTouchEvent: Some(Self::dispatch_touch_event),
+    };
+
+    const MOUSE_EVENT_CALLBACKS: OH_NativeXComponent_MouseEvent_Callback =
+        OH_NativeXComponent_MouseEvent_Callback {
+            DispatchMouseEvent: Some(Self::dispatch_mouse_event),
+            DispatchHoverEvent: Some(Self::dispatch_hover_event),
+        };
+
+    const EVENT_CALLBACKS: EventCallbacks = EventCallbacks {
+        on_focus: Some(Self::on_focus),
+        on_blur: Some(Self::on_blur),
+        on_key: Some(Self::on_key),
+    };
+
+    #[cfg(feature = "api-12")]
+    const VISIBILITY_CALLBACKS: crate::SurfaceVisibilityCallbacks =
+        crate::SurfaceVisibilityCallbacks {
+            on_surface_show: Some(Self::on_surface_show),
+            on_surface_hide: Some(Self::on_surface_hide),
+        };
+
+    /// Calls `f` with the XComponent and times it as a callback of `category`
+    fn with_xcomponent(
+        callback: &str,
+        category: EventCategory,
+        xcomponent: *mut OH_NativeXComponent,
+        window: *mut c_void,
+        f: impl FnOnce(&XComponent<'_>),
+    ) {
+        let Some(xc) = XComponent::new(xcomponent, window) else {
+            error!("{callback} was called with a null XComponent or window");
+            metrics::dropped(category);
+            return;
+        };
+        let _span = callback_span!(callback, xcomponent);
+        metrics::time_callback(category, || {
+            panic_guard::catch(callback, || f(&xc));
+        });
+    }
+
+    /// Like [`Self::with_xcomponent`] for callbacks whose event needs no query
+    fn received(
+        callback: &str,
+        category: EventCategory,
+        xcomponent: *mut OH_NativeXComponent,
+        window: *mut c_void,
+        f: impl FnOnce(&XComponent<'_>),
+    ) {
+        Self::with_xcomponent(callback, category, xcomponent, window, |xc| {
+            metrics::received(category);
+            f(xc);
+        });
+    }
+
+    extern "C" fn on_surface_created(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+        Self::received(
+            "OnSurfaceCreated",
+            EventCategory::Surface,
+            xcomponent,
+            window,
+            |xc| {
+                #[cfg(feature = "std")]
+                update_size(xc);
+                T::on_surface_created(xc);
+            },
+        );
+    }
+
+    extern "C" fn on_surface_changed(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+        Self::received(
+            "OnSurfaceChanged",
+            EventCategory::Surface,
+            xcomponent,
+            window,
+            |xc| {
+                let change = update_size(xc);
+                T::on_surface_changed(xc);
+                if let Some(change) = change.filter(SurfaceChanged::is_resized) {
+                    T::on_surface_resized(xc, change);
+                }
+            },
+        );
+    }
+
+    extern "C" fn on_surface_destroyed(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+        Self::received(
+            "OnSurfaceDestroyed",
+            EventCategory::Surface,
+            xcomponent,
+            window,
+            T::on_surface_destroyed,
+        );
+        #[cfg(feature = "std")]
+        {
+            crate::handle::invalidate(xcomponent);
+            SIZES.with_borrow_mut(|sizes| sizes.remove(&(xcomponent as usize)));
+        }
+    }
+
+    #[cfg(feature = "api-12")]
+    extern "C" fn on_surface_show(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+        Self::received(
+            "OnSurfaceShow",
+            EventCategory::Surface,
+            xcomponent,
+            window,
+            T::on_surface_show,
+        );
+    }
+
+    #[cfg(feature = "api-12")]
+    extern "C" fn on_surface_hide(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+        Self::received(
+            "OnSurfaceHide",
+            EventCategory::Surface,
+            xcomponent,
+            window,
+            T::on_surface_hide,
+        );
+    }
+
+    extern "C" fn dispatch_touch_event(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+        let category = EventCategory::Touch;
+        Self::with_xcomponent("DispatchTouchEvent", category, xcomponent, window, |xc| {
+            if let Ok(touch_event) = xc.touch_event() {
+                T::on_touch(xc, touch_event);
+            }
+        });
+    }
+
+    extern "C" fn dispatch_mouse_event(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+        let category = EventCategory::Mouse;
+        Self::with_xcomponent("DispatchMouseEvent", category, xcomponent, window, |xc| {
+            if let Ok(mouse_event) = xc.get_mouse_event() {
+                T::on_mouse(xc, mouse_event);
+            }
+        });
+    }
+
+    extern "C" fn dispatch_hover_event(xcomponent: *mut OH_NativeXComponent, is_hover: bool) {
+        metrics::received(EventCategory::Hover);
+        let _span = callback_span!("DispatchHoverEvent", xcomponent);
+        metrics::time_callback(EventCategory::Hover, || {
+            panic_guard::catch("DispatchHoverEvent", || T::on_hover(xcomponent, is_hover));
+        });
+    }
+
+    extern "C" fn on_focus(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+        let category = EventCategory::Focus;
+        Self::received("OnFocusEvent", category, xcomponent, window, T::on_focus);
+    }
+
+    extern "C" fn on_blur(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+        let category = EventCategory::Focus;
+        Self::received("OnBlurEvent", category, xcomponent, window, T::on_blur);
+    }
+
+    extern "C" fn on_key(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+        Self::with_xcomponent("OnKeyEvent", EventCategory::Key, xcomponent, window, |xc| {
+            if let Ok(key_event) = xc.get_key_event() {
+                T::on_key(xc, key_event);
+            }
+        });
+    }
+
+    #[cfg(feature = "axis")]
+    unsafe extern "C" fn on_ui_input_event(
+        xcomponent: *mut OH_NativeXComponent,
+        event: *mut ohos_sys::arkui::ui_input_event::ArkUI_UIInputEvent,
+        _type: ohos_sys::arkui::ui_input_event::ArkUI_UIInputEvent_Type,
+    ) {
+        // SAFETY: ArkUI passes a valid event, which lives until the callback returns.
+        let Some(axis_event) = (unsafe { crate::axis::AxisEvent::from_raw(event) }) else {
+            metrics::dropped(EventCategory::Mouse);
+            return;
+        };
+        let category = EventCategory::Mouse;
+        metrics::received(category);
+        let _span = callback_span!("OnUIInputEvent", xcomponent);
+        metrics::time_callback(category, || {
+            panic_guard::catch("OnUIInputEvent", || T::on_axis(xcomponent, axis_event));
+        });
+    }
+}
+
+/// The surface and touch callbacks calling `T`
+pub fn xcomponent_callbacks<T: XComponentHandler>() -> &'static OH_NativeXComponent_Callback {
+    &Shims::<T>::CALLBACKS
+}
+
+/// The mouse event callbacks calling `T`
+pub fn mouse_event_callbacks<T: XComponentHandler>(
+) -> &'static OH_NativeXComponent_MouseEvent_Callback {
+    &Shims::<T>::MOUSE_EVENT_CALLBACKS
+}
+
+/// The focus, blur and key callbacks calling `T`
+pub fn event_callbacks<T: XComponentHandler>() -> EventCallbacks {
+    Shims::<T>::EVENT_CALLBACKS
+}
+
+/// The surface show and hide callbacks calling `T`
+#[cfg(feature = "api-12")]
+#[cfg_attr(docsrs, doc(cfg(feature = "api-12")))]
+pub fn surface_visibility_callbacks<T: XComponentHandler>() -> crate::SurfaceVisibilityCallbacks {
+    Shims::<T>::VISIBILITY_CALLBACKS
+}
+
+/// The UI input event callback calling [`XComponentHandler::on_axis`] of `T`
+#[cfg(feature = "axis")]
+#[cfg_attr(docsrs, doc(cfg(feature = "axis")))]
+pub fn axis_event_callback<T: XComponentHandler>() -> crate::axis::UIInputEventCallback {
+    Shims::<T>::on_ui_input_event
+}
+
+/// Registers all callbacks of `T` for the XComponent
+///
+/// This combines [`register_xcomponent_callbacks`](crate::register_xcomponent_callbacks),
+/// [`register_mouse_event_callbacks`](crate::register_mouse_event_callbacks) and
+/// [`register_event_callbacks`](crate::register_event_callbacks), with the `api-12` feature
+/// [`register_surface_visibility_callbacks`](crate::register_surface_visibility_callbacks) and
+/// with the `axis` feature
+/// [`register_axis_event_callback`](crate::axis::register_axis_event_callback).
+#[cfg(feature = "register")]
+#[cfg_attr(docsrs, doc(cfg(feature = "register")))]
+pub fn register_handler<T: XComponentHandler, E: crate::NapiExports>(
+    exports: &E,
+    env: &E::Env,
+) -> Result<(), crate::RegisterCallbackError> {
+    crate::register_xcomponent_callbacks(exports, env, xcomponent_callbacks::<T>())?;
+    let native_xcomponent = exports.native_xcomponent(env)?;
+    let callbacks = HandlerCallbacks::of::<T>();
+    callbacks.register(native_xcomponent, false)?;
+    HANDLERS.with_borrow_mut(|handlers| handlers.insert(native_xcomponent as usize, callbacks));
+    Ok(())
+}
+
+/// The callbacks of an [`XComponentHandler`], as registered by [`register_handler`]
+///
+/// Returned by [`replace_handler`] and [`unregister_handler`], and can be registered again via
+/// [`swap_handler`], e.g. to restore the previous handler.
+#[cfg(feature = "register")]
+#[cfg_attr(docsrs, doc(cfg(feature = "register")))]
+#[derive(Clone, Copy, Debug)]
+pub struct HandlerCallbacks {
+    type_name: &'static str,
+    xcomponent: &'static OH_NativeXComponent_Callback,
+    mouse: &'static OH_NativeXComponent_MouseEvent_Callback,
+    event: EventCallbacks,
+    #[cfg(feature = "api-12")]
+    visibility: crate::SurfaceVisibilityCallbacks,
+    #[cfg(feature = "axis")]
+    axis: crate::axis::UIInputEventCallback,
+}
+
+#[cfg(feature = "register")]
+impl HandlerCallbacks {
+    /// The callbacks calling `T`
+    pub fn of<T: XComponentHandler>() -> Self {
+        Self {
+            type_name: core::any::type_name::<T>(),
+            xcomponent: xcomponent_callbacks::<T>(),
+            mouse: mouse_event_callbacks::<T>(),
+            event: event_callbacks::<T>(),
+            #[cfg(feature = "api-12")]
+            visibility: surface_visibility_callbacks::<T>(),
+            #[cfg(feature = "axis")]
+            axis: axis_event_callback::<T>(),
+        }
+    }
+
+    /// The name of the handler type, for diagnostics
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Registers the callbacks, the surface callbacks only if `surface` is true
+    fn register(
+        &self,
+        xcomponent: *mut OH_NativeXComponent,
+        surface: bool,
+    ) -> Result<(), crate::RegisterCallbackError> {
+        if surface {
+            crate::register_xcomponent_callbacks_raw(xcomponent, self.xcomponent)?;
+        }
+        crate::register_mouse_event_callbacks_raw(xcomponent, self.mouse)?;
+        #[cfg(feature = "api-12")]
+        crate::register_surface_visibility_callbacks_raw(xcomponent, &self.visibility)?;
+        #[cfg(feature = "axis")]
+        crate::axis::register_axis_event_callback_raw(xcomponent, self.axis)?;
+        crate::register_event_callbacks_raw(xcomponent, &self.event)
+    }
+}
+
+#[cfg(feature = "register")]
+thread_local! {
+    /// The handlers by the address of their `OH_NativeXComponent`
+    static HANDLERS: core::cell::RefCell<std::collections::HashMap<usize, HandlerCallbacks>> =
+        core::cell::RefCell::new(std::collections::HashMap::new());
+}
+
+/// Registers `callbacks` for `xcomponent` instead of the ones of its current handler
+///
+/// Returns the callbacks of the previous handler, or `None` if none was registered via
+/// [`register_handler`] or this function. ArkUI only calls callbacks on the UI thread, so no
+/// callback of the previous handler runs after this returns.
+#[cfg(feature = "register")]
+#[cfg_attr(docsrs, doc(cfg(feature = "register")))]
+pub fn swap_handler(
+    xcomponent: *mut OH_NativeXComponent,
+    callbacks: HandlerCallbacks,
+) -> Result<Option<HandlerCallbacks>, crate::RegisterCallbackError> {
+    crate::thread::check_ui_thread("swap_handler");
+    callbacks.register(xcomponent, true)?;
+    Ok(HANDLERS.with_borrow_mut(|handlers| handlers.insert(xcomponent as usize, callbacks)))
+}
+
+/// Registers the callbacks of `T` for `xcomponent`, see [`swap_handler`]
+#[cfg(feature = "register")]
+#[cfg_attr(docsrs, doc(cfg(feature = "register")))]
+pub fn replace_handler<T: XComponentHandler>(
+    xcomponent: *mut OH_NativeXComponent,
+) -> Result<Option<HandlerCallbacks>, crate::RegisterCallbackError> {
+    swap_handler(xcomponent, HandlerCallbacks::of::<T>())
+}
+
+/// Stops calling the current handler of `xcomponent` and removes its frame callback
+///
+/// ArkUI can't unregister callbacks, so they are replaced by callbacks which ignore all events.
+/// Returns the callbacks of the previous handler like [`swap_handler`].
+#[cfg(feature = "register")]
+#[cfg_attr(docsrs, doc(cfg(feature = "register")))]
+pub fn unregister_handler(
+    xcomponent: *mut OH_NativeXComponent,
+) -> Result<Option<HandlerCallbacks>, crate::RegisterCallbackError> {
+    crate::thread::check_ui_thread("unregister_handler");
+    #[cfg(feature = "api-11")]
+    if crate::frame::contains(xcomponent as usize) {
+        crate::remove_frame_callback(xcomponent)
+            .map_err(crate::RegisterCallbackError::RegisterCallbackFailed)?;
+    }
+    HandlerCallbacks::of::<Unregistered>().register(xcomponent, true)?;
+    #[cfg(feature = "strict")]
+    crate::strict::untrack(xcomponent);
+    Ok(HANDLERS.with_borrow_mut(|handlers| handlers.remove(&(xcomponent as usize))))
+}
+
+/// Handler of XComponents whose handler was unregistered
+#[cfg(feature = "register")]
+struct Unregistered;
+
+#[cfg(feature = "register")]
+impl XComponentHandler for Unregistered {}
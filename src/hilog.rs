@@ -0,0 +1,60 @@
+//! Configuration of the HiLog output used by the `hilog` feature
+//!
+//! With the `hilog` feature enabled, error and diagnostic messages of this crate are written
+//! directly to HiLog, so they show up in `hdc hilog` without setting up a `log` logger.
+//! By default messages are logged with domain `0x0` and the tag `xcomponent`.
+
+use alloc::string::ToString;
+use core::ffi::{c_char, c_uint, CStr};
+use core::fmt;
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, AtomicU32, Ordering};
+use ohos_sys::hilog::{LogLevel, LogType, OH_LOG_IsLoggable, OH_LOG_Print};
+
+const DEFAULT_TAG: &CStr = c"xcomponent";
+
+static DOMAIN: AtomicU32 = AtomicU32::new(0);
+static TAG: AtomicPtr<c_char> = AtomicPtr::new(ptr::null_mut());
+
+/// Set the HiLog service domain (`0x0` to `0xFFFF`) of messages logged by this crate
+pub fn set_domain(domain: u16) {
+    DOMAIN.store(domain.into(), Ordering::Relaxed);
+}
+
+/// Set the HiLog tag of messages logged by this crate
+pub fn set_tag(tag: &'static CStr) {
+    TAG.store(tag.as_ptr().cast_mut(), Ordering::Relaxed);
+}
+
+fn tag() -> *const c_char {
+    let tag = TAG.load(Ordering::Relaxed);
+    if tag.is_null() {
+        DEFAULT_TAG.as_ptr()
+    } else {
+        tag
+    }
+}
+
+pub(crate) fn print(level: LogLevel, args: fmt::Arguments<'_>) {
+    let domain: c_uint = DOMAIN.load(Ordering::Relaxed);
+    let tag = tag();
+    // SAFETY: `tag` is a NUL-terminated string with static lifetime.
+    if !unsafe { OH_LOG_IsLoggable(domain, tag, level) } {
+        return;
+    }
+    let mut message = args.to_string().into_bytes();
+    message.retain(|&b| b != 0);
+    message.push(0);
+    // SAFETY: All arguments are NUL-terminated strings and the format string consumes exactly
+    // one string argument.
+    unsafe {
+        OH_LOG_Print(
+            LogType::LOG_APP,
+            level,
+            domain,
+            tag,
+            c"%{public}s".as_ptr(),
+            message.as_ptr().cast::<c_char>(),
+        );
+    }
+}
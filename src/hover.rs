@@ -0,0 +1,133 @@
+//! Hover events of a mouse or stylus above the XComponent
+//!
+//! ArkUI calls `DispatchHoverEvent` when a mouse or hovering stylus enters or leaves the
+//! XComponent, and `DispatchMouseEvent` for each move of the mouse. [`HoverTracker`] combines
+//! both into [`HoverEvent`]s, reporting moves as hover moves only while no button is pressed, e.g.
+//! to highlight the widget below the pointer.
+//!
+//! ArkUI only sends hover events to an XComponent which registered mouse event callbacks, see
+//! [`register_mouse_event_callbacks`](crate::register_mouse_event_callbacks).
+//!
+//! ## Example
+//! ```no_run
+//! use xcomponent::hover::{HoverEvent, HoverTracker};
+//!
+//! let mut tracker = HoverTracker::new();
+//! // In `DispatchHoverEvent`:
+//! # let is_hover = true;
+//! tracker.on_hover(is_hover);
+//! // In `DispatchMouseEvent`:
+//! # let mouse_event: xcomponent::mouse::MouseEvent = todo!();
+//! match tracker.on_mouse_event(&mouse_event) {
+//!     Some(HoverEvent::Move { x, y, .. }) => { /* Highlight the widget at (x, y) ... */ }
+//!     Some(HoverEvent::Leave) => { /* Remove the highlight ... */ }
+//!     _ => {}
+//! }
+//! ```
+
+use crate::mouse::{MouseAction, MouseEvent};
+
+/// A change of the pointer hovering above the XComponent
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HoverEvent {
+    /// The pointer entered the XComponent
+    Enter,
+    /// The pointer moved without a pressed button
+    Move {
+        x: f32,
+        y: f32,
+        screen_x: f32,
+        screen_y: f32,
+    },
+    /// The pointer left the XComponent
+    Leave,
+}
+
+impl HoverEvent {
+    /// Converts the `isHover` argument of `DispatchHoverEvent`
+    pub fn from_is_hover(is_hover: bool) -> Self {
+        if is_hover {
+            Self::Enter
+        } else {
+            Self::Leave
+        }
+    }
+}
+
+/// Tracks whether and where a pointer hovers above the XComponent
+#[derive(Clone, Debug, Default)]
+pub struct HoverTracker {
+    hovered: bool,
+    /// The number of pressed buttons, while which moves are drags instead of hover moves
+    pressed: u32,
+    position: Option<(f32, f32)>,
+}
+
+impl HoverTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether a pointer hovers above the XComponent
+    pub fn is_hovered(&self) -> bool {
+        self.hovered
+    }
+
+    /// The last position of the hovering pointer in component coordinates
+    pub fn position(&self) -> Option<(f32, f32)> {
+        self.position
+    }
+
+    /// Updates the tracker from `DispatchHoverEvent`
+    ///
+    /// Returns `None` if the pointer was already known to hover, respectively not to hover.
+    pub fn on_hover(&mut self, is_hover: bool) -> Option<HoverEvent> {
+        if self.hovered == is_hover {
+            return None;
+        }
+        self.hovered = is_hover;
+        if !is_hover {
+            self.position = None;
+            self.pressed = 0;
+        }
+        Some(HoverEvent::from_is_hover(is_hover))
+    }
+
+    /// Updates the tracker from `DispatchMouseEvent`
+    ///
+    /// Returns a hover move for moves without a pressed button. A move before ArkUI reported the
+    /// pointer entering returns [`HoverEvent::Enter`].
+    pub fn on_mouse_event(&mut self, mouse_event: &MouseEvent) -> Option<HoverEvent> {
+        match mouse_event.action? {
+            MouseAction::Press => {
+                self.pressed += 1;
+                None
+            }
+            MouseAction::Release => {
+                self.pressed = self.pressed.saturating_sub(1);
+                None
+            }
+            MouseAction::Cancel => {
+                self.pressed = 0;
+                None
+            }
+            MouseAction::Move => {
+                self.position = Some((mouse_event.x, mouse_event.y));
+                if !self.hovered {
+                    self.hovered = true;
+                    return Some(HoverEvent::Enter);
+                }
+                if self.pressed > 0 {
+                    return None;
+                }
+                Some(HoverEvent::Move {
+                    x: mouse_event.x,
+                    y: mouse_event.y,
+                    screen_x: mouse_event.screen_x,
+                    screen_y: mouse_event.screen_y,
+                })
+            }
+        }
+    }
+}
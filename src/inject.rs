@@ -0,0 +1,150 @@
+//! Injection of scripted input for development
+//!
+//! [`listen`] accepts TCP connections on a thread of its own and pushes the touch events it
+//! reads into the [`event_queue`](crate::event_queue), as [`Event::Touch`] events for one
+//! XComponent. The render thread handles them like touch events from the screen, which allows
+//! driving an app on a device with scripted gestures from the host.
+//!
+//! Connections send lines in the [trace format](crate::record#trace-format) of the `record`
+//! module, so recorded sessions can be injected again. Only `touch` lines are injected, the
+//! surface lifecycle stays with ArkUI, so other lines are skipped like malformed ones. Every line
+//! is injected once the time of its first field has elapsed since the connection was opened,
+//! which keeps the timing of the gestures. Connections are handled one after the other.
+//!
+//! Anyone who can connect can control the app, so only listen on the loopback interface and
+//! only in development builds. The port can then be forwarded from the host with
+//! `hdc fport tcp:<port> tcp:<port>`.
+//!
+//! ## Example
+//! ```no_run
+//! use xcomponent::event_queue::{self, Event};
+//!
+//! # fn render_loop() {
+//! for queued in event_queue::drain_events() {
+//!     if let Event::SurfaceCreated { .. } = queued.event {
+//!         #[cfg(debug_assertions)]
+//!         xcomponent::inject::listen("127.0.0.1:9000", queued.component).unwrap();
+//!     }
+//! }
+//! # }
+//! ```
+//!
+//! On the host, after `hdc fport tcp:9000 tcp:9000`, e.g. `nc localhost 9000 < session.trace`.
+
+use crate::event_queue::{self, Event};
+use crate::log::error;
+use crate::record::parse_touch_event;
+use crate::touch::TouchEvent;
+use std::io::{BufRead, BufReader};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+/// Listens on `address` and injects the touch events of connections for `component`
+///
+/// `component` is the [`QueuedEvent::component`](crate::event_queue::QueuedEvent::component) of
+/// the XComponent the events are for. Returns the address listened on, e.g. the port chosen for
+/// port 0.
+pub fn listen(address: impl ToSocketAddrs, component: usize) -> std::io::Result<SocketAddr> {
+    let listener = TcpListener::bind(address)?;
+    let address = listener.local_addr()?;
+    std::thread::Builder::new()
+        .name("xcomponent-inject".into())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => inject(stream, component),
+                    Err(e) => error!("Accepting an injection connection failed: {e}"),
+                }
+            }
+        })?;
+    Ok(address)
+}
+
+/// Injects the touch events read from `stream` until it is closed
+fn inject(stream: TcpStream, component: usize) {
+    let start = Instant::now();
+    for (number, line) in BufReader::new(stream).lines().enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                error!("Reading injected input failed: {e}");
+                return;
+            }
+        };
+        let Some((time, touch_event)) = parse_line(&line) else {
+            error!("Skipping line {} of injected input: {line}", number + 1);
+            continue;
+        };
+        if let Some(delay) = (start + time).checked_duration_since(Instant::now()) {
+            std::thread::sleep(delay);
+        }
+        event_queue::push(component as *mut _, Event::Touch(touch_event));
+    }
+}
+
+/// Parses a `touch` line of a trace, `None` for other and malformed lines
+fn parse_line(line: &str) -> Option<(Duration, TouchEvent)> {
+    let mut fields = line.split_ascii_whitespace();
+    let time = Duration::from_nanos(fields.next()?.parse().ok()?);
+    if fields.next()? != "touch" {
+        return None;
+    }
+    let touch_event = parse_touch_event(&mut fields)?;
+    if fields.next().is_some() {
+        return None;
+    }
+    Some((time, TouchEvent::from(touch_event)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::touch::TouchAction;
+    use std::io::Write;
+
+    const DOWN: &str = "0 touch 1 0 10 20 10 20 0 0 0 0 1 0 10 20 10 20 0 0 0 true";
+    const UP: &str =
+        "50000000 touch 1 1 10 20 10 20 0 0 0 50000000 1 1 10 20 10 20 0 0 50000000 false";
+
+    #[test]
+    fn parses_touch_lines() {
+        let (time, touch_event) = parse_line(UP).unwrap();
+        assert_eq!(time, Duration::from_millis(50));
+        assert_eq!(touch_event.id, 1);
+        assert_eq!(touch_event.action, Some(TouchAction::Up));
+        assert_eq!(touch_event.points().len(), 1);
+
+        assert!(parse_line("0 created").is_none());
+        assert!(parse_line("0 changed 1080 2340").is_none());
+        assert!(parse_line("0 touch 1 0 10").is_none());
+        assert!(parse_line(&format!("{DOWN} 1")).is_none());
+    }
+
+    #[test]
+    fn injects_touch_events_into_the_queue() {
+        let _lock = event_queue::lock_for_test();
+        let address = listen("127.0.0.1:0", 0x10).unwrap();
+        let mut stream = TcpStream::connect(address).unwrap();
+        writeln!(stream, "{DOWN}\n0 created\nnot a trace line\n{UP}").unwrap();
+        drop(stream);
+
+        let mut events = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while events.len() < 2 && Instant::now() < deadline {
+            events.extend(event_queue::poll_events(Duration::from_millis(100)));
+        }
+        let actions: Vec<_> = events
+            .iter()
+            .map(|queued| {
+                assert_eq!(queued.component, 0x10);
+                match &queued.event {
+                    Event::Touch(touch_event) => touch_event.action,
+                    event => panic!("unexpected event {event:?}"),
+                }
+            })
+            .collect();
+        assert_eq!(actions, [Some(TouchAction::Down), Some(TouchAction::Up)]);
+        // The up event keeps its distance to the down event.
+        assert!(events[1].time - events[0].time >= Duration::from_millis(40));
+    }
+}
@@ -0,0 +1,355 @@
+//! Typed key events
+//!
+//! ArkUI only provides the key event of an XComponent while it calls the key event callback,
+//! e.g. [`EventCallbacks::on_key`](crate::EventCallbacks::on_key).
+//! [`XComponent::get_key_event`](crate::XComponent::get_key_event) copies it into a
+//! [`KeyEvent`], so the opaque native event does not need to be kept alive.
+//!
+//! Key events only report the key which changed. A [`ModifierTracker`] fed with all key events
+//! knows which [`Modifiers`] are held down, e.g. for shortcuts or for ctrl-clicks in touch and
+//! mouse events.
+//!
+//! ## Example
+//! ```no_run
+//! use xcomponent::key::{ModifierTracker, Modifiers};
+//!
+//! let mut modifiers = ModifierTracker::new();
+//! // In the key event callback:
+//! # let key_event: xcomponent::key::KeyEvent = todo!();
+//! modifiers.on_key_event(&key_event);
+//! // In `DispatchMouseEvent`:
+//! if modifiers.modifiers().contains(Modifiers::CTRL) {
+//!     // Add the clicked item to the selection ...
+//! }
+//! ```
+
+use crate::log::error;
+use crate::trace::ffi_span;
+use bitflags::bitflags;
+use core::mem::MaybeUninit;
+use ohos_sys::xcomponent::{
+    OH_NativeXComponent_EventSourceType, OH_NativeXComponent_GetKeyEventAction,
+    OH_NativeXComponent_GetKeyEventCode, OH_NativeXComponent_GetKeyEventDeviceId,
+    OH_NativeXComponent_GetKeyEventSourceType, OH_NativeXComponent_GetKeyEventTimestamp,
+    OH_NativeXComponent_KeyAction, OH_NativeXComponent_KeyCode, OH_NativeXComponent_KeyEvent,
+};
+
+/// Whether a key was pressed or released
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KeyAction {
+    Down,
+    Up,
+}
+
+impl KeyAction {
+    /// Converts a raw `OH_NativeXComponent_KeyAction`
+    ///
+    /// Returns `None` for `OH_NATIVEXCOMPONENT_KEY_ACTION_UNKNOWN` and unknown values.
+    pub fn from_raw(action: OH_NativeXComponent_KeyAction) -> Option<Self> {
+        match action {
+            OH_NativeXComponent_KeyAction::OH_NATIVEXCOMPONENT_KEY_ACTION_DOWN => Some(Self::Down),
+            OH_NativeXComponent_KeyAction::OH_NATIVEXCOMPONENT_KEY_ACTION_UP => Some(Self::Up),
+            _ => None,
+        }
+    }
+
+    pub fn as_raw(self) -> OH_NativeXComponent_KeyAction {
+        match self {
+            Self::Down => OH_NativeXComponent_KeyAction::OH_NATIVEXCOMPONENT_KEY_ACTION_DOWN,
+            Self::Up => OH_NativeXComponent_KeyAction::OH_NATIVEXCOMPONENT_KEY_ACTION_UP,
+        }
+    }
+}
+
+/// Kind of device an input event originates from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SourceType {
+    Mouse,
+    Touchscreen,
+    Touchpad,
+    Joystick,
+    Keyboard,
+}
+
+impl SourceType {
+    /// Converts a raw `OH_NativeXComponent_EventSourceType`
+    ///
+    /// Returns `None` for `OH_NATIVEXCOMPONENT_SOURCE_TYPE_UNKNOWN` and unknown values.
+    pub fn from_raw(source_type: OH_NativeXComponent_EventSourceType) -> Option<Self> {
+        use OH_NativeXComponent_EventSourceType as Raw;
+        match source_type {
+            Raw::OH_NATIVEXCOMPONENT_SOURCE_TYPE_MOUSE => Some(Self::Mouse),
+            Raw::OH_NATIVEXCOMPONENT_SOURCE_TYPE_TOUCHSCREEN => Some(Self::Touchscreen),
+            Raw::OH_NATIVEXCOMPONENT_SOURCE_TYPE_TOUCHPAD => Some(Self::Touchpad),
+            Raw::OH_NATIVEXCOMPONENT_SOURCE_TYPE_JOYSTICK => Some(Self::Joystick),
+            Raw::OH_NATIVEXCOMPONENT_SOURCE_TYPE_KEYBOARD => Some(Self::Keyboard),
+            _ => None,
+        }
+    }
+}
+
+/// A key event of an XComponent
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyEvent {
+    /// `None` if ArkUI reported an unknown action
+    pub action: Option<KeyAction>,
+    /// The physical key, e.g. `OH_NativeXComponent_KeyCode::KEY_A`
+    #[cfg_attr(feature = "serde", serde(with = "key_code"))]
+    pub code: OH_NativeXComponent_KeyCode,
+    /// `None` if ArkUI reported an unknown source type
+    pub source_type: Option<SourceType>,
+    /// Id of the input device
+    pub device_id: i64,
+    /// Time of the event in nanoseconds
+    pub timestamp: i64,
+}
+
+/// (De)serializes key codes as their raw value
+#[cfg(feature = "serde")]
+mod key_code {
+    use ohos_sys::xcomponent::OH_NativeXComponent_KeyCode;
+
+    pub(super) fn serialize<S: serde::Serializer>(
+        code: &OH_NativeXComponent_KeyCode,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i32(code.0)
+    }
+
+    pub(super) fn deserialize<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<OH_NativeXComponent_KeyCode, D::Error> {
+        <i32 as serde::Deserialize>::deserialize(deserializer).map(OH_NativeXComponent_KeyCode)
+    }
+}
+
+/// Reads `function` of the native key event
+///
+/// # Safety
+///
+/// `key_event` must be a valid native key event.
+unsafe fn get<T>(
+    function: &'static str,
+    key_event: *mut OH_NativeXComponent_KeyEvent,
+    getter: unsafe extern "C" fn(*mut OH_NativeXComponent_KeyEvent, *mut T) -> i32,
+) -> Result<T, i32> {
+    let mut span = ffi_span!(function);
+    let mut value = MaybeUninit::uninit();
+    let res = getter(key_event, value.as_mut_ptr());
+    span.set_result(res);
+    if res != 0 {
+        error!("{function} failed with {res}");
+        return Err(res);
+    }
+    Ok(value.assume_init())
+}
+
+impl KeyEvent {
+    /// Copies the native key event
+    ///
+    /// # Safety
+    ///
+    /// `key_event` must be a valid native key event.
+    pub(crate) unsafe fn from_raw(
+        key_event: *mut OH_NativeXComponent_KeyEvent,
+    ) -> Result<Self, i32> {
+        Ok(Self {
+            action: KeyAction::from_raw(get(
+                "OH_NativeXComponent_GetKeyEventAction",
+                key_event,
+                OH_NativeXComponent_GetKeyEventAction,
+            )?),
+            code: get(
+                "OH_NativeXComponent_GetKeyEventCode",
+                key_event,
+                OH_NativeXComponent_GetKeyEventCode,
+            )?,
+            source_type: SourceType::from_raw(get(
+                "OH_NativeXComponent_GetKeyEventSourceType",
+                key_event,
+                OH_NativeXComponent_GetKeyEventSourceType,
+            )?),
+            device_id: get(
+                "OH_NativeXComponent_GetKeyEventDeviceId",
+                key_event,
+                OH_NativeXComponent_GetKeyEventDeviceId,
+            )?,
+            timestamp: get(
+                "OH_NativeXComponent_GetKeyEventTimestamp",
+                key_event,
+                OH_NativeXComponent_GetKeyEventTimestamp,
+            )?,
+        })
+    }
+}
+
+bitflags! {
+    /// Modifier keys held down
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(transparent))]
+    pub struct Modifiers: u8 {
+        const SHIFT = 1 << 0;
+        const CTRL = 1 << 1;
+        const ALT = 1 << 2;
+        /// The Windows or Command key
+        const META = 1 << 3;
+        const FN = 1 << 4;
+    }
+}
+
+/// The modifier keys, with the modifier each of them holds down
+const MODIFIER_KEYS: [(OH_NativeXComponent_KeyCode, Modifiers); 9] = [
+    (
+        OH_NativeXComponent_KeyCode::KEY_SHIFT_LEFT,
+        Modifiers::SHIFT,
+    ),
+    (
+        OH_NativeXComponent_KeyCode::KEY_SHIFT_RIGHT,
+        Modifiers::SHIFT,
+    ),
+    (OH_NativeXComponent_KeyCode::KEY_CTRL_LEFT, Modifiers::CTRL),
+    (OH_NativeXComponent_KeyCode::KEY_CTRL_RIGHT, Modifiers::CTRL),
+    (OH_NativeXComponent_KeyCode::KEY_ALT_LEFT, Modifiers::ALT),
+    (OH_NativeXComponent_KeyCode::KEY_ALT_RIGHT, Modifiers::ALT),
+    (OH_NativeXComponent_KeyCode::KEY_META_LEFT, Modifiers::META),
+    (OH_NativeXComponent_KeyCode::KEY_META_RIGHT, Modifiers::META),
+    (OH_NativeXComponent_KeyCode::KEY_FN, Modifiers::FN),
+];
+
+impl Modifiers {
+    /// The modifier `code` holds down, empty if it isn't a modifier key
+    pub fn from_key_code(code: OH_NativeXComponent_KeyCode) -> Self {
+        MODIFIER_KEYS
+            .iter()
+            .find(|(key, _)| *key == code)
+            .map_or(Self::empty(), |(_, modifier)| *modifier)
+    }
+}
+
+/// Tracks the modifier keys held down from key events
+///
+/// Left and right modifier keys are tracked separately, so releasing one of them keeps the
+/// modifier held down while the other one is. Since the XComponent doesn't receive key events
+/// while it isn't focused, call [`ModifierTracker::reset`] when it loses the focus.
+#[derive(Clone, Debug, Default)]
+pub struct ModifierTracker {
+    /// Bit `i` is set while `MODIFIER_KEYS[i]` is held down
+    pressed: u16,
+}
+
+impl ModifierTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Updates the tracker from `key_event`, returning the modifiers held down afterwards
+    pub fn on_key_event(&mut self, key_event: &KeyEvent) -> Modifiers {
+        if let Some(index) = MODIFIER_KEYS
+            .iter()
+            .position(|(key, _)| *key == key_event.code)
+        {
+            match key_event.action {
+                Some(KeyAction::Down) => self.pressed |= 1 << index,
+                Some(KeyAction::Up) => self.pressed &= !(1 << index),
+                None => {}
+            }
+        }
+        self.modifiers()
+    }
+
+    /// The modifiers currently held down
+    pub fn modifiers(&self) -> Modifiers {
+        MODIFIER_KEYS
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| self.pressed & (1 << index) != 0)
+            .fold(Modifiers::empty(), |modifiers, (_, (_, modifier))| {
+                modifiers | *modifier
+            })
+    }
+
+    /// Releases all modifiers, e.g. when the XComponent lost the focus
+    pub fn reset(&mut self) {
+        self.pressed = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use OH_NativeXComponent_KeyCode as Key;
+
+    fn key(action: KeyAction, code: OH_NativeXComponent_KeyCode) -> KeyEvent {
+        KeyEvent {
+            action: Some(action),
+            code,
+            source_type: Some(SourceType::Keyboard),
+            device_id: 0,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn modifier_per_key() {
+        for (code, modifier) in MODIFIER_KEYS {
+            let mut tracker = ModifierTracker::new();
+            assert_eq!(tracker.on_key_event(&key(KeyAction::Down, code)), modifier);
+            assert_eq!(tracker.modifiers(), modifier);
+            assert_eq!(Modifiers::from_key_code(code), modifier);
+            assert_eq!(
+                tracker.on_key_event(&key(KeyAction::Up, code)),
+                Modifiers::empty()
+            );
+        }
+        assert_eq!(Modifiers::from_key_code(Key::KEY_A), Modifiers::empty());
+    }
+
+    #[test]
+    fn left_and_right_keys() {
+        let mut tracker = ModifierTracker::new();
+        tracker.on_key_event(&key(KeyAction::Down, Key::KEY_SHIFT_LEFT));
+        tracker.on_key_event(&key(KeyAction::Down, Key::KEY_SHIFT_RIGHT));
+        tracker.on_key_event(&key(KeyAction::Down, Key::KEY_CTRL_RIGHT));
+        assert_eq!(tracker.modifiers(), Modifiers::SHIFT | Modifiers::CTRL);
+        // The other shift key is still held down.
+        assert_eq!(
+            tracker.on_key_event(&key(KeyAction::Up, Key::KEY_SHIFT_LEFT)),
+            Modifiers::SHIFT | Modifiers::CTRL
+        );
+        assert_eq!(
+            tracker.on_key_event(&key(KeyAction::Up, Key::KEY_SHIFT_RIGHT)),
+            Modifiers::CTRL
+        );
+        tracker.reset();
+        assert_eq!(tracker.modifiers(), Modifiers::empty());
+    }
+
+    #[test]
+    fn release_without_press() {
+        let mut tracker = ModifierTracker::new();
+        tracker.on_key_event(&key(KeyAction::Down, Key::KEY_ALT_LEFT));
+        // E.g. pressed before the XComponent was focused
+        assert_eq!(
+            tracker.on_key_event(&key(KeyAction::Up, Key::KEY_ALT_RIGHT)),
+            Modifiers::ALT
+        );
+        assert_eq!(
+            tracker.on_key_event(&key(KeyAction::Up, Key::KEY_META_LEFT)),
+            Modifiers::ALT
+        );
+        // Other keys and unknown actions don't change the modifiers.
+        assert_eq!(
+            tracker.on_key_event(&key(KeyAction::Up, Key::KEY_A)),
+            Modifiers::ALT
+        );
+        let unknown = KeyEvent {
+            action: None,
+            ..key(KeyAction::Up, Key::KEY_ALT_LEFT)
+        };
+        assert_eq!(tracker.on_key_event(&unknown), Modifiers::ALT);
+    }
+}
@@ -0,0 +1,203 @@
+//! Input to photon latency measurement
+//!
+//! [`LatencyTracker`] correlates the timestamps of input events, of the frame callbacks and of
+//! the presentation of frames, and collects the resulting latencies in [`Histogram`]s. All
+//! timestamps are in nanoseconds of `CLOCK_MONOTONIC`, the clock of the timestamps of touch
+//! events and frame callbacks.
+//!
+//! Inputs are assigned to the next frame, frames are presented in the order they started.
+//!
+//! ## Example
+//! ```no_run
+//! use xcomponent::latency::LatencyTracker;
+//! # fn present_time() -> u64 { 0 }
+//!
+//! let mut tracker = LatencyTracker::new();
+//! // In `DispatchTouchEvent`:
+//! # let touch_event: xcomponent::touch::TouchEvent = todo!();
+//! tracker.on_input(touch_event.timestamp);
+//! // In the frame callback:
+//! # let timestamp = 0;
+//! tracker.on_frame(timestamp);
+//! // Once the frame was presented, e.g. from present timing feedback of the graphics API:
+//! tracker.on_present(present_time());
+//!
+//! let end_to_end = tracker.end_to_end();
+//! println!("p50 {:?}, p99 {:?}", end_to_end.percentile(0.5), end_to_end.percentile(0.99));
+//! ```
+
+use core::time::Duration;
+use std::collections::VecDeque;
+
+/// Number of 1 ms buckets of a [`Histogram`], longer latencies go into an overflow bucket
+pub const HISTOGRAM_BUCKETS: usize = 128;
+
+/// Distribution of latencies with a resolution of 1 ms
+#[derive(Clone, Debug)]
+pub struct Histogram {
+    buckets: [u64; HISTOGRAM_BUCKETS],
+    overflow: u64,
+    count: u64,
+    sum: u128,
+    min: u64,
+    max: u64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Histogram {
+    pub const fn new() -> Self {
+        Self {
+            buckets: [0; HISTOGRAM_BUCKETS],
+            overflow: 0,
+            count: 0,
+            sum: 0,
+            min: u64::MAX,
+            max: 0,
+        }
+    }
+
+    /// Adds a latency in nanoseconds
+    pub fn record(&mut self, nanoseconds: u64) {
+        match self.buckets.get_mut((nanoseconds / 1_000_000) as usize) {
+            Some(bucket) => *bucket += 1,
+            None => self.overflow += 1,
+        }
+        self.count += 1;
+        self.sum += u128::from(nanoseconds);
+        self.min = self.min.min(nanoseconds);
+        self.max = self.max.max(nanoseconds);
+    }
+
+    /// Number of recorded latencies
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn min(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_nanos(self.min))
+    }
+
+    pub fn max(&self) -> Option<Duration> {
+        (self.count > 0).then(|| Duration::from_nanos(self.max))
+    }
+
+    pub fn mean(&self) -> Option<Duration> {
+        let mean = self.sum.checked_div(u128::from(self.count))?;
+        Some(Duration::from_nanos(mean as u64))
+    }
+
+    /// The latency which `quantile` (from 0 to 1) of the recorded latencies don't exceed
+    ///
+    /// The result is rounded up to the next millisecond, and capped by the maximum.
+    pub fn percentile(&self, quantile: f64) -> Option<Duration> {
+        if self.count == 0 {
+            return None;
+        }
+        let rank = ((quantile.clamp(0.0, 1.0) * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (millis, bucket) in self.buckets.iter().enumerate() {
+            seen += bucket;
+            if seen >= rank {
+                let upper = (millis as u64 + 1) * 1_000_000;
+                return Some(Duration::from_nanos(upper.min(self.max)));
+            }
+        }
+        self.max()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// A frame which started, but wasn't presented yet
+#[derive(Debug)]
+struct PendingFrame {
+    start: u64,
+    /// Timestamp of the oldest input handled by the frame
+    oldest_input: Option<u64>,
+}
+
+/// Computes latencies from input, frame and present timestamps
+#[derive(Debug, Default)]
+pub struct LatencyTracker {
+    /// Timestamp of the oldest input not handled by a frame yet
+    oldest_input: Option<u64>,
+    frames: VecDeque<PendingFrame>,
+    input_to_frame: Histogram,
+    frame_to_present: Histogram,
+    end_to_end: Histogram,
+}
+
+impl LatencyTracker {
+    /// Frames which are not presented are forgotten once this many frames are pending
+    pub const MAX_PENDING_FRAMES: usize = 8;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records an input event, e.g. with [`TouchEvent::timestamp`](crate::touch::TouchEvent)
+    ///
+    /// Negative timestamps, which ArkUI doesn't produce, are ignored.
+    pub fn on_input(&mut self, timestamp: i64) {
+        let Ok(timestamp) = u64::try_from(timestamp) else {
+            return;
+        };
+        self.oldest_input = Some(self.oldest_input.map_or(timestamp, |t| t.min(timestamp)));
+    }
+
+    /// Records the start of a frame, which handles all inputs recorded since the last frame
+    pub fn on_frame(&mut self, timestamp: u64) {
+        let oldest_input = self.oldest_input.take();
+        if let Some(input) = oldest_input {
+            self.input_to_frame.record(timestamp.saturating_sub(input));
+        }
+        if self.frames.len() == Self::MAX_PENDING_FRAMES {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(PendingFrame {
+            start: timestamp,
+            oldest_input,
+        });
+    }
+
+    /// Records that the oldest frame which wasn't presented yet was presented at `timestamp`
+    pub fn on_present(&mut self, timestamp: u64) {
+        let Some(frame) = self.frames.pop_front() else {
+            return;
+        };
+        self.frame_to_present
+            .record(timestamp.saturating_sub(frame.start));
+        if let Some(input) = frame.oldest_input {
+            self.end_to_end.record(timestamp.saturating_sub(input));
+        }
+    }
+
+    /// Latency from the oldest input handled by a frame to the start of the frame
+    pub fn input_to_frame(&self) -> &Histogram {
+        &self.input_to_frame
+    }
+
+    /// Latency from the start of a frame to its presentation
+    pub fn frame_to_present(&self) -> &Histogram {
+        &self.frame_to_present
+    }
+
+    /// Latency from the oldest input handled by a frame to the presentation of the frame
+    pub fn end_to_end(&self) -> &Histogram {
+        &self.end_to_end
+    }
+
+    /// Clears the histograms, e.g. after a warm-up phase
+    pub fn reset(&mut self) {
+        self.input_to_frame.clear();
+        self.frame_to_present.clear();
+        self.end_to_end.clear();
+    }
+}
@@ -2,11 +2,11 @@
 //!
 //! ## Example
 //! ```
-//! # use ohos_sys::ace::xcomponent::native_interface_xcomponent::OH_NativeXComponent;
+//! # use ohos_sys::xcomponent::OH_NativeXComponent;
 //! # use core::ffi::c_void;
 //! pub extern "C" fn on_surface_created_cb(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
 //!     let xc = xcomponent::XComponent::new(xcomponent, window).expect("Invalid XC");
-//!     let size = xc.size();
+//!     let size = xc.try_size().unwrap();
 //!     // do something with the xcomponent ...
 //! }
 //!
@@ -22,29 +22,296 @@
 //!
 //! ## Features
 //!
+//! * std (default): Use the standard library. Without it the crate is `no_std`, and only the
+//!   core XComponent, event and native window APIs are available. Features which need the
+//!   standard library, like `register`, enable it.
+//! * alloc: Use the `alloc` crate without the standard library, for [`XComponent::id`]. Enabled
+//!   by `std`.
 //! * log: Outputs error and diagnostic messages via the `log` crate if enabled.
-//! * register: Add `register_xcomponent_callbacks` function to register XComponent callbacks.
+//! * register: Add the `register_xcomponent_callbacks`, `register_mouse_event_callbacks`,
+//!   `register_event_callbacks` and, with `api-12`, `register_surface_visibility_callbacks`
+//!   functions to register XComponent callbacks, and
+//!   [`handler::register_handler`](crate::handler) to register an `XComponentHandler`.
+//!   The following features select the `napi-ohos` version in use:
+//!   * napi-ohos-v0: Support the exports object of `napi-ohos` 0.1, the default of `register`
+//!   * napi-ohos-v1: Support the exports object of `napi-ohos` 1.x instead, even if
+//!     `napi-ohos-v0` is also enabled
+//! * tracing: Emits spans for calls into the native XComponent API and for the callbacks of
+//!   handlers and frame callbacks, and outputs error and diagnostic messages via the `tracing`
+//!   crate. If `log` is also enabled, messages go to `log`.
+//! * log-level-off, log-level-error, log-level-warn, log-level-info, log-level-debug: Remove
+//!   messages and spans more verbose than the given level at compile time, regardless of the
+//!   output selected by `log`, `tracing` or `hilog`. If multiple are enabled, the least verbose
+//!   level applies.
+//! * ffi-log: Add the [`ffi_log`](crate::ffi_log) module to record recent calls into the native
+//!   APIs with their return codes in a ring buffer, so misuse can be diagnosed from field logs.
+//! * hilog: Outputs error and diagnostic messages directly via HiLog, without requiring a `log`
+//!   logger. Takes precedence over `log` and `tracing`. See the [`hilog`](crate::hilog) module
+//!   for the domain and tag configuration.
+//! * bytemuck: Implements `bytemuck::Pod` and `bytemuck::Zeroable` for [`Size`] and [`Offset`], so
+//!   they can be copied into byte buffers without `unsafe`.
+//! * serde: Implements `Serialize` and `Deserialize` for the typed touch, mouse and key events,
+//!   [`Size`] and [`Offset`], e.g. to analyze recorded input sessions on another machine.
+//! * drm-fourcc: Conversions between [`PixelFormat`](crate::format::PixelFormat) and
+//!   `drm_fourcc::DrmFourcc`.
+//! * rwh_06: Implements the `HasWindowHandle` and `HasDisplayHandle` traits of
+//!   `raw-window-handle` 0.6 for [`XComponent`], so it can be passed to e.g. `wgpu` or `glutin`.
+//! * metrics: Report the counters of the [`metrics`](crate::metrics) module to the `metrics`
+//!   crate.
+//! * egui: Add the [`egui`](crate::egui) module to translate XComponent input into `egui` input.
+//!   Implies `time`.
+//! * image: Add [`blit_image`](crate::blit::blit_image) to copy an `image::RgbaImage` into a
+//!   CPU-mapped buffer.
+//! * cpu-buffer: Add the [`cpu_buffer`](crate::cpu_buffer) module to request, map and flush native
+//!   window buffers for rendering on the CPU.
+//! * egl: Add the [`egl`](crate::egl) module to create EGL window surfaces for XComponents via
+//!   `khronos-egl`.
+//! * glow: Add the [`glow`](crate::glow) module to load OpenGL ES functions into a `glow::Context`
+//!   via EGL. Implies `egl`.
+//! * vulkan: Add the [`vulkan`](crate::vulkan) module to create Vulkan surfaces for XComponents via
+//!   `ash` and `VK_OHOS_surface`.
+//! * display: Add the [`display`](crate::display) module to query the scale factor, rotation,
+//!   refresh rate and mode of the display hosting the XComponent, and
+//!   [`XComponent::logical_size`]. Requires `api-12`, which it enables.
+//! * config: Add the [`config`](crate::config) module to listen for system configuration changes,
+//!   like the color mode or font scale. Requires `api-12`, which it enables.
+//! * device: Add the [`device`](crate::device) module to query the device type.
+//! * vsync: Add the [`vsync`](crate::vsync) module to render from the vsync signal of the display.
+//! * display-soloist: Add the [`display_soloist`](crate::display_soloist) module to receive frame
+//!   callbacks at a frame rate independent of the UI. Requires `api-12`, which it enables.
+//! * window: Add the [`window`](crate::window) module to query the geometry and safe area of
+//!   the window hosting the XComponent, and to control its display behavior. Requires `api-15`,
+//!   which it enables.
+//! * accessibility: Add the [`accessibility`](crate::accessibility) module to expose content
+//!   rendered into the XComponent to screen readers. Requires `api-13`, which it enables.
+//! * accesskit: Add the [`accesskit`](crate::accesskit) module to expose an AccessKit tree to
+//!   screen readers. Enables `accessibility`.
+//! * arkui: Add the [`arkui`](crate::arkui) module to render into XComponents created via the
+//!   ArkUI C node API. Requires `api-19`, which it enables.
+//! * axis: Add the [`axis`](crate::axis) module to receive axis events, like mouse wheel scrolls
+//!   and touchpad pinches. Requires `api-12`, which it enables.
+//! * root-node: Add the [`root_node`](crate::root_node) module to attach native ArkUI nodes to an
+//!   XComponent. Requires `api-12`, which it enables.
+//! * text-input: Add the [`text_input`](crate::text_input) module to receive text from input
+//!   methods, including composition text. Requires `api-12`, which it enables.
+//! * clipboard: Add the [`clipboard`](crate::clipboard) module to copy and paste text, HTML and
+//!   file URIs via the system pasteboard. Requires `api-13`, which it enables.
+//! * mock: Add the [`mock`](crate::mock) module to drive XComponent callbacks with a fake
+//!   XComponent in host-side tests.
+//! * bench: Enables what the benchmarks in `benches/` need. Run them with
+//!   `cargo bench --features bench`.
+//! * record: Add the [`record`](crate::record) module to record XComponent callback sessions to a
+//!   trace. With `mock`, traces can be replayed via [`mock::load_trace`](crate::mock::load_trace).
+//! * event-queue: Add the [`event_queue`](crate::event_queue) module to receive XComponent
+//!   callbacks as events on a render thread.
+//! * inject: Add the [`inject`](crate::inject) module to inject touch events sent over TCP into
+//!   the event queue, for scripted input during development. Enables `event-queue` and `record`.
+//! * backend: Add the [`backend`](crate::backend) module, which collects what a windowing backend
+//!   like winit needs. Enables `event-queue`, `display` and `rwh_06`.
+//! * capi: Add the [`capi`](crate::capi) module, which exports a C ABI over the handler and event
+//!   queue for C and C++ engines. Enables `event-queue` and `register`.
+//! * async: Add [`event_queue::next_event`](crate::event_queue::next_event) and
+//!   [`event_queue::EventStream`](crate::event_queue::EventStream), a `futures_core::Stream`, to
+//!   await the queued events. Enables `event-queue`.
+//! * latency: Add the [`latency`](crate::latency) module to measure the latency from input
+//!   events to the presentation of the frames handling them.
+//! * time: Add the [`time`](crate::time) module to convert the timestamps of events into
+//!   `Instant`s and to compute their latency.
+//! * gestures: Add the [`gestures`](crate::gestures) module to recognize taps, long presses, pans
+//!   and pinches from touch events.
+//! * strict: Add the [`strict`](crate::strict) module to detect suspicious usage of XComponents,
+//!   like querying the size without a surface. Components are only tracked if their callbacks
+//!   are registered with the `register` feature.
+//! * thread-checks: Panic in debug builds if thread-restricted APIs, like creating an
+//!   [`XComponent`] or registering listeners, are called from a thread other than the UI thread.
+//! * api-11 ... api-20: Enable APIs which are only available since the given OpenHarmony API level.
 //!
 //! [XComponent]: https://gitee.com/openharmony/docs/blob/master/zh-cn/application-dev/ui/napi-xcomponent-guidelines.md
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 use crate::log::error;
+use crate::trace::ffi_span;
+#[cfg(feature = "alloc")]
+use alloc::string::String;
 use core::{ffi::c_void, marker::PhantomData, mem::MaybeUninit, ptr::NonNull};
-use ohos_sys::ace::xcomponent::native_interface_xcomponent::OH_NativeXComponent_GetXComponentSize;
-use ohos_sys::{
-    ace::xcomponent::native_interface_xcomponent::{
-        OH_NativeXComponent, OH_NativeXComponent_GetTouchEvent, OH_NativeXComponent_TouchEvent,
-    },
-    native_window::OHNativeWindow,
+#[cfg(feature = "register")]
+#[cfg_attr(docsrs, doc(cfg(feature = "register")))]
+pub use napi_compat::NapiExports;
+use ohos_sys::xcomponent::{
+    OH_NativeXComponent, OH_NativeXComponent_GetKeyEvent, OH_NativeXComponent_GetMouseEvent,
+    OH_NativeXComponent_GetTouchEvent, OH_NativeXComponent_GetXComponentOffset,
+    OH_NativeXComponent_GetXComponentSize, OH_NativeXComponent_MouseEvent,
+    OH_NativeXComponent_TouchEvent,
 };
+use ohos_sys_opaque_types::OHNativeWindow;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use panic_guard::set_abort_on_panic;
 
+#[cfg(feature = "accessibility")]
+#[cfg_attr(docsrs, doc(cfg(feature = "accessibility")))]
+pub mod accessibility;
+#[cfg(feature = "accesskit")]
+#[cfg_attr(docsrs, doc(cfg(feature = "accesskit")))]
+pub mod accesskit;
+#[cfg(feature = "arkui")]
+#[cfg_attr(docsrs, doc(cfg(feature = "arkui")))]
+pub mod arkui;
+#[cfg(feature = "axis")]
+#[cfg_attr(docsrs, doc(cfg(feature = "axis")))]
+pub mod axis;
+#[cfg(feature = "backend")]
+#[cfg_attr(docsrs, doc(cfg(feature = "backend")))]
+pub mod backend;
+pub mod blit;
+#[cfg(feature = "capi")]
+#[cfg_attr(docsrs, doc(cfg(feature = "capi")))]
+pub mod capi;
+#[cfg(feature = "clipboard")]
+#[cfg_attr(docsrs, doc(cfg(feature = "clipboard")))]
+pub mod clipboard;
+#[cfg(feature = "config")]
+#[cfg_attr(docsrs, doc(cfg(feature = "config")))]
+pub mod config;
+#[cfg(feature = "cpu-buffer")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cpu-buffer")))]
+pub mod cpu_buffer;
+#[cfg(feature = "device")]
+#[cfg_attr(docsrs, doc(cfg(feature = "device")))]
+pub mod device;
+#[cfg(feature = "display")]
+#[cfg_attr(docsrs, doc(cfg(feature = "display")))]
+pub mod display;
+#[cfg(feature = "display-soloist")]
+#[cfg_attr(docsrs, doc(cfg(feature = "display-soloist")))]
+pub mod display_soloist;
+#[cfg(feature = "egl")]
+#[cfg_attr(docsrs, doc(cfg(feature = "egl")))]
+pub mod egl;
+#[cfg(feature = "egui")]
+#[cfg_attr(docsrs, doc(cfg(feature = "egui")))]
+pub mod egui;
+#[cfg(feature = "event-queue")]
+#[cfg_attr(docsrs, doc(cfg(feature = "event-queue")))]
+pub mod event_queue;
+#[cfg(feature = "ffi-log")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ffi-log")))]
+pub mod ffi_log;
+pub mod format;
+#[cfg(all(feature = "api-11", feature = "std"))]
+mod frame;
+#[cfg(feature = "fuzz")]
+#[doc(hidden)]
+pub mod fuzz;
+pub mod geometry;
+#[cfg(feature = "gestures")]
+#[cfg_attr(docsrs, doc(cfg(feature = "gestures")))]
+pub mod gestures;
+#[cfg(feature = "glow")]
+#[cfg_attr(docsrs, doc(cfg(feature = "glow")))]
+pub mod glow;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod handle;
+pub mod handler;
+#[cfg(feature = "hilog")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hilog")))]
+pub mod hilog;
+pub mod hover;
+#[cfg(feature = "inject")]
+#[cfg_attr(docsrs, doc(cfg(feature = "inject")))]
+pub mod inject;
+pub mod key;
+#[cfg(feature = "latency")]
+#[cfg_attr(docsrs, doc(cfg(feature = "latency")))]
+pub mod latency;
 mod log;
+pub mod metrics;
+#[cfg(feature = "mock")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+pub mod mock;
+pub mod mouse;
+#[cfg(feature = "register")]
+mod napi_compat;
+pub mod native_window;
+mod panic_guard;
+#[cfg(feature = "record")]
+#[cfg_attr(docsrs, doc(cfg(feature = "record")))]
+pub mod record;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub mod registry;
+#[cfg(feature = "root-node")]
+#[cfg_attr(docsrs, doc(cfg(feature = "root-node")))]
+pub mod root_node;
+#[cfg(feature = "rwh_06")]
+mod rwh_06;
+#[cfg(feature = "strict")]
+#[cfg_attr(docsrs, doc(cfg(feature = "strict")))]
+pub mod strict;
+#[cfg(feature = "text-input")]
+#[cfg_attr(docsrs, doc(cfg(feature = "text-input")))]
+pub mod text_input;
+mod thread;
+#[cfg(feature = "time")]
+#[cfg_attr(docsrs, doc(cfg(feature = "time")))]
+pub mod time;
+pub mod touch;
+mod trace;
+#[cfg(feature = "vsync")]
+#[cfg_attr(docsrs, doc(cfg(feature = "vsync")))]
+pub mod vsync;
+#[cfg(feature = "vulkan")]
+#[cfg_attr(docsrs, doc(cfg(feature = "vulkan")))]
+pub mod vulkan;
+#[cfg(feature = "window")]
+#[cfg_attr(docsrs, doc(cfg(feature = "window")))]
+pub mod window;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
 pub struct Size {
     pub width: u64,
     pub height: u64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    _opaque: [u64; 0],
+}
+
+/// Offset of the XComponent relative to the top-left corner of its window
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
+pub struct Offset {
+    pub x: f64,
+    pub y: f64,
+    #[cfg_attr(feature = "serde", serde(skip))]
     _opaque: [u64; 0],
 }
 
+/// Id of the surface of an XComponent
+///
+/// Media APIs, e.g. of the player, recorder or camera, can render into a surface identified by
+/// its id. In ArkTS the id is passed as a string, which is the [`Display`](core::fmt::Display)
+/// output of this type.
+#[cfg(feature = "api-12")]
+#[cfg_attr(docsrs, doc(cfg(feature = "api-12")))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SurfaceId(pub u64);
+
+#[cfg(feature = "api-12")]
+impl core::fmt::Display for SurfaceId {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
 pub struct XComponent<'a> {
     xcomponent: NonNull<OH_NativeXComponent>,
     window: NonNull<OHNativeWindow>,
@@ -56,6 +323,14 @@ impl<'a> XComponent<'a> {
         xcomponent: *mut OH_NativeXComponent,
         window: *mut c_void,
     ) -> Option<XComponent<'a>> {
+        // Mocks are driven from the test threads instead.
+        #[cfg(feature = "mock")]
+        let mocked = mock::with_mock(xcomponent, |_| ()).is_some();
+        #[cfg(not(feature = "mock"))]
+        let mocked = false;
+        if !mocked {
+            thread::check_ui_thread("XComponent::new");
+        }
         Some(XComponent {
             xcomponent: NonNull::new(xcomponent)?,
             window: NonNull::new(window.cast())?,
@@ -63,7 +338,230 @@ impl<'a> XComponent<'a> {
         })
     }
 
+    /// Returns a reference to the native window of the XComponent
+    ///
+    /// The reference keeps the window alive, so it can be moved to a render thread. The raw
+    /// window can be passed to other NDK APIs rendering into a native window.
+    pub fn native_window(&self) -> Result<native_window::NativeWindow, i32> {
+        #[cfg(feature = "mock")]
+        if mock::with_mock(self.xcomponent.as_ptr(), |_| ()).is_some() {
+            return Ok(native_window::NativeWindow::mocked(self.window));
+        }
+        // SAFETY: The window was passed to the callback which created this XComponent.
+        unsafe { native_window::NativeWindow::from_raw(self.window) }
+    }
+
+    /// Returns the id of the surface backing the native window of the XComponent
+    #[cfg(feature = "api-12")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "api-12")))]
+    pub fn surface_id(&self) -> Result<SurfaceId, i32> {
+        #[cfg(feature = "mock")]
+        if let Some(surface_id) = mock::with_mock(self.xcomponent.as_ptr(), |m| m.surface_id) {
+            return Ok(SurfaceId(surface_id));
+        }
+        let mut surface_id: u64 = 0;
+        let mut span = ffi_span!("OH_NativeWindow_GetSurfaceId", self.xcomponent);
+        let res = unsafe {
+            ohos_sys::native_window::OH_NativeWindow_GetSurfaceId(
+                self.window.as_ptr(),
+                &mut surface_id as *mut _,
+            )
+        };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_NativeWindow_GetSurfaceId failed with {res}");
+            return Err(res);
+        }
+        Ok(SurfaceId(surface_id))
+    }
+
+    /// Returns the accessibility provider of the XComponent
+    #[cfg(feature = "accessibility")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "accessibility")))]
+    pub fn accessibility_provider(&self) -> Result<accessibility::AccessibilityProvider, i32> {
+        let mut provider = core::ptr::null_mut();
+        let mut span = ffi_span!(
+            "OH_NativeXComponent_GetNativeAccessibilityProvider",
+            self.xcomponent
+        );
+        let res = unsafe {
+            ohos_sys::xcomponent::OH_NativeXComponent_GetNativeAccessibilityProvider(
+                self.xcomponent.as_ptr(),
+                &mut provider,
+            )
+        };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_NativeXComponent_GetNativeAccessibilityProvider failed with {res}");
+            return Err(res);
+        }
+        // OH_NATIVEXCOMPONENT_RESULT_FAILED
+        let provider = NonNull::new(provider).ok_or(-1)?;
+        Ok(accessibility::AccessibilityProvider::new(provider))
+    }
+
+    /// Returns the id of the XComponent, as set in ArkTS
+    ///
+    /// Useful to route callbacks if an application uses multiple XComponents.
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn id(&self) -> Result<String, i32> {
+        use ohos_sys::xcomponent::{OH_NativeXComponent_GetXComponentId, OH_XCOMPONENT_ID_LEN_MAX};
+
+        #[cfg(feature = "mock")]
+        if let Some(id) = mock::with_mock(self.xcomponent.as_ptr(), |m| m.id.clone()) {
+            return Ok(id);
+        }
+        let mut buf = [0u8; OH_XCOMPONENT_ID_LEN_MAX as usize + 1];
+        let mut len = buf.len() as u64;
+        let mut span = ffi_span!("OH_NativeXComponent_GetXComponentId", self.xcomponent);
+        let res = unsafe {
+            OH_NativeXComponent_GetXComponentId(
+                self.xcomponent.as_ptr(),
+                buf.as_mut_ptr().cast(),
+                &mut len as *mut _,
+            )
+        };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_NativeXComponent_GetXComponentId failed with {res}");
+            return Err(res);
+        }
+        // `len` is the length of the id without the NUL terminator, but don't rely on the
+        // implementation not to report a larger value.
+        let len = (len as usize).min(OH_XCOMPONENT_ID_LEN_MAX as usize);
+        let id = &buf[..len];
+        let id = id.split(|&b| b == 0).next().unwrap_or(id);
+        Ok(String::from_utf8_lossy(id).into_owned())
+    }
+
+    /// Returns the current touch event, see [`XComponent::get_touch_event`]
+    ///
+    /// Also queries the tool type and tilt of each pointer, which stay unset if ArkUI fails to
+    /// report them.
+    pub fn touch_event(&self) -> Result<touch::TouchEvent, i32> {
+        let mut touch_event = touch::TouchEvent::from(self.get_touch_event()?);
+        for (index, point) in (0..).zip(touch_event.points_mut()) {
+            point.tool_type = self.touch_point_tool_type(index).ok().flatten();
+            if let Ok((tilt_x, tilt_y)) = self.touch_point_tilt(index) {
+                point.tilt_x = tilt_x;
+                point.tilt_y = tilt_y;
+            }
+        }
+        Ok(touch_event)
+    }
+
+    /// Returns the tool of the pointer at `index` of the current touch event
+    ///
+    /// `None` if ArkUI doesn't know the tool. Only succeeds while ArkUI calls
+    /// `DispatchTouchEvent`.
+    pub fn touch_point_tool_type(&self, index: u32) -> Result<Option<touch::ToolType>, i32> {
+        use ohos_sys::xcomponent::{
+            OH_NativeXComponent_GetTouchPointToolType, OH_NativeXComponent_TouchPointToolType,
+        };
+
+        #[cfg(feature = "strict")]
+        strict::check(
+            self.xcomponent.as_ptr(),
+            strict::Violation::TouchEventOutsideDispatch,
+        )
+        // OH_NATIVEXCOMPONENT_RESULT_FAILED
+        .map_err(|_| -1)?;
+        #[cfg(feature = "mock")]
+        if let Some(tool) = mock::with_mock(self.xcomponent.as_ptr(), |m| {
+            m.touch_event
+                .map(|_| m.touch_point_tools.get(&index).and_then(|tool| tool.0))
+        }) {
+            // OH_NATIVEXCOMPONENT_RESULT_FAILED
+            return tool.ok_or(-1);
+        }
+        let mut tool_type =
+            OH_NativeXComponent_TouchPointToolType::OH_NATIVEXCOMPONENT_TOOL_TYPE_UNKNOWN;
+        let mut span = ffi_span!("OH_NativeXComponent_GetTouchPointToolType", self.xcomponent);
+        let res = unsafe {
+            OH_NativeXComponent_GetTouchPointToolType(
+                self.xcomponent.as_ptr(),
+                index,
+                &mut tool_type,
+            )
+        };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_NativeXComponent_GetTouchPointToolType failed with {res}");
+            return Err(res);
+        }
+        Ok(touch::ToolType::from_raw(tool_type))
+    }
+
+    /// Returns the tilt of the pointer at `index` of the current touch event, in degrees
+    ///
+    /// Only succeeds while ArkUI calls `DispatchTouchEvent`.
+    pub fn touch_point_tilt(&self, index: u32) -> Result<(f32, f32), i32> {
+        use ohos_sys::xcomponent::{
+            OH_NativeXComponent_GetTouchPointTiltX, OH_NativeXComponent_GetTouchPointTiltY,
+        };
+
+        #[cfg(feature = "strict")]
+        strict::check(
+            self.xcomponent.as_ptr(),
+            strict::Violation::TouchEventOutsideDispatch,
+        )
+        // OH_NATIVEXCOMPONENT_RESULT_FAILED
+        .map_err(|_| -1)?;
+        #[cfg(feature = "mock")]
+        if let Some(tilt) = mock::with_mock(self.xcomponent.as_ptr(), |m| {
+            m.touch_event.map(|_| {
+                m.touch_point_tools
+                    .get(&index)
+                    .map_or((0.0, 0.0), |tool| (tool.1, tool.2))
+            })
+        }) {
+            // OH_NATIVEXCOMPONENT_RESULT_FAILED
+            return tilt.ok_or(-1);
+        }
+        let (mut tilt_x, mut tilt_y) = (0.0, 0.0);
+        let mut span = ffi_span!("OH_NativeXComponent_GetTouchPointTiltX", self.xcomponent);
+        let res = unsafe {
+            OH_NativeXComponent_GetTouchPointTiltX(self.xcomponent.as_ptr(), index, &mut tilt_x)
+        };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_NativeXComponent_GetTouchPointTiltX failed with {res}");
+            return Err(res);
+        }
+        let mut span = ffi_span!("OH_NativeXComponent_GetTouchPointTiltY", self.xcomponent);
+        let res = unsafe {
+            OH_NativeXComponent_GetTouchPointTiltY(self.xcomponent.as_ptr(), index, &mut tilt_y)
+        };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_NativeXComponent_GetTouchPointTiltY failed with {res}");
+            return Err(res);
+        }
+        Ok((tilt_x, tilt_y))
+    }
+
+    /// Returns the raw current touch event
+    ///
+    /// Only succeeds while ArkUI calls `DispatchTouchEvent`.
     pub fn get_touch_event(&self) -> Result<OH_NativeXComponent_TouchEvent, i32> {
+        metrics::record(metrics::EventCategory::Touch, self.query_touch_event())
+    }
+
+    fn query_touch_event(&self) -> Result<OH_NativeXComponent_TouchEvent, i32> {
+        #[cfg(feature = "strict")]
+        strict::check(
+            self.xcomponent.as_ptr(),
+            strict::Violation::TouchEventOutsideDispatch,
+        )
+        // OH_NATIVEXCOMPONENT_RESULT_FAILED
+        .map_err(|_| -1)?;
+        #[cfg(feature = "mock")]
+        if let Some(touch_event) = mock::with_mock(self.xcomponent.as_ptr(), |m| m.touch_event) {
+            // OH_NATIVEXCOMPONENT_RESULT_FAILED
+            return touch_event.ok_or(-1);
+        }
+        let mut span = ffi_span!("OH_NativeXComponent_GetTouchEvent", self.xcomponent);
         let touch_event = unsafe {
             let mut touch_event: MaybeUninit<OH_NativeXComponent_TouchEvent> =
                 MaybeUninit::uninit();
@@ -72,6 +570,7 @@ impl<'a> XComponent<'a> {
                 self.window.as_ptr().cast(),
                 touch_event.as_mut_ptr(),
             );
+            span.set_result(res);
             if res != 0 {
                 error!("OH_NativeXComponent_GetTouchEvent failed with {res}");
                 return Err(res);
@@ -82,10 +581,274 @@ impl<'a> XComponent<'a> {
         Ok(touch_event)
     }
 
+    /// Returns the samples ArkUI coalesced into the current touch event, oldest first
+    ///
+    /// Only succeeds while ArkUI calls `DispatchTouchEvent`, like [`XComponent::get_touch_event`].
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn touch_event_history(&self) -> Result<alloc::vec::Vec<touch::HistoricalPoint>, i32> {
+        use ohos_sys::xcomponent::{
+            OH_NativeXComponent_GetHistoricalPoints, OH_NativeXComponent_HistoricalPoint,
+        };
+
+        #[cfg(feature = "strict")]
+        strict::check(
+            self.xcomponent.as_ptr(),
+            strict::Violation::TouchEventOutsideDispatch,
+        )
+        // OH_NATIVEXCOMPONENT_RESULT_FAILED
+        .map_err(|_| -1)?;
+        #[cfg(feature = "mock")]
+        if let Some(history) = mock::with_mock(self.xcomponent.as_ptr(), |m| {
+            m.touch_event.map(|_| {
+                m.historical_points
+                    .iter()
+                    .map(touch::HistoricalPoint::from)
+                    .collect()
+            })
+        }) {
+            // OH_NATIVEXCOMPONENT_RESULT_FAILED
+            return history.ok_or(-1);
+        }
+        let mut size: i32 = 0;
+        let mut points: *mut OH_NativeXComponent_HistoricalPoint = core::ptr::null_mut();
+        let mut span = ffi_span!("OH_NativeXComponent_GetHistoricalPoints", self.xcomponent);
+        let res = unsafe {
+            OH_NativeXComponent_GetHistoricalPoints(
+                self.xcomponent.as_ptr(),
+                self.window.as_ptr().cast(),
+                &mut size,
+                &mut points,
+            )
+        };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_NativeXComponent_GetHistoricalPoints failed with {res}");
+            return Err(res);
+        }
+        let len = usize::try_from(size).unwrap_or(0);
+        if points.is_null() || len == 0 {
+            return Ok(alloc::vec::Vec::new());
+        }
+        // SAFETY: ArkUI owns the `len` points until `DispatchTouchEvent` returns.
+        let points = unsafe { core::slice::from_raw_parts(points, len) };
+        Ok(points.iter().map(touch::HistoricalPoint::from).collect())
+    }
+
+    /// Returns the current key event
+    ///
+    /// Only succeeds while ArkUI calls the [`EventCallbacks::on_key`] callback.
+    pub fn get_key_event(&self) -> Result<key::KeyEvent, i32> {
+        metrics::record(metrics::EventCategory::Key, self.query_key_event())
+    }
+
+    fn query_key_event(&self) -> Result<key::KeyEvent, i32> {
+        #[cfg(feature = "mock")]
+        if let Some(key_event) = mock::with_mock(self.xcomponent.as_ptr(), |m| m.key_event) {
+            // OH_NATIVEXCOMPONENT_RESULT_FAILED
+            return key_event.ok_or(-1);
+        }
+        let mut span = ffi_span!("OH_NativeXComponent_GetKeyEvent", self.xcomponent);
+        let mut key_event = core::ptr::null_mut();
+        let res =
+            unsafe { OH_NativeXComponent_GetKeyEvent(self.xcomponent.as_ptr(), &mut key_event) };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_NativeXComponent_GetKeyEvent failed with {res}");
+            return Err(res);
+        }
+        if key_event.is_null() {
+            // OH_NATIVEXCOMPONENT_RESULT_FAILED
+            return Err(-1);
+        }
+        // SAFETY: ArkUI keeps the event alive until the callback returns.
+        unsafe { key::KeyEvent::from_raw(key_event) }
+    }
+
+    /// Returns the current mouse event
+    ///
+    /// Only succeeds while ArkUI calls the `DispatchMouseEvent` callback.
+    pub fn get_mouse_event(&self) -> Result<mouse::MouseEvent, i32> {
+        metrics::record(metrics::EventCategory::Mouse, self.query_mouse_event())
+    }
+
+    fn query_mouse_event(&self) -> Result<mouse::MouseEvent, i32> {
+        #[cfg(feature = "mock")]
+        if let Some(mouse_event) = mock::with_mock(self.xcomponent.as_ptr(), |m| m.mouse_event) {
+            // OH_NATIVEXCOMPONENT_RESULT_FAILED
+            return mouse_event.ok_or(-1);
+        }
+        let mut span = ffi_span!("OH_NativeXComponent_GetMouseEvent", self.xcomponent);
+        let mouse_event = unsafe {
+            let mut mouse_event: MaybeUninit<OH_NativeXComponent_MouseEvent> =
+                MaybeUninit::uninit();
+            let res = OH_NativeXComponent_GetMouseEvent(
+                self.xcomponent.as_ptr(),
+                self.window.as_ptr() as *const c_void,
+                mouse_event.as_mut_ptr(),
+            );
+            span.set_result(res);
+            if res != 0 {
+                error!("OH_NativeXComponent_GetMouseEvent failed with {res}");
+                return Err(res);
+            }
+            mouse_event.assume_init()
+        };
+        Ok(mouse_event.into())
+    }
+
+    /// Calls `callback` on the UI thread for every frame rendered by the compositor
+    ///
+    /// The callback receives the time of the current frame and the time the next frame is
+    /// expected to be presented at, both relative to the same monotonic clock. Setting a new
+    /// callback replaces the previous one of this XComponent.
+    #[cfg(all(feature = "api-11", feature = "std"))]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "api-11", feature = "std"))))]
+    pub fn set_frame_callback(
+        &self,
+        callback: impl FnMut(core::time::Duration, core::time::Duration) + 'static,
+    ) -> Result<(), i32> {
+        frame::set(
+            self.xcomponent.as_ptr() as usize,
+            std::rc::Rc::new(core::cell::RefCell::new(callback)),
+        );
+        #[cfg(feature = "mock")]
+        if mock::with_mock(self.xcomponent.as_ptr(), |_| ()).is_some() {
+            return Ok(());
+        }
+        let mut span = ffi_span!(
+            "OH_NativeXComponent_RegisterOnFrameCallback",
+            self.xcomponent
+        );
+        let res = unsafe {
+            ohos_sys::xcomponent::OH_NativeXComponent_RegisterOnFrameCallback(
+                self.xcomponent.as_ptr(),
+                Some(frame::on_frame),
+            )
+        };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_NativeXComponent_RegisterOnFrameCallback failed with {res}");
+            frame::remove(self.xcomponent.as_ptr() as usize);
+            return Err(res);
+        }
+        Ok(())
+    }
+
+    /// Stops calling the callback set via [`XComponent::set_frame_callback`]
+    #[cfg(all(feature = "api-11", feature = "std"))]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "api-11", feature = "std"))))]
+    pub fn remove_frame_callback(&self) -> Result<(), i32> {
+        remove_frame_callback(self.xcomponent.as_ptr())
+    }
+
+    /// Asks the compositor to call the frame callback at `expected` frames per second
+    ///
+    /// The compositor may choose any rate between `min` and `max`, e.g. to match the refresh
+    /// rate of the display. Fails with [`FrameRateError::InvalidRange`] unless
+    /// `0 <= min <= expected <= max` and `max > 0`.
+    #[cfg(feature = "api-11")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "api-11")))]
+    pub fn set_expected_frame_rate_range(
+        &self,
+        min: i32,
+        max: i32,
+        expected: i32,
+    ) -> Result<(), FrameRateError> {
+        if min < 0 || min > expected || expected > max || max <= 0 {
+            return Err(FrameRateError::InvalidRange { min, max, expected });
+        }
+        let mut range =
+            ohos_sys::xcomponent::OH_NativeXComponent_ExpectedRateRange { min, max, expected };
+        #[cfg(feature = "mock")]
+        if mock::update_mock(self.xcomponent.as_ptr(), |m| {
+            m.frame_rate_range = Some((min, max, expected))
+        }) {
+            return Ok(());
+        }
+        let mut span = ffi_span!(
+            "OH_NativeXComponent_SetExpectedFrameRateRange",
+            self.xcomponent
+        );
+        let res = unsafe {
+            ohos_sys::xcomponent::OH_NativeXComponent_SetExpectedFrameRateRange(
+                self.xcomponent.as_ptr(),
+                &mut range,
+            )
+        };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_NativeXComponent_SetExpectedFrameRateRange failed with {res}");
+            return Err(FrameRateError::Native(res));
+        }
+        Ok(())
+    }
+
+    /// Sets whether focusing the XComponent raises the soft keyboard, `false` by default
+    ///
+    /// Enable this for components which draw their own text fields. The text itself is received
+    /// via key events, or with the `text-input` feature via an input method connection.
+    #[cfg(feature = "api-12")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "api-12")))]
+    pub fn set_needs_soft_keyboard(&self, needs_soft_keyboard: bool) -> Result<(), i32> {
+        #[cfg(feature = "mock")]
+        if mock::update_mock(self.xcomponent.as_ptr(), |m| {
+            m.needs_soft_keyboard = needs_soft_keyboard
+        }) {
+            return Ok(());
+        }
+        let mut span = ffi_span!("OH_NativeXComponent_SetNeedSoftKeyboard", self.xcomponent);
+        let res = unsafe {
+            ohos_sys::xcomponent::OH_NativeXComponent_SetNeedSoftKeyboard(
+                self.xcomponent.as_ptr(),
+                needs_soft_keyboard,
+            )
+        };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_NativeXComponent_SetNeedSoftKeyboard failed with {res}");
+            return Err(res);
+        }
+        Ok(())
+    }
+
     /// Returns the size of the XComponent
+    ///
+    /// # Panics
+    ///
+    /// Panics if querying the size fails, which unwinding out of an XComponent callback turns
+    /// into an abort.
+    #[deprecated(
+        note = "use `try_size`, `size` will return `Result<Size, i32>` like `try_size` in the next \
+                breaking release"
+    )]
     pub fn size(&self) -> Size {
+        self.try_size()
+            .expect("OH_NativeXComponent_GetXComponentSize failed")
+    }
+
+    /// Returns the size of the XComponent
+    ///
+    /// Fails with the error code of `OH_NativeXComponent_GetXComponentSize`. Like the other
+    /// accessors, this returns the plain `i32` code instead of a dedicated error type, so callers
+    /// can handle all ArkUI failures of the crate the same way.
+    pub fn try_size(&self) -> Result<Size, i32> {
+        #[cfg(feature = "strict")]
+        if let Err(violation) = strict::check(
+            self.xcomponent.as_ptr(),
+            strict::Violation::SizeWithoutSurface,
+        ) {
+            error!("XComponent {:?}: {violation}", self.xcomponent);
+            // OH_NATIVEXCOMPONENT_RESULT_FAILED
+            return Err(-1);
+        }
+        #[cfg(feature = "mock")]
+        if let Some(size) = mock::with_mock(self.xcomponent.as_ptr(), |m| m.size) {
+            return Ok(size);
+        }
         let mut width: u64 = 0;
         let mut height: u64 = 0;
+        let mut span = ffi_span!("OH_NativeXComponent_GetXComponentSize", self.xcomponent);
         let res = unsafe {
             OH_NativeXComponent_GetXComponentSize(
                 self.xcomponent.as_ptr(),
@@ -94,29 +857,202 @@ impl<'a> XComponent<'a> {
                 &mut height as *mut _,
             )
         };
-        assert_eq!(res, 0, "OH_NativeXComponent_GetXComponentSize failed");
-        Size {
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_NativeXComponent_GetXComponentSize failed with {res}");
+            return Err(res);
+        }
+        Ok(Size {
             width,
             height,
             _opaque: [],
+        })
+    }
+
+    /// Returns the offset of the XComponent within its window
+    ///
+    /// Use this to map window coordinates, e.g. of IME or drag events, into the coordinate space
+    /// of the XComponent.
+    pub fn offset(&self) -> Result<Offset, i32> {
+        #[cfg(feature = "mock")]
+        if let Some(offset) = mock::with_mock(self.xcomponent.as_ptr(), |m| m.offset) {
+            return Ok(offset);
+        }
+        let mut x: f64 = 0.0;
+        let mut y: f64 = 0.0;
+        let mut span = ffi_span!("OH_NativeXComponent_GetXComponentOffset", self.xcomponent);
+        let res = unsafe {
+            OH_NativeXComponent_GetXComponentOffset(
+                self.xcomponent.as_ptr(),
+                self.window.as_ptr() as *const c_void,
+                &mut x as *mut _,
+                &mut y as *mut _,
+            )
+        };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_NativeXComponent_GetXComponentOffset failed with {res}");
+            return Err(res);
+        }
+        Ok(Offset { x, y, _opaque: [] })
+    }
+
+    /// Returns the position and size of the XComponent within its window
+    pub fn rect(&self) -> Result<geometry::ComponentRect, i32> {
+        let offset = self.offset()?;
+        let size = self.try_size()?;
+        Ok(geometry::ComponentRect {
+            x: offset.x,
+            y: offset.y,
+            width: size.width,
+            height: size.height,
+        })
+    }
+}
+
+/// See [`XComponent::remove_frame_callback`]
+#[cfg(all(feature = "api-11", feature = "std"))]
+pub(crate) fn remove_frame_callback(xcomponent: *mut OH_NativeXComponent) -> Result<(), i32> {
+    frame::remove(xcomponent as usize);
+    #[cfg(feature = "mock")]
+    if mock::with_mock(xcomponent, |_| ()).is_some() {
+        return Ok(());
+    }
+    let mut span = ffi_span!("OH_NativeXComponent_UnregisterOnFrameCallback", xcomponent);
+    let res =
+        unsafe { ohos_sys::xcomponent::OH_NativeXComponent_UnregisterOnFrameCallback(xcomponent) };
+    span.set_result(res);
+    if res != 0 {
+        error!("OH_NativeXComponent_UnregisterOnFrameCallback failed with {res}");
+        return Err(res);
+    }
+    Ok(())
+}
+
+type Callback = Option<unsafe extern "C" fn(*mut OH_NativeXComponent, *mut c_void)>;
+
+/// Focus and key callbacks of an XComponent
+///
+/// Registered via [`register_event_callbacks`] with the `register` feature.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EventCallbacks {
+    /// Called when the XComponent gains focus
+    pub on_focus: Callback,
+    /// Called when the XComponent loses focus, e.g. to stop handling input
+    pub on_blur: Callback,
+    /// Called for key events while the XComponent has focus, see [`XComponent::get_key_event`]
+    pub on_key: Callback,
+}
+
+/// Surface visibility callbacks of an XComponent
+///
+/// Registered via [`register_surface_visibility_callbacks`] with the `register` feature. The
+/// surface is hidden e.g. when the app moves to the background. Render loops should stop producing
+/// frames until it is shown again, the surface and its native window stay valid meanwhile.
+#[cfg(feature = "api-12")]
+#[cfg_attr(docsrs, doc(cfg(feature = "api-12")))]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SurfaceVisibilityCallbacks {
+    /// Called when the surface is shown again after being hidden
+    pub on_surface_show: Callback,
+    /// Called when the surface is hidden, e.g. to stop rendering
+    pub on_surface_hide: Callback,
+}
+
+/// Error of [`XComponent::set_expected_frame_rate_range`]
+#[cfg(feature = "api-11")]
+#[cfg_attr(docsrs, doc(cfg(feature = "api-11")))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameRateError {
+    /// The range was rejected before calling into ArkUI
+    InvalidRange { min: i32, max: i32, expected: i32 },
+    /// ArkUI failed with the given error code
+    Native(i32),
+}
+
+/// Detects changes of the offset of an XComponent
+///
+/// There is no dedicated callback for offset changes. Layout changes which move the XComponent
+/// are followed by `OnSurfaceChanged`, so [`OffsetWatcher::update`] should be called from there.
+#[derive(Debug, Default)]
+pub struct OffsetWatcher {
+    last: Option<Offset>,
+}
+
+impl OffsetWatcher {
+    pub const fn new() -> Self {
+        Self { last: None }
+    }
+
+    /// The offset returned by the last call to [`OffsetWatcher::update`] which succeeded
+    pub fn offset(&self) -> Option<Offset> {
+        self.last
+    }
+
+    /// Queries the offset of `xcomponent` and returns it if it changed since the last call
+    pub fn update(&mut self, xcomponent: &XComponent<'_>) -> Option<Offset> {
+        let offset = xcomponent.offset().ok()?;
+        if self.last == Some(offset) {
+            return None;
+        }
+        self.last = Some(offset);
+        Some(offset)
+    }
+}
+
+#[cfg(feature = "api-11")]
+impl core::fmt::Display for FrameRateError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidRange { min, max, expected } => write!(
+                f,
+                "invalid frame rate range: min {min}, max {max}, expected {expected}"
+            ),
+            Self::Native(res) => write!(f, "setting the frame rate range failed with {res}"),
         }
     }
 }
 
+#[cfg(all(feature = "api-11", feature = "std"))]
+impl std::error::Error for FrameRateError {}
+
+/// Error of [`register_xcomponent_callbacks`] and the other registration functions
 #[cfg(feature = "register")]
 #[cfg_attr(docsrs, doc(cfg(feature = "register")))]
 #[derive(Debug)]
 pub enum RegisterCallbackError {
+    /// The exports object has no `__NATIVE_XCOMPONENT_OBJ__` property, with the NAPI error message
     XcomponentPropertyMissing(String),
+    /// `napi_unwrap` of the property failed with the given NAPI status
     UnwrapXComponentFailed(i32),
+    /// ArkUI failed to register the callbacks with the given error code
     RegisterCallbackFailed(i32),
 }
 
 #[cfg(feature = "register")]
-#[cfg_attr(docsrs, doc(cfg(feature = "register")))]
-impl Into<String> for RegisterCallbackError {
-    fn into(self) -> String {
-        format!("{:?}", self)
+impl core::fmt::Display for RegisterCallbackError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::XcomponentPropertyMissing(reason) => {
+                write!(f, "the exports have no XComponent: {reason}")
+            }
+            Self::UnwrapXComponentFailed(status) => {
+                write!(f, "unwrapping the XComponent failed with status {status}")
+            }
+            Self::RegisterCallbackFailed(res) => {
+                write!(f, "registering the callbacks failed with {res}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "register")]
+impl std::error::Error for RegisterCallbackError {}
+
+#[cfg(feature = "register")]
+impl From<RegisterCallbackError> for String {
+    fn from(err: RegisterCallbackError) -> String {
+        err.to_string()
     }
 }
 
@@ -134,10 +1070,11 @@ impl Into<String> for RegisterCallbackError {
 ///
 /// ```
 /// # use core::ffi::c_void;
-/// # use log::info;
-/// # use ohos_sys::ace::xcomponent::native_interface_xcomponent::{OH_NativeXComponent, OH_NativeXComponent_Callback};
+/// # use std::println as info;
+/// # use ohos_sys::xcomponent::{OH_NativeXComponent, OH_NativeXComponent_Callback};
 /// // use napi_derive_ohos::module_exports;
 /// // #[module_exports]
+/// # #[cfg(not(feature = "napi-ohos-v1"))]
 /// fn init(exports: napi_ohos::JsObject, env: napi_ohos::Env) -> napi_ohos::Result<()> {
 ///     xcomponent::register_xcomponent_callbacks(&exports, &env, &XC_CALLBACKS)
 ///         .expect("Registering Callback failed.");
@@ -168,37 +1105,212 @@ impl Into<String> for RegisterCallbackError {
 /// ```
 #[cfg(feature = "register")]
 #[cfg_attr(docsrs, doc(cfg(feature = "register")))]
-pub fn register_xcomponent_callbacks(
-    exports: &napi_ohos::JsObject,
-    env: &napi_ohos::Env,
-    callbacks: &'static ohos_sys::ace::xcomponent::native_interface_xcomponent::OH_NativeXComponent_Callback,
+pub fn register_xcomponent_callbacks<E: NapiExports>(
+    exports: &E,
+    env: &E::Env,
+    callbacks: &'static ohos_sys::xcomponent::OH_NativeXComponent_Callback,
 ) -> Result<(), RegisterCallbackError> {
-    use napi_ohos::NapiRaw;
-    use ohos_sys::ace::xcomponent::native_interface_xcomponent::OH_NativeXComponent_RegisterCallback;
-
-    let xcomponent_js_object = exports
-        .get_named_property::<napi_ohos::JsObject>("__NATIVE_XCOMPONENT_OBJ__")
-        .map_err(|e| RegisterCallbackError::XcomponentPropertyMissing(e.to_string()))?;
-    let raw = unsafe { xcomponent_js_object.raw() };
-    let raw_env = env.raw();
-    let mut native_xcomponent: *mut OH_NativeXComponent = core::ptr::null_mut();
-    let res = unsafe {
-        napi_ohos::sys::napi_unwrap(
-            raw_env,
-            raw,
-            &mut native_xcomponent as *mut *mut OH_NativeXComponent as *mut *mut c_void,
-        )
-    };
-    if res != 0 {
-        return Err(RegisterCallbackError::UnwrapXComponentFailed(res));
+    thread::check_ui_thread("register_xcomponent_callbacks");
+    let native_xcomponent = exports.native_xcomponent(env)?;
+    #[cfg(feature = "strict")]
+    strict::check(native_xcomponent, strict::Violation::RegisteredTwice)
+        // OH_NATIVEXCOMPONENT_RESULT_FAILED
+        .map_err(|_| RegisterCallbackError::RegisterCallbackFailed(-1))?;
+    register_xcomponent_callbacks_raw(native_xcomponent, callbacks)
+}
+
+/// Registers `callbacks` for `native_xcomponent`, replacing the registered ones
+#[cfg(feature = "register")]
+pub(crate) fn register_xcomponent_callbacks_raw(
+    native_xcomponent: *mut OH_NativeXComponent,
+    callbacks: &'static ohos_sys::xcomponent::OH_NativeXComponent_Callback,
+) -> Result<(), RegisterCallbackError> {
+    use ohos_sys::xcomponent::OH_NativeXComponent_RegisterCallback;
+
+    #[cfg(feature = "strict")]
+    let callbacks = strict::track(native_xcomponent, callbacks);
+    #[cfg(feature = "mock")]
+    if mock::update_mock(native_xcomponent, |m| m.callbacks = Some(callbacks)) {
+        return Ok(());
     }
+    let mut span = ffi_span!("OH_NativeXComponent_RegisterCallback", native_xcomponent);
     let res =
         // Note: The register function seems to offload the work to some other thread and return early.
         // so the CBs need to live longer than this function ....
         // SAFETY: `OH_NativeXComponent_RegisterCallback` will not mutate `callbacks`.
         unsafe { OH_NativeXComponent_RegisterCallback(native_xcomponent, callbacks as *const _ as *mut _) };
+    span.set_result(res);
+    if res != 0 {
+        return Err(RegisterCallbackError::RegisterCallbackFailed(res));
+    }
+    Ok(())
+}
+
+/// Register mouse event callbacks for the XComponent
+///
+/// Like [`register_xcomponent_callbacks`], this is intended to be called from the module init
+/// function. ArkUI calls `DispatchMouseEvent` for mouse input, where the event is available via
+/// [`XComponent::get_mouse_event`], and `DispatchHoverEvent` when the mouse enters or leaves the
+/// XComponent.
+#[cfg(feature = "register")]
+#[cfg_attr(docsrs, doc(cfg(feature = "register")))]
+pub fn register_mouse_event_callbacks<E: NapiExports>(
+    exports: &E,
+    env: &E::Env,
+    callbacks: &'static ohos_sys::xcomponent::OH_NativeXComponent_MouseEvent_Callback,
+) -> Result<(), RegisterCallbackError> {
+    thread::check_ui_thread("register_mouse_event_callbacks");
+    register_mouse_event_callbacks_raw(exports.native_xcomponent(env)?, callbacks)
+}
+
+#[cfg(feature = "register")]
+pub(crate) fn register_mouse_event_callbacks_raw(
+    native_xcomponent: *mut OH_NativeXComponent,
+    callbacks: &'static ohos_sys::xcomponent::OH_NativeXComponent_MouseEvent_Callback,
+) -> Result<(), RegisterCallbackError> {
+    use ohos_sys::xcomponent::OH_NativeXComponent_RegisterMouseEventCallback;
+
+    #[cfg(feature = "mock")]
+    if mock::update_mock(native_xcomponent, |m| m.mouse_callbacks = Some(callbacks)) {
+        return Ok(());
+    }
+    let mut span = ffi_span!(
+        "OH_NativeXComponent_RegisterMouseEventCallback",
+        native_xcomponent
+    );
+    // SAFETY: `OH_NativeXComponent_RegisterMouseEventCallback` will not mutate `callbacks`.
+    let res = unsafe {
+        OH_NativeXComponent_RegisterMouseEventCallback(
+            native_xcomponent,
+            callbacks as *const _ as *mut _,
+        )
+    };
+    span.set_result(res);
     if res != 0 {
         return Err(RegisterCallbackError::RegisterCallbackFailed(res));
     }
     Ok(())
 }
+
+/// Register focus, blur and key event callbacks for the XComponent
+///
+/// Like [`register_xcomponent_callbacks`], this is intended to be called from the module init
+/// function. Callbacks which are `None` are not registered.
+#[cfg(feature = "register")]
+#[cfg_attr(docsrs, doc(cfg(feature = "register")))]
+pub fn register_event_callbacks<E: NapiExports>(
+    exports: &E,
+    env: &E::Env,
+    callbacks: &EventCallbacks,
+) -> Result<(), RegisterCallbackError> {
+    thread::check_ui_thread("register_event_callbacks");
+    register_event_callbacks_raw(exports.native_xcomponent(env)?, callbacks)
+}
+
+#[cfg(feature = "register")]
+pub(crate) fn register_event_callbacks_raw(
+    native_xcomponent: *mut OH_NativeXComponent,
+    callbacks: &EventCallbacks,
+) -> Result<(), RegisterCallbackError> {
+    use ohos_sys::xcomponent::{
+        OH_NativeXComponent_RegisterBlurEventCallback,
+        OH_NativeXComponent_RegisterFocusEventCallback,
+        OH_NativeXComponent_RegisterKeyEventCallback,
+    };
+
+    #[cfg(feature = "mock")]
+    if mock::update_mock(native_xcomponent, |m| m.event_callbacks = Some(*callbacks)) {
+        return Ok(());
+    }
+    let registrations = [
+        (
+            "OH_NativeXComponent_RegisterFocusEventCallback",
+            callbacks.on_focus,
+            OH_NativeXComponent_RegisterFocusEventCallback as RegisterEventCallback,
+        ),
+        (
+            "OH_NativeXComponent_RegisterBlurEventCallback",
+            callbacks.on_blur,
+            OH_NativeXComponent_RegisterBlurEventCallback,
+        ),
+        (
+            "OH_NativeXComponent_RegisterKeyEventCallback",
+            callbacks.on_key,
+            OH_NativeXComponent_RegisterKeyEventCallback,
+        ),
+    ];
+    for (function, callback, register) in registrations {
+        if callback.is_none() {
+            continue;
+        }
+        let mut span = ffi_span!(function, native_xcomponent);
+        let res = unsafe { register(native_xcomponent, callback) };
+        span.set_result(res);
+        if res != 0 {
+            error!("{function} failed with {res}");
+            return Err(RegisterCallbackError::RegisterCallbackFailed(res));
+        }
+    }
+    Ok(())
+}
+
+/// Register surface show and hide callbacks for the XComponent
+///
+/// Like [`register_xcomponent_callbacks`], this is intended to be called from the module init
+/// function. Callbacks which are `None` are not registered.
+#[cfg(all(feature = "register", feature = "api-12"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "register", feature = "api-12"))))]
+pub fn register_surface_visibility_callbacks<E: NapiExports>(
+    exports: &E,
+    env: &E::Env,
+    callbacks: &SurfaceVisibilityCallbacks,
+) -> Result<(), RegisterCallbackError> {
+    thread::check_ui_thread("register_surface_visibility_callbacks");
+    register_surface_visibility_callbacks_raw(exports.native_xcomponent(env)?, callbacks)
+}
+
+#[cfg(all(feature = "register", feature = "api-12"))]
+pub(crate) fn register_surface_visibility_callbacks_raw(
+    native_xcomponent: *mut OH_NativeXComponent,
+    callbacks: &SurfaceVisibilityCallbacks,
+) -> Result<(), RegisterCallbackError> {
+    use ohos_sys::xcomponent::{
+        OH_NativeXComponent_RegisterSurfaceHideCallback,
+        OH_NativeXComponent_RegisterSurfaceShowCallback,
+    };
+
+    #[cfg(feature = "mock")]
+    if mock::update_mock(native_xcomponent, |m| {
+        m.visibility_callbacks = Some(*callbacks)
+    }) {
+        return Ok(());
+    }
+    let registrations = [
+        (
+            "OH_NativeXComponent_RegisterSurfaceShowCallback",
+            callbacks.on_surface_show,
+            OH_NativeXComponent_RegisterSurfaceShowCallback as RegisterEventCallback,
+        ),
+        (
+            "OH_NativeXComponent_RegisterSurfaceHideCallback",
+            callbacks.on_surface_hide,
+            OH_NativeXComponent_RegisterSurfaceHideCallback,
+        ),
+    ];
+    for (function, callback, register) in registrations {
+        if callback.is_none() {
+            continue;
+        }
+        let mut span = ffi_span!(function, native_xcomponent);
+        let res = unsafe { register(native_xcomponent, callback) };
+        span.set_result(res);
+        if res != 0 {
+            error!("{function} failed with {res}");
+            return Err(RegisterCallbackError::RegisterCallbackFailed(res));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "register")]
+type RegisterEventCallback = unsafe extern "C" fn(*mut OH_NativeXComponent, Callback) -> i32;
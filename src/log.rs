@@ -1,37 +1,154 @@
-#![allow(unused_imports)]
+// Not every level is used with every combination of features.
+#![allow(unused_imports, unused_macros, dead_code)]
 
-#[cfg(feature = "log")]
-pub(crate) use log::{debug, error, info, trace, warn};
-#[cfg(not(feature = "log"))]
-pub(crate) use mock::{debug, error, info, trace, warn};
+#[cfg(feature = "hilog")]
+pub(crate) use hilog as backend;
+#[cfg(all(feature = "log", not(feature = "hilog")))]
+pub(crate) use log as backend;
+#[cfg(not(any(feature = "hilog", feature = "log", feature = "tracing")))]
+pub(crate) use mock as backend;
+#[cfg(all(feature = "tracing", not(any(feature = "hilog", feature = "log"))))]
+pub(crate) use tracing as backend;
 
-#[cfg(not(feature = "log"))]
+// Every `log-level-*` feature disables its next more verbose level, and with it all levels
+// above, so the least verbose feature wins.
+pub(crate) const ERROR_ENABLED: bool = !cfg!(feature = "log-level-off");
+pub(crate) const WARN_ENABLED: bool = ERROR_ENABLED && !cfg!(feature = "log-level-error");
+pub(crate) const INFO_ENABLED: bool = WARN_ENABLED && !cfg!(feature = "log-level-warn");
+pub(crate) const DEBUG_ENABLED: bool = INFO_ENABLED && !cfg!(feature = "log-level-info");
+pub(crate) const TRACE_ENABLED: bool = DEBUG_ENABLED && !cfg!(feature = "log-level-debug");
+
+// The messages of stripped levels are type checked, but never formatted, so the compiler removes
+// them entirely.
+macro_rules! error {
+    ($($arg:tt)+) => {
+        if $crate::log::ERROR_ENABLED {
+            $crate::log::backend::error!($($arg)+);
+        }
+    };
+}
+macro_rules! warn_ {
+    ($($arg:tt)+) => {
+        if $crate::log::WARN_ENABLED {
+            $crate::log::backend::warn!($($arg)+);
+        }
+    };
+}
+macro_rules! info {
+    ($($arg:tt)+) => {
+        if $crate::log::INFO_ENABLED {
+            $crate::log::backend::info!($($arg)+);
+        }
+    };
+}
+macro_rules! debug {
+    ($($arg:tt)+) => {
+        if $crate::log::DEBUG_ENABLED {
+            $crate::log::backend::debug!($($arg)+);
+        }
+    };
+}
+macro_rules! trace {
+    ($($arg:tt)+) => {
+        if $crate::log::TRACE_ENABLED {
+            $crate::log::backend::trace!($($arg)+);
+        }
+    };
+}
+pub(crate) use warn_ as warn;
+pub(crate) use {debug, error, info, trace};
+
+#[cfg(feature = "hilog")]
+#[allow(unused_macros)]
+pub(crate) mod hilog {
+    macro_rules! log_ {
+        ($level:ident, target: $target:expr, $($arg:tt)+) => {
+            $crate::hilog::print(
+                ohos_sys::hilog::LogLevel::$level,
+                format_args!($($arg)+),
+            )
+        };
+
+        ($level:ident, $($arg:tt)+) => {
+            $crate::hilog::print(
+                ohos_sys::hilog::LogLevel::$level,
+                format_args!($($arg)+),
+            )
+        };
+    }
+    macro_rules! error {
+        ($($arg:tt)+) => { $crate::log::hilog::log_!(LOG_ERROR, $($arg)+) };
+    }
+    macro_rules! warn_ {
+        ($($arg:tt)+) => { $crate::log::hilog::log_!(LOG_WARN, $($arg)+) };
+    }
+    macro_rules! info {
+        ($($arg:tt)+) => { $crate::log::hilog::log_!(LOG_INFO, $($arg)+) };
+    }
+    macro_rules! debug {
+        ($($arg:tt)+) => { $crate::log::hilog::log_!(LOG_DEBUG, $($arg)+) };
+    }
+    // HiLog has no level below debug.
+    macro_rules! trace {
+        ($($arg:tt)+) => { $crate::log::hilog::log_!(LOG_DEBUG, $($arg)+) };
+    }
+    pub(crate) use log_;
+    pub(crate) use warn_ as warn;
+    pub(crate) use {debug, error, info, trace};
+}
+
+#[cfg(not(any(feature = "hilog", feature = "log", feature = "tracing")))]
 #[allow(unused_macros)]
-mod mock {
+pub(crate) mod mock {
     macro_rules! error {
-        (target: $target:expr, $($arg:tt)+) => {};
+        (target: $target:expr, $($arg:tt)+) => {
+            let _ = $target;
+            let _ = format_args!($($arg)+);
+        };
 
-        ($($arg:tt)+) => {};
+        ($($arg:tt)+) => {
+            let _ = format_args!($($arg)+);
+        };
     }
     macro_rules! warn_ {
-        (target: $target:expr, $($arg:tt)+) => {};
+        (target: $target:expr, $($arg:tt)+) => {
+            let _ = $target;
+            let _ = format_args!($($arg)+);
+        };
 
-        ($($arg:tt)+) => {};
+        ($($arg:tt)+) => {
+            let _ = format_args!($($arg)+);
+        };
     }
     macro_rules! info {
-        (target: $target:expr, $($arg:tt)+) => {};
+        (target: $target:expr, $($arg:tt)+) => {
+            let _ = $target;
+            let _ = format_args!($($arg)+);
+        };
 
-        ($($arg:tt)+) => {};
+        ($($arg:tt)+) => {
+            let _ = format_args!($($arg)+);
+        };
     }
     macro_rules! debug {
-        (target: $target:expr, $($arg:tt)+) => {};
+        (target: $target:expr, $($arg:tt)+) => {
+            let _ = $target;
+            let _ = format_args!($($arg)+);
+        };
 
-        ($($arg:tt)+) => {};
+        ($($arg:tt)+) => {
+            let _ = format_args!($($arg)+);
+        };
     }
     macro_rules! trace {
-        (target: $target:expr, $($arg:tt)+) => {};
+        (target: $target:expr, $($arg:tt)+) => {
+            let _ = $target;
+            let _ = format_args!($($arg)+);
+        };
 
-        ($($arg:tt)+) => {};
+        ($($arg:tt)+) => {
+            let _ = format_args!($($arg)+);
+        };
     }
     pub(crate) use warn_ as warn;
     pub(crate) use {debug, error, info, trace};
@@ -0,0 +1,183 @@
+//! Counters of the events received by this crate
+//!
+//! Every successful query of a touch, mouse or key event counts as received, every failed query
+//! as dropped. Surface, hover and focus callbacks are counted, and all callbacks are timed with
+//! the `std` feature, when they are dispatched through an
+//! [`XComponentHandler`](crate::handler::XComponentHandler).
+//! The counters are process-wide atomics, [`metrics`] returns a snapshot of them.
+//!
+//! With the `metrics` feature, the counters are additionally reported to the `metrics` crate, as
+//! the counters `xcomponent_events_received` and `xcomponent_events_dropped` and the histogram
+//! `xcomponent_callback_duration_seconds`, labeled with the `category` of the event.
+//!
+//! ## Example
+//! ```no_run
+//! use xcomponent::metrics::{self, EventCategory};
+//!
+//! let touch = metrics::metrics().get(EventCategory::Touch);
+//! if touch.dropped > 0 {
+//!     println!("{} of {} touch events were dropped", touch.dropped, touch.received + touch.dropped);
+//! }
+//! ```
+
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+
+/// Kind of event
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EventCategory {
+    /// `OnSurfaceCreated`, `OnSurfaceChanged` and `OnSurfaceDestroyed`
+    Surface,
+    Touch,
+    /// Mouse events, and with the `axis` feature axis events
+    Mouse,
+    Hover,
+    /// Focus and blur events
+    Focus,
+    Key,
+}
+
+impl EventCategory {
+    pub const ALL: [EventCategory; 6] = [
+        Self::Surface,
+        Self::Touch,
+        Self::Mouse,
+        Self::Hover,
+        Self::Focus,
+        Self::Key,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Surface => "surface",
+            Self::Touch => "touch",
+            Self::Mouse => "mouse",
+            Self::Hover => "hover",
+            Self::Focus => "focus",
+            Self::Key => "key",
+        }
+    }
+}
+
+/// Counters of one [`EventCategory`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CategoryMetrics {
+    pub received: u64,
+    pub dropped: u64,
+    /// Number of timed callbacks
+    pub callbacks: u64,
+    /// Total time spent in the timed callbacks
+    pub callback_time: Duration,
+    pub max_callback_time: Duration,
+}
+
+/// Snapshot of the counters of all categories
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Metrics {
+    categories: [CategoryMetrics; EventCategory::ALL.len()],
+}
+
+impl Metrics {
+    pub fn get(&self, category: EventCategory) -> CategoryMetrics {
+        self.categories[category as usize]
+    }
+}
+
+struct Counters {
+    received: AtomicU64,
+    dropped: AtomicU64,
+    callbacks: AtomicU64,
+    /// In nanoseconds
+    callback_time: AtomicU64,
+    max_callback_time: AtomicU64,
+}
+
+#[allow(clippy::declare_interior_mutable_const)]
+const ZERO: Counters = Counters {
+    received: AtomicU64::new(0),
+    dropped: AtomicU64::new(0),
+    callbacks: AtomicU64::new(0),
+    callback_time: AtomicU64::new(0),
+    max_callback_time: AtomicU64::new(0),
+};
+
+static COUNTERS: [Counters; EventCategory::ALL.len()] = [ZERO; EventCategory::ALL.len()];
+
+/// Returns a snapshot of the counters
+pub fn metrics() -> Metrics {
+    let mut metrics = Metrics::default();
+    for (metrics, counters) in metrics.categories.iter_mut().zip(&COUNTERS) {
+        *metrics = CategoryMetrics {
+            received: counters.received.load(Ordering::Relaxed),
+            dropped: counters.dropped.load(Ordering::Relaxed),
+            callbacks: counters.callbacks.load(Ordering::Relaxed),
+            callback_time: Duration::from_nanos(counters.callback_time.load(Ordering::Relaxed)),
+            max_callback_time: Duration::from_nanos(
+                counters.max_callback_time.load(Ordering::Relaxed),
+            ),
+        };
+    }
+    metrics
+}
+
+/// Resets all counters to zero
+pub fn reset() {
+    for counters in &COUNTERS {
+        counters.received.store(0, Ordering::Relaxed);
+        counters.dropped.store(0, Ordering::Relaxed);
+        counters.callbacks.store(0, Ordering::Relaxed);
+        counters.callback_time.store(0, Ordering::Relaxed);
+        counters.max_callback_time.store(0, Ordering::Relaxed);
+    }
+}
+
+pub(crate) fn received(category: EventCategory) {
+    COUNTERS[category as usize]
+        .received
+        .fetch_add(1, Ordering::Relaxed);
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!("xcomponent_events_received", "category" => category.name()).increment(1);
+}
+
+pub(crate) fn dropped(category: EventCategory) {
+    COUNTERS[category as usize]
+        .dropped
+        .fetch_add(1, Ordering::Relaxed);
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!("xcomponent_events_dropped", "category" => category.name()).increment(1);
+}
+
+/// Counts the result of querying an event of `category`
+pub(crate) fn record<T, E>(category: EventCategory, result: Result<T, E>) -> Result<T, E> {
+    match result {
+        Ok(_) => received(category),
+        Err(_) => dropped(category),
+    }
+    result
+}
+
+/// Times `callback` as a callback of `category`
+///
+/// Without the `std` feature there is no clock, so the callback is only called.
+#[cfg(not(feature = "std"))]
+pub(crate) fn time_callback(_category: EventCategory, callback: impl FnOnce()) {
+    callback();
+}
+
+/// Times `callback` as a callback of `category`
+#[cfg(feature = "std")]
+pub(crate) fn time_callback(category: EventCategory, callback: impl FnOnce()) {
+    let start = std::time::Instant::now();
+    callback();
+    let duration = start.elapsed();
+    let counters = &COUNTERS[category as usize];
+    let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+    counters.callbacks.fetch_add(1, Ordering::Relaxed);
+    counters.callback_time.fetch_add(nanos, Ordering::Relaxed);
+    counters
+        .max_callback_time
+        .fetch_max(nanos, Ordering::Relaxed);
+    #[cfg(feature = "metrics")]
+    ::metrics::histogram!("xcomponent_callback_duration_seconds", "category" => category.name())
+        .record(duration.as_secs_f64());
+}
@@ -0,0 +1,1204 @@
+//! Fake XComponent for testing application code on the host
+//!
+//! A [`MockXComponent`] hands out an [`XComponent`] whose accessors return the values
+//! configured on the mock instead of querying ArkUI, and drives the callbacks of an
+//! `OH_NativeXComponent_Callback` with it, like ArkUI would.
+//!
+//! `ohos-sys` links the OpenHarmony libraries unconditionally, so tests on the host still need
+//! libraries with the names of the OpenHarmony libraries to link against, e.g. empty ones. None
+//! of their functions is called for the accessors of a mocked XComponent.
+//!
+//! ## Example
+//! ```no_run
+//! use ohos_sys::xcomponent::{OH_NativeXComponent, OH_NativeXComponent_Callback};
+//! use std::ffi::c_void;
+//! use xcomponent::mock::{MockEvent, MockXComponent};
+//! use xcomponent::XComponent;
+//!
+//! extern "C" fn on_surface_changed(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+//!     let xcomponent = XComponent::new(xcomponent, window).unwrap();
+//!     assert_eq!(xcomponent.try_size().unwrap().width, 1080);
+//! }
+//!
+//! static CALLBACKS: OH_NativeXComponent_Callback = OH_NativeXComponent_Callback {
+//!     OnSurfaceCreated: None,
+//!     OnSurfaceChanged: Some(on_surface_changed),
+//!     OnSurfaceDestroyed: None,
+//!     DispatchTouchEvent: None,
+//! };
+//!
+//! let mock = MockXComponent::new("xcomponent");
+//! mock.replay(
+//!     &CALLBACKS,
+//!     [
+//!         MockEvent::SurfaceCreated,
+//!         MockEvent::SurfaceChanged {
+//!             width: 1080,
+//!             height: 2340,
+//!         },
+//!         MockEvent::SurfaceDestroyed,
+//!     ],
+//! );
+//! ```
+
+#[cfg(feature = "api-11")]
+#[cfg_attr(docsrs, doc(cfg(feature = "api-11")))]
+pub mod frames;
+pub mod soak;
+
+use crate::key::KeyEvent;
+use crate::mouse::MouseEvent;
+use crate::touch::{ToolType, MAX_TOUCH_POINTS};
+use crate::{EventCallbacks, Offset, Size, XComponent};
+use core::ffi::c_void;
+use ohos_sys::xcomponent::{
+    OH_NativeXComponent, OH_NativeXComponent_Callback, OH_NativeXComponent_HistoricalPoint,
+    OH_NativeXComponent_MouseEvent_Callback, OH_NativeXComponent_TouchEvent,
+    OH_NativeXComponent_TouchEventType, OH_NativeXComponent_TouchPoint,
+};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[derive(Clone)]
+pub(crate) struct MockState {
+    pub(crate) id: String,
+    pub(crate) size: Size,
+    pub(crate) offset: Offset,
+    pub(crate) touch_event: Option<OH_NativeXComponent_TouchEvent>,
+    pub(crate) historical_points: Vec<OH_NativeXComponent_HistoricalPoint>,
+    /// The tool type and tilt of the touch points, by index
+    pub(crate) touch_point_tools: HashMap<u32, (Option<ToolType>, f32, f32)>,
+    pub(crate) key_event: Option<KeyEvent>,
+    pub(crate) mouse_event: Option<MouseEvent>,
+    pub(crate) callbacks: Option<&'static OH_NativeXComponent_Callback>,
+    pub(crate) mouse_callbacks: Option<&'static OH_NativeXComponent_MouseEvent_Callback>,
+    pub(crate) event_callbacks: Option<EventCallbacks>,
+    #[cfg(feature = "api-12")]
+    pub(crate) visibility_callbacks: Option<crate::SurfaceVisibilityCallbacks>,
+    #[cfg(feature = "api-11")]
+    pub(crate) frame_rate_range: Option<(i32, i32, i32)>,
+    #[cfg(feature = "api-12")]
+    pub(crate) surface_id: u64,
+    #[cfg(feature = "api-12")]
+    pub(crate) needs_soft_keyboard: bool,
+}
+
+/// Mocks by the address of their fake `OH_NativeXComponent`
+static MOCKS: Mutex<Option<HashMap<usize, MockState>>> = Mutex::new(None);
+
+/// Returns `f` applied to the state of the mock `xcomponent`, if it is mocked
+pub(crate) fn with_mock<R>(
+    xcomponent: *mut OH_NativeXComponent,
+    f: impl FnOnce(&MockState) -> R,
+) -> Option<R> {
+    let mocks = MOCKS.lock().unwrap_or_else(|e| e.into_inner());
+    mocks.as_ref()?.get(&(xcomponent as usize)).map(f)
+}
+
+/// Applies `f` to the state of the mock `xcomponent`, returns whether it is mocked
+pub(crate) fn update_mock(
+    xcomponent: *mut OH_NativeXComponent,
+    f: impl FnOnce(&mut MockState),
+) -> bool {
+    let mut mocks = MOCKS.lock().unwrap_or_else(|e| e.into_inner());
+    match mocks
+        .as_mut()
+        .and_then(|mocks| mocks.get_mut(&(xcomponent as usize)))
+    {
+        Some(state) => {
+            f(state);
+            true
+        }
+        None => false,
+    }
+}
+
+/// A fake XComponent and native window
+///
+/// The XComponent is 0x0 pixels at offset (0, 0) until configured otherwise.
+pub struct MockXComponent {
+    // The boxes give the fake pointers unique addresses.
+    xcomponent: Box<u8>,
+    window: Box<u8>,
+}
+
+impl MockXComponent {
+    /// Creates a mock with the XComponent id `id`
+    pub fn new(id: &str) -> Self {
+        let mock = Self {
+            xcomponent: Box::new(0),
+            window: Box::new(0),
+        };
+        let state = MockState {
+            id: id.to_string(),
+            size: Size {
+                width: 0,
+                height: 0,
+                _opaque: [],
+            },
+            offset: Offset {
+                x: 0.0,
+                y: 0.0,
+                _opaque: [],
+            },
+            touch_event: None,
+            historical_points: Vec::new(),
+            touch_point_tools: HashMap::new(),
+            key_event: None,
+            mouse_event: None,
+            callbacks: None,
+            mouse_callbacks: None,
+            event_callbacks: None,
+            #[cfg(feature = "api-12")]
+            visibility_callbacks: None,
+            #[cfg(feature = "api-11")]
+            frame_rate_range: None,
+            #[cfg(feature = "api-12")]
+            surface_id: 0,
+            #[cfg(feature = "api-12")]
+            needs_soft_keyboard: false,
+        };
+        let mut mocks = MOCKS.lock().unwrap_or_else(|e| e.into_inner());
+        mocks
+            .get_or_insert_with(HashMap::new)
+            .insert(mock.raw_xcomponent() as usize, state);
+        drop(mocks);
+        mock
+    }
+
+    /// The fake `OH_NativeXComponent` passed to callbacks
+    pub fn raw_xcomponent(&self) -> *mut OH_NativeXComponent {
+        (&*self.xcomponent as *const u8).cast_mut().cast()
+    }
+
+    /// The fake native window passed to callbacks
+    ///
+    /// The window must not be passed to NDK functions.
+    pub fn raw_window(&self) -> *mut c_void {
+        (&*self.window as *const u8).cast_mut().cast()
+    }
+
+    /// An [`XComponent`] backed by this mock
+    pub fn xcomponent(&self) -> XComponent<'_> {
+        XComponent::new(self.raw_xcomponent(), self.raw_window())
+            .expect("mock pointers are not null")
+    }
+
+    fn update(&self, f: impl FnOnce(&mut MockState)) {
+        update_mock(self.raw_xcomponent(), f);
+    }
+
+    /// The callbacks registered via `register_xcomponent_callbacks` with a `MockExports`
+    pub fn registered_callbacks(&self) -> Option<&'static OH_NativeXComponent_Callback> {
+        with_mock(self.raw_xcomponent(), |m| m.callbacks).flatten()
+    }
+
+    /// The callbacks registered via `register_mouse_event_callbacks` with a `MockExports`
+    pub fn registered_mouse_callbacks(
+        &self,
+    ) -> Option<&'static OH_NativeXComponent_MouseEvent_Callback> {
+        with_mock(self.raw_xcomponent(), |m| m.mouse_callbacks).flatten()
+    }
+
+    /// The callbacks registered via `register_event_callbacks` with a `MockExports`
+    pub fn registered_event_callbacks(&self) -> Option<EventCallbacks> {
+        with_mock(self.raw_xcomponent(), |m| m.event_callbacks).flatten()
+    }
+
+    /// The callbacks registered via `register_surface_visibility_callbacks` with a `MockExports`
+    #[cfg(feature = "api-12")]
+    pub fn registered_visibility_callbacks(&self) -> Option<crate::SurfaceVisibilityCallbacks> {
+        with_mock(self.raw_xcomponent(), |m| m.visibility_callbacks).flatten()
+    }
+
+    pub fn set_size(&self, width: u64, height: u64) {
+        self.update(|state| {
+            state.size.width = width;
+            state.size.height = height;
+        });
+    }
+
+    pub fn set_offset(&self, x: f64, y: f64) {
+        self.update(|state| {
+            state.offset.x = x;
+            state.offset.y = y;
+        });
+    }
+
+    /// Sets the id returned by [`XComponent::surface_id`]
+    #[cfg(feature = "api-12")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "api-12")))]
+    pub fn set_surface_id(&self, surface_id: u64) {
+        self.update(|state| state.surface_id = surface_id);
+    }
+
+    /// Sets the event returned by [`XComponent::get_touch_event`]
+    ///
+    /// Until a touch event is set, `get_touch_event` fails like outside of `DispatchTouchEvent`.
+    pub fn set_touch_event(&self, touch_event: OH_NativeXComponent_TouchEvent) {
+        self.update(|state| state.touch_event = Some(touch_event));
+    }
+
+    /// Sets the samples returned by [`XComponent::touch_event_history`]
+    ///
+    /// Like the touch event, the history is only returned once a touch event is set.
+    pub fn set_historical_points(
+        &self,
+        points: impl IntoIterator<Item = OH_NativeXComponent_HistoricalPoint>,
+    ) {
+        let points = points.into_iter().collect();
+        self.update(|state| state.historical_points = points);
+    }
+
+    /// Sets the tool type and tilt of the touch point at `index`
+    ///
+    /// Until they are set, touch points have no tool type and no tilt.
+    pub fn set_touch_point_tool(
+        &self,
+        index: u32,
+        tool_type: Option<ToolType>,
+        tilt_x: f32,
+        tilt_y: f32,
+    ) {
+        self.update(|state| {
+            state
+                .touch_point_tools
+                .insert(index, (tool_type, tilt_x, tilt_y));
+        });
+    }
+
+    /// Sets the event returned by [`XComponent::get_key_event`]
+    ///
+    /// Until a key event is set, `get_key_event` fails like outside of the key event callback.
+    pub fn set_key_event(&self, key_event: KeyEvent) {
+        self.update(|state| state.key_event = Some(key_event));
+    }
+
+    /// Sets the event returned by [`XComponent::get_mouse_event`]
+    ///
+    /// Until a mouse event is set, `get_mouse_event` fails like outside of `DispatchMouseEvent`.
+    pub fn set_mouse_event(&self, mouse_event: MouseEvent) {
+        self.update(|state| state.mouse_event = Some(mouse_event));
+    }
+
+    /// Calls the `OnSurfaceCreated` callback of `callbacks`
+    pub fn surface_created(&self, callbacks: &OH_NativeXComponent_Callback) {
+        self.call(callbacks.OnSurfaceCreated);
+    }
+
+    /// Calls the `OnSurfaceChanged` callback of `callbacks`
+    pub fn surface_changed(&self, callbacks: &OH_NativeXComponent_Callback) {
+        self.call(callbacks.OnSurfaceChanged);
+    }
+
+    /// Calls the `OnSurfaceDestroyed` callback of `callbacks`
+    pub fn surface_destroyed(&self, callbacks: &OH_NativeXComponent_Callback) {
+        self.call(callbacks.OnSurfaceDestroyed);
+    }
+
+    /// Sets the current touch event and calls the `DispatchTouchEvent` callback of `callbacks`
+    pub fn dispatch_touch_event(
+        &self,
+        callbacks: &OH_NativeXComponent_Callback,
+        touch_event: OH_NativeXComponent_TouchEvent,
+    ) {
+        self.set_touch_event(touch_event);
+        self.call(callbacks.DispatchTouchEvent);
+    }
+
+    /// Calls the `on_focus` callback of `callbacks`
+    pub fn focus(&self, callbacks: &EventCallbacks) {
+        self.call(callbacks.on_focus);
+    }
+
+    /// Calls the `on_blur` callback of `callbacks`
+    pub fn blur(&self, callbacks: &EventCallbacks) {
+        self.call(callbacks.on_blur);
+    }
+
+    /// Calls the `on_surface_show` callback of `callbacks`
+    #[cfg(feature = "api-12")]
+    pub fn show_surface(&self, callbacks: &crate::SurfaceVisibilityCallbacks) {
+        self.call(callbacks.on_surface_show);
+    }
+
+    /// Calls the `on_surface_hide` callback of `callbacks`
+    #[cfg(feature = "api-12")]
+    pub fn hide_surface(&self, callbacks: &crate::SurfaceVisibilityCallbacks) {
+        self.call(callbacks.on_surface_hide);
+    }
+
+    /// Sets the current key event and calls the `on_key` callback of `callbacks`
+    pub fn dispatch_key_event(&self, callbacks: &EventCallbacks, key_event: KeyEvent) {
+        self.set_key_event(key_event);
+        self.call(callbacks.on_key);
+    }
+
+    /// Calls the callback set via [`XComponent::set_frame_callback`], if any
+    ///
+    /// `timestamp` and `target_timestamp` are in nanoseconds. [`frames::FrameTicker`] calls this
+    /// with the timestamps of a fake clock.
+    #[cfg(feature = "api-11")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "api-11")))]
+    pub fn frame(&self, timestamp: u64, target_timestamp: u64) {
+        // SAFETY: Frame callbacks only use the component as key.
+        unsafe { crate::frame::on_frame(self.raw_xcomponent(), timestamp, target_timestamp) };
+    }
+
+    /// The `(min, max, expected)` range of the last [`XComponent::set_expected_frame_rate_range`]
+    #[cfg(feature = "api-11")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "api-11")))]
+    pub fn expected_frame_rate_range(&self) -> Option<(i32, i32, i32)> {
+        with_mock(self.raw_xcomponent(), |m| m.frame_rate_range).flatten()
+    }
+
+    /// The value of the last [`XComponent::set_needs_soft_keyboard`], `false` by default
+    #[cfg(feature = "api-12")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "api-12")))]
+    pub fn needs_soft_keyboard(&self) -> bool {
+        with_mock(self.raw_xcomponent(), |m| m.needs_soft_keyboard).unwrap_or(false)
+    }
+
+    /// Dispatches each of `touch_events` in order, e.g. the events of a [`TouchEventBuilder`]
+    pub fn dispatch_touch_events(
+        &self,
+        callbacks: &OH_NativeXComponent_Callback,
+        touch_events: impl IntoIterator<Item = OH_NativeXComponent_TouchEvent>,
+    ) {
+        for touch_event in touch_events {
+            self.dispatch_touch_event(callbacks, touch_event);
+        }
+    }
+
+    /// Replays a session of lifecycle and touch events through `callbacks`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `events` are in an order ArkUI wouldn't dispatch them in, i.e. if any event
+    /// other than [`MockEvent::SurfaceCreated`] is dispatched while there is no surface, or if
+    /// the surface is created twice.
+    pub fn replay(
+        &self,
+        callbacks: &OH_NativeXComponent_Callback,
+        events: impl IntoIterator<Item = MockEvent>,
+    ) {
+        let mut has_surface = false;
+        for (index, event) in events.into_iter().enumerate() {
+            let created = matches!(event, MockEvent::SurfaceCreated);
+            assert!(
+                has_surface != created,
+                "event {index} ({event:?}) is not possible {} a surface",
+                if has_surface { "with" } else { "without" }
+            );
+            has_surface = !matches!(event, MockEvent::SurfaceDestroyed);
+            self.dispatch(callbacks, event);
+        }
+    }
+
+    fn dispatch(&self, callbacks: &OH_NativeXComponent_Callback, event: MockEvent) {
+        match event {
+            MockEvent::SurfaceCreated => self.surface_created(callbacks),
+            MockEvent::SurfaceChanged { width, height } => {
+                self.set_size(width, height);
+                self.surface_changed(callbacks);
+            }
+            MockEvent::Touch(touch_event) => self.dispatch_touch_event(callbacks, touch_event),
+            MockEvent::SurfaceDestroyed => self.surface_destroyed(callbacks),
+        }
+    }
+
+    fn call(&self, callback: Option<unsafe extern "C" fn(*mut OH_NativeXComponent, *mut c_void)>) {
+        if let Some(callback) = callback {
+            // SAFETY: The callback gets the same arguments it would get from ArkUI, except that
+            // they are backed by this mock.
+            unsafe { callback(self.raw_xcomponent(), self.raw_window()) };
+        }
+    }
+}
+
+impl Drop for MockXComponent {
+    fn drop(&mut self) {
+        let mut mocks = MOCKS.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(mocks) = mocks.as_mut() {
+            mocks.remove(&(self.raw_xcomponent() as usize));
+        }
+        // The address may be reused by a later mock.
+        #[cfg(feature = "api-11")]
+        crate::frame::remove(self.raw_xcomponent() as usize);
+        #[cfg(feature = "strict")]
+        crate::strict::untrack(self.raw_xcomponent());
+    }
+}
+
+/// A fake `exports` object of a native module
+///
+/// Passing this to the `register_*` functions of the `register` feature registers the callbacks
+/// with a [`MockXComponent`] instead of ArkUI, where they can be inspected via e.g.
+/// [`MockXComponent::registered_callbacks`]. The napi environment is `()`.
+///
+/// ```no_run
+/// # use ohos_sys::xcomponent::OH_NativeXComponent_Callback;
+/// use xcomponent::mock::{MockExports, MockXComponent};
+/// use xcomponent::{register_xcomponent_callbacks, RegisterCallbackError};
+///
+/// static CALLBACKS: OH_NativeXComponent_Callback = OH_NativeXComponent_Callback {
+///     OnSurfaceCreated: None,
+///     OnSurfaceChanged: None,
+///     OnSurfaceDestroyed: None,
+///     DispatchTouchEvent: None,
+/// };
+///
+/// let mock = MockXComponent::new("xcomponent");
+/// register_xcomponent_callbacks(&MockExports::new(&mock), &(), &CALLBACKS).unwrap();
+/// assert!(mock.registered_callbacks().is_some());
+///
+/// let res = register_xcomponent_callbacks(&MockExports::without_xcomponent(), &(), &CALLBACKS);
+/// assert!(matches!(res, Err(RegisterCallbackError::XcomponentPropertyMissing(_))));
+/// ```
+#[cfg(feature = "register")]
+#[cfg_attr(docsrs, doc(cfg(feature = "register")))]
+#[derive(Clone, Debug)]
+pub struct MockExports {
+    xcomponent: Result<usize, MockExportsError>,
+}
+
+#[cfg(feature = "register")]
+#[derive(Clone, Copy, Debug)]
+enum MockExportsError {
+    PropertyMissing,
+    UnwrapFailed(i32),
+}
+
+#[cfg(feature = "register")]
+impl MockExports {
+    /// The exports of a module loaded by the XComponent `mock`
+    pub fn new(mock: &MockXComponent) -> Self {
+        Self {
+            xcomponent: Ok(mock.raw_xcomponent() as usize),
+        }
+    }
+
+    /// The exports of a module which was not loaded by an XComponent
+    ///
+    /// Registration fails with `RegisterCallbackError::XcomponentPropertyMissing`.
+    pub fn without_xcomponent() -> Self {
+        Self {
+            xcomponent: Err(MockExportsError::PropertyMissing),
+        }
+    }
+
+    /// Exports whose XComponent can't be unwrapped
+    ///
+    /// Registration fails with `RegisterCallbackError::UnwrapXComponentFailed(status)`.
+    pub fn with_unwrap_error(status: i32) -> Self {
+        Self {
+            xcomponent: Err(MockExportsError::UnwrapFailed(status)),
+        }
+    }
+
+    pub(crate) fn xcomponent(
+        &self,
+    ) -> Result<*mut OH_NativeXComponent, crate::RegisterCallbackError> {
+        use crate::RegisterCallbackError;
+
+        match self.xcomponent {
+            Ok(xcomponent) => Ok(xcomponent as *mut OH_NativeXComponent),
+            Err(MockExportsError::PropertyMissing) => {
+                Err(RegisterCallbackError::XcomponentPropertyMissing(
+                    "__NATIVE_XCOMPONENT_OBJ__ is undefined".to_string(),
+                ))
+            }
+            Err(MockExportsError::UnwrapFailed(status)) => {
+                Err(RegisterCallbackError::UnwrapXComponentFailed(status))
+            }
+        }
+    }
+}
+
+/// An event of an XComponent session replayed by [`MockXComponent::replay`]
+#[derive(Clone, Copy, Debug)]
+// Scripts are short, so the size of the touch events does not matter.
+#[allow(clippy::large_enum_variant)]
+pub enum MockEvent {
+    SurfaceCreated,
+    /// Resizes the XComponent and calls `OnSurfaceChanged`
+    SurfaceChanged {
+        width: u64,
+        height: u64,
+    },
+    Touch(OH_NativeXComponent_TouchEvent),
+    SurfaceDestroyed,
+}
+
+/// Reads a trace written by [`record`](crate::record::record)
+///
+/// Returns the events with the time they were recorded at, relative to the start of the
+/// recording. On failure returns the number of the first malformed line, starting at 1.
+///
+/// ```no_run
+/// # use ohos_sys::xcomponent::OH_NativeXComponent_Callback;
+/// # use xcomponent::mock::{load_trace, MockXComponent};
+/// # fn replay(callbacks: &OH_NativeXComponent_Callback) {
+/// let trace = std::fs::read_to_string("session.trace").unwrap();
+/// let events = load_trace(&trace).unwrap();
+/// MockXComponent::new("xcomponent").replay(callbacks, events.into_iter().map(|(_, e)| e));
+/// # }
+/// ```
+#[cfg(feature = "record")]
+#[cfg_attr(docsrs, doc(cfg(feature = "record")))]
+pub fn load_trace(trace: &str) -> Result<Vec<(core::time::Duration, MockEvent)>, usize> {
+    trace
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| crate::record::parse_line(line).ok_or(index + 1))
+        .collect()
+}
+
+/// Builds the sequence of touch events of a gesture
+///
+/// Every step appends one event for the pointer which changed. Each event also lists all
+/// pointers currently touching the screen in its `touchPoints`, like ArkUI at most
+/// [`MAX_TOUCH_POINTS`]. Positions are in component
+/// coordinates, the screen coordinates are offset by [`TouchEventBuilder::screen_offset`].
+///
+/// ```
+/// use xcomponent::mock::TouchEventBuilder;
+///
+/// // A two finger pinch
+/// let events = TouchEventBuilder::down(0, (100.0, 100.0))
+///     .down_pointer(1, (200.0, 200.0))
+///     .move_pointer(1, (300.0, 300.0))
+///     .up_pointer(1)
+///     .up()
+///     .build();
+/// assert_eq!(events.len(), 5);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TouchEventBuilder {
+    pressed: Vec<OH_NativeXComponent_TouchPoint>,
+    last: i32,
+    screen_offset: (f32, f32),
+    timestamp: i64,
+    interval: i64,
+    events: Vec<OH_NativeXComponent_TouchEvent>,
+}
+
+impl TouchEventBuilder {
+    /// Starts a gesture with pointer `id` touching down at `position`
+    pub fn down(id: i32, position: (f32, f32)) -> Self {
+        Self {
+            pressed: Vec::new(),
+            last: id,
+            screen_offset: (0.0, 0.0),
+            timestamp: 0,
+            // One frame at 60 Hz
+            interval: 16_666_667,
+            events: Vec::new(),
+        }
+        .down_pointer(id, position)
+    }
+
+    /// Offset added to the positions to get the screen coordinates of the following events
+    pub fn screen_offset(mut self, x: f32, y: f32) -> Self {
+        self.screen_offset = (x, y);
+        self
+    }
+
+    /// Time between the following events in nanoseconds, one frame at 60 Hz by default
+    pub fn interval(mut self, nanoseconds: i64) -> Self {
+        self.interval = nanoseconds;
+        self
+    }
+
+    /// Another pointer `id` touching down at `position`
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`MAX_TOUCH_POINTS`] other pointers already touch the screen, since a touch
+    /// event can't list more.
+    pub fn down_pointer(mut self, id: i32, position: (f32, f32)) -> Self {
+        self.pressed.retain(|point| point.id != id);
+        assert!(
+            self.pressed.len() < MAX_TOUCH_POINTS,
+            "at most {MAX_TOUCH_POINTS} pointers can touch the screen"
+        );
+        self.pressed.push(self.point(id, position));
+        self.push(
+            id,
+            OH_NativeXComponent_TouchEventType::OH_NATIVEXCOMPONENT_DOWN,
+        );
+        self.last = id;
+        self
+    }
+
+    /// Moves the pointer which touched down last to `position`
+    pub fn move_to(self, position: (f32, f32)) -> Self {
+        let id = self.last;
+        self.move_pointer(id, position)
+    }
+
+    /// Moves the pointer `id` to `position`
+    ///
+    /// Does nothing if the pointer doesn't touch the screen.
+    pub fn move_pointer(mut self, id: i32, position: (f32, f32)) -> Self {
+        let moved = self.point(id, position);
+        let Some(point) = self.pressed.iter_mut().find(|point| point.id == id) else {
+            return self;
+        };
+        *point = moved;
+        self.push(
+            id,
+            OH_NativeXComponent_TouchEventType::OH_NATIVEXCOMPONENT_MOVE,
+        );
+        self
+    }
+
+    /// Lifts the pointer which touched down last
+    pub fn up(self) -> Self {
+        let id = self.last;
+        self.up_pointer(id)
+    }
+
+    /// Lifts the pointer `id`
+    ///
+    /// Does nothing if the pointer doesn't touch the screen.
+    pub fn up_pointer(mut self, id: i32) -> Self {
+        if !self.pressed.iter().any(|point| point.id == id) {
+            return self;
+        }
+        self.push(
+            id,
+            OH_NativeXComponent_TouchEventType::OH_NATIVEXCOMPONENT_UP,
+        );
+        self.pressed.retain(|point| point.id != id);
+        if let Some(point) = self.pressed.last() {
+            self.last = point.id;
+        }
+        self
+    }
+
+    /// Cancels the gesture, e.g. because the system took over the touch sequence
+    pub fn cancel(mut self) -> Self {
+        if self.pressed.is_empty() {
+            return self;
+        }
+        let id = self.last;
+        self.push(
+            id,
+            OH_NativeXComponent_TouchEventType::OH_NATIVEXCOMPONENT_CANCEL,
+        );
+        self.pressed.clear();
+        self
+    }
+
+    /// The touch events of the gesture, in order
+    pub fn build(self) -> Vec<OH_NativeXComponent_TouchEvent> {
+        self.events
+    }
+
+    fn point(&self, id: i32, (x, y): (f32, f32)) -> OH_NativeXComponent_TouchPoint {
+        OH_NativeXComponent_TouchPoint {
+            id,
+            screenX: x + self.screen_offset.0,
+            screenY: y + self.screen_offset.1,
+            x,
+            y,
+            type_: OH_NativeXComponent_TouchEventType::OH_NATIVEXCOMPONENT_MOVE,
+            size: 0.0,
+            force: 0.0,
+            timeStamp: self.timestamp,
+            isPressed: true,
+        }
+    }
+
+    /// Appends an event of `type_` for the pressed pointer `id`
+    fn push(&mut self, id: i32, type_: OH_NativeXComponent_TouchEventType) {
+        let mut touch_points = [self.point(0, (0.0, 0.0)); MAX_TOUCH_POINTS];
+        let mut changed = None;
+        for (slot, point) in touch_points.iter_mut().zip(&self.pressed) {
+            *slot = *point;
+            slot.timeStamp = self.timestamp;
+            if point.id == id {
+                slot.type_ = type_;
+                slot.isPressed = type_
+                    == OH_NativeXComponent_TouchEventType::OH_NATIVEXCOMPONENT_DOWN
+                    || type_ == OH_NativeXComponent_TouchEventType::OH_NATIVEXCOMPONENT_MOVE;
+                changed = Some(*slot);
+            }
+        }
+        let Some(changed) = changed else {
+            return;
+        };
+        self.events.push(OH_NativeXComponent_TouchEvent {
+            id,
+            screenX: changed.screenX,
+            screenY: changed.screenY,
+            x: changed.x,
+            y: changed.y,
+            type_,
+            size: changed.size,
+            force: changed.force,
+            deviceId: 0,
+            timeStamp: self.timestamp,
+            touchPoints: touch_points,
+            numPoints: self.pressed.len() as u32,
+        });
+        self.timestamp += self.interval;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::{KeyAction, SourceType};
+    use crate::mouse::{MouseAction, MouseButton};
+    #[cfg(feature = "register")]
+    use crate::{register_xcomponent_callbacks, RegisterCallbackError};
+    use ohos_sys::xcomponent::OH_NativeXComponent_KeyCode;
+
+    const DOWN: OH_NativeXComponent_TouchEventType =
+        OH_NativeXComponent_TouchEventType::OH_NATIVEXCOMPONENT_DOWN;
+    const MOVE: OH_NativeXComponent_TouchEventType =
+        OH_NativeXComponent_TouchEventType::OH_NATIVEXCOMPONENT_MOVE;
+    const UP: OH_NativeXComponent_TouchEventType =
+        OH_NativeXComponent_TouchEventType::OH_NATIVEXCOMPONENT_UP;
+    const CANCEL: OH_NativeXComponent_TouchEventType =
+        OH_NativeXComponent_TouchEventType::OH_NATIVEXCOMPONENT_CANCEL;
+
+    #[test]
+    fn touch_event_builder_lists_pressed_pointers() {
+        let events = TouchEventBuilder::down(0, (10.0, 20.0))
+            .screen_offset(100.0, 200.0)
+            .down_pointer(1, (30.0, 40.0))
+            .move_pointer(0, (15.0, 25.0))
+            .up_pointer(0)
+            .up()
+            .build();
+        let types: Vec<_> = events.iter().map(|event| (event.id, event.type_)).collect();
+        assert_eq!(types, [(0, DOWN), (1, DOWN), (0, MOVE), (0, UP), (1, UP),]);
+        let num_points: Vec<_> = events.iter().map(|event| event.numPoints).collect();
+        assert_eq!(num_points, [1, 2, 2, 2, 1]);
+
+        let moved = &events[2];
+        assert_eq!((moved.x, moved.y), (15.0, 25.0));
+        assert_eq!((moved.screenX, moved.screenY), (115.0, 225.0));
+        // The pointer which didn't change keeps its position and isn't the changed one.
+        assert_eq!(moved.touchPoints[1].id, 1);
+        assert_eq!(moved.touchPoints[1].type_, MOVE);
+        assert!(!events[3].touchPoints[0].isPressed);
+        assert!(events[3].touchPoints[1].isPressed);
+    }
+
+    #[test]
+    fn touch_event_builder_timestamps() {
+        let events = TouchEventBuilder::down(0, (0.0, 0.0))
+            .interval(1_000)
+            .move_to((1.0, 1.0))
+            .up()
+            .build();
+        let timestamps: Vec<_> = events.iter().map(|event| event.timeStamp).collect();
+        assert_eq!(timestamps, [0, 16_666_667, 16_667_667]);
+        assert!(events
+            .iter()
+            .all(|event| event.touchPoints[0].timeStamp == event.timeStamp));
+    }
+
+    #[test]
+    fn touch_event_builder_ignores_released_pointers() {
+        let events = TouchEventBuilder::down(0, (0.0, 0.0))
+            .move_pointer(1, (1.0, 1.0))
+            .up_pointer(1)
+            .cancel()
+            .up()
+            .cancel()
+            .build();
+        let types: Vec<_> = events.iter().map(|event| event.type_).collect();
+        assert_eq!(types, [DOWN, CANCEL]);
+    }
+
+    #[test]
+    fn touch_event_builder_max_touch_points() {
+        let builder = (1..MAX_TOUCH_POINTS as i32)
+            .fold(TouchEventBuilder::down(0, (0.0, 0.0)), |builder, id| {
+                builder.down_pointer(id, (id as f32, 0.0))
+            });
+        // Touching down again with a pressed pointer doesn't add one.
+        let events = builder.down_pointer(0, (0.0, 0.0)).build();
+        assert_eq!(events.len(), MAX_TOUCH_POINTS + 1);
+        assert_eq!(
+            events[MAX_TOUCH_POINTS - 1].numPoints,
+            MAX_TOUCH_POINTS as u32
+        );
+        assert_eq!(events[MAX_TOUCH_POINTS].numPoints, MAX_TOUCH_POINTS as u32);
+    }
+
+    #[test]
+    #[should_panic(expected = "pointers can touch the screen")]
+    fn touch_event_builder_too_many_pointers() {
+        (1..=MAX_TOUCH_POINTS as i32)
+            .fold(TouchEventBuilder::down(0, (0.0, 0.0)), |builder, id| {
+                builder.down_pointer(id, (0.0, 0.0))
+            });
+    }
+
+    thread_local! {
+        static CREATED: std::cell::Cell<(usize, usize)> = const { std::cell::Cell::new((0, 0)) };
+    }
+
+    unsafe extern "C" fn on_surface_created(
+        xcomponent: *mut OH_NativeXComponent,
+        window: *mut c_void,
+    ) {
+        CREATED.set((xcomponent as usize, window as usize));
+    }
+
+    static CALLBACKS: OH_NativeXComponent_Callback = OH_NativeXComponent_Callback {
+        OnSurfaceCreated: Some(on_surface_created),
+        OnSurfaceChanged: None,
+        OnSurfaceDestroyed: None,
+        DispatchTouchEvent: None,
+    };
+
+    #[test]
+    fn accessors_return_the_configured_values() {
+        let mock = MockXComponent::new("accessors");
+        let xc = mock.xcomponent();
+        assert_eq!(xc.id().unwrap(), "accessors");
+        let size = xc.try_size().unwrap();
+        assert_eq!((size.width, size.height), (0, 0));
+
+        mock.set_size(1080, 2340);
+        mock.set_offset(10.0, 20.0);
+        let size = xc.try_size().unwrap();
+        assert_eq!((size.width, size.height), (1080, 2340));
+        let offset = xc.offset().unwrap();
+        assert_eq!((offset.x, offset.y), (10.0, 20.0));
+        assert_eq!(
+            xc.native_window().unwrap().as_ptr().cast(),
+            mock.raw_window()
+        );
+    }
+
+    #[test]
+    fn touch_event_after_set() {
+        let mock = MockXComponent::new("touch-event");
+        let xc = mock.xcomponent();
+        assert_eq!(xc.get_touch_event().err(), Some(-1));
+        assert!(xc.touch_event().is_err());
+        assert!(xc.touch_event_history().is_err());
+
+        let events = TouchEventBuilder::down(3, (1.0, 2.0))
+            .down_pointer(4, (5.0, 6.0))
+            .build();
+        mock.set_touch_event(events[1]);
+        mock.set_touch_point_tool(1, Some(ToolType::Pen), 30.0, -15.0);
+        mock.set_historical_points([]);
+        let touch_event = xc.touch_event().unwrap();
+        assert_eq!(touch_event.id, 4);
+        let points: Vec<_> = touch_event
+            .points()
+            .iter()
+            .map(|point| (point.id, point.tool_type, point.tilt_x, point.tilt_y))
+            .collect();
+        assert_eq!(
+            points,
+            [(3, None, 0.0, 0.0), (4, Some(ToolType::Pen), 30.0, -15.0)]
+        );
+        assert!(xc.touch_event_history().unwrap().is_empty());
+    }
+
+    #[test]
+    fn callbacks_get_the_mock() {
+        let mock = MockXComponent::new("callbacks");
+        mock.surface_created(&CALLBACKS);
+        assert_eq!(
+            CREATED.get(),
+            (mock.raw_xcomponent() as usize, mock.raw_window() as usize)
+        );
+    }
+
+    #[test]
+    fn dropped_mock_is_removed() {
+        let mock = MockXComponent::new("dropped");
+        let xcomponent = mock.raw_xcomponent();
+        drop(mock);
+        // Another test may have created a mock at the same address since.
+        assert_ne!(
+            with_mock(xcomponent, |m| m.id.clone()).as_deref(),
+            Some("dropped")
+        );
+    }
+
+    thread_local! {
+        static KEY_EVENTS: std::cell::RefCell<Vec<&'static str>> =
+            const { std::cell::RefCell::new(Vec::new()) };
+    }
+
+    unsafe extern "C" fn on_focus(_: *mut OH_NativeXComponent, _: *mut c_void) {
+        KEY_EVENTS.with_borrow_mut(|events| events.push("focus"));
+    }
+
+    unsafe extern "C" fn on_blur(_: *mut OH_NativeXComponent, _: *mut c_void) {
+        KEY_EVENTS.with_borrow_mut(|events| events.push("blur"));
+    }
+
+    unsafe extern "C" fn on_key(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+        let xc = XComponent::new(xcomponent, window).unwrap();
+        let event = match xc.get_key_event().unwrap().action {
+            Some(KeyAction::Down) => "down",
+            Some(KeyAction::Up) => "up",
+            None => "unknown",
+        };
+        KEY_EVENTS.with_borrow_mut(|events| events.push(event));
+    }
+
+    fn key_event(action: KeyAction) -> KeyEvent {
+        KeyEvent {
+            action: Some(action),
+            code: OH_NativeXComponent_KeyCode::KEY_A,
+            source_type: Some(SourceType::Keyboard),
+            device_id: 1,
+            timestamp: 1_000,
+        }
+    }
+
+    #[test]
+    fn key_events() {
+        let mock = MockXComponent::new("key-events");
+        let xc = mock.xcomponent();
+        assert!(xc.get_key_event().is_err());
+        mock.set_key_event(key_event(KeyAction::Down));
+        assert_eq!(xc.get_key_event(), Ok(key_event(KeyAction::Down)));
+
+        let callbacks = EventCallbacks {
+            on_focus: Some(on_focus),
+            on_blur: Some(on_blur),
+            on_key: Some(on_key),
+        };
+        mock.focus(&callbacks);
+        mock.dispatch_key_event(&callbacks, key_event(KeyAction::Down));
+        mock.dispatch_key_event(&callbacks, key_event(KeyAction::Up));
+        mock.blur(&callbacks);
+        assert_eq!(KEY_EVENTS.take(), ["focus", "down", "up", "blur"]);
+    }
+
+    #[test]
+    fn mouse_event_after_set() {
+        let mock = MockXComponent::new("mouse-event");
+        let xc = mock.xcomponent();
+        assert!(xc.get_mouse_event().is_err());
+        let event = MouseEvent {
+            action: Some(MouseAction::Press),
+            button: Some(MouseButton::Right),
+            x: 1.0,
+            y: 2.0,
+            screen_x: 3.0,
+            screen_y: 4.0,
+            timestamp: 5,
+        };
+        mock.set_mouse_event(event);
+        assert_eq!(xc.get_mouse_event(), Ok(event));
+    }
+
+    thread_local! {
+        static REPLAYED: std::cell::RefCell<Vec<String>> =
+            const { std::cell::RefCell::new(Vec::new()) };
+    }
+
+    fn replayed(xcomponent: *mut OH_NativeXComponent, window: *mut c_void, callback: &str) {
+        let xc = XComponent::new(xcomponent, window).unwrap();
+        let size = xc.try_size().unwrap();
+        REPLAYED.with_borrow_mut(|replayed| {
+            replayed.push(format!("{callback} {}x{}", size.width, size.height))
+        });
+    }
+
+    unsafe extern "C" fn replay_created(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+        replayed(xcomponent, window, "created");
+    }
+
+    unsafe extern "C" fn replay_changed(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+        replayed(xcomponent, window, "changed");
+    }
+
+    unsafe extern "C" fn replay_destroyed(
+        xcomponent: *mut OH_NativeXComponent,
+        window: *mut c_void,
+    ) {
+        replayed(xcomponent, window, "destroyed");
+    }
+
+    unsafe extern "C" fn replay_touch(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+        let xc = XComponent::new(xcomponent, window).unwrap();
+        let id = xc.get_touch_event().unwrap().id;
+        replayed(xcomponent, window, &format!("touch {id}"));
+    }
+
+    static REPLAY_CALLBACKS: OH_NativeXComponent_Callback = OH_NativeXComponent_Callback {
+        OnSurfaceCreated: Some(replay_created),
+        OnSurfaceChanged: Some(replay_changed),
+        OnSurfaceDestroyed: Some(replay_destroyed),
+        DispatchTouchEvent: Some(replay_touch),
+    };
+
+    #[test]
+    fn replay_dispatches_in_order() {
+        let mock = MockXComponent::new("replay");
+        let touch = TouchEventBuilder::down(7, (0.0, 0.0)).build()[0];
+        mock.replay(
+            &REPLAY_CALLBACKS,
+            [
+                MockEvent::SurfaceCreated,
+                MockEvent::SurfaceChanged {
+                    width: 100,
+                    height: 200,
+                },
+                MockEvent::Touch(touch),
+                MockEvent::SurfaceDestroyed,
+                // E.g. after the app returned from the background
+                MockEvent::SurfaceCreated,
+                MockEvent::SurfaceChanged {
+                    width: 200,
+                    height: 100,
+                },
+                MockEvent::SurfaceDestroyed,
+            ],
+        );
+        assert_eq!(
+            REPLAYED.take(),
+            [
+                "created 0x0",
+                "changed 100x200",
+                "touch 7 100x200",
+                "destroyed 100x200",
+                "created 100x200",
+                "changed 200x100",
+                "destroyed 200x100",
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "(SurfaceChanged { width: 1, height: 1 }) is not possible without")]
+    fn replay_without_surface() {
+        let mock = MockXComponent::new("replay-without-surface");
+        mock.replay(
+            &REPLAY_CALLBACKS,
+            [MockEvent::SurfaceChanged {
+                width: 1,
+                height: 1,
+            }],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "event 1 (SurfaceCreated) is not possible with a surface")]
+    fn replay_created_twice() {
+        let mock = MockXComponent::new("replay-created-twice");
+        mock.replay(
+            &REPLAY_CALLBACKS,
+            [MockEvent::SurfaceCreated, MockEvent::SurfaceCreated],
+        );
+    }
+
+    #[test]
+    fn replay_stops_at_touch_after_destroyed() {
+        let mock = MockXComponent::new("replay-touch-after-destroyed");
+        let touch = TouchEventBuilder::down(0, (0.0, 0.0)).build()[0];
+        let replay = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            mock.replay(
+                &REPLAY_CALLBACKS,
+                [
+                    MockEvent::SurfaceCreated,
+                    MockEvent::SurfaceDestroyed,
+                    MockEvent::Touch(touch),
+                ],
+            )
+        }));
+        assert!(replay.is_err());
+        // The events before the invalid one were dispatched, the invalid one wasn't.
+        assert_eq!(REPLAYED.take(), ["created 0x0", "destroyed 0x0"]);
+    }
+
+    #[cfg(feature = "register")]
+    static NO_CALLBACKS: OH_NativeXComponent_Callback = OH_NativeXComponent_Callback {
+        OnSurfaceCreated: None,
+        OnSurfaceChanged: None,
+        OnSurfaceDestroyed: None,
+        DispatchTouchEvent: None,
+    };
+
+    #[cfg(feature = "register")]
+    static NO_MOUSE_CALLBACKS: OH_NativeXComponent_MouseEvent_Callback =
+        OH_NativeXComponent_MouseEvent_Callback {
+            DispatchMouseEvent: None,
+            DispatchHoverEvent: None,
+        };
+
+    #[test]
+    #[cfg(feature = "register")]
+    fn exports_register_with_their_mock() {
+        use crate::{register_event_callbacks, register_mouse_event_callbacks};
+
+        let mock = MockXComponent::new("exports");
+        let other = MockXComponent::new("other-exports");
+        let exports = MockExports::new(&mock);
+        register_xcomponent_callbacks(&exports, &(), &NO_CALLBACKS).unwrap();
+        register_mouse_event_callbacks(&exports, &(), &NO_MOUSE_CALLBACKS).unwrap();
+        let event_callbacks = EventCallbacks {
+            on_focus: Some(on_focus),
+            on_blur: None,
+            on_key: None,
+        };
+        register_event_callbacks(&exports, &(), &event_callbacks).unwrap();
+
+        assert!(mock.registered_callbacks().is_some());
+        assert!(core::ptr::eq(
+            mock.registered_mouse_callbacks().unwrap(),
+            &NO_MOUSE_CALLBACKS
+        ));
+        mock.focus(&mock.registered_event_callbacks().unwrap());
+        assert_eq!(KEY_EVENTS.take(), ["focus"]);
+        // Each module's exports find the XComponent which loaded it.
+        assert!(other.registered_callbacks().is_none());
+        assert!(other.registered_mouse_callbacks().is_none());
+        assert!(other.registered_event_callbacks().is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "register")]
+    fn exports_without_xcomponent() {
+        let exports = MockExports::without_xcomponent();
+        let res = register_xcomponent_callbacks(&exports, &(), &NO_CALLBACKS);
+        assert!(matches!(
+            res,
+            Err(RegisterCallbackError::XcomponentPropertyMissing(message))
+                if message == "__NATIVE_XCOMPONENT_OBJ__ is undefined"
+        ));
+        let res = crate::register_mouse_event_callbacks(&exports, &(), &NO_MOUSE_CALLBACKS);
+        assert!(matches!(
+            res,
+            Err(RegisterCallbackError::XcomponentPropertyMissing(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "register")]
+    fn exports_with_unwrap_error() {
+        // napi_invalid_arg, e.g. if the property is not a wrapped object
+        let exports = MockExports::with_unwrap_error(1);
+        let res = register_xcomponent_callbacks(&exports, &(), &NO_CALLBACKS);
+        assert!(matches!(
+            res,
+            Err(RegisterCallbackError::UnwrapXComponentFailed(1))
+        ));
+        struct Handler;
+        impl crate::handler::XComponentHandler for Handler {}
+        let res = crate::handler::register_handler::<Handler, _>(&exports, &());
+        assert!(matches!(
+            res,
+            Err(RegisterCallbackError::UnwrapXComponentFailed(1))
+        ));
+    }
+}
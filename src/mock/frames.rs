@@ -0,0 +1,254 @@
+//! Deterministic frame ticks
+//!
+//! On a device the compositor calls the frame callback set via
+//! [`XComponent::set_frame_callback`](crate::XComponent::set_frame_callback) at the refresh rate
+//! of the display. [`FrameTicker`] stands in for it: it calls the frame callback of a
+//! [`MockXComponent`] with timestamps of a fake clock, so frame pacing can be tested without
+//! depending on the time the test takes. Frames can be dropped and the rate changed in between,
+//! like the compositor does when rendering is too slow or the app asks for another rate.
+//!
+//! ## Example
+//! ```no_run
+//! use xcomponent::mock::frames::FrameTicker;
+//! use xcomponent::mock::MockXComponent;
+//!
+//! let mock = MockXComponent::new("xcomponent");
+//! mock.xcomponent()
+//!     .set_frame_callback(|timestamp, target_timestamp| {
+//!         // Render the frame ...
+//!     })
+//!     .unwrap();
+//!
+//! let mut ticker = FrameTicker::new(60);
+//! ticker.ticks(&mock, 3);
+//! // The next frame is three periods late.
+//! ticker.drop_frames(2);
+//! ticker.tick(&mock);
+//! ```
+
+use super::MockXComponent;
+use core::time::Duration;
+
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+/// Fake frame clock driving the frame callback of mock XComponents
+#[derive(Debug, Clone)]
+pub struct FrameTicker {
+    rate: u32,
+    /// Timestamp of the next frame in nanoseconds
+    timestamp: u64,
+    frames: u64,
+    dropped: u64,
+}
+
+impl FrameTicker {
+    /// A ticker at `rate` frames per second, whose first frame is at timestamp zero
+    ///
+    /// # Panics
+    /// If `rate` is zero.
+    pub fn new(rate: u32) -> Self {
+        assert!(rate > 0, "the frame rate must be positive");
+        Self {
+            rate,
+            timestamp: 0,
+            frames: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Starts the clock at `timestamp` instead, e.g. to test timestamps which don't start at zero
+    pub fn starting_at(mut self, timestamp: Duration) -> Self {
+        self.timestamp = timestamp.as_nanos() as u64;
+        self
+    }
+
+    /// The current rate in frames per second
+    pub fn rate(&self) -> u32 {
+        self.rate
+    }
+
+    /// Changes the rate of the following frames
+    ///
+    /// # Panics
+    /// If `rate` is zero.
+    pub fn set_rate(&mut self, rate: u32) {
+        assert!(rate > 0, "the frame rate must be positive");
+        self.rate = rate;
+    }
+
+    /// Changes the rate to the expected rate set via
+    /// [`XComponent::set_expected_frame_rate_range`](crate::XComponent::set_expected_frame_rate_range)
+    /// of `mock`, like a compositor granting the request
+    ///
+    /// Returns whether `mock` had an expected rate.
+    pub fn follow_expected_rate(&mut self, mock: &MockXComponent) -> bool {
+        match mock.expected_frame_rate_range() {
+            Some((_, _, expected)) if expected > 0 => {
+                self.rate = expected as u32;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The time between two frames at the current rate, truncated to nanoseconds
+    pub fn period(&self) -> Duration {
+        Duration::from_nanos(self.period_nanos())
+    }
+
+    fn period_nanos(&self) -> u64 {
+        NANOS_PER_SECOND / u64::from(self.rate)
+    }
+
+    /// The timestamp of the next frame
+    pub fn timestamp(&self) -> Duration {
+        Duration::from_nanos(self.timestamp)
+    }
+
+    /// The number of frames the callback was called for
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// The number of frames skipped via [`FrameTicker::drop_frames`]
+    pub fn dropped_frames(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Calls the frame callback of `mock` for the next frame and advances the clock by one period
+    ///
+    /// The target timestamp of the frame is one period after its timestamp.
+    pub fn tick(&mut self, mock: &MockXComponent) {
+        let period = self.period_nanos();
+        mock.frame(self.timestamp, self.timestamp + period);
+        self.timestamp += period;
+        self.frames += 1;
+    }
+
+    /// Calls [`FrameTicker::tick`] `frames` times
+    pub fn ticks(&mut self, mock: &MockXComponent, frames: usize) {
+        for _ in 0..frames {
+            self.tick(mock);
+        }
+    }
+
+    /// Advances the clock by `frames` periods without calling any callback
+    ///
+    /// This simulates missed vsyncs, e.g. because the previous frame took too long to render.
+    pub fn drop_frames(&mut self, frames: u32) {
+        self.timestamp += self.period_nanos() * u64::from(frames);
+        self.dropped += u64::from(frames);
+    }
+
+    /// Advances the clock by `duration` without calling any callback, e.g. while the surface is
+    /// hidden
+    pub fn advance(&mut self, duration: Duration) {
+        self.timestamp += duration.as_nanos() as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    type Frames = Rc<RefCell<Vec<(u64, u64)>>>;
+
+    fn record_frames(mock: &MockXComponent) -> Frames {
+        let frames = Frames::default();
+        let recorded = frames.clone();
+        mock.xcomponent()
+            .set_frame_callback(move |timestamp, target_timestamp| {
+                recorded.borrow_mut().push((
+                    timestamp.as_nanos() as u64,
+                    target_timestamp.as_nanos() as u64,
+                ))
+            })
+            .unwrap();
+        frames
+    }
+
+    #[test]
+    fn ticks_at_the_rate() {
+        let mock = MockXComponent::new("ticks_at_the_rate");
+        let frames = record_frames(&mock);
+        let mut ticker = FrameTicker::new(60);
+        assert_eq!(ticker.period(), Duration::from_nanos(16_666_666));
+        ticker.ticks(&mock, 3);
+        assert_eq!(
+            *frames.borrow(),
+            [
+                (0, 16_666_666),
+                (16_666_666, 33_333_332),
+                (33_333_332, 49_999_998)
+            ]
+        );
+        assert_eq!(ticker.frames(), 3);
+        assert_eq!(ticker.timestamp(), Duration::from_nanos(49_999_998));
+    }
+
+    #[test]
+    fn dropped_frames_make_the_next_frame_late() {
+        let mock = MockXComponent::new("dropped_frames");
+        let frames = record_frames(&mock);
+        let mut ticker = FrameTicker::new(100).starting_at(Duration::from_secs(1));
+        ticker.tick(&mock);
+        ticker.drop_frames(2);
+        ticker.tick(&mock);
+        assert_eq!(
+            *frames.borrow(),
+            [
+                (1_000_000_000, 1_010_000_000),
+                (1_030_000_000, 1_040_000_000)
+            ]
+        );
+        assert_eq!(ticker.frames(), 2);
+        assert_eq!(ticker.dropped_frames(), 2);
+
+        ticker.advance(Duration::from_millis(500));
+        ticker.tick(&mock);
+        assert_eq!(frames.borrow()[2], (1_540_000_000, 1_550_000_000));
+    }
+
+    #[test]
+    fn rate_changes_apply_to_the_following_frames() {
+        let mock = MockXComponent::new("rate_changes");
+        let frames = record_frames(&mock);
+        let mut ticker = FrameTicker::new(50);
+        ticker.tick(&mock);
+        ticker.set_rate(100);
+        ticker.tick(&mock);
+        assert_eq!(
+            *frames.borrow(),
+            [(0, 20_000_000), (20_000_000, 30_000_000)]
+        );
+
+        assert!(!ticker.follow_expected_rate(&mock));
+        mock.xcomponent()
+            .set_expected_frame_rate_range(30, 120, 40)
+            .unwrap();
+        assert!(ticker.follow_expected_rate(&mock));
+        assert_eq!(ticker.rate(), 40);
+        ticker.tick(&mock);
+        assert_eq!(frames.borrow()[2], (30_000_000, 55_000_000));
+    }
+
+    #[test]
+    fn stops_calling_a_removed_callback() {
+        let mock = MockXComponent::new("removed_callback");
+        let frames = record_frames(&mock);
+        let mut ticker = FrameTicker::new(60);
+        ticker.tick(&mock);
+        mock.xcomponent().remove_frame_callback().unwrap();
+        ticker.tick(&mock);
+        assert_eq!(frames.borrow().len(), 1);
+        assert_eq!(ticker.frames(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "the frame rate must be positive")]
+    fn zero_rate() {
+        FrameTicker::new(0);
+    }
+}
@@ -0,0 +1,272 @@
+//! Stress test for surface churn
+//!
+//! [`SoakTest`] rapidly creates and destroys the surfaces of several mock XComponents, while
+//! touch input and size changes flow to them in an interleaved order. After every cycle it
+//! checks that the per-component registries of the crate were cleaned up, which catches
+//! callbacks that e.g. set a frame callback in `OnSurfaceCreated` but don't remove it in
+//! `OnSurfaceDestroyed`.
+//!
+//! The test only runs against the mock backend, since ArkUI can't be made to destroy surfaces
+//! from native code. The same callbacks can be soaked on a device by repeatedly toggling the
+//! visibility of the XComponent from ArkTS.
+
+use super::{MockEvent, MockXComponent, TouchEventBuilder};
+use ohos_sys::xcomponent::OH_NativeXComponent_Callback;
+
+/// Deterministic pseudo-random numbers, so failures can be reproduced from the seed
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 33) % bound.max(1)
+    }
+}
+
+/// Configuration of a surface churn stress test
+///
+/// ```no_run
+/// # use ohos_sys::xcomponent::OH_NativeXComponent_Callback;
+/// # fn soak(callbacks: &OH_NativeXComponent_Callback) {
+/// let report = xcomponent::mock::soak::SoakTest::new()
+///     .cycles(1000)
+///     .components(3)
+///     .run(callbacks);
+/// println!("Dispatched {} events", report.events);
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct SoakTest {
+    cycles: usize,
+    components: usize,
+    seed: u64,
+}
+
+/// Statistics of a finished [`SoakTest`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SoakReport {
+    pub surfaces: usize,
+    pub events: usize,
+}
+
+impl Default for SoakTest {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SoakTest {
+    /// 100 cycles of 2 components
+    pub fn new() -> Self {
+        Self {
+            cycles: 100,
+            components: 2,
+            seed: 0,
+        }
+    }
+
+    /// Number of times every component's surface is created and destroyed
+    pub fn cycles(mut self, cycles: usize) -> Self {
+        self.cycles = cycles;
+        self
+    }
+
+    /// Number of components whose events are interleaved
+    pub fn components(mut self, components: usize) -> Self {
+        self.components = components;
+        self
+    }
+
+    /// Seed of the order and content of the events
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Runs the test, dispatching all events to `callbacks`
+    ///
+    /// # Panics
+    ///
+    /// Panics if a component still has crate state which should have been cleaned up after its
+    /// surface was destroyed, or if the callbacks panic.
+    pub fn run(&self, callbacks: &OH_NativeXComponent_Callback) -> SoakReport {
+        let mut rng = Lcg(self.seed);
+        let mut report = SoakReport::default();
+        for cycle in 0..self.cycles {
+            let mocks: Vec<_> = (0..self.components)
+                .map(|index| MockXComponent::new(&format!("soak-{index}")))
+                .collect();
+            let mut scripts: Vec<_> = mocks.iter().map(|_| script(&mut rng).into_iter()).collect();
+            let mut remaining: Vec<usize> = (0..mocks.len()).collect();
+            while !remaining.is_empty() {
+                let slot = rng.next(remaining.len() as u64) as usize;
+                let index = remaining[slot];
+                let Some(event) = scripts[index].next() else {
+                    remaining.swap_remove(slot);
+                    continue;
+                };
+                let surface_destroyed = matches!(event, MockEvent::SurfaceDestroyed);
+                mocks[index].dispatch(callbacks, event);
+                report.events += 1;
+                if surface_destroyed {
+                    report.surfaces += 1;
+                    check_cleaned_up(&mocks[index], cycle);
+                }
+            }
+        }
+        report
+    }
+}
+
+/// The events of one surface lifetime of a component
+fn script(rng: &mut Lcg) -> Vec<MockEvent> {
+    let mut events = vec![MockEvent::SurfaceCreated];
+    for _ in 0..rng.next(8) {
+        if rng.next(4) == 0 {
+            events.push(MockEvent::SurfaceChanged {
+                width: 1 + rng.next(4096),
+                height: 1 + rng.next(4096),
+            });
+            continue;
+        }
+        let start = (rng.next(1080) as f32, rng.next(2340) as f32);
+        let mut gesture = TouchEventBuilder::down(0, start);
+        for id in 1..rng.next(3) as i32 {
+            gesture = gesture.down_pointer(id, (start.0 + 10.0 * id as f32, start.1));
+        }
+        for _ in 0..rng.next(5) {
+            gesture = gesture.move_to((rng.next(1080) as f32, rng.next(2340) as f32));
+        }
+        // Some gestures are still in progress when the surface is destroyed.
+        gesture = match rng.next(4) {
+            0 => gesture.cancel(),
+            1 => gesture,
+            _ => gesture.up(),
+        };
+        events.extend(gesture.build().into_iter().map(MockEvent::Touch));
+    }
+    events.push(MockEvent::SurfaceDestroyed);
+    events
+}
+
+fn check_cleaned_up(mock: &MockXComponent, cycle: usize) {
+    #[cfg(feature = "api-11")]
+    assert!(
+        !crate::frame::contains(mock.raw_xcomponent() as usize),
+        "cycle {cycle}: the frame callback of a component is still set after its surface was \
+         destroyed"
+    );
+    #[cfg(not(feature = "api-11"))]
+    let _ = (mock, cycle);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ffi::c_void;
+    use ohos_sys::xcomponent::OH_NativeXComponent;
+    use std::cell::Cell;
+
+    thread_local! {
+        static CALLS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe extern "C" fn count(_: *mut OH_NativeXComponent, _: *mut c_void) {
+        CALLS.set(CALLS.get() + 1);
+    }
+
+    static COUNTING: OH_NativeXComponent_Callback = OH_NativeXComponent_Callback {
+        OnSurfaceCreated: Some(count),
+        OnSurfaceChanged: Some(count),
+        OnSurfaceDestroyed: Some(count),
+        DispatchTouchEvent: Some(count),
+    };
+
+    #[test]
+    fn report_counts_surfaces_and_events() {
+        let soak = SoakTest::new().cycles(10).components(3).seed(7);
+        let report = soak.run(&COUNTING);
+        assert_eq!(report.surfaces, 30);
+        assert_eq!(report.events, CALLS.take());
+        // The events only depend on the seed.
+        assert_eq!(soak.run(&COUNTING), report);
+        assert_ne!(soak.seed(8).run(&COUNTING), report);
+    }
+
+    #[cfg(feature = "api-11")]
+    unsafe extern "C" fn set_frame_callback(
+        xcomponent: *mut OH_NativeXComponent,
+        window: *mut c_void,
+    ) {
+        let xc = crate::XComponent::new(xcomponent, window).unwrap();
+        xc.set_frame_callback(|_, _| {}).unwrap();
+    }
+
+    #[cfg(feature = "api-11")]
+    static LEAKING_FRAME_CALLBACK: OH_NativeXComponent_Callback = OH_NativeXComponent_Callback {
+        OnSurfaceCreated: Some(set_frame_callback),
+        OnSurfaceChanged: None,
+        OnSurfaceDestroyed: None,
+        DispatchTouchEvent: None,
+    };
+
+    #[test]
+    #[cfg(feature = "api-11")]
+    #[should_panic(expected = "cycle 0: the frame callback of a component is still set")]
+    fn leaked_frame_callback() {
+        SoakTest::new().run(&LEAKING_FRAME_CALLBACK);
+    }
+
+    #[test]
+    #[cfg(all(feature = "event-queue", feature = "register"))]
+    fn event_queue() {
+        use crate::event_queue::{self, Event, EventQueue};
+        use crate::handler::xcomponent_callbacks;
+        use std::collections::HashMap;
+
+        let _lock = event_queue::lock_for_test();
+        // There is no render thread to take `SurfaceDestroyed`.
+        event_queue::set_destroy_timeout(core::time::Duration::ZERO);
+        let report = SoakTest::new()
+            .components(3)
+            .run(xcomponent_callbacks::<EventQueue>());
+        let queued = event_queue::drain_events();
+        event_queue::set_destroy_timeout(event_queue::DEFAULT_DESTROY_TIMEOUT);
+
+        // Resizes to the same size are not queued.
+        assert!(queued.len() <= report.events);
+        let mut has_surface = HashMap::new();
+        let mut destroyed = 0;
+        for queued in queued {
+            let has_surface = has_surface.entry(queued.component).or_insert(false);
+            match queued.event {
+                Event::SurfaceCreated { .. } => {
+                    assert!(
+                        !*has_surface,
+                        "{:#x}: surface created twice",
+                        queued.component
+                    );
+                    *has_surface = true;
+                }
+                Event::SurfaceDestroyed => {
+                    assert!(
+                        *has_surface,
+                        "{:#x}: destroyed without surface",
+                        queued.component
+                    );
+                    *has_surface = false;
+                    destroyed += 1;
+                }
+                _ => assert!(
+                    *has_surface,
+                    "{:#x}: event without surface",
+                    queued.component
+                ),
+            }
+        }
+        assert_eq!(destroyed, report.surfaces);
+    }
+}
@@ -0,0 +1,119 @@
+//! Typed mouse events
+//!
+//! ArkUI only sends mouse events to an XComponent which registered mouse event callbacks, e.g.
+//! via [`register_mouse_event_callbacks`](crate::register_mouse_event_callbacks). The event is
+//! available via [`XComponent::get_mouse_event`](crate::XComponent::get_mouse_event) while
+//! ArkUI calls `DispatchMouseEvent`. Coordinates are in physical pixels, `x` and `y` relative to
+//! the XComponent and `screen_x` and `screen_y` relative to the display.
+
+use ohos_sys::xcomponent::{
+    OH_NativeXComponent_MouseEvent, OH_NativeXComponent_MouseEventAction,
+    OH_NativeXComponent_MouseEventButton,
+};
+
+/// What the mouse did
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MouseAction {
+    Press,
+    Release,
+    Move,
+    /// The press was interrupted, e.g. because the system took over the gesture
+    Cancel,
+}
+
+impl MouseAction {
+    /// Converts a raw `OH_NativeXComponent_MouseEventAction`
+    ///
+    /// Returns `None` for `OH_NATIVEXCOMPONENT_MOUSE_NONE` and unknown values.
+    pub fn from_raw(action: OH_NativeXComponent_MouseEventAction) -> Option<Self> {
+        use OH_NativeXComponent_MouseEventAction as Raw;
+        match action {
+            Raw::OH_NATIVEXCOMPONENT_MOUSE_PRESS => Some(Self::Press),
+            Raw::OH_NATIVEXCOMPONENT_MOUSE_RELEASE => Some(Self::Release),
+            Raw::OH_NATIVEXCOMPONENT_MOUSE_MOVE => Some(Self::Move),
+            // OH_NATIVEXCOMPONENT_MOUSE_CANCEL, only sent since API level 18
+            Raw(4) => Some(Self::Cancel),
+            _ => None,
+        }
+    }
+
+    pub fn as_raw(self) -> OH_NativeXComponent_MouseEventAction {
+        use OH_NativeXComponent_MouseEventAction as Raw;
+        match self {
+            Self::Press => Raw::OH_NATIVEXCOMPONENT_MOUSE_PRESS,
+            Self::Release => Raw::OH_NATIVEXCOMPONENT_MOUSE_RELEASE,
+            Self::Move => Raw::OH_NATIVEXCOMPONENT_MOUSE_MOVE,
+            Self::Cancel => Raw(4),
+        }
+    }
+}
+
+/// A mouse button
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    Back,
+    Forward,
+}
+
+impl MouseButton {
+    /// Converts a raw `OH_NativeXComponent_MouseEventButton`
+    ///
+    /// Returns `None` for `OH_NATIVEXCOMPONENT_NONE_BUTTON` and unknown values.
+    pub fn from_raw(button: OH_NativeXComponent_MouseEventButton) -> Option<Self> {
+        use OH_NativeXComponent_MouseEventButton as Raw;
+        match button {
+            Raw::OH_NATIVEXCOMPONENT_LEFT_BUTTON => Some(Self::Left),
+            Raw::OH_NATIVEXCOMPONENT_RIGHT_BUTTON => Some(Self::Right),
+            Raw::OH_NATIVEXCOMPONENT_MIDDLE_BUTTON => Some(Self::Middle),
+            Raw::OH_NATIVEXCOMPONENT_BACK_BUTTON => Some(Self::Back),
+            Raw::OH_NATIVEXCOMPONENT_FORWARD_BUTTON => Some(Self::Forward),
+            _ => None,
+        }
+    }
+
+    pub fn as_raw(self) -> OH_NativeXComponent_MouseEventButton {
+        use OH_NativeXComponent_MouseEventButton as Raw;
+        match self {
+            Self::Left => Raw::OH_NATIVEXCOMPONENT_LEFT_BUTTON,
+            Self::Right => Raw::OH_NATIVEXCOMPONENT_RIGHT_BUTTON,
+            Self::Middle => Raw::OH_NATIVEXCOMPONENT_MIDDLE_BUTTON,
+            Self::Back => Raw::OH_NATIVEXCOMPONENT_BACK_BUTTON,
+            Self::Forward => Raw::OH_NATIVEXCOMPONENT_FORWARD_BUTTON,
+        }
+    }
+}
+
+/// A mouse event of an XComponent
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MouseEvent {
+    /// `None` if ArkUI reported no or an unknown action
+    pub action: Option<MouseAction>,
+    /// The button which was pressed or released, `None` e.g. for moves
+    pub button: Option<MouseButton>,
+    pub x: f32,
+    pub y: f32,
+    pub screen_x: f32,
+    pub screen_y: f32,
+    /// Time of the event in nanoseconds
+    pub timestamp: i64,
+}
+
+impl From<OH_NativeXComponent_MouseEvent> for MouseEvent {
+    fn from(raw: OH_NativeXComponent_MouseEvent) -> Self {
+        Self {
+            action: MouseAction::from_raw(raw.action),
+            button: MouseButton::from_raw(raw.button),
+            x: raw.x,
+            y: raw.y,
+            screen_x: raw.screenX,
+            screen_y: raw.screenY,
+            timestamp: raw.timestamp,
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! The napi operations needed by [`register_xcomponent_callbacks`](crate::register_xcomponent_callbacks)
+//!
+//! Implemented for the exports object of each supported `napi-ohos` version, so that
+//! applications aren't forced to upgrade `napi-ohos` in lockstep with this crate.
+
+use crate::RegisterCallbackError;
+use core::ffi::c_void;
+use ohos_sys::napi::{napi_env, napi_value};
+use ohos_sys::xcomponent::OH_NativeXComponent;
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// The `exports` object of a module, as passed to the module init function
+///
+/// Implemented for `napi_ohos::JsObject` of `napi-ohos` 0.1 by default, and instead for
+/// `napi_ohos::bindgen_prelude::Object` of `napi-ohos` 1.x with the `napi-ohos-v1` feature.
+/// This trait is sealed and can not be implemented outside of this crate.
+pub trait NapiExports: private::Sealed {
+    /// The napi environment type of the `napi-ohos` version
+    type Env;
+
+    /// The raw napi environment
+    #[doc(hidden)]
+    fn raw_env(env: &Self::Env) -> napi_env;
+
+    /// The raw value of the `__NATIVE_XCOMPONENT_OBJ__` property of the exports object
+    #[doc(hidden)]
+    fn raw_xcomponent_object(&self) -> Result<napi_value, String>;
+
+    /// The native XComponent wrapped by the `__NATIVE_XCOMPONENT_OBJ__` property
+    #[doc(hidden)]
+    fn native_xcomponent(
+        &self,
+        env: &Self::Env,
+    ) -> Result<*mut OH_NativeXComponent, RegisterCallbackError> {
+        let raw = self
+            .raw_xcomponent_object()
+            .map_err(RegisterCallbackError::XcomponentPropertyMissing)?;
+        let raw_env = Self::raw_env(env);
+        let mut native_xcomponent: *mut OH_NativeXComponent = core::ptr::null_mut();
+        let res = unsafe {
+            ohos_sys::napi::napi_unwrap(
+                raw_env,
+                raw,
+                &mut native_xcomponent as *mut *mut OH_NativeXComponent as *mut *mut c_void,
+            )
+        };
+        if res != ohos_sys::napi::napi_status::napi_ok {
+            return Err(RegisterCallbackError::UnwrapXComponentFailed(res.0 as i32));
+        }
+        Ok(native_xcomponent)
+    }
+}
+
+#[cfg(feature = "mock")]
+impl private::Sealed for crate::mock::MockExports {}
+
+/// Skips napi and returns the mock XComponent directly
+#[cfg(feature = "mock")]
+impl NapiExports for crate::mock::MockExports {
+    type Env = ();
+
+    fn raw_env(_env: &Self::Env) -> napi_env {
+        core::ptr::null_mut()
+    }
+
+    fn raw_xcomponent_object(&self) -> Result<napi_value, String> {
+        // Never passed to napi, since `native_xcomponent` is overridden.
+        self.xcomponent()
+            .map(|xcomponent| xcomponent.cast())
+            .map_err(Into::into)
+    }
+
+    fn native_xcomponent(
+        &self,
+        _env: &Self::Env,
+    ) -> Result<*mut OH_NativeXComponent, RegisterCallbackError> {
+        self.xcomponent()
+    }
+}
+
+#[cfg(not(feature = "napi-ohos-v1"))]
+impl private::Sealed for napi_ohos::JsObject {}
+
+#[cfg(not(feature = "napi-ohos-v1"))]
+impl NapiExports for napi_ohos::JsObject {
+    type Env = napi_ohos::Env;
+
+    fn raw_env(env: &Self::Env) -> napi_env {
+        env.raw().cast()
+    }
+
+    fn raw_xcomponent_object(&self) -> Result<napi_value, String> {
+        use napi_ohos::NapiRaw;
+        let xcomponent_js_object = self
+            .get_named_property::<napi_ohos::JsObject>("__NATIVE_XCOMPONENT_OBJ__")
+            .map_err(|e| e.to_string())?;
+        // SAFETY: The returned value is only used while the exports object is alive.
+        Ok(unsafe { xcomponent_js_object.raw() }.cast())
+    }
+}
+
+#[cfg(feature = "napi-ohos-v1")]
+impl private::Sealed for napi_ohos_v1::bindgen_prelude::Object<'_> {}
+
+#[cfg(feature = "napi-ohos-v1")]
+impl NapiExports for napi_ohos_v1::bindgen_prelude::Object<'_> {
+    type Env = napi_ohos_v1::Env;
+
+    fn raw_env(env: &Self::Env) -> napi_env {
+        env.raw().cast()
+    }
+
+    fn raw_xcomponent_object(&self) -> Result<napi_value, String> {
+        use napi_ohos_v1::bindgen_prelude::{JsObjectValue, JsValue, Object};
+        let xcomponent_js_object = self
+            .get_named_property::<Object>("__NATIVE_XCOMPONENT_OBJ__")
+            .map_err(|e| e.to_string())?;
+        Ok(xcomponent_js_object.raw().cast())
+    }
+}
@@ -0,0 +1,205 @@
+//! Reference-counted native windows
+//!
+//! ArkUI passes the native window of an XComponent to its callbacks as a raw pointer, which is
+//! only known to be valid for the duration of the callback. A [`NativeWindow`] holds a reference
+//! to the window, so the window object stays alive until the last [`NativeWindow`] is dropped,
+//! and can be moved to a render thread.
+//!
+//! Once `OnSurfaceDestroyed` returned, the window is still a valid object, but ArkUI doesn't
+//! display its buffers anymore, so rendering should stop.
+//!
+//! The size, format and usage of the buffers of the window default to the ones chosen by ArkUI,
+//! and can be changed before the buffers are requested, e.g. to render at a lower resolution than
+//! the size of the XComponent, which scales the buffers to the component.
+//!
+//! ## Example
+//! ```no_run
+//! # use ohos_sys::xcomponent::OH_NativeXComponent;
+//! # use core::ffi::c_void;
+//! extern "C" fn on_surface_created(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+//!     let xc = xcomponent::XComponent::new(xcomponent, window).unwrap();
+//!     let window = xc.native_window().unwrap();
+//!     let size = xc.try_size().unwrap();
+//!     std::thread::spawn(move || {
+//!         // Render at half resolution.
+//!         window.set_buffer_geometry(size.width as u32 / 2, size.height as u32 / 2).unwrap();
+//!         // Create an EGL surface for `window.as_ptr()` and render ...
+//!         drop(window);
+//!     });
+//! }
+//! ```
+
+use crate::format::PixelFormat;
+use crate::log::error;
+use crate::trace::ffi_span;
+use core::ffi::c_int;
+use core::ptr::NonNull;
+use ohos_sys::native_buffer::native_buffer::OH_NativeBuffer_Usage;
+use ohos_sys::native_window::{
+    NativeWindowOperation, OH_NativeWindow_NativeObjectReference,
+    OH_NativeWindow_NativeObjectUnreference, OH_NativeWindow_NativeWindowHandleOpt,
+};
+use ohos_sys_opaque_types::OHNativeWindow;
+
+/// A reference to an `OHNativeWindow`
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct NativeWindow {
+    window: NonNull<OHNativeWindow>,
+    /// Windows of mock XComponents are no real windows, so they aren't reference-counted.
+    #[cfg(feature = "mock")]
+    mocked: bool,
+}
+
+// SAFETY: Native windows are reference-counted atomically and can be used from any thread.
+unsafe impl Send for NativeWindow {}
+unsafe impl Sync for NativeWindow {}
+
+impl NativeWindow {
+    /// Takes a new reference to `window`
+    ///
+    /// # Safety
+    ///
+    /// `window` must point to a valid `OHNativeWindow`.
+    pub unsafe fn from_raw(window: NonNull<OHNativeWindow>) -> Result<Self, i32> {
+        reference(window)?;
+        Ok(Self {
+            window,
+            #[cfg(feature = "mock")]
+            mocked: false,
+        })
+    }
+
+    #[cfg(feature = "mock")]
+    pub(crate) fn mocked(window: NonNull<OHNativeWindow>) -> Self {
+        Self {
+            window,
+            mocked: true,
+        }
+    }
+
+    /// The raw window, valid as long as `self` is alive
+    pub fn as_ptr(&self) -> *mut OHNativeWindow {
+        self.window.as_ptr()
+    }
+
+    /// Sets the size of the buffers requested from the window, in pixels
+    ///
+    /// The buffers are scaled to the size of the XComponent when they are displayed.
+    pub fn set_buffer_geometry(&self, width: u32, height: u32) -> Result<(), i32> {
+        // OH_NATIVEXCOMPONENT_RESULT_FAILED
+        let width = i32::try_from(width).map_err(|_| -1)?;
+        let height = i32::try_from(height).map_err(|_| -1)?;
+        self.handle_opt("SET_BUFFER_GEOMETRY", |window| {
+            // SAFETY: `SET_BUFFER_GEOMETRY` takes the width and height as `int32_t`.
+            unsafe {
+                OH_NativeWindow_NativeWindowHandleOpt(
+                    window,
+                    NativeWindowOperation::SET_BUFFER_GEOMETRY as c_int,
+                    width,
+                    height,
+                )
+            }
+        })
+    }
+
+    /// Sets the pixel format of the buffers requested from the window
+    pub fn set_format(&self, format: PixelFormat) -> Result<(), i32> {
+        let format = format.as_raw().0 as i32;
+        self.handle_opt("SET_FORMAT", |window| {
+            // SAFETY: `SET_FORMAT` takes the format as `int32_t`.
+            unsafe {
+                OH_NativeWindow_NativeWindowHandleOpt(
+                    window,
+                    NativeWindowOperation::SET_FORMAT as c_int,
+                    format,
+                )
+            }
+        })
+    }
+
+    /// Sets the usage of the buffers requested from the window, e.g.
+    /// `NATIVEBUFFER_USAGE_CPU_WRITE` to write to the buffers from the CPU
+    pub fn set_usage(&self, usage: OH_NativeBuffer_Usage) -> Result<(), i32> {
+        let usage = u64::from(usage.0);
+        self.handle_opt("SET_USAGE", |window| {
+            // SAFETY: `SET_USAGE` takes the usage as `uint64_t`.
+            unsafe {
+                OH_NativeWindow_NativeWindowHandleOpt(
+                    window,
+                    NativeWindowOperation::SET_USAGE as c_int,
+                    usage,
+                )
+            }
+        })
+    }
+
+    /// Calls `OH_NativeWindow_NativeWindowHandleOpt` with `operation` via `opt`
+    fn handle_opt(
+        &self,
+        operation: &str,
+        opt: impl FnOnce(*mut OHNativeWindow) -> i32,
+    ) -> Result<(), i32> {
+        if self.is_mocked() {
+            return Ok(());
+        }
+        let mut span = ffi_span!("OH_NativeWindow_NativeWindowHandleOpt");
+        let res = opt(self.window.as_ptr());
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_NativeWindow_NativeWindowHandleOpt({operation}) failed with {res}");
+            return Err(res);
+        }
+        Ok(())
+    }
+
+    /// Whether this is the window of a mock XComponent, which must not be passed to the native API
+    pub(crate) fn is_mocked(&self) -> bool {
+        #[cfg(feature = "mock")]
+        return self.mocked;
+        #[cfg(not(feature = "mock"))]
+        false
+    }
+}
+
+/// Increments the reference count of `window`, which must be valid
+fn reference(window: NonNull<OHNativeWindow>) -> Result<(), i32> {
+    let mut span = ffi_span!("OH_NativeWindow_NativeObjectReference");
+    // SAFETY: The callers ensure that `window` is valid.
+    let res = unsafe { OH_NativeWindow_NativeObjectReference(window.as_ptr().cast()) };
+    span.set_result(res);
+    if res != 0 {
+        error!("OH_NativeWindow_NativeObjectReference failed with {res}");
+        return Err(res);
+    }
+    Ok(())
+}
+
+impl Clone for NativeWindow {
+    fn clone(&self) -> Self {
+        if !self.is_mocked() {
+            // `self` keeps the window alive, so this doesn't fail in practice. Failures are
+            // logged by `reference`.
+            let _ = reference(self.window);
+        }
+        Self {
+            window: self.window,
+            #[cfg(feature = "mock")]
+            mocked: self.mocked,
+        }
+    }
+}
+
+impl Drop for NativeWindow {
+    fn drop(&mut self) {
+        if self.is_mocked() {
+            return;
+        }
+        let mut span = ffi_span!("OH_NativeWindow_NativeObjectUnreference");
+        // SAFETY: `self` holds a reference, which is released here.
+        let res = unsafe { OH_NativeWindow_NativeObjectUnreference(self.window.as_ptr().cast()) };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_NativeWindow_NativeObjectUnreference failed with {res}");
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! Panics of user code called from native callbacks
+//!
+//! A panic unwinding out of an `extern "C"` callback into ArkUI aborts the process without any
+//! message about the panic. The callbacks this crate implements, like the shims of handlers and
+//! the trampolines of closures, call user code via [`catch`] instead, which logs the panic and
+//! returns to ArkUI as if the callback did nothing, or aborts after [`set_abort_on_panic`].
+//!
+//! Without the `std` feature, panics can't be caught, so they abort as before.
+
+#[cfg(feature = "std")]
+use core::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(feature = "std")]
+static ABORT_ON_PANIC: AtomicBool = AtomicBool::new(false);
+
+/// Whether to abort the process when user code called from a native callback panicked
+///
+/// By default, the panic is logged and the callback returns, so the app keeps running, possibly
+/// with state which was only partially updated. Aborting right away e.g. makes crash reports
+/// point at the panic.
+#[cfg(feature = "std")]
+pub fn set_abort_on_panic(abort: bool) {
+    ABORT_ON_PANIC.store(abort, Ordering::Relaxed);
+}
+
+/// Calls `f`, returning `None` if it panicked
+///
+/// `callback` names the native callback for the log message.
+#[cfg(feature = "std")]
+pub(crate) fn catch<R>(callback: &str, f: impl FnOnce() -> R) -> Option<R> {
+    // Like after a panic of a thread, the state `f` updated may be left partially updated.
+    let payload = match std::panic::catch_unwind(core::panic::AssertUnwindSafe(f)) {
+        Ok(result) => return Some(result),
+        Err(payload) => payload,
+    };
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("<non-string payload>");
+    crate::log::error!("{callback} panicked: {message}");
+    if ABORT_ON_PANIC.load(Ordering::Relaxed) {
+        std::process::abort();
+    }
+    None
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn catch<R>(_callback: &str, f: impl FnOnce() -> R) -> Option<R> {
+    Some(f())
+}
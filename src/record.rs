@@ -0,0 +1,465 @@
+//! Recording of XComponent callback sessions
+//!
+//! [`record`] wraps the callbacks of an app, so that every callback invocation is written to a
+//! trace before it is forwarded. This allows capturing the input sequence of a user-reported
+//! issue on the device, e.g. into a file in the sandbox of the app, and replaying it host-side
+//! with [`mock::load_trace`](crate::mock::load_trace) if the `mock` feature is enabled.
+//!
+//! ## Trace format
+//!
+//! The trace is plain text with one callback invocation per line. Every line starts with the time
+//! since recording started in nanoseconds, followed by the kind of callback and its arguments:
+//!
+//! ```text
+//! <time> created
+//! <time> changed <width> <height>
+//! <time> touch <event> <point>...
+//! <time> destroyed
+//! ```
+//!
+//! `<event>` lists the fields `id type x y screenX screenY size force deviceId timeStamp` of the
+//! touch event and is followed by `numPoints` touch points with the fields
+//! `id type x y screenX screenY size force timeStamp isPressed`. Like the touch events of ArkUI,
+//! a line lists at most [`MAX_TOUCH_POINTS`](crate::touch::MAX_TOUCH_POINTS) points, lines with
+//! more are rejected as malformed.
+//!
+//! ## Example
+//! ```no_run
+//! # use ohos_sys::xcomponent::{OH_NativeXComponent, OH_NativeXComponent_Callback};
+//! # use std::ffi::c_void;
+//! # extern "C" fn on_surface_created(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {}
+//! static CALLBACKS: OH_NativeXComponent_Callback = OH_NativeXComponent_Callback {
+//!     OnSurfaceCreated: Some(on_surface_created),
+//!     OnSurfaceChanged: None,
+//!     OnSurfaceDestroyed: None,
+//!     DispatchTouchEvent: None,
+//! };
+//!
+//! let trace = std::fs::File::create("/data/storage/el2/base/files/session.trace").unwrap();
+//! // Register the returned callbacks instead of `CALLBACKS`.
+//! let callbacks = xcomponent::record::record(CALLBACKS, std::io::BufWriter::new(trace));
+//! ```
+
+use crate::log::error;
+#[cfg(feature = "mock")]
+use crate::mock::MockEvent;
+#[cfg(any(feature = "mock", feature = "inject"))]
+use crate::touch::MAX_TOUCH_POINTS;
+use crate::XComponent;
+use core::ffi::c_void;
+use ohos_sys::xcomponent::{
+    OH_NativeXComponent, OH_NativeXComponent_Callback, OH_NativeXComponent_TouchEvent,
+};
+#[cfg(any(feature = "mock", feature = "inject"))]
+use ohos_sys::xcomponent::{OH_NativeXComponent_TouchEventType, OH_NativeXComponent_TouchPoint};
+use std::fmt::Write as _;
+use std::io::Write;
+use std::sync::Mutex;
+#[cfg(feature = "mock")]
+use std::time::Duration;
+use std::time::Instant;
+
+struct Recorder {
+    callbacks: OH_NativeXComponent_Callback,
+    trace: Box<dyn Write + Send>,
+    start: Instant,
+}
+
+static RECORDER: Mutex<Option<Recorder>> = Mutex::new(None);
+
+static PROXY: OH_NativeXComponent_Callback = OH_NativeXComponent_Callback {
+    OnSurfaceCreated: Some(on_surface_created),
+    OnSurfaceChanged: Some(on_surface_changed),
+    OnSurfaceDestroyed: Some(on_surface_destroyed),
+    DispatchTouchEvent: Some(dispatch_touch_event),
+};
+
+/// Records all invocations of the returned callbacks to `trace` and forwards them to `callbacks`
+///
+/// The returned callbacks need to be registered in place of `callbacks`. There is only one
+/// recorder per process, calling this again flushes the previous trace and replaces it.
+pub fn record(
+    callbacks: OH_NativeXComponent_Callback,
+    trace: impl Write + Send + 'static,
+) -> &'static OH_NativeXComponent_Callback {
+    let mut recorder = RECORDER.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(mut previous) = recorder.take() {
+        if let Err(e) = previous.trace.flush() {
+            error!("Flushing the previous trace failed with {e}");
+        }
+    }
+    *recorder = Some(Recorder {
+        callbacks,
+        trace: Box::new(trace),
+        start: Instant::now(),
+    });
+    &PROXY
+}
+
+/// Flushes the trace written by [`record`]
+///
+/// Call this e.g. from `OnSurfaceDestroyed`, if the trace is buffered.
+pub fn flush() -> std::io::Result<()> {
+    let mut recorder = RECORDER.lock().unwrap_or_else(|e| e.into_inner());
+    match recorder.as_mut() {
+        Some(recorder) => recorder.trace.flush(),
+        None => Ok(()),
+    }
+}
+
+/// Parses one line of a trace, returns `None` if it is malformed
+#[cfg(feature = "mock")]
+pub(crate) fn parse_line(line: &str) -> Option<(Duration, MockEvent)> {
+    let mut fields = line.split_ascii_whitespace();
+    let time = Duration::from_nanos(fields.next()?.parse().ok()?);
+    let recorded = match fields.next()? {
+        "created" => MockEvent::SurfaceCreated,
+        "changed" => MockEvent::SurfaceChanged {
+            width: fields.next()?.parse().ok()?,
+            height: fields.next()?.parse().ok()?,
+        },
+        "touch" => MockEvent::Touch(parse_touch_event(&mut fields)?),
+        "destroyed" => MockEvent::SurfaceDestroyed,
+        _ => return None,
+    };
+    if fields.next().is_some() {
+        return None;
+    }
+    Some((time, recorded))
+}
+
+/// Parses the fields of a touch event following `touch`
+#[cfg(any(feature = "mock", feature = "inject"))]
+pub(crate) fn parse_touch_event<'a>(
+    fields: &mut impl Iterator<Item = &'a str>,
+) -> Option<OH_NativeXComponent_TouchEvent> {
+    fn next<'a, T: core::str::FromStr>(fields: &mut impl Iterator<Item = &'a str>) -> Option<T> {
+        fields.next()?.parse().ok()
+    }
+    let mut event = OH_NativeXComponent_TouchEvent {
+        id: next(fields)?,
+        type_: OH_NativeXComponent_TouchEventType(next(fields)?),
+        x: next(fields)?,
+        y: next(fields)?,
+        screenX: next(fields)?,
+        screenY: next(fields)?,
+        size: next(fields)?,
+        force: next(fields)?,
+        deviceId: next(fields)?,
+        timeStamp: next(fields)?,
+        touchPoints: [OH_NativeXComponent_TouchPoint {
+            id: 0,
+            screenX: 0.0,
+            screenY: 0.0,
+            x: 0.0,
+            y: 0.0,
+            type_: OH_NativeXComponent_TouchEventType::OH_NATIVEXCOMPONENT_UNKNOWN,
+            size: 0.0,
+            force: 0.0,
+            timeStamp: 0,
+            isPressed: false,
+        }; MAX_TOUCH_POINTS],
+        numPoints: 0,
+    };
+    // Further points are left to `parse_line`, which rejects them as trailing fields.
+    for point in event.touchPoints.iter_mut() {
+        let Some(id) = fields.next() else {
+            break;
+        };
+        *point = OH_NativeXComponent_TouchPoint {
+            id: id.parse().ok()?,
+            type_: OH_NativeXComponent_TouchEventType(next(fields)?),
+            x: next(fields)?,
+            y: next(fields)?,
+            screenX: next(fields)?,
+            screenY: next(fields)?,
+            size: next(fields)?,
+            force: next(fields)?,
+            timeStamp: next(fields)?,
+            isPressed: next(fields)?,
+        };
+        event.numPoints += 1;
+    }
+    Some(event)
+}
+
+pub(crate) fn format_touch_event(line: &mut String, event: &OH_NativeXComponent_TouchEvent) {
+    // Writing to a `String` can't fail.
+    let _ = write!(
+        line,
+        " {} {} {} {} {} {} {} {} {} {}",
+        event.id,
+        event.type_.0,
+        event.x,
+        event.y,
+        event.screenX,
+        event.screenY,
+        event.size,
+        event.force,
+        event.deviceId,
+        event.timeStamp
+    );
+    let num_points = (event.numPoints as usize).min(event.touchPoints.len());
+    for point in &event.touchPoints[..num_points] {
+        let _ = write!(
+            line,
+            " {} {} {} {} {} {} {} {} {} {}",
+            point.id,
+            point.type_.0,
+            point.x,
+            point.y,
+            point.screenX,
+            point.screenY,
+            point.size,
+            point.force,
+            point.timeStamp,
+            point.isPressed
+        );
+    }
+}
+
+type Callback = Option<unsafe extern "C" fn(*mut OH_NativeXComponent, *mut c_void)>;
+
+/// Writes a line for the invocation described by `args` and forwards it to the callback selected
+/// by `callback`
+unsafe fn forward(
+    xcomponent: *mut OH_NativeXComponent,
+    window: *mut c_void,
+    kind: &str,
+    args: impl FnOnce(&XComponent<'_>, &mut String) -> Result<(), i32>,
+    callback: impl FnOnce(&OH_NativeXComponent_Callback) -> Callback,
+) {
+    let mut line = String::new();
+    let recorded = XComponent::new(xcomponent, window)
+        .ok_or(-1)
+        .and_then(|xc| args(&xc, &mut line));
+    let forward = {
+        let mut recorder = RECORDER.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(recorder) = recorder.as_mut() else {
+            return;
+        };
+        if recorded.is_ok() {
+            let time = recorder.start.elapsed().as_nanos();
+            if let Err(e) = writeln!(recorder.trace, "{time} {kind}{line}") {
+                error!("Writing the trace failed with {e}");
+            }
+        }
+        callback(&recorder.callbacks)
+    };
+    // The lock is released, so the callback may call `flush`.
+    if let Some(forward) = forward {
+        forward(xcomponent, window);
+    }
+}
+
+unsafe extern "C" fn on_surface_created(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+    forward(
+        xcomponent,
+        window,
+        "created",
+        |_, _| Ok(()),
+        |c| c.OnSurfaceCreated,
+    );
+}
+
+unsafe extern "C" fn on_surface_changed(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+    forward(
+        xcomponent,
+        window,
+        "changed",
+        |xc, line| {
+            let size = xc.try_size()?;
+            let _ = write!(line, " {} {}", size.width, size.height);
+            Ok(())
+        },
+        |c| c.OnSurfaceChanged,
+    );
+}
+
+unsafe extern "C" fn on_surface_destroyed(
+    xcomponent: *mut OH_NativeXComponent,
+    window: *mut c_void,
+) {
+    forward(
+        xcomponent,
+        window,
+        "destroyed",
+        |_, _| Ok(()),
+        |c| c.OnSurfaceDestroyed,
+    );
+}
+
+unsafe extern "C" fn dispatch_touch_event(
+    xcomponent: *mut OH_NativeXComponent,
+    window: *mut c_void,
+) {
+    forward(
+        xcomponent,
+        window,
+        "touch",
+        |xc, line| {
+            format_touch_event(line, &xc.get_touch_event()?);
+            Ok(())
+        },
+        |c| c.DispatchTouchEvent,
+    );
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::mock::{load_trace, MockXComponent, TouchEventBuilder};
+    use std::sync::Arc;
+
+    fn touch_line(event: &OH_NativeXComponent_TouchEvent) -> String {
+        let mut line = "0 touch".to_string();
+        format_touch_event(&mut line, event);
+        line
+    }
+
+    /// A touch event with `points` pointers touching the screen
+    fn touch_event(points: usize) -> OH_NativeXComponent_TouchEvent {
+        let mut builder = TouchEventBuilder::down(0, (1.5, 2.25)).screen_offset(100.0, 200.0);
+        for id in 1..points.max(1) as i32 {
+            builder = builder.down_pointer(id, (id as f32 * 10.0, 0.5));
+        }
+        let mut event = *builder.build().last().unwrap();
+        event.numPoints = points as u32;
+        event
+    }
+
+    #[test]
+    fn touch_event_round_trip() {
+        for points in [0, 1, MAX_TOUCH_POINTS] {
+            let event = touch_event(points);
+            let line = touch_line(&event);
+            let Some((time, MockEvent::Touch(parsed))) = parse_line(&line) else {
+                panic!("{points} points: {line:?} was not parsed as a touch event");
+            };
+            assert_eq!(time, Duration::ZERO);
+            assert_eq!(parsed.numPoints, points as u32);
+            assert_eq!((parsed.id, parsed.type_), (event.id, event.type_));
+            assert_eq!(
+                (parsed.screenX, parsed.screenY),
+                (event.screenX, event.screenY)
+            );
+            assert_eq!(touch_line(&parsed), line);
+        }
+    }
+
+    #[test]
+    fn lifecycle_lines() {
+        assert!(matches!(
+            parse_line("0 created"),
+            Some((Duration::ZERO, MockEvent::SurfaceCreated))
+        ));
+        assert!(matches!(
+            parse_line("16 changed 1080 2340"),
+            Some((time, MockEvent::SurfaceChanged { width: 1080, height: 2340 }))
+                if time == Duration::from_nanos(16)
+        ));
+        assert!(matches!(
+            parse_line("32 destroyed"),
+            Some((_, MockEvent::SurfaceDestroyed))
+        ));
+    }
+
+    #[test]
+    fn malformed_lines() {
+        for line in [
+            "",
+            "created",
+            "-1 created",
+            "0 resized 1 2",
+            "0 created 1",
+            "0 changed 1",
+            "0 changed 1 2 3",
+            "0 changed 1 -2",
+            "0 destroyed now",
+        ] {
+            assert!(parse_line(line).is_none(), "{line:?} was parsed");
+        }
+        let line = touch_line(&touch_event(2));
+        let truncated = line.rsplit_once(' ').unwrap().0;
+        assert!(parse_line(truncated).is_none(), "{truncated:?} was parsed");
+        assert!(parse_line(&format!("{line} 1")).is_none());
+        // Only the event without a point
+        let event = line.split(' ').take(12).collect::<Vec<_>>().join(" ");
+        assert!(matches!(parse_line(&event), Some((_, MockEvent::Touch(e))) if e.numPoints == 0));
+        let truncated = line.split(' ').take(11).collect::<Vec<_>>().join(" ");
+        assert!(parse_line(&truncated).is_none(), "{truncated:?} was parsed");
+    }
+
+    #[test]
+    fn more_than_max_touch_points() {
+        let line = touch_line(&touch_event(MAX_TOUCH_POINTS));
+        let fields: Vec<_> = line.split(' ').collect();
+        // The last point again
+        let point = fields[fields.len() - 10..].join(" ");
+        let line = format!("{line} {point}");
+        assert!(parse_line(&line).is_none());
+    }
+
+    #[derive(Clone, Default)]
+    struct SharedTrace(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedTrace {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    static NO_CALLBACKS: OH_NativeXComponent_Callback = OH_NativeXComponent_Callback {
+        OnSurfaceCreated: None,
+        OnSurfaceChanged: None,
+        OnSurfaceDestroyed: None,
+        DispatchTouchEvent: None,
+    };
+
+    #[test]
+    fn recorded_session_replays() {
+        let trace = SharedTrace::default();
+        let callbacks = record(NO_CALLBACKS, trace.clone());
+        let mock = MockXComponent::new("record");
+        let touch_events = TouchEventBuilder::down(0, (1.0, 2.0))
+            .down_pointer(1, (3.0, 4.0))
+            .up()
+            .up()
+            .build();
+        mock.replay(
+            callbacks,
+            [
+                MockEvent::SurfaceCreated,
+                MockEvent::SurfaceChanged {
+                    width: 720,
+                    height: 1280,
+                },
+            ]
+            .into_iter()
+            .chain(touch_events.iter().copied().map(MockEvent::Touch))
+            .chain([MockEvent::SurfaceDestroyed]),
+        );
+        flush().unwrap();
+        let trace = String::from_utf8(trace.0.lock().unwrap().clone()).unwrap();
+        let events = load_trace(&trace).unwrap();
+        assert_eq!(events.len(), 3 + touch_events.len());
+        assert!(events.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+        assert!(matches!(
+            events[1].1,
+            MockEvent::SurfaceChanged {
+                width: 720,
+                height: 1280
+            }
+        ));
+        for ((_, event), touch_event) in events[2..].iter().zip(&touch_events) {
+            let MockEvent::Touch(event) = event else {
+                panic!("{event:?} is not a touch event");
+            };
+            assert_eq!(touch_line(event), touch_line(touch_event));
+        }
+    }
+}
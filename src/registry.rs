@@ -0,0 +1,319 @@
+//! Per-component state for pages with multiple XComponents
+//!
+//! ArkUI calls the same callbacks for every XComponent of a library, and the callbacks carry no
+//! user data. [`Registry`] is an [`XComponentHandler`] which creates an [`XComponentInstance`]
+//! for each XComponent in `OnSurfaceCreated`, routes all callbacks of the XComponent to it, and
+//! drops it after `OnSurfaceDestroyed`. Instances are identified by the id set in ArkTS, see
+//! [`XComponent::id`], and can be reached from elsewhere on the UI thread via [`with_instance`].
+//!
+//! ## Example
+//! ```no_run
+//! use xcomponent::registry::{self, Registry, XComponentInstance};
+//! use xcomponent::touch::TouchEvent;
+//! use xcomponent::XComponent;
+//!
+//! struct Canvas {
+//!     strokes: Vec<(f32, f32)>,
+//! }
+//!
+//! impl XComponentInstance for Canvas {
+//!     fn new(_xcomponent: &XComponent<'_>, id: &str) -> Self {
+//!         println!("Created canvas {id}");
+//!         Canvas { strokes: Vec::new() }
+//!     }
+//!
+//!     fn on_touch(&mut self, _xcomponent: &XComponent<'_>, touch_event: TouchEvent) {
+//!         self.strokes.push((touch_event.x, touch_event.y));
+//!     }
+//! }
+//!
+//! # #[cfg(all(feature = "register", not(feature = "napi-ohos-v1")))]
+//! fn init(exports: napi_ohos::JsObject, env: napi_ohos::Env) -> napi_ohos::Result<()> {
+//!     xcomponent::handler::register_handler::<Registry<Canvas>, _>(&exports, &env)
+//!         .expect("Registering the registry failed");
+//!     Ok(())
+//! }
+//!
+//! // E.g. from a NAPI function clearing the canvas with the id "left":
+//! registry::with_instance("left", |canvas: &mut Canvas| canvas.strokes.clear());
+//! ```
+
+use crate::handler::{SurfaceChanged, XComponentHandler};
+use crate::key::KeyEvent;
+use crate::log::{error, warn};
+use crate::mouse::MouseEvent;
+use crate::touch::TouchEvent;
+use crate::XComponent;
+use core::any::Any;
+use core::cell::RefCell;
+use core::marker::PhantomData;
+use ohos_sys::xcomponent::OH_NativeXComponent;
+use std::collections::HashMap;
+
+/// The state of one XComponent, receiving its callbacks
+///
+/// All methods except [`XComponentInstance::new`] default to doing nothing. They are called on
+/// the UI thread.
+pub trait XComponentInstance: 'static {
+    /// Creates the instance for the XComponent with `id`, when its surface was created
+    fn new(xcomponent: &XComponent<'_>, id: &str) -> Self;
+
+    /// Called right after [`XComponentInstance::new`]
+    fn on_surface_created(&mut self, xcomponent: &XComponent<'_>) {
+        let _ = xcomponent;
+    }
+
+    fn on_surface_changed(&mut self, xcomponent: &XComponent<'_>) {
+        let _ = xcomponent;
+    }
+
+    /// See [`XComponentHandler::on_surface_resized`]
+    fn on_surface_resized(&mut self, xcomponent: &XComponent<'_>, change: SurfaceChanged) {
+        let _ = (xcomponent, change);
+    }
+
+    /// The surface was destroyed, the instance is dropped afterwards
+    fn on_surface_destroyed(&mut self, xcomponent: &XComponent<'_>) {
+        let _ = xcomponent;
+    }
+
+    #[cfg(feature = "api-12")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "api-12")))]
+    fn on_surface_show(&mut self, xcomponent: &XComponent<'_>) {
+        let _ = xcomponent;
+    }
+
+    /// The surface was hidden, rendering should stop until it is shown again
+    #[cfg(feature = "api-12")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "api-12")))]
+    fn on_surface_hide(&mut self, xcomponent: &XComponent<'_>) {
+        let _ = xcomponent;
+    }
+
+    fn on_touch(&mut self, xcomponent: &XComponent<'_>, touch_event: TouchEvent) {
+        let _ = (xcomponent, touch_event);
+    }
+
+    fn on_mouse(&mut self, xcomponent: &XComponent<'_>, mouse_event: MouseEvent) {
+        let _ = (xcomponent, mouse_event);
+    }
+
+    /// The mouse entered (`is_hover == true`) or left the XComponent
+    fn on_hover(&mut self, is_hover: bool) {
+        let _ = is_hover;
+    }
+
+    fn on_focus(&mut self, xcomponent: &XComponent<'_>) {
+        let _ = xcomponent;
+    }
+
+    fn on_blur(&mut self, xcomponent: &XComponent<'_>) {
+        let _ = xcomponent;
+    }
+
+    fn on_key(&mut self, xcomponent: &XComponent<'_>, key_event: KeyEvent) {
+        let _ = (xcomponent, key_event);
+    }
+
+    #[cfg(feature = "axis")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "axis")))]
+    fn on_axis(&mut self, axis_event: crate::axis::AxisEvent) {
+        let _ = axis_event;
+    }
+}
+
+struct Entry {
+    id: String,
+    /// `None` while the instance is dispatched to
+    instance: Option<Box<dyn Any>>,
+}
+
+thread_local! {
+    /// The instances by the address of their `OH_NativeXComponent`
+    static INSTANCES: RefCell<HashMap<usize, Entry>> = RefCell::new(HashMap::new());
+}
+
+/// An instance taken out of [`INSTANCES`], which is put back when dropped, also if the callback
+/// using it panicked
+struct Taken {
+    key: usize,
+    instance: Option<Box<dyn Any>>,
+}
+
+impl Taken {
+    fn get<T: 'static>(&mut self) -> Option<&mut T> {
+        self.instance.as_mut()?.downcast_mut()
+    }
+}
+
+impl Drop for Taken {
+    fn drop(&mut self) {
+        let instance = self.instance.take();
+        let removed = INSTANCES.with_borrow_mut(|instances| match instances.get_mut(&self.key) {
+            Some(entry) => {
+                entry.instance = instance;
+                None
+            }
+            None => instance,
+        });
+        // Drop the instance outside of the borrow, its `Drop` may use `with_instance`.
+        drop(removed);
+    }
+}
+
+/// Removes the entry of an XComponent when dropped, also if its `on_surface_destroyed` panicked
+struct RemoveOnDrop(usize);
+
+impl Drop for RemoveOnDrop {
+    fn drop(&mut self) {
+        let entry = INSTANCES.with_borrow_mut(|instances| instances.remove(&self.0));
+        // Drop the instance outside of the borrow, its `Drop` may use `with_instance`.
+        drop(entry);
+    }
+}
+
+/// [`XComponentHandler`] routing the callbacks of each XComponent to its own `T`, see the
+/// [module documentation](self)
+pub struct Registry<T>(PhantomData<T>);
+
+impl<T: XComponentInstance> Registry<T> {
+    /// Calls `f` with the instance of `xcomponent`
+    ///
+    /// Like in [`with_instance`], the instance is taken out of the map during the call, so that
+    /// `f` may use [`with_instance`] for other instances.
+    fn dispatch(xcomponent: *mut OH_NativeXComponent, f: impl FnOnce(&mut T)) {
+        let key = xcomponent as usize;
+        let instance = INSTANCES.with_borrow_mut(|instances| {
+            instances
+                .get_mut(&key)
+                .and_then(|entry| entry.instance.take())
+        });
+        let mut taken = Taken { key, instance };
+        if let Some(instance) = taken.get::<T>() {
+            f(instance);
+        }
+    }
+}
+
+impl<T: XComponentInstance> XComponentHandler for Registry<T> {
+    fn on_surface_created(xcomponent: &XComponent<'_>) {
+        let id = match xcomponent.id() {
+            Ok(id) => id,
+            Err(res) => {
+                error!("Querying the id of a new XComponent failed with {res}");
+                return;
+            }
+        };
+        let instance = T::new(xcomponent, &id);
+        let previous = INSTANCES.with_borrow_mut(|instances| {
+            instances.insert(
+                xcomponent.xcomponent.as_ptr() as usize,
+                Entry {
+                    id,
+                    instance: Some(Box::new(instance)),
+                },
+            )
+        });
+        if let Some(previous) = previous {
+            warn!(
+                "XComponent {} was created again without being destroyed",
+                previous.id
+            );
+        }
+        Self::dispatch(xcomponent.xcomponent.as_ptr(), |instance| {
+            instance.on_surface_created(xcomponent)
+        });
+    }
+
+    fn on_surface_changed(xcomponent: &XComponent<'_>) {
+        Self::dispatch(xcomponent.xcomponent.as_ptr(), |instance| {
+            instance.on_surface_changed(xcomponent)
+        });
+    }
+
+    fn on_surface_resized(xcomponent: &XComponent<'_>, change: SurfaceChanged) {
+        Self::dispatch(xcomponent.xcomponent.as_ptr(), |instance| {
+            instance.on_surface_resized(xcomponent, change)
+        });
+    }
+
+    fn on_surface_destroyed(xcomponent: &XComponent<'_>) {
+        let _remove = RemoveOnDrop(xcomponent.xcomponent.as_ptr() as usize);
+        Self::dispatch(xcomponent.xcomponent.as_ptr(), |instance| {
+            instance.on_surface_destroyed(xcomponent)
+        });
+    }
+
+    #[cfg(feature = "api-12")]
+    fn on_surface_show(xcomponent: &XComponent<'_>) {
+        Self::dispatch(xcomponent.xcomponent.as_ptr(), |instance| {
+            instance.on_surface_show(xcomponent)
+        });
+    }
+
+    #[cfg(feature = "api-12")]
+    fn on_surface_hide(xcomponent: &XComponent<'_>) {
+        Self::dispatch(xcomponent.xcomponent.as_ptr(), |instance| {
+            instance.on_surface_hide(xcomponent)
+        });
+    }
+
+    fn on_touch(xcomponent: &XComponent<'_>, touch_event: TouchEvent) {
+        Self::dispatch(xcomponent.xcomponent.as_ptr(), |instance| {
+            instance.on_touch(xcomponent, touch_event)
+        });
+    }
+
+    fn on_mouse(xcomponent: &XComponent<'_>, mouse_event: MouseEvent) {
+        Self::dispatch(xcomponent.xcomponent.as_ptr(), |instance| {
+            instance.on_mouse(xcomponent, mouse_event)
+        });
+    }
+
+    fn on_hover(xcomponent: *mut OH_NativeXComponent, is_hover: bool) {
+        Self::dispatch(xcomponent, |instance| instance.on_hover(is_hover));
+    }
+
+    fn on_focus(xcomponent: &XComponent<'_>) {
+        Self::dispatch(xcomponent.xcomponent.as_ptr(), |instance| {
+            instance.on_focus(xcomponent)
+        });
+    }
+
+    fn on_blur(xcomponent: &XComponent<'_>) {
+        Self::dispatch(xcomponent.xcomponent.as_ptr(), |instance| {
+            instance.on_blur(xcomponent)
+        });
+    }
+
+    fn on_key(xcomponent: &XComponent<'_>, key_event: KeyEvent) {
+        Self::dispatch(xcomponent.xcomponent.as_ptr(), |instance| {
+            instance.on_key(xcomponent, key_event)
+        });
+    }
+
+    #[cfg(feature = "axis")]
+    fn on_axis(xcomponent: *mut OH_NativeXComponent, axis_event: crate::axis::AxisEvent) {
+        Self::dispatch(xcomponent, |instance| instance.on_axis(axis_event));
+    }
+}
+
+/// Calls `f` with the instance of the XComponent with `id`
+///
+/// Returns `None` if there is no such instance of type `T` on the calling thread, or if the
+/// instance is currently in use, i.e. handling a callback or in an outer `with_instance`.
+pub fn with_instance<T: XComponentInstance, R>(id: &str, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+    let mut taken = INSTANCES.with_borrow_mut(|instances| {
+        let (key, entry) = instances.iter_mut().find(|(_, entry)| entry.id == id)?;
+        Some(Taken {
+            key: *key,
+            instance: Some(entry.instance.take()?),
+        })
+    })?;
+    taken.get::<T>().map(f)
+}
+
+/// The ids of the XComponents with an instance on the calling thread
+pub fn ids() -> Vec<String> {
+    INSTANCES.with_borrow(|instances| instances.values().map(|entry| entry.id.clone()).collect())
+}
@@ -0,0 +1,111 @@
+//! Native ArkUI nodes as the content of an XComponent
+//!
+//! Instead of rendering into the surface of the XComponent, an app can attach a tree of nodes
+//! created via the native node API of ArkUI to the XComponent, e.g. a node hosting its own surface
+//! which it produces frames into. [`XComponent::attach_native_root_node`] attaches the root of the
+//! tree, and the returned [`AttachedRootNode`] detaches it again when dropped.
+//!
+//! Since API 20, ArkUI recommends `OH_ArkUI_NodeContent_AddNode` instead.
+//!
+//! ## Example
+//! ```no_run
+//! # use ohos_sys::xcomponent::OH_NativeXComponent;
+//! # use core::ffi::c_void;
+//! # let root: ohos_sys_opaque_types::ArkUI_NodeHandle = core::ptr::null_mut();
+//! # let (xcomponent, window): (*mut OH_NativeXComponent, *mut c_void) = todo!();
+//! let xc = xcomponent::XComponent::new(xcomponent, window).unwrap();
+//! // `root` was created via the native node API.
+//! let attached = unsafe { xc.attach_native_root_node(root) }.unwrap();
+//! // Dropping the guard detaches the node.
+//! drop(attached);
+//! ```
+
+use crate::log::error;
+use crate::trace::ffi_span;
+use crate::XComponent;
+use core::ptr::NonNull;
+#[allow(deprecated)]
+use ohos_sys::xcomponent::{
+    OH_NativeXComponent, OH_NativeXComponent_AttachNativeRootNode,
+    OH_NativeXComponent_DetachNativeRootNode,
+};
+use ohos_sys_opaque_types::ArkUI_NodeHandle;
+
+/// A node attached to an XComponent, detached when dropped
+#[derive(Debug)]
+#[must_use = "dropping the guard detaches the node"]
+pub struct AttachedRootNode {
+    xcomponent: NonNull<OH_NativeXComponent>,
+    root: ArkUI_NodeHandle,
+}
+
+impl AttachedRootNode {
+    /// The attached node
+    pub fn root(&self) -> ArkUI_NodeHandle {
+        self.root
+    }
+
+    /// Detaches the node, returning the error code if ArkUI failed to detach it
+    pub fn detach(self) -> Result<(), i32> {
+        let this = core::mem::ManuallyDrop::new(self);
+        detach(this.xcomponent, this.root)
+    }
+}
+
+impl Drop for AttachedRootNode {
+    fn drop(&mut self) {
+        let _ = detach(self.xcomponent, self.root);
+    }
+}
+
+#[allow(deprecated)]
+fn detach(xcomponent: NonNull<OH_NativeXComponent>, root: ArkUI_NodeHandle) -> Result<(), i32> {
+    #[cfg(feature = "mock")]
+    if crate::mock::with_mock(xcomponent.as_ptr(), |_| ()).is_some() {
+        return Ok(());
+    }
+    let mut span = ffi_span!("OH_NativeXComponent_DetachNativeRootNode", xcomponent);
+    // SAFETY: The node was attached to the XComponent, both outlive the guard.
+    let res = unsafe { OH_NativeXComponent_DetachNativeRootNode(xcomponent.as_ptr(), root) };
+    span.set_result(res);
+    if res != 0 {
+        error!("OH_NativeXComponent_DetachNativeRootNode failed with {res}");
+        return Err(res);
+    }
+    Ok(())
+}
+
+impl XComponent<'_> {
+    /// Attaches the native node `root` to the XComponent, until the returned guard is dropped
+    ///
+    /// # Safety
+    ///
+    /// `root` must be a valid node created via the native node API, and both `root` and the
+    /// `OH_NativeXComponent` must stay valid until the guard is dropped.
+    #[allow(deprecated)]
+    pub unsafe fn attach_native_root_node(
+        &self,
+        root: ArkUI_NodeHandle,
+    ) -> Result<AttachedRootNode, i32> {
+        crate::thread::check_ui_thread("XComponent::attach_native_root_node");
+        #[cfg(feature = "mock")]
+        let mocked = crate::mock::with_mock(self.xcomponent.as_ptr(), |_| ()).is_some();
+        #[cfg(not(feature = "mock"))]
+        let mocked = false;
+        if !mocked {
+            let mut span = ffi_span!("OH_NativeXComponent_AttachNativeRootNode", self.xcomponent);
+            // SAFETY: The caller ensures that `root` is a valid node.
+            let res =
+                unsafe { OH_NativeXComponent_AttachNativeRootNode(self.xcomponent.as_ptr(), root) };
+            span.set_result(res);
+            if res != 0 {
+                error!("OH_NativeXComponent_AttachNativeRootNode failed with {res}");
+                return Err(res);
+            }
+        }
+        Ok(AttachedRootNode {
+            xcomponent: self.xcomponent,
+            root,
+        })
+    }
+}
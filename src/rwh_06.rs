@@ -0,0 +1,32 @@
+//! `raw-window-handle` 0.6 support
+
+use crate::XComponent;
+use rwh_06::{
+    DisplayHandle, HandleError, HasDisplayHandle, HasWindowHandle, OhosNdkWindowHandle,
+    RawWindowHandle, WindowHandle,
+};
+
+impl HasWindowHandle for XComponent<'_> {
+    fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
+        let raw = RawWindowHandle::OhosNdk(OhosNdkWindowHandle::new(self.window.cast()));
+        // SAFETY: The window is valid until `OnSurfaceDestroyed`, and XComponents are only
+        // created for the duration of a callback.
+        Ok(unsafe { WindowHandle::borrow_raw(raw) })
+    }
+}
+
+impl HasDisplayHandle for XComponent<'_> {
+    fn display_handle(&self) -> Result<DisplayHandle<'_>, HandleError> {
+        Ok(DisplayHandle::ohos())
+    }
+}
+
+impl HasWindowHandle for crate::native_window::NativeWindow {
+    fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
+        let window =
+            core::ptr::NonNull::new(self.as_ptr().cast()).ok_or(HandleError::Unavailable)?;
+        let raw = RawWindowHandle::OhosNdk(OhosNdkWindowHandle::new(window));
+        // SAFETY: `self` holds a reference to the window.
+        Ok(unsafe { WindowHandle::borrow_raw(raw) })
+    }
+}
@@ -0,0 +1,372 @@
+//! Diagnostics for suspicious usage of XComponents
+//!
+//! With the `strict` feature, [`register_xcomponent_callbacks`](crate::register_xcomponent_callbacks)
+//! forwards the callbacks through this module, which tracks whether a component has a surface and
+//! whether it is dispatching a touch event. Suspicious usage of a registered component is then
+//! reported according to its [`StrictMode`]:
+//!
+//! * Registering callbacks for a component twice
+//! * Querying the size of a component without a surface
+//! * Querying the touch event of a component outside of `DispatchTouchEvent`
+//!
+//! Components are tracked by address, from registering their callbacks until
+//! [`unregister_handler`](crate::handler::unregister_handler). ArkUI may free a component once its
+//! surface was destroyed and reuse the address for a new one, so callbacks registered after that
+//! start a new registration, with a new generation and without the mode of the previous one.
+//!
+//! ## Example
+//! ```no_run
+//! # use ohos_sys::xcomponent::OH_NativeXComponent;
+//! # use core::ffi::c_void;
+//! use xcomponent::strict::{self, StrictMode};
+//!
+//! // Fail early on misuse of any component while developing.
+//! strict::set_default_mode(StrictMode::Enforce);
+//!
+//! extern "C" fn on_surface_created(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+//!     let xc = xcomponent::XComponent::new(xcomponent, window).unwrap();
+//!     // This component is known to query its size late, only warn.
+//!     strict::set_mode(&xc, StrictMode::Warn);
+//! }
+//! ```
+
+use crate::log::warn;
+use crate::XComponent;
+#[cfg(feature = "register")]
+use core::ffi::c_void;
+use core::fmt;
+use ohos_sys::xcomponent::{OH_NativeXComponent, OH_NativeXComponent_Callback};
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+/// How suspicious usage is reported
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum StrictMode {
+    /// Not at all
+    Off,
+    /// As a logged warning
+    #[default]
+    Warn,
+    /// In debug builds, by returning an error from the API, or by panicking if the API can't fail.
+    /// Release builds log a warning.
+    Enforce,
+}
+
+/// Suspicious usage of an XComponent
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Violation {
+    RegisteredTwice,
+    SizeWithoutSurface,
+    TouchEventOutsideDispatch,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::RegisteredTwice => "callbacks were registered twice",
+            Self::SizeWithoutSurface => "the size was queried without a surface",
+            Self::TouchEventOutsideDispatch => {
+                "the touch event was queried outside of DispatchTouchEvent"
+            }
+        })
+    }
+}
+
+#[derive(Default)]
+struct Component {
+    mode: Option<StrictMode>,
+    callbacks: Option<&'static OH_NativeXComponent_Callback>,
+    /// Distinguishes the registrations of components at the same address
+    generation: u64,
+    has_surface: bool,
+    /// Whether the surface was destroyed, after which the component may have been freed
+    surface_destroyed: bool,
+    dispatching_touch_event: bool,
+}
+
+struct State {
+    default_mode: StrictMode,
+    components: Option<HashMap<usize, Component>>,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    default_mode: StrictMode::Warn,
+    components: None,
+});
+
+fn state() -> MutexGuard<'static, State> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Sets the mode of components without a mode set via [`set_mode`]
+pub fn set_default_mode(mode: StrictMode) {
+    state().default_mode = mode;
+}
+
+/// Sets the mode of `xcomponent`
+pub fn set_mode(xcomponent: &XComponent<'_>, mode: StrictMode) {
+    let mut state = state();
+    let components = state.components.get_or_insert_with(HashMap::new);
+    components
+        .entry(xcomponent.xcomponent.as_ptr() as usize)
+        .or_default()
+        .mode = Some(mode);
+}
+
+/// Reports `violation` if it applies to `xcomponent`, returns `Err` if it should fail the API
+///
+/// Components whose callbacks weren't registered via this module aren't tracked, so nothing is
+/// reported for them.
+pub(crate) fn check(
+    xcomponent: *mut OH_NativeXComponent,
+    violation: Violation,
+) -> Result<(), Violation> {
+    let mode = {
+        let state = state();
+        let Some(component) = state
+            .components
+            .as_ref()
+            .and_then(|components| components.get(&(xcomponent as usize)))
+        else {
+            return Ok(());
+        };
+        let violated = match violation {
+            Violation::RegisteredTwice => {
+                component.callbacks.is_some() && !component.surface_destroyed
+            }
+            Violation::SizeWithoutSurface => {
+                component.callbacks.is_some() && !component.has_surface
+            }
+            Violation::TouchEventOutsideDispatch => {
+                component.callbacks.is_some() && !component.dispatching_touch_event
+            }
+        };
+        if !violated {
+            return Ok(());
+        }
+        component.mode.unwrap_or(state.default_mode)
+    };
+    match mode {
+        StrictMode::Off => Ok(()),
+        StrictMode::Enforce if cfg!(debug_assertions) => Err(violation),
+        StrictMode::Warn | StrictMode::Enforce => {
+            warn!("XComponent {xcomponent:?}: {violation}");
+            Ok(())
+        }
+    }
+}
+
+/// Remembers `callbacks` of `xcomponent` and returns the callbacks to register instead
+#[cfg(feature = "register")]
+pub(crate) fn track(
+    xcomponent: *mut OH_NativeXComponent,
+    callbacks: &'static OH_NativeXComponent_Callback,
+) -> &'static OH_NativeXComponent_Callback {
+    let mut state = state();
+    let components = state.components.get_or_insert_with(HashMap::new);
+    let component = components.entry(xcomponent as usize).or_default();
+    if component.surface_destroyed {
+        // Most likely a new component at the address of a freed one.
+        *component = Component::default();
+    }
+    if component.callbacks.is_none() {
+        component.generation = crate::handle::next_generation();
+    }
+    component.callbacks = Some(callbacks);
+    &PROXY
+}
+
+/// Stops tracking `xcomponent`, e.g. because its callbacks were unregistered
+#[cfg(any(feature = "register", feature = "mock"))]
+pub(crate) fn untrack(xcomponent: *mut OH_NativeXComponent) {
+    if let Some(components) = state().components.as_mut() {
+        components.remove(&(xcomponent as usize));
+    }
+}
+
+#[cfg(feature = "register")]
+static PROXY: OH_NativeXComponent_Callback = OH_NativeXComponent_Callback {
+    OnSurfaceCreated: Some(on_surface_created),
+    OnSurfaceChanged: Some(on_surface_changed),
+    OnSurfaceDestroyed: Some(on_surface_destroyed),
+    DispatchTouchEvent: Some(dispatch_touch_event),
+};
+
+#[cfg(feature = "register")]
+type Callback = Option<unsafe extern "C" fn(*mut OH_NativeXComponent, *mut c_void)>;
+
+/// Updates the state of `xcomponent` and calls the callback selected by `callback`
+///
+/// `after` updates the state once the callback returned, unless the callback unregistered the
+/// component or registered it again.
+#[cfg(feature = "register")]
+unsafe fn forward(
+    xcomponent: *mut OH_NativeXComponent,
+    window: *mut c_void,
+    before: impl FnOnce(&mut Component),
+    callback: impl FnOnce(&OH_NativeXComponent_Callback) -> Callback,
+    after: impl FnOnce(&mut Component),
+) {
+    let (generation, forward) = {
+        let mut state = state();
+        let Some(component) = state
+            .components
+            .as_mut()
+            .and_then(|components| components.get_mut(&(xcomponent as usize)))
+        else {
+            return;
+        };
+        before(component);
+        (component.generation, component.callbacks.and_then(callback))
+    };
+    // The lock is released, so the callback may use the XComponent.
+    if let Some(forward) = forward {
+        forward(xcomponent, window);
+    }
+    let mut state = state();
+    if let Some(component) = state
+        .components
+        .as_mut()
+        .and_then(|components| components.get_mut(&(xcomponent as usize)))
+        .filter(|component| component.generation == generation)
+    {
+        after(component);
+    }
+}
+
+#[cfg(feature = "register")]
+unsafe extern "C" fn on_surface_created(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+    forward(
+        xcomponent,
+        window,
+        |c| {
+            c.has_surface = true;
+            c.surface_destroyed = false;
+        },
+        |c| c.OnSurfaceCreated,
+        |_| {},
+    );
+}
+
+#[cfg(feature = "register")]
+unsafe extern "C" fn on_surface_changed(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+    forward(xcomponent, window, |_| {}, |c| c.OnSurfaceChanged, |_| {});
+}
+
+#[cfg(feature = "register")]
+unsafe extern "C" fn on_surface_destroyed(
+    xcomponent: *mut OH_NativeXComponent,
+    window: *mut c_void,
+) {
+    // The surface is still usable while `OnSurfaceDestroyed` runs.
+    forward(
+        xcomponent,
+        window,
+        |_| {},
+        |c| c.OnSurfaceDestroyed,
+        |c| {
+            c.has_surface = false;
+            c.surface_destroyed = true;
+        },
+    );
+}
+
+#[cfg(feature = "register")]
+unsafe extern "C" fn dispatch_touch_event(
+    xcomponent: *mut OH_NativeXComponent,
+    window: *mut c_void,
+) {
+    forward(
+        xcomponent,
+        window,
+        |c| c.dispatching_touch_event = true,
+        |c| c.DispatchTouchEvent,
+        |c| c.dispatching_touch_event = false,
+    );
+}
+
+#[cfg(all(test, feature = "mock", feature = "register"))]
+mod tests {
+    use super::*;
+    use crate::mock::{MockExports, MockXComponent, TouchEventBuilder};
+    use crate::register_xcomponent_callbacks;
+    use std::cell::Cell;
+
+    thread_local! {
+        static TOOL_TYPE_QUERIED: Cell<bool> = const { Cell::new(false) };
+    }
+
+    unsafe extern "C" fn dispatch_touch_event(
+        xcomponent: *mut OH_NativeXComponent,
+        window: *mut c_void,
+    ) {
+        let xc = XComponent::new(xcomponent, window).unwrap();
+        assert!(xc.touch_point_tool_type(0).is_ok());
+        assert!(xc.touch_point_tilt(0).is_ok());
+        TOOL_TYPE_QUERIED.set(true);
+    }
+
+    static CALLBACKS: OH_NativeXComponent_Callback = OH_NativeXComponent_Callback {
+        OnSurfaceCreated: None,
+        OnSurfaceChanged: None,
+        OnSurfaceDestroyed: None,
+        DispatchTouchEvent: Some(dispatch_touch_event),
+    };
+
+    fn register(mock: &MockXComponent) -> Result<(), crate::RegisterCallbackError> {
+        register_xcomponent_callbacks(&MockExports::new(mock), &(), &CALLBACKS)
+    }
+
+    #[test]
+    fn registered_twice() {
+        let mock = MockXComponent::new("registered-twice");
+        register(&mock).unwrap();
+        set_mode(&mock.xcomponent(), StrictMode::Enforce);
+        assert!(register(&mock).is_err());
+    }
+
+    #[test]
+    fn registered_again_after_surface_destroyed() {
+        let mock = MockXComponent::new("registered-again");
+        register(&mock).unwrap();
+        set_mode(&mock.xcomponent(), StrictMode::Enforce);
+        let callbacks = mock.registered_callbacks().unwrap();
+        mock.surface_created(callbacks);
+        mock.surface_destroyed(callbacks);
+
+        // A new component at the same address doesn't inherit the registration or the mode.
+        register(&mock).unwrap();
+        assert!(mock.xcomponent().try_size().is_ok());
+        set_mode(&mock.xcomponent(), StrictMode::Enforce);
+        assert!(mock.xcomponent().try_size().is_err());
+        assert!(register(&mock).is_err());
+    }
+
+    #[test]
+    fn unregistered() {
+        let mock = MockXComponent::new("unregistered");
+        register(&mock).unwrap();
+        set_mode(&mock.xcomponent(), StrictMode::Enforce);
+        crate::handler::unregister_handler(mock.raw_xcomponent()).unwrap();
+        register(&mock).unwrap();
+        set_mode(&mock.xcomponent(), StrictMode::Enforce);
+        assert!(register(&mock).is_err());
+    }
+
+    #[test]
+    fn touch_point_outside_dispatch() {
+        let mock = MockXComponent::new("touch-point");
+        register(&mock).unwrap();
+        set_mode(&mock.xcomponent(), StrictMode::Enforce);
+        let callbacks = mock.registered_callbacks().unwrap();
+        mock.surface_created(callbacks);
+        mock.dispatch_touch_events(callbacks, TouchEventBuilder::down(0, (0.0, 0.0)).build());
+        assert!(TOOL_TYPE_QUERIED.get());
+
+        // The mock still returns the last touch event, but ArkUI wouldn't.
+        let xc = mock.xcomponent();
+        assert_eq!(xc.touch_point_tool_type(0), Err(-1));
+        assert_eq!(xc.touch_point_tilt(0), Err(-1));
+        assert!(xc.get_touch_event().is_err());
+    }
+}
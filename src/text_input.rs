@@ -0,0 +1,618 @@
+//! Text input via the input method framework
+//!
+//! Key events are not enough for text fields rendered into an XComponent: input methods for CJK
+//! languages compose text over several key strokes, and soft keyboards don't send key events at
+//! all. [`TextInput::attach`] connects a [`TextInputHandler`] to the input method, which then
+//! receives the committed text, the composition (preview) text and editing commands.
+//!
+//! The native callbacks don't identify the text field they belong to, so there can only be one
+//! attached handler per process, which matches the single focused text field. Attaching a handler
+//! replaces the previous one.
+//!
+//! ## Example
+//! ```no_run
+//! use xcomponent::text_input::{TextInput, TextInputHandler};
+//!
+//! #[derive(Default)]
+//! struct TextField {
+//!     text: String,
+//!     preview: String,
+//! }
+//!
+//! impl TextInputHandler for TextField {
+//!     fn insert_text(&mut self, text: &str) {
+//!         self.preview.clear();
+//!         self.text.push_str(text);
+//!     }
+//!
+//!     fn set_preview_text(&mut self, text: &str, _start: i32, _end: i32) {
+//!         self.preview = text.to_owned();
+//!     }
+//! }
+//!
+//! // Once the text field gained focus:
+//! let input = TextInput::attach(TextField::default(), true).unwrap();
+//! input.set_cursor_rect(100.0, 200.0, 2.0, 40.0).unwrap();
+//! // Once it lost focus:
+//! drop(input);
+//! ```
+
+use crate::log::error;
+use crate::trace::ffi_span;
+use core::ptr::{self, NonNull};
+use ohos_sys::inputmethod::attach_options::{OH_AttachOptions_Create, OH_AttachOptions_Destroy};
+use ohos_sys::inputmethod::controller::{
+    OH_InputMethodController_Attach, OH_InputMethodController_Detach,
+};
+use ohos_sys::inputmethod::cursor_info::{OH_CursorInfo_Create, OH_CursorInfo_Destroy};
+use ohos_sys::inputmethod::inputmethod_proxy::{
+    InputMethod_InputMethodProxy, OH_InputMethodProxy_HideKeyboard,
+    OH_InputMethodProxy_NotifyCursorUpdate, OH_InputMethodProxy_NotifySelectionChange,
+    OH_InputMethodProxy_ShowKeyboard,
+};
+use ohos_sys::inputmethod::private_command::InputMethod_PrivateCommand;
+use ohos_sys::inputmethod::text_config::{
+    InputMethod_TextConfig, OH_TextConfig_SetEnterKeyType, OH_TextConfig_SetInputType,
+    OH_TextConfig_SetPreviewTextSupport,
+};
+use ohos_sys::inputmethod::text_editor_proxy::*;
+use ohos_sys::inputmethod::types::{
+    InputMethodResult, InputMethod_Direction, InputMethod_EnterKeyType, InputMethod_ExtendAction,
+    InputMethod_KeyboardStatus, InputMethod_TextInputType,
+};
+use std::sync::{Mutex, MutexGuard};
+
+// OH_NATIVEXCOMPONENT_RESULT_FAILED
+const RESULT_FAILED: i32 = -1;
+
+/// The kind of text a text field accepts, which selects the layout of the soft keyboard
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum InputType {
+    None,
+    #[default]
+    Text,
+    Multiline,
+    Number,
+    Phone,
+    Datetime,
+    EmailAddress,
+    Url,
+    VisiblePassword,
+    NumberPassword,
+    NumberDecimal,
+}
+
+impl InputType {
+    pub fn as_raw(self) -> InputMethod_TextInputType {
+        match self {
+            Self::None => InputMethod_TextInputType::IME_TEXT_INPUT_TYPE_NONE,
+            Self::Text => InputMethod_TextInputType::IME_TEXT_INPUT_TYPE_TEXT,
+            Self::Multiline => InputMethod_TextInputType::IME_TEXT_INPUT_TYPE_MULTILINE,
+            Self::Number => InputMethod_TextInputType::IME_TEXT_INPUT_TYPE_NUMBER,
+            Self::Phone => InputMethod_TextInputType::IME_TEXT_INPUT_TYPE_PHONE,
+            Self::Datetime => InputMethod_TextInputType::IME_TEXT_INPUT_TYPE_DATETIME,
+            Self::EmailAddress => InputMethod_TextInputType::IME_TEXT_INPUT_TYPE_EMAIL_ADDRESS,
+            Self::Url => InputMethod_TextInputType::IME_TEXT_INPUT_TYPE_URL,
+            Self::VisiblePassword => {
+                InputMethod_TextInputType::IME_TEXT_INPUT_TYPE_VISIBLE_PASSWORD
+            }
+            Self::NumberPassword => InputMethod_TextInputType::IME_TEXT_INPUT_TYPE_NUMBER_PASSWORD,
+            Self::NumberDecimal => InputMethod_TextInputType::IME_TEXT_INPUT_TYPE_NUMBER_DECIMAL,
+        }
+    }
+}
+
+/// The label of the enter key of the soft keyboard
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EnterKey {
+    #[default]
+    Unspecified,
+    None,
+    Go,
+    Search,
+    Send,
+    Next,
+    Done,
+    Previous,
+    Newline,
+}
+
+impl EnterKey {
+    pub fn from_raw(key: InputMethod_EnterKeyType) -> Option<Self> {
+        let key = match key {
+            InputMethod_EnterKeyType::IME_ENTER_KEY_UNSPECIFIED => Self::Unspecified,
+            InputMethod_EnterKeyType::IME_ENTER_KEY_NONE => Self::None,
+            InputMethod_EnterKeyType::IME_ENTER_KEY_GO => Self::Go,
+            InputMethod_EnterKeyType::IME_ENTER_KEY_SEARCH => Self::Search,
+            InputMethod_EnterKeyType::IME_ENTER_KEY_SEND => Self::Send,
+            InputMethod_EnterKeyType::IME_ENTER_KEY_NEXT => Self::Next,
+            InputMethod_EnterKeyType::IME_ENTER_KEY_DONE => Self::Done,
+            InputMethod_EnterKeyType::IME_ENTER_KEY_PREVIOUS => Self::Previous,
+            InputMethod_EnterKeyType::IME_ENTER_KEY_NEWLINE => Self::Newline,
+            _ => return None,
+        };
+        Some(key)
+    }
+
+    pub fn as_raw(self) -> InputMethod_EnterKeyType {
+        match self {
+            Self::Unspecified => InputMethod_EnterKeyType::IME_ENTER_KEY_UNSPECIFIED,
+            Self::None => InputMethod_EnterKeyType::IME_ENTER_KEY_NONE,
+            Self::Go => InputMethod_EnterKeyType::IME_ENTER_KEY_GO,
+            Self::Search => InputMethod_EnterKeyType::IME_ENTER_KEY_SEARCH,
+            Self::Send => InputMethod_EnterKeyType::IME_ENTER_KEY_SEND,
+            Self::Next => InputMethod_EnterKeyType::IME_ENTER_KEY_NEXT,
+            Self::Done => InputMethod_EnterKeyType::IME_ENTER_KEY_DONE,
+            Self::Previous => InputMethod_EnterKeyType::IME_ENTER_KEY_PREVIOUS,
+            Self::Newline => InputMethod_EnterKeyType::IME_ENTER_KEY_NEWLINE,
+        }
+    }
+}
+
+/// Direction to move the cursor in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub fn from_raw(direction: InputMethod_Direction) -> Option<Self> {
+        let direction = match direction {
+            InputMethod_Direction::IME_DIRECTION_UP => Self::Up,
+            InputMethod_Direction::IME_DIRECTION_DOWN => Self::Down,
+            InputMethod_Direction::IME_DIRECTION_LEFT => Self::Left,
+            InputMethod_Direction::IME_DIRECTION_RIGHT => Self::Right,
+            _ => return None,
+        };
+        Some(direction)
+    }
+}
+
+/// Editing command of the input method
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ExtendAction {
+    SelectAll,
+    Cut,
+    Copy,
+    Paste,
+}
+
+impl ExtendAction {
+    pub fn from_raw(action: InputMethod_ExtendAction) -> Option<Self> {
+        let action = match action {
+            InputMethod_ExtendAction::IME_EXTEND_ACTION_SELECT_ALL => Self::SelectAll,
+            InputMethod_ExtendAction::IME_EXTEND_ACTION_CUT => Self::Cut,
+            InputMethod_ExtendAction::IME_EXTEND_ACTION_COPY => Self::Copy,
+            InputMethod_ExtendAction::IME_EXTEND_ACTION_PASTE => Self::Paste,
+            _ => return None,
+        };
+        Some(action)
+    }
+}
+
+/// Properties of the text field, queried by the input method when attaching
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TextConfig {
+    pub input_type: InputType,
+    pub enter_key: EnterKey,
+    /// Whether the text field displays the composition text, see
+    /// [`TextInputHandler::set_preview_text`]. Otherwise the input method displays it itself.
+    pub preview_text: bool,
+}
+
+impl Default for TextConfig {
+    fn default() -> Self {
+        Self {
+            input_type: InputType::Text,
+            enter_key: EnterKey::Unspecified,
+            preview_text: true,
+        }
+    }
+}
+
+/// Receives the input of the input method
+///
+/// Positions and lengths are in UTF-16 code units, as used by the input method framework. The
+/// callbacks may be called on any thread.
+pub trait TextInputHandler: Send {
+    fn text_config(&mut self) -> TextConfig {
+        TextConfig::default()
+    }
+
+    /// Commits `text` at the cursor, replacing the selection and the preview text
+    fn insert_text(&mut self, text: &str);
+
+    /// Displays `text` as composition text, replacing the text from `start` to `end`
+    ///
+    /// `start` and `end` are `-1` if the preview text should be inserted at the cursor.
+    fn set_preview_text(&mut self, _text: &str, _start: i32, _end: i32) {}
+
+    /// Keeps the preview text as committed text
+    fn finish_preview_text(&mut self) {}
+
+    /// Deletes `length` characters before the cursor
+    fn delete_backward(&mut self, _length: i32) {}
+
+    /// Deletes `length` characters after the cursor
+    fn delete_forward(&mut self, _length: i32) {}
+
+    fn enter_key(&mut self, _key: EnterKey) {}
+
+    fn move_cursor(&mut self, _direction: Direction) {}
+
+    fn set_selection(&mut self, _start: i32, _end: i32) {}
+
+    fn extend_action(&mut self, _action: ExtendAction) {}
+
+    /// The soft keyboard was shown (`true`) or hidden
+    fn keyboard_visibility_changed(&mut self, _visible: bool) {}
+
+    /// Up to `count` characters before the cursor, for input methods which predict text
+    fn text_before_cursor(&mut self, _count: usize) -> String {
+        String::new()
+    }
+
+    /// Up to `count` characters after the cursor
+    fn text_after_cursor(&mut self, _count: usize) -> String {
+        String::new()
+    }
+
+    /// Position of the cursor in the text
+    fn cursor_index(&mut self) -> i32 {
+        0
+    }
+}
+
+struct State {
+    handler: Option<Box<dyn TextInputHandler>>,
+    /// The text editor proxy of the attached [`TextInput`]
+    editor: usize,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    handler: None,
+    editor: 0,
+});
+
+fn state() -> MutexGuard<'static, State> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn with_handler<R>(f: impl FnOnce(&mut (dyn TextInputHandler + 'static)) -> R) -> Option<R> {
+    let mut state = state();
+    let handler = state.handler.as_deref_mut()?;
+    crate::panic_guard::catch("TextInputHandler", || f(handler))
+}
+
+fn check(function: &str, res: InputMethodResult) -> Result<(), i32> {
+    res.map_err(|err| {
+        let res = err.0.get() as i32;
+        error!("{function} failed with {res}");
+        res
+    })
+}
+
+/// A connection to the input method, detached when dropped
+#[derive(Debug)]
+pub struct TextInput {
+    editor: NonNull<InputMethod_TextEditorProxy>,
+    input_method: NonNull<InputMethod_InputMethodProxy>,
+}
+
+// SAFETY: The proxies may be used from any thread.
+unsafe impl Send for TextInput {}
+
+impl TextInput {
+    /// Attaches `handler` to the input method, and shows the soft keyboard if `show_keyboard`
+    pub fn attach(
+        handler: impl TextInputHandler + 'static,
+        show_keyboard: bool,
+    ) -> Result<Self, i32> {
+        // SAFETY: The proxy is destroyed again if attaching fails.
+        let editor = NonNull::new(unsafe { OH_TextEditorProxy_Create() }).ok_or(RESULT_FAILED)?;
+        // The handler needs to be in place before attaching, which queries the text config.
+        let previous = {
+            let mut state = state();
+            state.editor = editor.as_ptr() as usize;
+            state.handler.replace(Box::new(handler))
+        };
+        drop(previous);
+        // SAFETY: `editor` is a valid proxy.
+        match unsafe { attach(editor, show_keyboard) } {
+            Ok(input_method) => Ok(Self {
+                editor,
+                input_method,
+            }),
+            Err(res) => {
+                let mut state = state();
+                if state.editor == editor.as_ptr() as usize {
+                    state.editor = 0;
+                    state.handler = None;
+                }
+                drop(state);
+                // SAFETY: The proxy isn't attached.
+                unsafe { OH_TextEditorProxy_Destroy(editor.as_ptr()) };
+                Err(res)
+            }
+        }
+    }
+
+    pub fn show_keyboard(&self) -> Result<(), i32> {
+        let _span = ffi_span!("OH_InputMethodProxy_ShowKeyboard");
+        // SAFETY: `self` is attached.
+        let res = unsafe { OH_InputMethodProxy_ShowKeyboard(self.input_method.as_ptr()) };
+        check("OH_InputMethodProxy_ShowKeyboard", res)
+    }
+
+    pub fn hide_keyboard(&self) -> Result<(), i32> {
+        let _span = ffi_span!("OH_InputMethodProxy_HideKeyboard");
+        // SAFETY: `self` is attached.
+        let res = unsafe { OH_InputMethodProxy_HideKeyboard(self.input_method.as_ptr()) };
+        check("OH_InputMethodProxy_HideKeyboard", res)
+    }
+
+    /// Reports the position of the cursor, in pixels relative to the top left corner of the
+    /// display, so that the input method can place its candidate window next to it
+    pub fn set_cursor_rect(&self, left: f64, top: f64, width: f64, height: f64) -> Result<(), i32> {
+        let _span = ffi_span!("OH_InputMethodProxy_NotifyCursorUpdate");
+        // SAFETY: The cursor info is only used during the call.
+        unsafe {
+            let cursor = NonNull::new(OH_CursorInfo_Create(left, top, width, height))
+                .ok_or(RESULT_FAILED)?;
+            let res =
+                OH_InputMethodProxy_NotifyCursorUpdate(self.input_method.as_ptr(), cursor.as_ptr());
+            OH_CursorInfo_Destroy(cursor.as_ptr());
+            check("OH_InputMethodProxy_NotifyCursorUpdate", res)
+        }
+    }
+
+    /// Reports that the text or the selection, from `start` to `end`, changed
+    pub fn notify_selection_change(&self, text: &str, start: i32, end: i32) -> Result<(), i32> {
+        let mut text: Vec<u16> = text.encode_utf16().collect();
+        let _span = ffi_span!("OH_InputMethodProxy_NotifySelectionChange");
+        // SAFETY: `text` is valid for its length during the call.
+        let res = unsafe {
+            OH_InputMethodProxy_NotifySelectionChange(
+                self.input_method.as_ptr(),
+                text.as_mut_ptr(),
+                text.len(),
+                start,
+                end,
+            )
+        };
+        check("OH_InputMethodProxy_NotifySelectionChange", res)
+    }
+}
+
+impl Drop for TextInput {
+    fn drop(&mut self) {
+        {
+            let _span = ffi_span!("OH_InputMethodController_Detach");
+            // SAFETY: `self` is attached, the proxy isn't used afterwards.
+            let res = unsafe { OH_InputMethodController_Detach(self.input_method.as_ptr()) };
+            let _ = check("OH_InputMethodController_Detach", res);
+        }
+        let handler = {
+            let mut state = state();
+            if state.editor != self.editor.as_ptr() as usize {
+                // Another handler was attached in the meantime.
+                None
+            } else {
+                state.editor = 0;
+                state.handler.take()
+            }
+        };
+        drop(handler);
+        // SAFETY: The proxy is detached.
+        unsafe { OH_TextEditorProxy_Destroy(self.editor.as_ptr()) };
+    }
+}
+
+/// Sets the callbacks of `editor` and attaches it to the input method
+unsafe fn attach(
+    editor: NonNull<InputMethod_TextEditorProxy>,
+    show_keyboard: bool,
+) -> Result<NonNull<InputMethod_InputMethodProxy>, i32> {
+    let editor = editor.as_ptr();
+    macro_rules! set {
+        ($setter:ident, $callback:ident) => {
+            check(stringify!($setter), $setter(editor, Some($callback)))?
+        };
+    }
+    set!(OH_TextEditorProxy_SetGetTextConfigFunc, get_text_config);
+    set!(OH_TextEditorProxy_SetInsertTextFunc, insert_text);
+    set!(OH_TextEditorProxy_SetDeleteForwardFunc, delete_forward);
+    set!(OH_TextEditorProxy_SetDeleteBackwardFunc, delete_backward);
+    set!(
+        OH_TextEditorProxy_SetSendKeyboardStatusFunc,
+        keyboard_status
+    );
+    set!(OH_TextEditorProxy_SetSendEnterKeyFunc, enter_key);
+    set!(OH_TextEditorProxy_SetMoveCursorFunc, move_cursor);
+    set!(OH_TextEditorProxy_SetHandleSetSelectionFunc, set_selection);
+    set!(OH_TextEditorProxy_SetHandleExtendActionFunc, extend_action);
+    set!(OH_TextEditorProxy_SetGetLeftTextOfCursorFunc, left_text);
+    set!(OH_TextEditorProxy_SetGetRightTextOfCursorFunc, right_text);
+    set!(OH_TextEditorProxy_SetGetTextIndexAtCursorFunc, cursor_index);
+    set!(
+        OH_TextEditorProxy_SetReceivePrivateCommandFunc,
+        private_command
+    );
+    set!(OH_TextEditorProxy_SetSetPreviewTextFunc, set_preview_text);
+    set!(
+        OH_TextEditorProxy_SetFinishTextPreviewFunc,
+        finish_preview_text
+    );
+
+    let options = NonNull::new(OH_AttachOptions_Create(show_keyboard)).ok_or(RESULT_FAILED)?;
+    let mut input_method = ptr::null_mut();
+    let res = {
+        let _span = ffi_span!("OH_InputMethodController_Attach");
+        OH_InputMethodController_Attach(editor, options.as_ptr(), &mut input_method)
+    };
+    OH_AttachOptions_Destroy(options.as_ptr());
+    check("OH_InputMethodController_Attach", res)?;
+    NonNull::new(input_method).ok_or(RESULT_FAILED)
+}
+
+/// Converts text passed by the input method
+unsafe fn text(text: *const u16, length: usize) -> String {
+    if text.is_null() {
+        return String::new();
+    }
+    String::from_utf16_lossy(core::slice::from_raw_parts(text, length))
+}
+
+/// Copies up to `count` UTF-16 code units of `text` into the buffer of the input method
+///
+/// `from_end` copies the end of `text`, which is the part next to the cursor for the text before
+/// the cursor.
+unsafe fn copy_text(text: &str, count: i32, buffer: *mut u16, length: *mut usize, from_end: bool) {
+    if buffer.is_null() || length.is_null() {
+        return;
+    }
+    let text: Vec<u16> = text.encode_utf16().collect();
+    let count = text.len().min(usize::try_from(count).unwrap_or(0));
+    let text = if from_end {
+        &text[text.len() - count..]
+    } else {
+        &text[..count]
+    };
+    ptr::copy_nonoverlapping(text.as_ptr(), buffer, text.len());
+    *length = text.len();
+}
+
+unsafe extern "C" fn get_text_config(
+    _editor: *mut InputMethod_TextEditorProxy,
+    config: *mut InputMethod_TextConfig,
+) {
+    let text_config = with_handler(|h| h.text_config()).unwrap_or_default();
+    let _ = check(
+        "OH_TextConfig_SetInputType",
+        OH_TextConfig_SetInputType(config, text_config.input_type.as_raw()),
+    );
+    let _ = check(
+        "OH_TextConfig_SetEnterKeyType",
+        OH_TextConfig_SetEnterKeyType(config, text_config.enter_key.as_raw()),
+    );
+    let _ = check(
+        "OH_TextConfig_SetPreviewTextSupport",
+        OH_TextConfig_SetPreviewTextSupport(config, text_config.preview_text),
+    );
+}
+
+unsafe extern "C" fn insert_text(
+    _editor: *mut InputMethod_TextEditorProxy,
+    text_ptr: *const u16,
+    length: usize,
+) {
+    let text = text(text_ptr, length);
+    with_handler(|h| h.insert_text(&text));
+}
+
+unsafe extern "C" fn delete_forward(_editor: *mut InputMethod_TextEditorProxy, length: i32) {
+    with_handler(|h| h.delete_forward(length));
+}
+
+unsafe extern "C" fn delete_backward(_editor: *mut InputMethod_TextEditorProxy, length: i32) {
+    with_handler(|h| h.delete_backward(length));
+}
+
+unsafe extern "C" fn keyboard_status(
+    _editor: *mut InputMethod_TextEditorProxy,
+    status: InputMethod_KeyboardStatus,
+) {
+    let visible = match status {
+        InputMethod_KeyboardStatus::IME_KEYBOARD_STATUS_SHOW => true,
+        InputMethod_KeyboardStatus::IME_KEYBOARD_STATUS_HIDE => false,
+        _ => return,
+    };
+    with_handler(|h| h.keyboard_visibility_changed(visible));
+}
+
+unsafe extern "C" fn enter_key(
+    _editor: *mut InputMethod_TextEditorProxy,
+    key: InputMethod_EnterKeyType,
+) {
+    let key = EnterKey::from_raw(key).unwrap_or_default();
+    with_handler(|h| h.enter_key(key));
+}
+
+unsafe extern "C" fn move_cursor(
+    _editor: *mut InputMethod_TextEditorProxy,
+    direction: InputMethod_Direction,
+) {
+    if let Some(direction) = Direction::from_raw(direction) {
+        with_handler(|h| h.move_cursor(direction));
+    }
+}
+
+unsafe extern "C" fn set_selection(
+    _editor: *mut InputMethod_TextEditorProxy,
+    start: i32,
+    end: i32,
+) {
+    with_handler(|h| h.set_selection(start, end));
+}
+
+unsafe extern "C" fn extend_action(
+    _editor: *mut InputMethod_TextEditorProxy,
+    action: InputMethod_ExtendAction,
+) {
+    if let Some(action) = ExtendAction::from_raw(action) {
+        with_handler(|h| h.extend_action(action));
+    }
+}
+
+unsafe extern "C" fn left_text(
+    _editor: *mut InputMethod_TextEditorProxy,
+    count: i32,
+    buffer: *mut u16,
+    length: *mut usize,
+) {
+    let count_usize = usize::try_from(count).unwrap_or(0);
+    let text = with_handler(|h| h.text_before_cursor(count_usize)).unwrap_or_default();
+    copy_text(&text, count, buffer, length, true);
+}
+
+unsafe extern "C" fn right_text(
+    _editor: *mut InputMethod_TextEditorProxy,
+    count: i32,
+    buffer: *mut u16,
+    length: *mut usize,
+) {
+    let count_usize = usize::try_from(count).unwrap_or(0);
+    let text = with_handler(|h| h.text_after_cursor(count_usize)).unwrap_or_default();
+    copy_text(&text, count, buffer, length, false);
+}
+
+unsafe extern "C" fn cursor_index(_editor: *mut InputMethod_TextEditorProxy) -> i32 {
+    with_handler(|h| h.cursor_index()).unwrap_or(0)
+}
+
+unsafe extern "C" fn private_command(
+    _editor: *mut InputMethod_TextEditorProxy,
+    _commands: *mut *mut InputMethod_PrivateCommand,
+    _size: usize,
+) -> i32 {
+    0
+}
+
+unsafe extern "C" fn set_preview_text(
+    _editor: *mut InputMethod_TextEditorProxy,
+    text_ptr: *const u16,
+    length: usize,
+    start: i32,
+    end: i32,
+) -> i32 {
+    let text = text(text_ptr, length);
+    with_handler(|h| h.set_preview_text(&text, start, end)).map_or(RESULT_FAILED, |()| 0)
+}
+
+unsafe extern "C" fn finish_preview_text(_editor: *mut InputMethod_TextEditorProxy) {
+    with_handler(|h| h.finish_preview_text());
+}
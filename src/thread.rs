@@ -0,0 +1,35 @@
+//! Thread affinity checks for the `thread-checks` feature
+//!
+//! ArkUI calls the XComponent callbacks on the UI thread of the app, and the native XComponent
+//! and ArkUI node APIs may only be used from that thread. Calling them from another thread does
+//! not fail, but silently corrupts ArkUI state. With the `thread-checks` feature, debug builds
+//! remember the thread of the first call into a thread-restricted API and panic if a later call
+//! arrives on a different thread.
+
+/// The thread which made the first thread-restricted call
+#[cfg(all(feature = "thread-checks", debug_assertions, not(test)))]
+static UI_THREAD: std::sync::OnceLock<std::thread::ThreadId> = std::sync::OnceLock::new();
+
+/// Panics if this is not the UI thread
+///
+/// `api` names the thread-restricted function in the panic message. Does nothing unless the
+/// `thread-checks` feature and debug assertions are enabled.
+#[inline]
+#[track_caller]
+pub(crate) fn check_ui_thread(api: &str) {
+    // Unit tests use the restricted APIs from their own threads, like the mocks.
+    #[cfg(all(feature = "thread-checks", debug_assertions, not(test)))]
+    {
+        let current = std::thread::current();
+        let ui_thread = *UI_THREAD.get_or_init(|| current.id());
+        assert!(
+            ui_thread == current.id(),
+            "{api} must be called on the UI thread {ui_thread:?}, but was called on thread {:?} \
+             ({:?})",
+            current.name().unwrap_or("<unnamed>"),
+            current.id(),
+        );
+    }
+    #[cfg(not(all(feature = "thread-checks", debug_assertions, not(test))))]
+    let _ = api;
+}
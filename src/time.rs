@@ -0,0 +1,80 @@
+//! Conversion of event timestamps
+//!
+//! The timestamps of touch, mouse and key events and of frame callbacks are nanoseconds of
+//! `CLOCK_MONOTONIC`, which starts at an unspecified point, e.g. the boot of the device. This
+//! module converts them to [`Instant`]s, which can be compared with `Instant::now()` and the
+//! [`QueuedEvent::time`](crate::event_queue::QueuedEvent) of queued events, and to durations
+//! since the [`epoch`], the first use of this module.
+//!
+//! ## Example
+//! ```no_run
+//! # let touch_event: xcomponent::touch::TouchEvent = todo!();
+//! // In `DispatchTouchEvent`:
+//! let delay = xcomponent::time::latency(touch_event.timestamp);
+//! if delay.as_millis() > 100 {
+//!     // Skip the animation of the touch, it would lag behind ...
+//! }
+//! let time = xcomponent::time::to_instant(touch_event.timestamp);
+//! ```
+
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// The epoch as an [`Instant`] and as a timestamp
+static EPOCH: OnceLock<(Instant, i64)> = OnceLock::new();
+
+/// The current time as a timestamp, in nanoseconds of `CLOCK_MONOTONIC`
+// `time_t` and `c_long` are only 32 bits wide on 32-bit targets.
+#[allow(clippy::useless_conversion)]
+pub fn now() -> i64 {
+    let mut time = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: `time` is a valid timespec, and `CLOCK_MONOTONIC` is supported everywhere.
+    unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut time) };
+    i64::from(time.tv_sec)
+        .saturating_mul(1_000_000_000)
+        .saturating_add(i64::from(time.tv_nsec))
+}
+
+fn epoch_pair() -> (Instant, i64) {
+    *EPOCH.get_or_init(|| (Instant::now(), now()))
+}
+
+/// The instant all durations of this module are relative to, set by the first call into it
+pub fn epoch() -> Instant {
+    epoch_pair().0
+}
+
+/// The time of `timestamp` since the [`epoch`], zero for timestamps before it
+pub fn since_epoch(timestamp: i64) -> Duration {
+    nanos(timestamp.saturating_sub(epoch_pair().1))
+}
+
+/// Converts `timestamp` to an [`Instant`]
+///
+/// Timestamps too long before the epoch for an `Instant`, e.g. zero, return the epoch.
+pub fn to_instant(timestamp: i64) -> Instant {
+    let (instant, epoch) = epoch_pair();
+    let offset = timestamp.saturating_sub(epoch);
+    if offset >= 0 {
+        instant + nanos(offset)
+    } else {
+        instant
+            .checked_sub(nanos(offset.saturating_neg()))
+            .unwrap_or(instant)
+    }
+}
+
+/// The time since `timestamp`, e.g. the latency of an event when it is handled
+///
+/// Zero for timestamps in the future.
+pub fn latency(timestamp: i64) -> Duration {
+    nanos(now().saturating_sub(timestamp))
+}
+
+/// Converts a difference of timestamps, zero if it is negative
+fn nanos(nanoseconds: i64) -> Duration {
+    Duration::from_nanos(u64::try_from(nanoseconds).unwrap_or(0))
+}
@@ -0,0 +1,390 @@
+//! Typed touch events
+//!
+//! [`TouchEvent`] is an owned copy of the raw `OH_NativeXComponent_TouchEvent`, with typed
+//! actions and the pointers touching the screen. Coordinates are in physical pixels, `x` and `y`
+//! relative to the XComponent and `screen_x` and `screen_y` relative to the display.
+//!
+//! The pointers are stored inline in the event, so converting a touch event doesn't allocate.
+//! [`XComponent::touch_event`](crate::XComponent::touch_event) also queries the [`ToolType`] and
+//! the tilt of each pointer, which a plain conversion of the raw event leaves unset.
+//!
+//! ArkUI may coalesce several samples of a moving pointer into one event. With the `alloc`
+//! feature, [`XComponent::touch_event_history`](crate::XComponent::touch_event_history) returns
+//! the samples since the previous event as [`HistoricalPoint`]s, e.g. to draw smooth strokes.
+//!
+//! ## Example
+//! ```no_run
+//! # use ohos_sys::xcomponent::OH_NativeXComponent;
+//! # use core::ffi::c_void;
+//! use xcomponent::touch::TouchAction;
+//!
+//! extern "C" fn on_dispatch_touch_event(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+//!     let xc = xcomponent::XComponent::new(xcomponent, window).unwrap();
+//!     let touch_event = xc.touch_event().unwrap();
+//!     if touch_event.action == Some(TouchAction::Down) && touch_event.points().len() == 2 {
+//!         // Start a pinch ...
+//!     }
+//! }
+//! ```
+
+use ohos_sys::xcomponent::{
+    OH_NativeXComponent_HistoricalPoint, OH_NativeXComponent_TouchEvent,
+    OH_NativeXComponent_TouchEventType, OH_NativeXComponent_TouchEvent_SourceTool,
+    OH_NativeXComponent_TouchPoint, OH_NativeXComponent_TouchPointToolType,
+    OH_MAX_TOUCH_POINTS_NUMBER,
+};
+use smallvec::SmallVec;
+
+/// The maximum number of pointers of a touch event
+pub const MAX_TOUCH_POINTS: usize = OH_MAX_TOUCH_POINTS_NUMBER as usize;
+
+/// The pointers of a [`TouchEvent`]
+///
+/// The inline capacity is [`MAX_TOUCH_POINTS`], so this never allocates.
+pub type TouchPoints = SmallVec<[TouchPoint; MAX_TOUCH_POINTS]>;
+
+/// What a pointer did
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TouchAction {
+    Down,
+    Up,
+    Move,
+    /// The touch sequence was interrupted, e.g. because the system took over the gesture
+    Cancel,
+}
+
+impl TouchAction {
+    /// Converts a raw `OH_NativeXComponent_TouchEventType`
+    ///
+    /// Returns `None` for `OH_NATIVEXCOMPONENT_UNKNOWN` and unknown values.
+    pub fn from_raw(type_: OH_NativeXComponent_TouchEventType) -> Option<Self> {
+        match type_ {
+            OH_NativeXComponent_TouchEventType::OH_NATIVEXCOMPONENT_DOWN => Some(Self::Down),
+            OH_NativeXComponent_TouchEventType::OH_NATIVEXCOMPONENT_UP => Some(Self::Up),
+            OH_NativeXComponent_TouchEventType::OH_NATIVEXCOMPONENT_MOVE => Some(Self::Move),
+            OH_NativeXComponent_TouchEventType::OH_NATIVEXCOMPONENT_CANCEL => Some(Self::Cancel),
+            _ => None,
+        }
+    }
+
+    pub fn as_raw(self) -> OH_NativeXComponent_TouchEventType {
+        match self {
+            Self::Down => OH_NativeXComponent_TouchEventType::OH_NATIVEXCOMPONENT_DOWN,
+            Self::Up => OH_NativeXComponent_TouchEventType::OH_NATIVEXCOMPONENT_UP,
+            Self::Move => OH_NativeXComponent_TouchEventType::OH_NATIVEXCOMPONENT_MOVE,
+            Self::Cancel => OH_NativeXComponent_TouchEventType::OH_NATIVEXCOMPONENT_CANCEL,
+        }
+    }
+}
+
+/// What touches the screen
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ToolType {
+    Finger,
+    Pen,
+    /// The eraser end of a stylus
+    Eraser,
+    Brush,
+    Pencil,
+    Airbrush,
+    Mouse,
+    Lens,
+}
+
+impl ToolType {
+    /// Converts a raw `OH_NativeXComponent_TouchPointToolType`
+    ///
+    /// Returns `None` for `OH_NATIVEXCOMPONENT_TOOL_TYPE_UNKNOWN` and unknown values.
+    pub fn from_raw(tool_type: OH_NativeXComponent_TouchPointToolType) -> Option<Self> {
+        use OH_NativeXComponent_TouchPointToolType as Raw;
+        match tool_type {
+            Raw::OH_NATIVEXCOMPONENT_TOOL_TYPE_FINGER => Some(Self::Finger),
+            Raw::OH_NATIVEXCOMPONENT_TOOL_TYPE_PEN => Some(Self::Pen),
+            Raw::OH_NATIVEXCOMPONENT_TOOL_TYPE_RUBBER => Some(Self::Eraser),
+            Raw::OH_NATIVEXCOMPONENT_TOOL_TYPE_BRUSH => Some(Self::Brush),
+            Raw::OH_NATIVEXCOMPONENT_TOOL_TYPE_PENCIL => Some(Self::Pencil),
+            Raw::OH_NATIVEXCOMPONENT_TOOL_TYPE_AIRBRUSH => Some(Self::Airbrush),
+            Raw::OH_NATIVEXCOMPONENT_TOOL_TYPE_MOUSE => Some(Self::Mouse),
+            Raw::OH_NATIVEXCOMPONENT_TOOL_TYPE_LENS => Some(Self::Lens),
+            _ => None,
+        }
+    }
+
+    /// Converts the raw source tool of a historical point
+    ///
+    /// Returns `None` for unknown tools and touchpads.
+    pub fn from_source_tool(
+        source_tool: OH_NativeXComponent_TouchEvent_SourceTool,
+    ) -> Option<Self> {
+        // Both enums share the values of the tools they have in common.
+        Self::from_raw(OH_NativeXComponent_TouchPointToolType(source_tool.0))
+    }
+
+    pub fn as_raw(self) -> OH_NativeXComponent_TouchPointToolType {
+        use OH_NativeXComponent_TouchPointToolType as Raw;
+        match self {
+            Self::Finger => Raw::OH_NATIVEXCOMPONENT_TOOL_TYPE_FINGER,
+            Self::Pen => Raw::OH_NATIVEXCOMPONENT_TOOL_TYPE_PEN,
+            Self::Eraser => Raw::OH_NATIVEXCOMPONENT_TOOL_TYPE_RUBBER,
+            Self::Brush => Raw::OH_NATIVEXCOMPONENT_TOOL_TYPE_BRUSH,
+            Self::Pencil => Raw::OH_NATIVEXCOMPONENT_TOOL_TYPE_PENCIL,
+            Self::Airbrush => Raw::OH_NATIVEXCOMPONENT_TOOL_TYPE_AIRBRUSH,
+            Self::Mouse => Raw::OH_NATIVEXCOMPONENT_TOOL_TYPE_MOUSE,
+            Self::Lens => Raw::OH_NATIVEXCOMPONENT_TOOL_TYPE_LENS,
+        }
+    }
+}
+
+/// A pointer touching the screen
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TouchPoint {
+    pub id: i32,
+    /// The action of this pointer, if it is the one the event is about
+    pub action: Option<TouchAction>,
+    pub x: f32,
+    pub y: f32,
+    pub screen_x: f32,
+    pub screen_y: f32,
+    /// Contact area of the pointer
+    pub size: f64,
+    /// Pressure, from 0 to 1
+    pub force: f32,
+    /// Time of the last change of the pointer in nanoseconds
+    pub timestamp: i64,
+    pub is_pressed: bool,
+    /// `None` if unknown, or if the point wasn't queried via
+    /// [`XComponent::touch_event`](crate::XComponent::touch_event)
+    pub tool_type: Option<ToolType>,
+    /// Tilt of a stylus in degrees, `0` for fingers or if the point wasn't queried
+    pub tilt_x: f32,
+    pub tilt_y: f32,
+}
+
+impl From<&OH_NativeXComponent_TouchPoint> for TouchPoint {
+    fn from(point: &OH_NativeXComponent_TouchPoint) -> Self {
+        Self {
+            id: point.id,
+            action: TouchAction::from_raw(point.type_),
+            x: point.x,
+            y: point.y,
+            screen_x: point.screenX,
+            screen_y: point.screenY,
+            size: point.size,
+            force: point.force,
+            timestamp: point.timeStamp,
+            is_pressed: point.isPressed,
+            tool_type: None,
+            tilt_x: 0.0,
+            tilt_y: 0.0,
+        }
+    }
+}
+
+/// A sample of a pointer which ArkUI coalesced into the current touch event
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HistoricalPoint {
+    pub id: i32,
+    pub action: Option<TouchAction>,
+    pub x: f32,
+    pub y: f32,
+    pub screen_x: f32,
+    pub screen_y: f32,
+    /// Contact area of the pointer
+    pub size: f64,
+    /// Pressure, from 0 to 1
+    pub force: f32,
+    /// Time of the sample in nanoseconds
+    pub timestamp: i64,
+    pub tool_type: Option<ToolType>,
+    /// Tilt of a stylus in degrees, `0` for fingers
+    pub tilt_x: f32,
+    pub tilt_y: f32,
+}
+
+impl From<&OH_NativeXComponent_HistoricalPoint> for HistoricalPoint {
+    fn from(point: &OH_NativeXComponent_HistoricalPoint) -> Self {
+        Self {
+            id: point.id,
+            action: TouchAction::from_raw(point.type_),
+            x: point.x,
+            y: point.y,
+            screen_x: point.screenX,
+            screen_y: point.screenY,
+            size: point.size,
+            force: point.force,
+            timestamp: point.timeStamp,
+            tool_type: ToolType::from_source_tool(point.sourceTool),
+            tilt_x: point.titlX,
+            tilt_y: point.titlY,
+        }
+    }
+}
+
+/// A touch event of an XComponent
+///
+/// The top-level fields describe the pointer which changed, [`TouchEvent::points`] lists all
+/// pointers currently touching the screen. With the `serde` feature, the raw event is not
+/// serialized, but rebuilt from the other fields when deserializing.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "SerdeTouchEvent", into = "SerdeTouchEvent")
+)]
+pub struct TouchEvent {
+    /// Id of the pointer which changed
+    pub id: i32,
+    /// `None` if ArkUI reported an unknown type
+    pub action: Option<TouchAction>,
+    pub x: f32,
+    pub y: f32,
+    pub screen_x: f32,
+    pub screen_y: f32,
+    /// Contact area of the pointer
+    pub size: f64,
+    /// Pressure, from 0 to 1
+    pub force: f32,
+    /// Id of the input device
+    pub device_id: i64,
+    /// Time of the event in nanoseconds
+    pub timestamp: i64,
+    points: TouchPoints,
+    raw: OH_NativeXComponent_TouchEvent,
+}
+
+impl TouchEvent {
+    /// The pointers touching the screen, at most [`MAX_TOUCH_POINTS`]
+    pub fn points(&self) -> &[TouchPoint] {
+        &self.points
+    }
+
+    /// The pointer with `id`, if it touches the screen
+    pub fn point(&self, id: i32) -> Option<&TouchPoint> {
+        self.points.iter().find(|point| point.id == id)
+    }
+
+    /// The pointers touching the screen, to fill in what the raw event doesn't contain
+    pub(crate) fn points_mut(&mut self) -> &mut [TouchPoint] {
+        &mut self.points
+    }
+
+    /// The raw event this was converted from
+    pub fn as_raw(&self) -> &OH_NativeXComponent_TouchEvent {
+        &self.raw
+    }
+}
+
+impl From<OH_NativeXComponent_TouchEvent> for TouchEvent {
+    fn from(raw: OH_NativeXComponent_TouchEvent) -> Self {
+        let num_points = (raw.numPoints as usize).min(raw.touchPoints.len());
+        Self {
+            id: raw.id,
+            action: TouchAction::from_raw(raw.type_),
+            x: raw.x,
+            y: raw.y,
+            screen_x: raw.screenX,
+            screen_y: raw.screenY,
+            size: raw.size,
+            force: raw.force,
+            device_id: raw.deviceId,
+            timestamp: raw.timeStamp,
+            points: raw.touchPoints[..num_points]
+                .iter()
+                .map(TouchPoint::from)
+                .collect(),
+            raw,
+        }
+    }
+}
+
+/// The serialized form of a [`TouchEvent`], without the raw event
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename = "TouchEvent")]
+struct SerdeTouchEvent {
+    id: i32,
+    action: Option<TouchAction>,
+    x: f32,
+    y: f32,
+    screen_x: f32,
+    screen_y: f32,
+    size: f64,
+    force: f32,
+    device_id: i64,
+    timestamp: i64,
+    points: TouchPoints,
+}
+
+#[cfg(feature = "serde")]
+impl From<TouchEvent> for SerdeTouchEvent {
+    fn from(event: TouchEvent) -> Self {
+        Self {
+            id: event.id,
+            action: event.action,
+            x: event.x,
+            y: event.y,
+            screen_x: event.screen_x,
+            screen_y: event.screen_y,
+            size: event.size,
+            force: event.force,
+            device_id: event.device_id,
+            timestamp: event.timestamp,
+            points: event.points,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<SerdeTouchEvent> for TouchEvent {
+    fn from(event: SerdeTouchEvent) -> Self {
+        let raw_type = |action: Option<TouchAction>| {
+            action.map_or(
+                OH_NativeXComponent_TouchEventType::OH_NATIVEXCOMPONENT_UNKNOWN,
+                TouchAction::as_raw,
+            )
+        };
+        let raw_point = |point: &TouchPoint| OH_NativeXComponent_TouchPoint {
+            id: point.id,
+            screenX: point.screen_x,
+            screenY: point.screen_y,
+            x: point.x,
+            y: point.y,
+            type_: raw_type(point.action),
+            size: point.size,
+            force: point.force,
+            timeStamp: point.timestamp,
+            isPressed: point.is_pressed,
+        };
+        let mut raw = OH_NativeXComponent_TouchEvent {
+            id: event.id,
+            screenX: event.screen_x,
+            screenY: event.screen_y,
+            x: event.x,
+            y: event.y,
+            type_: raw_type(event.action),
+            size: event.size,
+            force: event.force,
+            deviceId: event.device_id,
+            timeStamp: event.timestamp,
+            // SAFETY: The touch points are plain data, for which all zeroes are valid.
+            touchPoints: unsafe { core::mem::zeroed() },
+            numPoints: 0,
+        };
+        for (slot, point) in raw.touchPoints.iter_mut().zip(&event.points) {
+            *slot = raw_point(point);
+            raw.numPoints += 1;
+        }
+        // Convert like a native event, which drops points beyond `MAX_TOUCH_POINTS`.
+        let mut touch_event = Self::from(raw);
+        for (point, serialized) in touch_event.points.iter_mut().zip(&event.points) {
+            point.tool_type = serialized.tool_type;
+            point.tilt_x = serialized.tilt_x;
+            point.tilt_y = serialized.tilt_y;
+        }
+        touch_event
+    }
+}
@@ -0,0 +1,139 @@
+//! Spans for the `tracing` feature
+//!
+//! Without the `tracing` feature the span macros expand to a guard without a span, so call sites
+//! don't need to be feature gated. The spans of native calls are at trace level and the spans of
+//! callbacks at debug level, so they are stripped by the `log-level-*` features. With the `ffi-log` feature, the guard additionally records the
+//! call in the [`ffi_log`](crate::ffi_log) ring buffer when it is dropped.
+
+/// Enters a span covering a call into the native XComponent API
+///
+/// The span is named after the native function and records the address of the
+/// `OH_NativeXComponent` as `component`, which allows correlating calls of multiple components.
+/// Calls which are not tied to a component omit it.
+#[cfg(feature = "tracing")]
+macro_rules! ffi_span {
+    ($function:expr) => {
+        $crate::trace::FfiSpan::new(
+            $crate::log::TRACE_ENABLED
+                .then(|| ::tracing::trace_span!("ffi", function = $function).entered()),
+            $function,
+            None,
+        )
+    };
+    ($function:expr, $component:expr) => {
+        $crate::trace::FfiSpan::new(
+            $crate::log::TRACE_ENABLED.then(|| {
+                ::tracing::trace_span!("ffi", function = $function, component = ?$component)
+                    .entered()
+            }),
+            $function,
+            Some($crate::trace::Address::address(&$component)),
+        )
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! ffi_span {
+    ($function:expr) => {
+        $crate::trace::FfiSpan::new($function, None)
+    };
+    ($function:expr, $component:expr) => {
+        $crate::trace::FfiSpan::new(
+            $function,
+            Some($crate::trace::Address::address(&$component)),
+        )
+    };
+}
+
+pub(crate) use ffi_span;
+
+/// Enters a span covering a callback of ArkUI into this crate, e.g. of a handler shim
+///
+/// Unlike the spans of native calls these are at debug level, so the timing of callbacks can be
+/// traced without the spans of every native call made during the callbacks.
+#[cfg(feature = "tracing")]
+macro_rules! callback_span {
+    ($callback:expr, $component:expr) => {
+        $crate::log::DEBUG_ENABLED.then(|| {
+            ::tracing::debug_span!("callback", callback = $callback, component = ?$component)
+                .entered()
+        })
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! callback_span {
+    ($callback:expr, $component:expr) => {{
+        let _ = (&$callback, &$component);
+        $crate::trace::NoSpan
+    }};
+}
+
+pub(crate) use callback_span;
+
+/// The guard of [`callback_span`] without the `tracing` feature
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct NoSpan;
+
+/// Guard of an entered span
+pub(crate) struct FfiSpan {
+    #[cfg(feature = "tracing")]
+    _span: Option<tracing::span::EnteredSpan>,
+    #[cfg(feature = "ffi-log")]
+    call: Option<crate::ffi_log::PendingCall>,
+}
+
+impl FfiSpan {
+    #[inline]
+    pub(crate) fn new(
+        #[cfg(feature = "tracing")] span: Option<tracing::span::EnteredSpan>,
+        function: &'static str,
+        component: Option<usize>,
+    ) -> Self {
+        #[cfg(not(feature = "ffi-log"))]
+        let _ = (function, component);
+        Self {
+            #[cfg(feature = "tracing")]
+            _span: span,
+            #[cfg(feature = "ffi-log")]
+            call: crate::ffi_log::PendingCall::begin(function, component),
+        }
+    }
+
+    /// Records the return code of the native function
+    #[inline]
+    pub(crate) fn set_result(&mut self, result: i32) {
+        #[cfg(feature = "ffi-log")]
+        if let Some(call) = self.call.as_mut() {
+            call.result = Some(result);
+        }
+        #[cfg(not(feature = "ffi-log"))]
+        let _ = result;
+    }
+}
+
+#[cfg(feature = "ffi-log")]
+impl Drop for FfiSpan {
+    fn drop(&mut self) {
+        if let Some(call) = self.call.take() {
+            call.finish();
+        }
+    }
+}
+
+/// Address of the component a call is tied to
+pub(crate) trait Address {
+    fn address(&self) -> usize;
+}
+
+impl<T> Address for *mut T {
+    fn address(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl<T> Address for core::ptr::NonNull<T> {
+    fn address(&self) -> usize {
+        self.as_ptr() as usize
+    }
+}
@@ -0,0 +1,208 @@
+//! Rendering driven by the vsync signal of the display
+//!
+//! [`NativeVsync`] wraps an `OH_NativeVSync` connection, which calls a callback once at the next
+//! vsync after each request. [`VsyncLoop`] requests the next vsync from each callback, so its
+//! closure is called once per vsync until the loop is paused or dropped. Rendering from the vsync
+//! instead of a timer paces frames with the display, and no frames are rendered while paused,
+//! e.g. while the surface is hidden.
+//!
+//! The callbacks are called on a thread of the vsync connection, not on the UI thread.
+//!
+//! ## Example
+//! ```no_run
+//! use xcomponent::vsync::VsyncLoop;
+//!
+//! let vsync_loop = VsyncLoop::new("render", |timestamp| {
+//!     // Render the frame for `timestamp` ...
+//! })
+//! .expect("Failed to start the vsync loop");
+//! // E.g. when the surface is hidden:
+//! vsync_loop.pause();
+//! // Dropping the loop stops it.
+//! drop(vsync_loop);
+//! ```
+
+use crate::log::error;
+use crate::trace::ffi_span;
+use core::ffi::{c_longlong, c_void};
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::time::Duration;
+use ohos_sys::vsync::{
+    OH_NativeVSync, OH_NativeVSync_Create, OH_NativeVSync_Destroy, OH_NativeVSync_FrameCallback,
+    OH_NativeVSync_GetPeriod, OH_NativeVSync_RequestFrame,
+};
+use std::sync::{Arc, Mutex};
+
+/// An `OH_NativeVSync` connection, destroyed when dropped
+#[derive(Debug)]
+pub struct NativeVsync {
+    vsync: NonNull<OH_NativeVSync>,
+}
+
+// SAFETY: The native vsync connection can be used from any thread.
+unsafe impl Send for NativeVsync {}
+unsafe impl Sync for NativeVsync {}
+
+impl NativeVsync {
+    /// Creates a vsync connection, `name` identifies it e.g. in traces
+    pub fn new(name: &str) -> Result<Self, i32> {
+        // OH_NATIVEXCOMPONENT_RESULT_FAILED
+        let length = u32::try_from(name.len()).map_err(|_| -1)?;
+        let vsync = unsafe { OH_NativeVSync_Create(name.as_ptr().cast(), length) };
+        let Some(vsync) = NonNull::new(vsync) else {
+            error!("OH_NativeVSync_Create failed");
+            // OH_NATIVEXCOMPONENT_RESULT_FAILED
+            return Err(-1);
+        };
+        Ok(Self { vsync })
+    }
+
+    /// Requests a single call of `callback` with `data` at the next vsync
+    ///
+    /// If this is called multiple times before the next vsync, only the last callback is called.
+    ///
+    /// # Safety
+    ///
+    /// `callback` must be safe to call with `data` from another thread, and `data` must stay
+    /// valid until the callback was called.
+    pub unsafe fn request_frame(
+        &self,
+        callback: OH_NativeVSync_FrameCallback,
+        data: *mut c_void,
+    ) -> Result<(), i32> {
+        let mut span = ffi_span!("OH_NativeVSync_RequestFrame", self.vsync);
+        // SAFETY: The caller ensures that `callback` can be called with `data`.
+        let res = unsafe { OH_NativeVSync_RequestFrame(self.vsync.as_ptr(), callback, data) };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_NativeVSync_RequestFrame failed with {res}");
+            return Err(res);
+        }
+        Ok(())
+    }
+
+    /// The vsync period of the display
+    ///
+    /// Only known after the first vsync callback of the connection.
+    pub fn period(&self) -> Result<Duration, i32> {
+        let mut period: c_longlong = 0;
+        let mut span = ffi_span!("OH_NativeVSync_GetPeriod", self.vsync);
+        let res = unsafe { OH_NativeVSync_GetPeriod(self.vsync.as_ptr(), &mut period) };
+        span.set_result(res);
+        if res != 0 {
+            error!("OH_NativeVSync_GetPeriod failed with {res}");
+            return Err(res);
+        }
+        // OH_NATIVEXCOMPONENT_RESULT_FAILED
+        let period = u64::try_from(period).map_err(|_| -1)?;
+        Ok(Duration::from_nanos(period))
+    }
+
+    pub fn as_ptr(&self) -> *mut OH_NativeVSync {
+        self.vsync.as_ptr()
+    }
+}
+
+impl Drop for NativeVsync {
+    fn drop(&mut self) {
+        unsafe { OH_NativeVSync_Destroy(self.vsync.as_ptr()) };
+    }
+}
+
+type VsyncCallback = Box<dyn FnMut(Duration) + Send>;
+
+struct LoopState {
+    vsync: NativeVsync,
+    running: AtomicBool,
+    /// Whether a vsync was requested, which owns a reference to the state until it is received
+    pending: AtomicBool,
+    callback: Mutex<VsyncCallback>,
+}
+
+/// Calls a closure with the timestamp of every vsync, until paused or dropped
+///
+/// The closure is called on a thread of the vsync connection, and dropped there after the loop
+/// was dropped.
+pub struct VsyncLoop {
+    state: Arc<LoopState>,
+}
+
+impl VsyncLoop {
+    /// Starts calling `callback` with the timestamp of every vsync
+    ///
+    /// `name` identifies the vsync connection, see [`NativeVsync::new`].
+    pub fn new(name: &str, callback: impl FnMut(Duration) + Send + 'static) -> Result<Self, i32> {
+        let state = Arc::new(LoopState {
+            vsync: NativeVsync::new(name)?,
+            running: AtomicBool::new(true),
+            pending: AtomicBool::new(false),
+            callback: Mutex::new(Box::new(callback)),
+        });
+        request(&state)?;
+        Ok(Self { state })
+    }
+
+    /// Stops calling the closure, from the next vsync on
+    pub fn pause(&self) {
+        self.state.running.store(false, Ordering::Release);
+    }
+
+    /// Calls the closure again from the next vsync on
+    pub fn resume(&self) -> Result<(), i32> {
+        self.state.running.store(true, Ordering::Release);
+        request(&self.state)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.state.running.load(Ordering::Acquire)
+    }
+
+    /// The vsync period of the display, see [`NativeVsync::period`]
+    pub fn period(&self) -> Result<Duration, i32> {
+        self.state.vsync.period()
+    }
+}
+
+impl Drop for VsyncLoop {
+    fn drop(&mut self) {
+        self.pause();
+    }
+}
+
+/// Requests the next vsync for `state`, unless one is already pending
+fn request(state: &Arc<LoopState>) -> Result<(), i32> {
+    if state.pending.swap(true, Ordering::AcqRel) {
+        return Ok(());
+    }
+    let data = Arc::into_raw(Arc::clone(state));
+    // SAFETY: `on_vsync` takes back the reference `data`, which keeps the state alive until then.
+    let res = unsafe {
+        state
+            .vsync
+            .request_frame(Some(on_vsync), data.cast_mut().cast())
+    };
+    if res.is_err() {
+        state.pending.store(false, Ordering::Release);
+        // SAFETY: The callback won't be called, so the reference is still ours.
+        drop(unsafe { Arc::from_raw(data) });
+    }
+    res
+}
+
+unsafe extern "C" fn on_vsync(timestamp: c_longlong, data: *mut c_void) {
+    // SAFETY: `data` is the reference passed to `request_frame` by `request`.
+    let state = unsafe { Arc::from_raw(data.cast_const().cast::<LoopState>()) };
+    state.pending.store(false, Ordering::Release);
+    if !state.running.load(Ordering::Acquire) {
+        return;
+    }
+    {
+        let mut callback = state.callback.lock().unwrap_or_else(|e| e.into_inner());
+        let timestamp = Duration::from_nanos(u64::try_from(timestamp).unwrap_or(0));
+        crate::panic_guard::catch("OH_NativeVSync_FrameCallback", || callback(timestamp));
+    }
+    if state.running.load(Ordering::Acquire) {
+        let _ = request(&state);
+    }
+}
@@ -0,0 +1,116 @@
+//! Create Vulkan surfaces for XComponents via `VK_OHOS_surface`
+//!
+//! `ash` has no bindings of `VK_OHOS_surface` yet, so this module declares the structure and
+//! function of the extension as in `vulkan_ohos.h` of the OpenHarmony SDK, and loads
+//! `vkCreateSurfaceOHOS` from the instance. The instance must be created with
+//! [`SURFACE_EXTENSION_NAME`] and `VK_KHR_surface` enabled.
+//!
+//! ## Example
+//! ```no_run
+//! # use ohos_sys::xcomponent::OH_NativeXComponent;
+//! # use core::ffi::c_void;
+//! # fn entry_and_instance() -> &'static (ash::Entry, ash::Instance) { todo!() }
+//! extern "C" fn on_surface_created(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+//!     let xc = xcomponent::XComponent::new(xcomponent, window).unwrap();
+//!     let (entry, instance) = entry_and_instance();
+//!     let surface = xc.create_vulkan_surface(entry, instance, None).unwrap();
+//!     // Create a swapchain for `surface` via `VK_KHR_swapchain` ...
+//! }
+//! ```
+
+use crate::log::error;
+use crate::native_window::NativeWindow;
+use crate::XComponent;
+use ash::vk;
+use core::ffi::{c_void, CStr};
+use ohos_sys_opaque_types::OHNativeWindow;
+
+/// Name of the `VK_OHOS_surface` instance extension
+pub const SURFACE_EXTENSION_NAME: &CStr = c"VK_OHOS_surface";
+
+const STRUCTURE_TYPE_SURFACE_CREATE_INFO_OHOS: vk::StructureType =
+    vk::StructureType::from_raw(1000685000);
+
+/// `VkSurfaceCreateInfoOHOS`
+#[repr(C)]
+struct SurfaceCreateInfoOhos {
+    s_type: vk::StructureType,
+    p_next: *const c_void,
+    flags: vk::Flags,
+    window: *mut OHNativeWindow,
+}
+
+type CreateSurfaceOhos = unsafe extern "system" fn(
+    instance: vk::Instance,
+    create_info: *const SurfaceCreateInfoOhos,
+    allocator: *const vk::AllocationCallbacks<'_>,
+    surface: *mut vk::SurfaceKHR,
+) -> vk::Result;
+
+impl XComponent<'_> {
+    /// Creates a Vulkan surface for the native window of the XComponent
+    ///
+    /// Fails with `ERROR_INITIALIZATION_FAILED` for mock XComponents.
+    #[cfg_attr(docsrs, doc(cfg(feature = "vulkan")))]
+    pub fn create_vulkan_surface(
+        &self,
+        entry: &ash::Entry,
+        instance: &ash::Instance,
+        allocator: Option<&vk::AllocationCallbacks<'_>>,
+    ) -> Result<vk::SurfaceKHR, vk::Result> {
+        let window = self
+            .native_window()
+            .map_err(|_| vk::Result::ERROR_INITIALIZATION_FAILED)?;
+        window.create_vulkan_surface(entry, instance, allocator)
+    }
+}
+
+impl NativeWindow {
+    /// Creates a Vulkan surface for the window
+    ///
+    /// Fails with `ERROR_EXTENSION_NOT_PRESENT` if `instance` wasn't created with
+    /// [`SURFACE_EXTENSION_NAME`] enabled, and with `ERROR_INITIALIZATION_FAILED` for the
+    /// windows of mock XComponents.
+    #[cfg_attr(docsrs, doc(cfg(feature = "vulkan")))]
+    pub fn create_vulkan_surface(
+        &self,
+        entry: &ash::Entry,
+        instance: &ash::Instance,
+        allocator: Option<&vk::AllocationCallbacks<'_>>,
+    ) -> Result<vk::SurfaceKHR, vk::Result> {
+        if self.is_mocked() {
+            return Err(vk::Result::ERROR_INITIALIZATION_FAILED);
+        }
+        // SAFETY: `instance` is a valid instance and the name is nul-terminated.
+        let create_surface = unsafe {
+            entry.get_instance_proc_addr(instance.handle(), c"vkCreateSurfaceOHOS".as_ptr())
+        };
+        let Some(create_surface) = create_surface else {
+            error!("vkCreateSurfaceOHOS not found, is VK_OHOS_surface enabled?");
+            return Err(vk::Result::ERROR_EXTENSION_NOT_PRESENT);
+        };
+        // SAFETY: `vkCreateSurfaceOHOS` has this signature.
+        let create_surface: CreateSurfaceOhos = unsafe { core::mem::transmute(create_surface) };
+        let create_info = SurfaceCreateInfoOhos {
+            s_type: STRUCTURE_TYPE_SURFACE_CREATE_INFO_OHOS,
+            p_next: core::ptr::null(),
+            flags: 0,
+            window: self.as_ptr(),
+        };
+        let mut surface = vk::SurfaceKHR::null();
+        // SAFETY: All pointers are valid for the call, and `self` keeps the window alive.
+        let res = unsafe {
+            create_surface(
+                instance.handle(),
+                &create_info,
+                allocator.map_or(core::ptr::null(), |a| a as *const _),
+                &mut surface,
+            )
+        };
+        if res != vk::Result::SUCCESS {
+            error!("vkCreateSurfaceOHOS failed with {res}");
+            return Err(res);
+        }
+        Ok(surface)
+    }
+}
@@ -0,0 +1,236 @@
+//! Properties of the window hosting the XComponent
+//!
+//! The native window manager identifies windows by their id. The XComponent does not expose the
+//! id of its window, so it needs to be passed from ArkTS, e.g. from
+//! `window.getLastWindow(context).getWindowProperties().id`.
+//!
+//! ## Example
+//! ```no_run
+//! use xcomponent::window::{SafeAreaWatcher, WindowId};
+//!
+//! let mut watcher = SafeAreaWatcher::new(WindowId(42));
+//! // Call this from `OnSurfaceChanged`, since the safe area usually changes together with the
+//! // surface size.
+//! if let Ok(Some(insets)) = watcher.poll() {
+//!     // Move the UI out of `insets` ...
+//! }
+//! ```
+
+use crate::geometry::WindowRect;
+use crate::log::error;
+use crate::trace::ffi_span;
+use core::mem::MaybeUninit;
+use ohos_sys::window_manager::window::{
+    OH_WindowManager_GetWindowAvoidArea, OH_WindowManager_GetWindowProperties,
+    OH_WindowManager_SetWindowKeepScreenOn, OH_WindowManager_SetWindowPrivacyMode,
+};
+use ohos_sys::window_manager::window_comm::{
+    WindowManager_AvoidArea, WindowManager_AvoidAreaType, WindowManager_Rect,
+    WindowManager_WindowProperties,
+};
+
+/// Id of an ArkUI window
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WindowId(pub i32);
+
+impl WindowId {
+    /// Prevent the display from turning off while the window is shown
+    ///
+    /// Useful e.g. for video playback or navigation, where the user does not touch the screen
+    /// for a long time.
+    pub fn set_keep_screen_on(self, keep_screen_on: bool) -> Result<(), i32> {
+        let _span = ffi_span!("OH_WindowManager_SetWindowKeepScreenOn");
+        let res = unsafe { OH_WindowManager_SetWindowKeepScreenOn(self.0, keep_screen_on) };
+        if res != 0 {
+            error!("OH_WindowManager_SetWindowKeepScreenOn failed with {res}");
+            return Err(res);
+        }
+        Ok(())
+    }
+
+    /// Hide the content of the window from screenshots, screen recordings and the recents view
+    ///
+    /// Requires the `ohos.permission.PRIVACY_WINDOW` permission.
+    pub fn set_privacy_mode(self, privacy_mode: bool) -> Result<(), i32> {
+        let _span = ffi_span!("OH_WindowManager_SetWindowPrivacyMode");
+        let res = unsafe { OH_WindowManager_SetWindowPrivacyMode(self.0, privacy_mode) };
+        if res != 0 {
+            error!("OH_WindowManager_SetWindowPrivacyMode failed with {res}");
+            return Err(res);
+        }
+        Ok(())
+    }
+}
+
+/// Returns the position and size of `window` on its display
+pub fn window_rect(window: WindowId) -> Result<WindowRect, i32> {
+    let _span = ffi_span!("OH_WindowManager_GetWindowProperties");
+    let properties = unsafe {
+        let mut properties: MaybeUninit<WindowManager_WindowProperties> = MaybeUninit::uninit();
+        let res = OH_WindowManager_GetWindowProperties(window.0, properties.as_mut_ptr());
+        if res != 0 {
+            error!("OH_WindowManager_GetWindowProperties failed with {res}");
+            return Err(res);
+        }
+        properties.assume_init()
+    };
+    let rect = properties.windowRect;
+    Ok(WindowRect {
+        x: rect.posX,
+        y: rect.posY,
+        width: rect.width,
+        height: rect.height,
+    })
+}
+
+/// Kind of area the content of a window should avoid
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AvoidAreaType {
+    /// The status bar and the navigation bar
+    System,
+    /// Notches and punch holes of the display
+    Cutout,
+    /// Areas reserved for system gestures, e.g. the back gesture
+    SystemGesture,
+    /// The soft keyboard
+    Keyboard,
+    /// The navigation indicator at the bottom of the display
+    NavigationIndicator,
+}
+
+impl AvoidAreaType {
+    fn as_raw(self) -> WindowManager_AvoidAreaType {
+        match self {
+            Self::System => WindowManager_AvoidAreaType::SYSTEM,
+            Self::Cutout => WindowManager_AvoidAreaType::CUTOUT,
+            Self::SystemGesture => WindowManager_AvoidAreaType::SYSTEM_GESTURE,
+            Self::Keyboard => WindowManager_AvoidAreaType::KEYBOARD,
+            Self::NavigationIndicator => WindowManager_AvoidAreaType::NAVIGATION_INDICATOR,
+        }
+    }
+}
+
+/// Distances from the edges of a window in physical pixels
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Insets {
+    pub top: u32,
+    pub left: u32,
+    pub right: u32,
+    pub bottom: u32,
+}
+
+impl Insets {
+    /// The larger inset of `self` and `other` for every edge
+    pub fn max(self, other: Insets) -> Insets {
+        Insets {
+            top: self.top.max(other.top),
+            left: self.left.max(other.left),
+            right: self.right.max(other.right),
+            bottom: self.bottom.max(other.bottom),
+        }
+    }
+
+    fn from_raw(area: &WindowManager_AvoidArea) -> Insets {
+        fn is_empty(rect: &WindowManager_Rect) -> bool {
+            rect.width == 0 || rect.height == 0
+        }
+        let top = &area.topRect;
+        let left = &area.leftRect;
+        Insets {
+            top: if is_empty(top) {
+                0
+            } else {
+                (top.posY.max(0) as u32).saturating_add(top.height)
+            },
+            left: if is_empty(left) {
+                0
+            } else {
+                (left.posX.max(0) as u32).saturating_add(left.width)
+            },
+            right: if is_empty(&area.rightRect) {
+                0
+            } else {
+                area.rightRect.width
+            },
+            bottom: if is_empty(&area.bottomRect) {
+                0
+            } else {
+                area.bottomRect.height
+            },
+        }
+    }
+}
+
+/// Returns the insets of the area of `kind` the content of `window` should avoid
+pub fn avoid_area_insets(window: WindowId, kind: AvoidAreaType) -> Result<Insets, i32> {
+    let mut area = WindowManager_AvoidArea {
+        topRect: empty_rect(),
+        leftRect: empty_rect(),
+        rightRect: empty_rect(),
+        bottomRect: empty_rect(),
+    };
+    let _span = ffi_span!("OH_WindowManager_GetWindowAvoidArea");
+    let res = unsafe { OH_WindowManager_GetWindowAvoidArea(window.0, kind.as_raw(), &mut area) };
+    if res != 0 {
+        error!("OH_WindowManager_GetWindowAvoidArea failed with {res}");
+        return Err(res);
+    }
+    Ok(Insets::from_raw(&area))
+}
+
+/// Returns the insets interactive content of `window` should keep clear of
+///
+/// This combines the system bars, display cutouts and the navigation indicator.
+pub fn safe_area_insets(window: WindowId) -> Result<Insets, i32> {
+    let mut insets = Insets::default();
+    for kind in [
+        AvoidAreaType::System,
+        AvoidAreaType::Cutout,
+        AvoidAreaType::NavigationIndicator,
+    ] {
+        insets = insets.max(avoid_area_insets(window, kind)?);
+    }
+    Ok(insets)
+}
+
+fn empty_rect() -> WindowManager_Rect {
+    WindowManager_Rect {
+        posX: 0,
+        posY: 0,
+        width: 0,
+        height: 0,
+    }
+}
+
+/// Detects changes of the safe area of a window
+///
+/// The native window manager has no listener for avoid area changes, so the safe area needs to
+/// be polled, e.g. whenever the surface of the XComponent changed.
+#[derive(Debug)]
+pub struct SafeAreaWatcher {
+    window: WindowId,
+    last: Option<Insets>,
+}
+
+impl SafeAreaWatcher {
+    pub fn new(window: WindowId) -> Self {
+        Self { window, last: None }
+    }
+
+    /// The safe area insets returned by the last successful [`SafeAreaWatcher::poll`]
+    pub fn insets(&self) -> Option<Insets> {
+        self.last
+    }
+
+    /// Queries the safe area and returns the new insets if they changed since the last call
+    ///
+    /// The first successful call always returns the insets.
+    pub fn poll(&mut self) -> Result<Option<Insets>, i32> {
+        let insets = safe_area_insets(self.window)?;
+        if self.last == Some(insets) {
+            return Ok(None);
+        }
+        self.last = Some(insets);
+        Ok(Some(insets))
+    }
+}
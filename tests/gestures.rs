@@ -0,0 +1,238 @@
+//! Golden-trace tests of the gesture recognizer
+//!
+//! The traces in `tests/traces` are in the format of the `record` feature. They are replayed
+//! through a mock XComponent whose `DispatchTouchEvent` feeds a [`GestureRecognizer`] with the
+//! default [`GestureConfig`](xcomponent::gestures::GestureConfig), and the recognized gestures
+//! are compared to the expected ones. `update` is called with the timestamp of every touch event
+//! before the event, like a frame callback would be.
+
+use core::ffi::c_void;
+use ohos_sys::xcomponent::{OH_NativeXComponent, OH_NativeXComponent_Callback};
+use std::cell::RefCell;
+use std::f32::consts::{FRAC_PI_4, SQRT_2};
+use xcomponent::gestures::{Gesture, GestureRecognizer};
+use xcomponent::mock::{load_trace, MockXComponent};
+use xcomponent::XComponent;
+
+thread_local! {
+    static RECOGNIZER: RefCell<GestureRecognizer> = RefCell::new(GestureRecognizer::new());
+    static GESTURES: RefCell<Vec<Gesture>> = const { RefCell::new(Vec::new()) };
+}
+
+extern "C" fn dispatch_touch_event(xcomponent: *mut OH_NativeXComponent, window: *mut c_void) {
+    let xc = XComponent::new(xcomponent, window).unwrap();
+    let touch_event = xc.touch_event().unwrap();
+    RECOGNIZER.with_borrow_mut(|recognizer| {
+        GESTURES.with_borrow_mut(|gestures| {
+            gestures.extend(recognizer.update(touch_event.timestamp));
+            gestures.extend(recognizer.on_touch(&touch_event));
+        })
+    });
+}
+
+static CALLBACKS: OH_NativeXComponent_Callback = OH_NativeXComponent_Callback {
+    OnSurfaceCreated: None,
+    OnSurfaceChanged: None,
+    OnSurfaceDestroyed: None,
+    DispatchTouchEvent: Some(dispatch_touch_event),
+};
+
+/// Replays `trace` and returns the recognized gestures
+fn replay(trace: &str) -> Vec<Gesture> {
+    let events = load_trace(trace).unwrap_or_else(|line| panic!("line {line} is malformed"));
+    RECOGNIZER.with_borrow_mut(GestureRecognizer::reset);
+    let mock = MockXComponent::new("gestures");
+    mock.replay(&CALLBACKS, events.into_iter().map(|(_, event)| event));
+    GESTURES.take()
+}
+
+/// The name and the values of `gesture`, to compare the values approximately
+fn fields(gesture: &Gesture) -> (&'static str, Vec<f32>) {
+    match *gesture {
+        Gesture::Tap { x, y } => ("Tap", vec![x, y]),
+        Gesture::DoubleTap { x, y } => ("DoubleTap", vec![x, y]),
+        Gesture::LongPress { x, y } => ("LongPress", vec![x, y]),
+        Gesture::PanStart { x, y } => ("PanStart", vec![x, y]),
+        Gesture::Pan {
+            x,
+            y,
+            dx,
+            dy,
+            velocity_x,
+            velocity_y,
+        } => ("Pan", vec![x, y, dx, dy, velocity_x, velocity_y]),
+        Gesture::PanEnd {
+            velocity_x,
+            velocity_y,
+        } => ("PanEnd", vec![velocity_x, velocity_y]),
+        Gesture::PinchStart { focus_x, focus_y } => ("PinchStart", vec![focus_x, focus_y]),
+        Gesture::Pinch {
+            focus_x,
+            focus_y,
+            scale,
+            rotation,
+        } => ("Pinch", vec![focus_x, focus_y, scale, rotation]),
+        Gesture::PinchEnd => ("PinchEnd", vec![]),
+    }
+}
+
+#[track_caller]
+fn assert_gestures(trace: &str, expected: &[Gesture]) {
+    let actual = replay(trace);
+    let matches = actual.len() == expected.len()
+        && actual.iter().zip(expected).all(|(actual, expected)| {
+            let (actual, expected) = (fields(actual), fields(expected));
+            actual.0 == expected.0
+                && actual
+                    .1
+                    .iter()
+                    .zip(&expected.1)
+                    .all(|(a, e)| (a - e).abs() <= 1e-3 * e.abs().max(1.0))
+        });
+    assert!(
+        matches,
+        "expected {expected:#?}\nbut recognized {actual:#?}"
+    );
+}
+
+#[test]
+fn tap() {
+    assert_gestures(
+        include_str!("traces/tap.trace"),
+        &[Gesture::Tap { x: 100.0, y: 100.0 }],
+    );
+}
+
+#[test]
+fn tap_slop() {
+    // The first move stays within the slop, the second one starts a pan.
+    assert_gestures(
+        include_str!("traces/tap_slop.trace"),
+        &[
+            Gesture::PanStart { x: 100.0, y: 100.0 },
+            Gesture::Pan {
+                x: 100.0,
+                y: 120.0,
+                dx: 0.0,
+                dy: 20.0,
+                velocity_x: 0.0,
+                velocity_y: 500.0,
+            },
+            Gesture::PanEnd {
+                velocity_x: 0.0,
+                velocity_y: 500.0,
+            },
+        ],
+    );
+}
+
+#[test]
+fn double_tap() {
+    assert_gestures(
+        include_str!("traces/double_tap.trace"),
+        &[
+            Gesture::Tap { x: 100.0, y: 100.0 },
+            Gesture::Tap { x: 150.0, y: 120.0 },
+            Gesture::DoubleTap { x: 150.0, y: 120.0 },
+        ],
+    );
+}
+
+#[test]
+fn double_tap_timeout() {
+    // The second tap ends 350 ms after the first one.
+    assert_gestures(
+        include_str!("traces/double_tap_timeout.trace"),
+        &[
+            Gesture::Tap { x: 100.0, y: 100.0 },
+            Gesture::Tap { x: 100.0, y: 100.0 },
+        ],
+    );
+}
+
+#[test]
+fn double_tap_slop() {
+    // The second tap is 150 pixels away from the first one.
+    assert_gestures(
+        include_str!("traces/double_tap_slop.trace"),
+        &[
+            Gesture::Tap { x: 100.0, y: 100.0 },
+            Gesture::Tap { x: 250.0, y: 100.0 },
+        ],
+    );
+}
+
+#[test]
+fn long_press_move_done() {
+    // Moving after the long press neither pans nor taps, the next gesture starts fresh.
+    assert_gestures(
+        include_str!("traces/long_press.trace"),
+        &[
+            Gesture::LongPress { x: 200.0, y: 300.0 },
+            Gesture::Tap { x: 500.0, y: 500.0 },
+        ],
+    );
+}
+
+#[test]
+fn pan_pinch_handoff() {
+    // Moving the remaining pointer after the pinch doesn't pan again.
+    assert_gestures(
+        include_str!("traces/pan_pinch.trace"),
+        &[
+            Gesture::PanStart { x: 100.0, y: 100.0 },
+            Gesture::Pan {
+                x: 100.0,
+                y: 150.0,
+                dx: 0.0,
+                dy: 50.0,
+                velocity_x: 0.0,
+                velocity_y: 2500.0,
+            },
+            Gesture::Pan {
+                x: 100.0,
+                y: 200.0,
+                dx: 0.0,
+                dy: 50.0,
+                velocity_x: 0.0,
+                velocity_y: 2500.0,
+            },
+            Gesture::PanEnd {
+                velocity_x: 0.0,
+                velocity_y: 0.0,
+            },
+            Gesture::PinchStart {
+                focus_x: 200.0,
+                focus_y: 200.0,
+            },
+            Gesture::Pinch {
+                focus_x: 250.0,
+                focus_y: 200.0,
+                scale: 1.5,
+                rotation: 0.0,
+            },
+            Gesture::PinchEnd,
+        ],
+    );
+}
+
+#[test]
+fn cancel_mid_pinch() {
+    assert_gestures(
+        include_str!("traces/cancel_pinch.trace"),
+        &[
+            Gesture::PinchStart {
+                focus_x: 150.0,
+                focus_y: 100.0,
+            },
+            Gesture::Pinch {
+                focus_x: 150.0,
+                focus_y: 150.0,
+                scale: SQRT_2,
+                rotation: FRAC_PI_4,
+            },
+            Gesture::PinchEnd,
+            Gesture::Tap { x: 600.0, y: 600.0 },
+        ],
+    );
+}